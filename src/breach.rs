@@ -0,0 +1,212 @@
+//! Offline breached-password checks against a local copy of the HIBP Pwned Passwords list.
+//!
+//! The list is the SHA-1 version ordered by hash, one `HASH:COUNT` line per password, as
+//! produced by the official downloader. Lookups binary search the file on disk, so the multi-GB
+//! list is never loaded into memory and no network calls are made.
+
+/*
+Copyright (C) 2025  Luke Wilkinson
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::storage::Locations;
+use anyhow::Error;
+use sha1::{Digest, Sha1};
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Length of a hex-encoded SHA-1 hash.
+const SHA1_HEX_LENGTH: usize = 40;
+
+/// The result of checking a password against the breach list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreachStatus {
+    /// No breach list is installed, so the password could not be checked.
+    Unavailable,
+    NotFound,
+    /// The password appears in the list this many times.
+    Found(u64),
+}
+
+/// An open, hash-ordered Pwned Passwords file.
+pub struct PwnedPasswords {
+    reader: BufReader<File>,
+    length: u64,
+}
+
+impl PwnedPasswords {
+    /// Opens a Pwned Passwords file and checks that it looks like the SHA-1 list.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the file.
+    ///
+    /// # Returns
+    /// * `Result<PwnedPasswords, Error>` - Returns the opened list on success, or an error on failure.
+    ///
+    /// # Errors
+    /// * If the file cannot be opened, or its first line is not a `HASH:COUNT` SHA-1 entry.
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        let file = File::open(path).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to open breached password list `{}`: {}",
+                path.display(),
+                e
+            )
+        })?;
+        let length = file.metadata()?.len();
+
+        let mut reader = BufReader::new(file);
+        let mut first_line = String::new();
+        reader.read_line(&mut first_line)?;
+
+        if length > 0 && parse_line(&first_line).is_none() {
+            return Err(anyhow::anyhow!(
+                "`{}` is not a SHA-1 Pwned Passwords list (expected `HASH:COUNT` lines)",
+                path.display()
+            ));
+        }
+
+        Ok(Self { reader, length })
+    }
+
+    /// Looks up a password, hashing it with SHA-1 first.
+    ///
+    /// # Arguments
+    /// * `password` - The password to check.
+    ///
+    /// # Returns
+    /// * `Result<Option<u64>, Error>` - Returns how often the password was seen in breaches, or `None` if it was not.
+    ///
+    /// # Errors
+    /// * If the file cannot be read.
+    pub fn check_password(&mut self, password: &str) -> Result<Option<u64>, Error> {
+        let hash = Sha1::digest(password.as_bytes())
+            .iter()
+            .map(|byte| format!("{byte:02X}"))
+            .collect::<String>();
+
+        self.lookup_hash(&hash)
+    }
+
+    /// Looks up a hex-encoded SHA-1 hash by binary searching the file.
+    ///
+    /// # Arguments
+    /// * `hash` - The 40 character hex hash, in either case.
+    ///
+    /// # Returns
+    /// * `Result<Option<u64>, Error>` - Returns the breach count for the hash, or `None` if it is not listed.
+    ///
+    /// # Errors
+    /// * If the hash is not 40 hex characters, or the file cannot be read.
+    pub fn lookup_hash(&mut self, hash: &str) -> Result<Option<u64>, Error> {
+        if hash.len() != SHA1_HEX_LENGTH || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(anyhow::anyhow!("`{}` is not a SHA-1 hash", hash));
+        }
+        let target = hash.to_ascii_uppercase();
+
+        // The line for `target`, if any, starts somewhere in low..high.
+        let mut low = 0;
+        let mut high = self.length;
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let Some((next_line, line_hash, count)) = self.line_at_or_after(middle)? else {
+                high = middle;
+                continue;
+            };
+
+            match line_hash.cmp(&target) {
+                Ordering::Equal => return Ok(Some(count)),
+                Ordering::Less => low = next_line,
+                // The first line starting at or after `middle` is already too far.
+                Ordering::Greater => high = middle,
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Reads the first whole line starting at or after `offset`.
+    ///
+    /// Returns the offset of the line after it, with its hash and count, or `None` at the end of
+    /// the file. Lines that are not `HASH:COUNT` are skipped.
+    fn line_at_or_after(&mut self, offset: u64) -> Result<Option<(u64, String, u64)>, Error> {
+        let mut line = String::new();
+
+        // Unless at the very start, finish the line `offset` lands in (or the one just before it).
+        let mut position = if offset == 0 {
+            self.reader.seek(SeekFrom::Start(0))?;
+            0
+        } else {
+            self.reader.seek(SeekFrom::Start(offset - 1))?;
+            let mut skipped = Vec::new();
+            offset - 1 + self.reader.read_until(b'\n', &mut skipped)? as u64
+        };
+
+        loop {
+            line.clear();
+            let read = self.reader.read_line(&mut line)?;
+            if read == 0 {
+                return Ok(None);
+            }
+            position += read as u64;
+
+            if let Some((hash, count)) = parse_line(&line) {
+                return Ok(Some((position, hash, count)));
+            }
+        }
+    }
+}
+
+/// Returns where fmp looks for the Pwned Passwords list.
+///
+/// # Returns
+/// * `PathBuf` - The path to the list, which may not exist.
+pub fn default_pwned_passwords_path() -> PathBuf {
+    Locations::new("", "").pwned_passwords
+}
+
+/// Checks a password against the installed breach list, if there is one.
+///
+/// # Arguments
+/// * `password` - The password to check.
+///
+/// # Returns
+/// * `Result<BreachStatus, Error>` - Returns whether the password was found, or `Unavailable` when no list is installed.
+///
+/// # Errors
+/// * If the list exists but cannot be read or is not a SHA-1 Pwned Passwords list.
+pub fn check_password_breached(password: &str) -> Result<BreachStatus, Error> {
+    let path = default_pwned_passwords_path();
+    if !path.exists() {
+        return Ok(BreachStatus::Unavailable);
+    }
+
+    let mut list = PwnedPasswords::open(&path)?;
+    Ok(match list.check_password(password)? {
+        Some(count) => BreachStatus::Found(count),
+        None => BreachStatus::NotFound,
+    })
+}
+
+fn parse_line(line: &str) -> Option<(String, u64)> {
+    let (hash, count) = line.trim_end().split_once(':')?;
+    if hash.len() != SHA1_HEX_LENGTH || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    Some((hash.to_ascii_uppercase(), count.trim().parse().ok()?))
+}
@@ -0,0 +1,1464 @@
+//! Command-line interface for driving vaults from a terminal or a script without starting GTK.
+
+/*
+Copyright (C) 2025  Luke Wilkinson
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::{
+    models::{Account, AccountTotp},
+    password::{PasswordConfig, PasswordMode, generate_password},
+    security::SecurePassword,
+    storage::{
+        account_names::{hide_account_names, show_account_names},
+        archive::{ImportMode, export_vault, import_vault, verify_archive},
+        auto_backup::{BackupTrigger, run_auto_backup},
+        container::{convert_to_directories, convert_to_single_file},
+        filesystem::{get_available_accounts, get_available_vaults, rename_account},
+        manifest::{ManifestCheck, check_vault_manifest, sign_vault_manifest},
+        profile::{
+            HomeSelection, current_home, default_home, load_profiles, profiles_path, resolve_home,
+        },
+        vault_files,
+    },
+    totp::{
+        TotpAlgorithm, TotpParams, current_account_totp_code, decode_totp_secret, is_totp_required,
+        parse_otpauth_uri, read_otpauth_qr_image, verify_totp_code,
+    },
+    vault::{
+        Locations, add_vault_recipient, create_account, delete_account, get_full_account_details,
+        get_vault_recipients, recover_interrupted_rekey, rekey_vault, remove_vault_recipient,
+        update_account,
+    },
+};
+use anyhow::Error;
+use gpgme::{Context, Protocol};
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    io::{BufRead, IsTerminal, Write},
+    path::PathBuf,
+    rc::Rc,
+};
+use zeroize::Zeroize;
+
+pub const USAGE: &str = "\
+Usage: fmp [--home <dir> | --profile <name>] [COMMAND] [ARGS...]
+
+Starts the graphical interface when no command is given.
+
+Options (before the command, for the graphical interface too):
+  --home <dir>            Keep all data in <dir> instead of the user data directories
+  --profile <name>        Use a named profile, each with its own data (and GnuPG home)
+FMP_HOME and FMP_PROFILE set these from the environment instead.
+
+Commands:
+  vaults                                List all vaults
+  profiles                              List the profiles described in profiles.json
+  list <vault>                          List the accounts in a vault
+  show <vault> <account>                Show an account (password masked)
+      --reveal                          Show the password in plain text
+      --password-only                   Print only the password, for piping
+  code <vault> <account>                Print the account's current one-time code
+  add <vault> <account> [FIELDS]        Create an account
+  edit <vault> <account> [FIELDS]       Update fields of an existing account
+      --remove-field <key>              Remove an additional field (repeatable)
+  rename <vault> <account> <new-name>   Rename an account
+  delete <vault> <account>              Move an account to the vault's trash
+      --yes                             Do not ask for confirmation
+  members <vault>                       List the GPG keys a vault is encrypted to
+  add-member <vault> <key>              Share a vault with another GPG key
+  remove-member <vault> <key>           Stop sharing a vault with a GPG key
+  rekey <vault> <key>...                Re-encrypt a vault to new GPG keys only
+  convert <vault> <layout>              Store a vault as one encrypted file (single-file)
+                                        or one directory per account (directories);
+                                        the vault is backed up first
+  account-names <vault> <mode>          Store accounts under opaque IDs with their names
+                                        encrypted (hide), or under their names (show);
+                                        the vault is backed up first
+  verify <vault>                        Check a vault against its signed manifest
+      --trust                           Sign the vault as it is now, after checking it
+  export <vault> <file>                 Write a vault to a portable archive file
+  import <file> [<vault>]               Create a vault from an archive file, named as
+                                        in the archive unless <vault> is given
+      --replace                         Replace the existing vault (backed up first)
+      --verify-only                     Only check the archive, importing nothing
+      --yes                             Do not ask for confirmation
+  generate [GENERATOR OPTIONS]          Print a random password
+      --count <n>                       Number of passwords to print
+  help                                  Show this message
+
+Fields (add/edit):
+  --username <value>      --website <value>      --type <value>
+  --notes <value>         --field <key=value>    (repeatable)
+  --tags <a,b,...>        Replace the account's tags
+  --password-stdin        Read the password from standard input
+  --generate              Generate the password (accepts generator options)
+  --otp-stdin             Read a Base32 one-time code secret from standard input
+  --otp-algorithm <name>  One-time code hash: SHA1 (default), SHA256 or SHA512
+  --otp-digits <n>        Length of one-time codes, 6 (default) to 8
+  --otp-period <seconds>  How long each one-time code lasts, 1 to 300 (default 30)
+  --otp-skew <steps>      Periods a code may be early or late, 0 to 10 (default 1)
+  --otp-uri-stdin         Read an otpauth:// link from standard input
+  --otp-qr <image>        Read an otpauth QR code from a PNG or JPEG image
+  --remove-otp            Remove the one-time code secret (edit only)
+
+Generator options:
+  --length <n>  --symbols  --spaces  --extended  --no-lowercase
+  --no-uppercase  --no-numbers  --include <chars>  --exclude <chars>
+
+Passphrase options (instead of the generator options above):
+  --words <n>             Generate a passphrase of n words from the EFF wordlist
+  --separator <text>      Text between words (default -)
+  --capitalize  --append-digit  --append-symbol
+
+Vault commands accept --totp <code> for vaults with two-factor authentication enabled.
+";
+
+/// A parsed command-line invocation.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Help,
+    Vaults,
+    Profiles,
+    List {
+        vault: String,
+        totp: Option<String>,
+    },
+    Show {
+        vault: String,
+        account: String,
+        reveal: bool,
+        password_only: bool,
+        totp: Option<String>,
+    },
+    Code {
+        vault: String,
+        account: String,
+        totp: Option<String>,
+    },
+    Add {
+        vault: String,
+        account: String,
+        changes: AccountChanges,
+        totp: Option<String>,
+    },
+    Edit {
+        vault: String,
+        account: String,
+        changes: AccountChanges,
+        totp: Option<String>,
+    },
+    Rename {
+        vault: String,
+        account: String,
+        new_name: String,
+        totp: Option<String>,
+    },
+    Delete {
+        vault: String,
+        account: String,
+        assume_yes: bool,
+        totp: Option<String>,
+    },
+    Members {
+        vault: String,
+        totp: Option<String>,
+    },
+    AddMember {
+        vault: String,
+        recipient: String,
+        totp: Option<String>,
+    },
+    RemoveMember {
+        vault: String,
+        recipient: String,
+        totp: Option<String>,
+    },
+    Rekey {
+        vault: String,
+        recipients: Vec<String>,
+        totp: Option<String>,
+    },
+    Convert {
+        vault: String,
+        /// `true` to move the accounts into a container file, `false` to move them back out.
+        single_file: bool,
+        totp: Option<String>,
+    },
+    AccountNames {
+        vault: String,
+        /// `true` to store accounts under opaque IDs, `false` to store them under their names.
+        hide: bool,
+        totp: Option<String>,
+    },
+    Verify {
+        vault: String,
+        /// `true` to sign the vault as it is now, accepting any changes made outside fmp.
+        trust: bool,
+        totp: Option<String>,
+    },
+    Export {
+        vault: String,
+        file: PathBuf,
+        totp: Option<String>,
+    },
+    Import {
+        file: PathBuf,
+        vault: Option<String>,
+        replace: bool,
+        verify_only: bool,
+        assume_yes: bool,
+        totp: Option<String>,
+    },
+    Generate {
+        config: PasswordConfig,
+        count: usize,
+    },
+}
+
+/// Where a new password for an account should come from.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PasswordSource {
+    Stdin,
+    Generate(PasswordConfig),
+}
+
+/// A change to an account's one-time code secret.
+#[derive(Debug, PartialEq, Eq)]
+pub enum OtpChange {
+    /// Read a Base32 secret from standard input, generating codes with `params`.
+    Stdin(TotpParams),
+    /// Read an otpauth URI, which carries its own parameters, from standard input.
+    UriStdin,
+    /// Scan an otpauth QR code from an image file.
+    QrImage(PathBuf),
+    Remove,
+}
+
+/// Field changes requested by `add` or `edit`. `None` leaves a field untouched.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct AccountChanges {
+    pub username: Option<String>,
+    pub website: Option<String>,
+    pub account_type: Option<String>,
+    pub notes: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub fields: Vec<(String, String)>,
+    pub removed_fields: Vec<String>,
+    pub password: Option<PasswordSource>,
+    pub otp: Option<OtpChange>,
+}
+
+const GENERATOR_FLAGS: &[&str] = &[
+    "--symbols",
+    "--spaces",
+    "--extended",
+    "--no-lowercase",
+    "--no-uppercase",
+    "--no-numbers",
+    "--capitalize",
+    "--append-digit",
+    "--append-symbol",
+];
+const GENERATOR_VALUES: &[&str] = &[
+    "--length",
+    "--include",
+    "--exclude",
+    "--words",
+    "--separator",
+];
+const PASSPHRASE_OPTIONS: &[&str] = &[
+    "--words",
+    "--separator",
+    "--capitalize",
+    "--append-digit",
+    "--append-symbol",
+];
+const FIELD_VALUES: &[&str] = &[
+    "--username",
+    "--website",
+    "--type",
+    "--notes",
+    "--tags",
+    "--field",
+    "--remove-field",
+];
+const OTP_VALUES: &[&str] = &[
+    "--otp-algorithm",
+    "--otp-digits",
+    "--otp-period",
+    "--otp-skew",
+];
+const FIELD_FLAGS: &[&str] = &[
+    "--password-stdin",
+    "--generate",
+    "--otp-stdin",
+    "--otp-uri-stdin",
+    "--remove-otp",
+];
+
+/// Options and positional arguments collected for one subcommand.
+struct ParsedArgs {
+    positionals: Vec<String>,
+    values: Vec<(String, String)>,
+    flags: HashSet<String>,
+}
+
+impl ParsedArgs {
+    fn value(&self, name: &str) -> Option<String> {
+        self.values
+            .iter()
+            .rev()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+    }
+
+    fn all_values(&self, name: &str) -> Vec<String> {
+        self.values
+            .iter()
+            .filter(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+            .collect()
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+
+    fn expect_positionals(&self, command: &str, names: &[&str]) -> Result<(), Error> {
+        if self.positionals.len() == names.len() {
+            return Ok(());
+        }
+
+        let expected: Vec<String> = names.iter().map(|name| format!("<{name}>")).collect();
+        Err(anyhow::anyhow!(
+            "`{command}` expects {}, got {} argument(s).",
+            if expected.is_empty() {
+                "no arguments".to_string()
+            } else {
+                expected.join(" ")
+            },
+            self.positionals.len()
+        ))
+    }
+}
+
+/// Splits arguments into positionals, `--option value` pairs and boolean flags.
+///
+/// # Arguments
+/// * `args` - The arguments following the subcommand.
+/// * `value_options` - Options that take a value, either as `--name value` or `--name=value`.
+/// * `flag_options` - Options that take no value.
+///
+/// # Returns
+/// * `Result<ParsedArgs, Error>` - The sorted arguments on success, or an error on failure.
+///
+/// # Errors
+/// * If an unknown option is given, an option is missing its value, or a flag is given a value.
+fn split_args(
+    args: &[String],
+    value_options: &[&str],
+    flag_options: &[&str],
+) -> Result<ParsedArgs, Error> {
+    let mut parsed = ParsedArgs {
+        positionals: Vec::new(),
+        values: Vec::new(),
+        flags: HashSet::new(),
+    };
+
+    let mut iter = args.iter();
+    let mut options_done = false;
+
+    while let Some(arg) = iter.next() {
+        if options_done || !arg.starts_with("--") {
+            parsed.positionals.push(arg.clone());
+            continue;
+        }
+
+        if arg == "--" {
+            options_done = true;
+            continue;
+        }
+
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (arg.as_str(), None),
+        };
+
+        if value_options.contains(&name) {
+            let value = match inline_value {
+                Some(value) => value,
+                None => iter
+                    .next()
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("Option `{name}` requires a value."))?,
+            };
+            parsed.values.push((name.to_string(), value));
+        } else if flag_options.contains(&name) {
+            if inline_value.is_some() {
+                return Err(anyhow::anyhow!("Option `{name}` does not take a value."));
+            }
+            parsed.flags.insert(name.to_string());
+        } else {
+            return Err(anyhow::anyhow!("Unknown option `{name}`."));
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Takes `--home <dir>` or `--profile <name>` off the front of the process arguments.
+///
+/// # Arguments
+/// * `args` - The command-line arguments, excluding `argv[0]`.
+///
+/// # Returns
+/// * `Result<(Option<HomeSelection>, Vec<String>), Error>` - The chosen home, or `None` to use the
+///   environment, and the remaining arguments.
+///
+/// # Errors
+/// * If an option is missing its value, or both options are given.
+pub fn parse_global_options(
+    args: &[String],
+) -> Result<(Option<HomeSelection>, Vec<String>), Error> {
+    let mut selection = None;
+    let mut iter = args.iter().peekable();
+
+    while let Some(arg) = iter.next_if(|arg| {
+        ["--home", "--profile"]
+            .iter()
+            .any(|option| arg.as_str() == *option || arg.starts_with(&format!("{option}=")))
+    }) {
+        let (name, value) = match arg.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (arg.as_str(), iter.next().cloned()),
+        };
+        let value = value
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Option `{name}` requires a value."))?;
+
+        if selection.is_some() {
+            return Err(anyhow::anyhow!(
+                "Only one of `--home` and `--profile` can be used."
+            ));
+        }
+        selection = Some(if name == "--home" {
+            HomeSelection::Home(PathBuf::from(value))
+        } else {
+            HomeSelection::Profile(value)
+        });
+    }
+
+    Ok((selection, iter.cloned().collect()))
+}
+
+/// Parses the process arguments (without the program name) into a `Command`.
+///
+/// # Arguments
+/// * `args` - The command-line arguments, excluding `argv[0]`.
+///
+/// # Returns
+/// * `Result<Command, Error>` - The parsed command on success, or an error describing the misuse.
+///
+/// # Errors
+/// * If the subcommand is unknown, positional arguments are missing or extra, or options are invalid.
+pub fn parse_args(args: &[String]) -> Result<Command, Error> {
+    let Some((subcommand, rest)) = args.split_first() else {
+        return Ok(Command::Help);
+    };
+
+    match subcommand.as_str() {
+        "help" | "--help" | "-h" => Ok(Command::Help),
+        "vaults" => {
+            let parsed = split_args(rest, &[], &[])?;
+            parsed.expect_positionals("vaults", &[])?;
+            Ok(Command::Vaults)
+        }
+        "profiles" => {
+            let parsed = split_args(rest, &[], &[])?;
+            parsed.expect_positionals("profiles", &[])?;
+            Ok(Command::Profiles)
+        }
+        "list" => {
+            let parsed = split_args(rest, &["--totp"], &[])?;
+            parsed.expect_positionals("list", &["vault"])?;
+            Ok(Command::List {
+                vault: parsed.positionals[0].clone(),
+                totp: parsed.value("--totp"),
+            })
+        }
+        "show" => {
+            let parsed = split_args(rest, &["--totp"], &["--reveal", "--password-only"])?;
+            parsed.expect_positionals("show", &["vault", "account"])?;
+            Ok(Command::Show {
+                vault: parsed.positionals[0].clone(),
+                account: parsed.positionals[1].clone(),
+                reveal: parsed.flag("--reveal"),
+                password_only: parsed.flag("--password-only"),
+                totp: parsed.value("--totp"),
+            })
+        }
+        "code" => {
+            let parsed = split_args(rest, &["--totp"], &[])?;
+            parsed.expect_positionals("code", &["vault", "account"])?;
+            Ok(Command::Code {
+                vault: parsed.positionals[0].clone(),
+                account: parsed.positionals[1].clone(),
+                totp: parsed.value("--totp"),
+            })
+        }
+        "add" | "edit" => {
+            let values: Vec<&str> = [
+                &["--totp", "--otp-qr"],
+                FIELD_VALUES,
+                OTP_VALUES,
+                GENERATOR_VALUES,
+            ]
+            .concat();
+            let flags: Vec<&str> = [FIELD_FLAGS, GENERATOR_FLAGS].concat();
+            let parsed = split_args(rest, &values, &flags)?;
+            parsed.expect_positionals(subcommand, &["vault", "account"])?;
+
+            if subcommand == "add" && !parsed.all_values("--remove-field").is_empty() {
+                return Err(anyhow::anyhow!("`add` does not accept `--remove-field`."));
+            }
+            if subcommand == "add" && parsed.flag("--remove-otp") {
+                return Err(anyhow::anyhow!("`add` does not accept `--remove-otp`."));
+            }
+
+            let vault = parsed.positionals[0].clone();
+            let account = parsed.positionals[1].clone();
+            let changes = parse_account_changes(&parsed)?;
+            let totp = parsed.value("--totp");
+
+            Ok(if subcommand == "add" {
+                Command::Add {
+                    vault,
+                    account,
+                    changes,
+                    totp,
+                }
+            } else {
+                Command::Edit {
+                    vault,
+                    account,
+                    changes,
+                    totp,
+                }
+            })
+        }
+        "rename" => {
+            let parsed = split_args(rest, &["--totp"], &[])?;
+            parsed.expect_positionals("rename", &["vault", "account", "new-name"])?;
+            Ok(Command::Rename {
+                vault: parsed.positionals[0].clone(),
+                account: parsed.positionals[1].clone(),
+                new_name: parsed.positionals[2].clone(),
+                totp: parsed.value("--totp"),
+            })
+        }
+        "delete" => {
+            let parsed = split_args(rest, &["--totp"], &["--yes"])?;
+            parsed.expect_positionals("delete", &["vault", "account"])?;
+            Ok(Command::Delete {
+                vault: parsed.positionals[0].clone(),
+                account: parsed.positionals[1].clone(),
+                assume_yes: parsed.flag("--yes"),
+                totp: parsed.value("--totp"),
+            })
+        }
+        "members" => {
+            let parsed = split_args(rest, &["--totp"], &[])?;
+            parsed.expect_positionals("members", &["vault"])?;
+            Ok(Command::Members {
+                vault: parsed.positionals[0].clone(),
+                totp: parsed.value("--totp"),
+            })
+        }
+        "add-member" | "remove-member" => {
+            let parsed = split_args(rest, &["--totp"], &[])?;
+            parsed.expect_positionals(subcommand, &["vault", "key"])?;
+
+            let vault = parsed.positionals[0].clone();
+            let recipient = parsed.positionals[1].clone();
+            let totp = parsed.value("--totp");
+
+            Ok(if subcommand == "add-member" {
+                Command::AddMember {
+                    vault,
+                    recipient,
+                    totp,
+                }
+            } else {
+                Command::RemoveMember {
+                    vault,
+                    recipient,
+                    totp,
+                }
+            })
+        }
+        "rekey" => {
+            let parsed = split_args(rest, &["--totp"], &[])?;
+            if parsed.positionals.len() < 2 {
+                return Err(anyhow::anyhow!(
+                    "`rekey` expects <vault> followed by at least one <key>."
+                ));
+            }
+
+            Ok(Command::Rekey {
+                vault: parsed.positionals[0].clone(),
+                recipients: parsed.positionals[1..].to_vec(),
+                totp: parsed.value("--totp"),
+            })
+        }
+        "convert" => {
+            let parsed = split_args(rest, &["--totp"], &[])?;
+            parsed.expect_positionals("convert", &["vault", "layout"])?;
+
+            let single_file = match parsed.positionals[1].as_str() {
+                "single-file" => true,
+                "directories" => false,
+                other => {
+                    return Err(anyhow::anyhow!(
+                        "Unknown layout `{other}`. Use `single-file` or `directories`."
+                    ));
+                }
+            };
+
+            Ok(Command::Convert {
+                vault: parsed.positionals[0].clone(),
+                single_file,
+                totp: parsed.value("--totp"),
+            })
+        }
+        "account-names" => {
+            let parsed = split_args(rest, &["--totp"], &[])?;
+            parsed.expect_positionals("account-names", &["vault", "mode"])?;
+
+            let hide = match parsed.positionals[1].as_str() {
+                "hide" => true,
+                "show" => false,
+                other => {
+                    return Err(anyhow::anyhow!(
+                        "Unknown mode `{other}`. Use `hide` or `show`."
+                    ));
+                }
+            };
+
+            Ok(Command::AccountNames {
+                vault: parsed.positionals[0].clone(),
+                hide,
+                totp: parsed.value("--totp"),
+            })
+        }
+        "verify" => {
+            let parsed = split_args(rest, &["--totp"], &["--trust"])?;
+            parsed.expect_positionals("verify", &["vault"])?;
+            Ok(Command::Verify {
+                vault: parsed.positionals[0].clone(),
+                trust: parsed.flag("--trust"),
+                totp: parsed.value("--totp"),
+            })
+        }
+        "export" => {
+            let parsed = split_args(rest, &["--totp"], &[])?;
+            parsed.expect_positionals("export", &["vault", "file"])?;
+            Ok(Command::Export {
+                vault: parsed.positionals[0].clone(),
+                file: PathBuf::from(&parsed.positionals[1]),
+                totp: parsed.value("--totp"),
+            })
+        }
+        "import" => {
+            let parsed = split_args(rest, &["--totp"], &["--replace", "--verify-only", "--yes"])?;
+            if !(1..=2).contains(&parsed.positionals.len()) {
+                return Err(anyhow::anyhow!(
+                    "`import` expects <file> optionally followed by <vault>."
+                ));
+            }
+            if parsed.flag("--replace") && parsed.flag("--verify-only") {
+                return Err(anyhow::anyhow!(
+                    "`--replace` and `--verify-only` cannot be used together."
+                ));
+            }
+
+            Ok(Command::Import {
+                file: PathBuf::from(&parsed.positionals[0]),
+                vault: parsed.positionals.get(1).cloned(),
+                replace: parsed.flag("--replace"),
+                verify_only: parsed.flag("--verify-only"),
+                assume_yes: parsed.flag("--yes"),
+                totp: parsed.value("--totp"),
+            })
+        }
+        "generate" => {
+            let values: Vec<&str> = [&["--count"], GENERATOR_VALUES].concat();
+            let parsed = split_args(rest, &values, GENERATOR_FLAGS)?;
+            parsed.expect_positionals("generate", &[])?;
+
+            let count = match parsed.value("--count") {
+                Some(count) => parse_number("--count", &count)?,
+                None => 1,
+            };
+
+            Ok(Command::Generate {
+                config: parse_generator_options(&parsed)?,
+                count,
+            })
+        }
+        other => Err(anyhow::anyhow!("Unknown command `{other}`.")),
+    }
+}
+
+fn parse_number(option: &str, value: &str) -> Result<usize, Error> {
+    value
+        .parse::<usize>()
+        .map_err(|_| anyhow::anyhow!("Option `{option}` expects a number, got `{value}`."))
+}
+
+fn parse_generator_options(parsed: &ParsedArgs) -> Result<PasswordConfig, Error> {
+    let mut options = PasswordConfig::default();
+
+    let uses = |option: &str| parsed.flag(option) || parsed.value(option).is_some();
+    if let Some(words) = parsed.value("--words") {
+        if let Some(option) = GENERATOR_FLAGS
+            .iter()
+            .chain(GENERATOR_VALUES)
+            .find(|option| !PASSPHRASE_OPTIONS.contains(option) && uses(option))
+        {
+            return Err(anyhow::anyhow!("`{option}` cannot be used with `--words`."));
+        }
+
+        options.mode = PasswordMode::Passphrase;
+        options.passphrase.word_count = parse_number("--words", &words)?;
+        if let Some(separator) = parsed.value("--separator") {
+            options.passphrase.separator = separator;
+        }
+        options.passphrase.capitalize = parsed.flag("--capitalize");
+        options.passphrase.append_digit = parsed.flag("--append-digit");
+        options.passphrase.append_symbol = parsed.flag("--append-symbol");
+
+        return Ok(options);
+    }
+    if let Some(option) = PASSPHRASE_OPTIONS.iter().find(|option| uses(option)) {
+        return Err(anyhow::anyhow!("`{option}` requires `--words`."));
+    }
+
+    if let Some(length) = parsed.value("--length") {
+        options.length = parse_number("--length", &length)?;
+    }
+
+    options.include_symbols |= parsed.flag("--symbols");
+    options.include_spaces |= parsed.flag("--spaces");
+    options.include_extended |= parsed.flag("--extended");
+    options.include_lowercase &= !parsed.flag("--no-lowercase");
+    options.include_uppercase &= !parsed.flag("--no-uppercase");
+    options.include_numbers &= !parsed.flag("--no-numbers");
+
+    if let Some(include) = parsed.value("--include") {
+        options.additional_characters = include;
+    }
+    if let Some(exclude) = parsed.value("--exclude") {
+        options.excluded_characters = exclude;
+    }
+
+    Ok(options)
+}
+
+fn parse_otp_params(parsed: &ParsedArgs) -> Result<TotpParams, Error> {
+    let mut params = TotpParams::default();
+
+    if let Some(algorithm) = parsed.value("--otp-algorithm") {
+        params.algorithm = TotpAlgorithm::from_name(&algorithm).ok_or_else(|| {
+            anyhow::anyhow!("`--otp-algorithm` must be SHA1, SHA256 or SHA512, got `{algorithm}`.")
+        })?;
+    }
+    if let Some(digits) = parsed.value("--otp-digits") {
+        params.digits = u32::try_from(parse_number("--otp-digits", &digits)?)?;
+    }
+    if let Some(period) = parsed.value("--otp-period") {
+        params.period = u64::try_from(parse_number("--otp-period", &period)?)?;
+    }
+    if let Some(skew) = parsed.value("--otp-skew") {
+        params.skew = u32::try_from(parse_number("--otp-skew", &skew)?)?;
+    }
+
+    params.validate()?;
+    Ok(params)
+}
+
+fn parse_account_changes(parsed: &ParsedArgs) -> Result<AccountChanges, Error> {
+    let mut fields = Vec::new();
+    for field in parsed.all_values("--field") {
+        let (key, value) = field
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("`--field` expects `key=value`, got `{field}`."))?;
+        if key.is_empty() {
+            return Err(anyhow::anyhow!("`--field` requires a non-empty key."));
+        }
+        fields.push((key.to_string(), value.to_string()));
+    }
+
+    let uses_generator_options = GENERATOR_FLAGS.iter().any(|flag| parsed.flag(flag))
+        || GENERATOR_VALUES
+            .iter()
+            .any(|option| parsed.value(option).is_some());
+
+    let password = match (parsed.flag("--password-stdin"), parsed.flag("--generate")) {
+        (true, true) => {
+            return Err(anyhow::anyhow!(
+                "`--password-stdin` and `--generate` cannot be used together."
+            ));
+        }
+        (true, false) => Some(PasswordSource::Stdin),
+        (false, true) => Some(PasswordSource::Generate(parse_generator_options(parsed)?)),
+        (false, false) => None,
+    };
+
+    if uses_generator_options && !parsed.flag("--generate") {
+        return Err(anyhow::anyhow!("Generator options require `--generate`."));
+    }
+
+    let mut otp_changes = Vec::new();
+    if parsed.flag("--otp-stdin") {
+        otp_changes.push(OtpChange::Stdin(parse_otp_params(parsed)?));
+    }
+    if parsed.flag("--otp-uri-stdin") {
+        otp_changes.push(OtpChange::UriStdin);
+    }
+    if let Some(path) = parsed.value("--otp-qr") {
+        otp_changes.push(OtpChange::QrImage(PathBuf::from(path)));
+    }
+    if parsed.flag("--remove-otp") {
+        otp_changes.push(OtpChange::Remove);
+    }
+    if otp_changes.len() > 1 {
+        return Err(anyhow::anyhow!(
+            "Only one of `--otp-stdin`, `--otp-uri-stdin`, `--otp-qr` and `--remove-otp` can be used."
+        ));
+    }
+    let otp = otp_changes.pop();
+
+    if OTP_VALUES
+        .iter()
+        .any(|option| parsed.value(option).is_some())
+        && !parsed.flag("--otp-stdin")
+    {
+        return Err(anyhow::anyhow!(
+            "One-time code options require `--otp-stdin`."
+        ));
+    }
+
+    Ok(AccountChanges {
+        username: parsed.value("--username"),
+        website: parsed.value("--website"),
+        account_type: parsed.value("--type"),
+        notes: parsed.value("--notes"),
+        tags: parsed
+            .value("--tags")
+            .map(|tags| Account::parse_tags(&tags)),
+        fields,
+        removed_fields: parsed.all_values("--remove-field"),
+        password,
+        otp,
+    })
+}
+
+/// Runs the command line interface and returns the process exit code.
+///
+/// # Arguments
+/// * `args` - The command-line arguments, excluding `argv[0]`.
+///
+/// # Returns
+/// * `i32` - `0` on success, `1` when the command fails and `2` on invalid usage.
+pub fn run(args: &[String]) -> i32 {
+    crate::crypto::disable_core_dumps();
+
+    let command = match parse_args(args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("fmp: {e}");
+            eprintln!("Run `fmp help` for usage.");
+            return 2;
+        }
+    };
+
+    match execute(command) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("fmp: {e}");
+            1
+        }
+    }
+}
+
+/// Executes a parsed command, writing results to standard output.
+///
+/// # Arguments
+/// * `command` - The command to execute.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault cannot be unlocked or the underlying vault operation fails.
+pub fn execute(command: Command) -> Result<(), Error> {
+    match command {
+        Command::Help => {
+            print!("{USAGE}");
+        }
+        Command::Vaults => {
+            let mut vaults = get_available_vaults();
+            vaults.sort();
+            for vault in vaults {
+                println!("{vault}");
+            }
+        }
+        Command::Profiles => {
+            let profiles = load_profiles(&profiles_path())?;
+            let active = current_home().profile;
+
+            let mut names: Vec<&String> = profiles.keys().collect();
+            // A profile that is not described in the file can still be in use
+            if let Some(name) = &active
+                && !profiles.contains_key(name)
+            {
+                names.push(name);
+                names.sort();
+            }
+
+            for name in names {
+                let home = resolve_home(
+                    &HomeSelection::Profile(name.clone()),
+                    &profiles,
+                    &default_home(),
+                )?;
+                let marker = if active.as_ref() == Some(name) {
+                    "*"
+                } else {
+                    " "
+                };
+                println!("{marker} {name}\t{}", home.data.display());
+            }
+        }
+        Command::List { vault, totp } => {
+            unlock_vault(&vault, totp.as_deref())?;
+
+            let mut accounts = get_available_accounts(&vault);
+            accounts.sort();
+            for account in accounts {
+                println!("{account}");
+            }
+        }
+        Command::Show {
+            vault,
+            account,
+            reveal,
+            password_only,
+            totp,
+        } => {
+            unlock_vault(&vault, totp.as_deref())?;
+
+            let account = get_full_account_details(&vault, &account)?;
+            if password_only {
+                account
+                    .password
+                    .with_exposed(|password| println!("{password}"));
+            } else {
+                print_account(&account, reveal);
+            }
+        }
+        Command::Code {
+            vault,
+            account,
+            totp,
+        } => {
+            unlock_vault(&vault, totp.as_deref())?;
+
+            let account = get_full_account_details(&vault, &account)?;
+            let otp = account.totp.as_ref().ok_or_else(|| {
+                anyhow::anyhow!("Account `{}` has no one-time code secret.", account.name)
+            })?;
+            let (code, _) = current_account_totp_code(otp)?;
+            println!("{code}");
+        }
+        Command::Add {
+            vault,
+            account,
+            changes,
+            totp,
+        } => {
+            unlock_vault(&vault, totp.as_deref())?;
+
+            if vault_files::exists(&Locations::new(&vault, &account).account) {
+                return Err(anyhow::anyhow!(
+                    "Account `{account}` already exists in vault `{vault}`."
+                ));
+            }
+
+            let mut new_account = Account {
+                name: account.clone(),
+                ..Account::default()
+            };
+            apply_changes(&mut new_account, changes)?;
+            create_account(&vault, &new_account)?;
+
+            println!("Created account `{account}` in vault `{vault}`.");
+            auto_backup_after_change(&vault);
+        }
+        Command::Edit {
+            vault,
+            account,
+            changes,
+            totp,
+        } => {
+            unlock_vault(&vault, totp.as_deref())?;
+
+            let mut existing = get_full_account_details(&vault, &account)?;
+            existing.name = account.clone();
+            apply_changes(&mut existing, changes)?;
+            existing.update_modified_time();
+            update_account(&vault, &existing)?;
+
+            println!("Updated account `{account}` in vault `{vault}`.");
+            auto_backup_after_change(&vault);
+        }
+        Command::Rename {
+            vault,
+            account,
+            new_name,
+            totp,
+        } => {
+            unlock_vault(&vault, totp.as_deref())?;
+
+            rename_account(&vault, &account, &new_name)?;
+
+            println!("Renamed account `{account}` to `{new_name}`.");
+            auto_backup_after_change(&vault);
+        }
+        Command::Delete {
+            vault,
+            account,
+            assume_yes,
+            totp,
+        } => {
+            unlock_vault(&vault, totp.as_deref())?;
+
+            if !assume_yes && !confirm(&format!("Delete account `{account}` from `{vault}`?"))? {
+                println!("Cancelled.");
+                return Ok(());
+            }
+
+            delete_account(&vault, &account)?;
+
+            println!("Moved account `{account}` to the trash of vault `{vault}`.");
+            auto_backup_after_change(&vault);
+        }
+        Command::Members { vault, totp } => {
+            unlock_vault(&vault, totp.as_deref())?;
+
+            for recipient in get_vault_recipients(&vault)? {
+                println!("{recipient}");
+            }
+        }
+        Command::AddMember {
+            vault,
+            recipient,
+            totp,
+        } => {
+            unlock_vault(&vault, totp.as_deref())?;
+
+            add_vault_recipient(&vault, &recipient)?;
+
+            println!("Added `{recipient}` to vault `{vault}` and re-encrypted all accounts.");
+        }
+        Command::RemoveMember {
+            vault,
+            recipient,
+            totp,
+        } => {
+            unlock_vault(&vault, totp.as_deref())?;
+
+            remove_vault_recipient(&vault, &recipient)?;
+
+            println!("Removed `{recipient}` from vault `{vault}` and re-encrypted all accounts.");
+        }
+        Command::Rekey {
+            vault,
+            recipients,
+            totp,
+        } => {
+            unlock_vault(&vault, totp.as_deref())?;
+
+            rekey_vault(&vault, &recipients)?;
+
+            println!("Re-encrypted vault `{vault}` to {}.", recipients.join(", "));
+        }
+        Command::Convert {
+            vault,
+            single_file,
+            totp,
+        } => {
+            unlock_vault(&vault, totp.as_deref())?;
+
+            if single_file {
+                let moved = convert_to_single_file(&vault)?;
+                println!("Moved {moved} files of vault `{vault}` into a single file.");
+            } else {
+                let moved = convert_to_directories(&vault)?;
+                println!("Moved {moved} files of vault `{vault}` back into account directories.");
+            }
+        }
+        Command::AccountNames { vault, hide, totp } => {
+            unlock_vault(&vault, totp.as_deref())?;
+
+            if hide {
+                let moved = hide_account_names(&vault)?;
+                println!("Moved {moved} accounts of vault `{vault}` under opaque IDs.");
+            } else {
+                let moved = show_account_names(&vault)?;
+                println!("Moved {moved} accounts of vault `{vault}` back under their names.");
+            }
+        }
+        Command::Verify { vault, trust, totp } => {
+            let check = unlock_vault(&vault, totp.as_deref())?;
+
+            if trust {
+                let version = sign_vault_manifest(&vault)?;
+                println!("Signed vault `{vault}` as it is now (version {version}).");
+            } else if !check.is_ok() {
+                return Err(anyhow::anyhow!(
+                    "Vault `{vault}` does not match its signed manifest."
+                ));
+            } else if let Some(version) = check.version {
+                println!("Vault `{vault}` matches its signed manifest (version {version}).");
+            } else {
+                println!("Vault `{vault}` has no signed manifest.");
+            }
+        }
+        Command::Export { vault, file, totp } => {
+            unlock_vault(&vault, totp.as_deref())?;
+
+            export_vault(&vault, &file)?;
+
+            println!(
+                "Exported vault `{vault}` ({} accounts) to `{}`.",
+                get_available_accounts(&vault).len(),
+                file.display()
+            );
+        }
+        Command::Import {
+            file,
+            vault,
+            replace,
+            verify_only,
+            assume_yes,
+            totp,
+        } => {
+            let manifest = verify_archive(&file)?;
+            println!(
+                "Verified archive of vault `{}` made {}: {} accounts, {} files.",
+                manifest.vault_name,
+                manifest.created_at,
+                manifest.account_names().len(),
+                manifest.files.len()
+            );
+            if verify_only {
+                return Ok(());
+            }
+
+            let vault = vault.unwrap_or(manifest.vault_name);
+            let mode = if replace {
+                unlock_vault(&vault, totp.as_deref())?;
+
+                if !assume_yes
+                    && !confirm(&format!(
+                        "Replace all data in vault `{vault}` with the archive?"
+                    ))?
+                {
+                    println!("Cancelled.");
+                    return Ok(());
+                }
+                ImportMode::Replace
+            } else {
+                ImportMode::New
+            };
+
+            import_vault(&file, &vault, mode)?;
+
+            if replace {
+                println!("Replaced vault `{vault}`; its previous data was backed up first.");
+            } else {
+                println!("Imported vault `{vault}`.");
+            }
+        }
+        Command::Generate { config, count } => {
+            for _ in 0..count {
+                let password = generate_password(&config).map_err(|e| anyhow::anyhow!(e))?;
+                println!("{password}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Applies the requested field changes to an account.
+fn apply_changes(account: &mut Account, changes: AccountChanges) -> Result<(), Error> {
+    if let Some(username) = changes.username {
+        account.username = username;
+    }
+    if let Some(website) = changes.website {
+        account.website = website;
+    }
+    if let Some(account_type) = changes.account_type {
+        account.account_type = account_type;
+    }
+    if let Some(notes) = changes.notes {
+        account.notes = notes;
+    }
+    if let Some(tags) = changes.tags {
+        account.tags = tags;
+    }
+    for (key, value) in changes.fields {
+        account.additional_fields.insert(key, value);
+    }
+    for key in changes.removed_fields {
+        if account.additional_fields.remove(&key).is_none() {
+            return Err(anyhow::anyhow!("Field `{key}` does not exist."));
+        }
+    }
+
+    match changes.password {
+        Some(PasswordSource::Stdin) => {
+            account.password = SecurePassword::new(read_secret("Password: ")?);
+        }
+        Some(PasswordSource::Generate(options)) => {
+            let password = generate_password(&options).map_err(|e| anyhow::anyhow!(e))?;
+            account.password = SecurePassword::new(password);
+        }
+        None => {}
+    }
+
+    match changes.otp {
+        Some(OtpChange::Stdin(params)) => {
+            let secret = read_secret("One-time code secret: ")?;
+            decode_totp_secret(&secret)?;
+            account.totp = Some(AccountTotp {
+                secret: SecurePassword::new(secret),
+                params,
+                ..AccountTotp::default()
+            });
+        }
+        Some(OtpChange::UriStdin) => {
+            let mut uri = read_secret("otpauth link: ")?;
+            let imported = parse_otpauth_uri(&uri);
+            uri.zeroize();
+            account.totp = Some(imported?);
+        }
+        Some(OtpChange::QrImage(path)) => account.totp = Some(read_otpauth_qr_image(&path)?),
+        Some(OtpChange::Remove) => account.totp = None,
+        None => {}
+    }
+
+    Ok(())
+}
+
+fn print_account(account: &Account, reveal: bool) {
+    let password = if reveal {
+        account.password.with_exposed(str::to_string)
+    } else {
+        account.password.masked(8)
+    };
+
+    println!("Name:      {}", account.name);
+    println!("Type:      {}", account.account_type);
+    println!("Website:   {}", account.website);
+    println!("Username:  {}", account.username);
+    if !account.tags.is_empty() {
+        println!("Tags:      {}", account.tags_display());
+    }
+    println!("Password:  {password}");
+    if let Some(totp) = &account.totp {
+        match current_account_totp_code(totp) {
+            Ok((code, seconds_remaining)) => {
+                println!("Code:      {code} ({seconds_remaining}s left)");
+            }
+            Err(e) => println!("Code:      unavailable ({e})"),
+        }
+    }
+    println!("Created:   {}", account.created_at);
+    println!("Modified:  {}", account.modified_at);
+
+    if !account.additional_fields.is_empty() {
+        println!("Fields:");
+        let mut fields: Vec<_> = account.additional_fields.iter().collect();
+        fields.sort();
+        for (key, value) in fields {
+            println!("  {key}: {value}");
+        }
+    }
+
+    if !account.notes.is_empty() {
+        println!("Notes:");
+        for line in account.notes.lines() {
+            println!("  {line}");
+        }
+    }
+}
+
+/// Checks the vault exists, asks for the TOTP code when the vault requires one, and checks the vault
+/// against its signed manifest. Anything the manifest does not vouch for is printed as a warning.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault to unlock.
+/// * `totp_code` - A code given with `--totp`, if any.
+///
+/// # Returns
+/// * `Result<ManifestCheck, Error>` - Returns the manifest check when the vault may be used, or an
+///   error otherwise.
+///
+/// # Errors
+/// * If the vault does not exist, no code can be read, the code is wrong, or the vault's files
+///   cannot be read.
+fn unlock_vault(vault_name: &str, totp_code: Option<&str>) -> Result<ManifestCheck, Error> {
+    Locations::new(vault_name, "").does_vault_exist()?;
+    recover_interrupted_rekey(vault_name)?;
+
+    if is_totp_required(vault_name) {
+        check_totp(vault_name, totp_code)?;
+    }
+
+    let check = check_vault_manifest(vault_name)?;
+    for problem in &check.problems {
+        eprintln!("fmp: warning: vault `{vault_name}`: {problem}");
+    }
+    if !check.is_ok() {
+        eprintln!(
+            "fmp: warning: make sure vault `{vault_name}` is as you left it, then run `fmp verify {vault_name} --trust`; changes are not signed until then."
+        );
+    }
+
+    Ok(check)
+}
+
+/// Asks for the vault's TOTP code, unless one was given, and checks it.
+fn check_totp(vault_name: &str, totp_code: Option<&str>) -> Result<(), Error> {
+    let code = match totp_code {
+        Some(code) => code.to_string(),
+        None if std::io::stdin().is_terminal() => prompt("Two-factor code: ")?,
+        None => {
+            return Err(anyhow::anyhow!(
+                "Vault `{vault_name}` requires two-factor authentication; pass `--totp <code>`."
+            ));
+        }
+    };
+
+    let ctx = Rc::new(RefCell::new(Context::from_protocol(Protocol::OpenPgp)?));
+    if verify_totp_code(vault_name, &code, ctx)? {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("Invalid two-factor code."))
+    }
+}
+
+/// Makes any automatic backup the vault's policy asks for once a command has changed it. A command
+/// ending counts as closing fmp. A failed backup is reported without failing the command, since the
+/// change itself was saved.
+///
+/// # Arguments
+/// * `vault_name` - The name of the changed vault.
+fn auto_backup_after_change(vault_name: &str) {
+    match run_auto_backup(vault_name, BackupTrigger::Exit) {
+        Ok(Some(snapshot)) => println!(
+            "Backed up vault `{vault_name}` automatically ({}).",
+            snapshot.created_at_display()
+        ),
+        Ok(None) => {}
+        Err(e) => eprintln!("fmp: automatic backup of `{vault_name}` failed: {e}"),
+    }
+}
+
+fn confirm(question: &str) -> Result<bool, Error> {
+    if !std::io::stdin().is_terminal() {
+        return Err(anyhow::anyhow!(
+            "Refusing to continue without confirmation; pass `--yes`."
+        ));
+    }
+
+    let answer = prompt(&format!("{question} [y/N] "))?;
+    Ok(matches!(answer.to_lowercase().as_str(), "y" | "yes"))
+}
+
+fn prompt(message: &str) -> Result<String, Error> {
+    eprint!("{message}");
+    std::io::stderr().flush()?;
+
+    let mut line = String::new();
+    std::io::stdin().lock().read_line(&mut line)?;
+    Ok(line.trim().to_string())
+}
+
+/// Reads one line from standard input without echoing it when stdin is a terminal.
+fn read_secret(message: &str) -> Result<String, Error> {
+    let stdin = std::io::stdin();
+    let interactive = stdin.is_terminal();
+
+    if interactive {
+        eprint!("{message}");
+        std::io::stderr().flush()?;
+    }
+
+    #[cfg(unix)]
+    let saved_termios = if interactive { disable_echo() } else { None };
+
+    let mut line = String::new();
+    let result = stdin.lock().read_line(&mut line);
+
+    #[cfg(unix)]
+    if let Some(termios) = saved_termios {
+        // SAFETY: restores the attributes previously read from the same descriptor.
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios);
+        }
+        eprintln!();
+    }
+
+    result?;
+
+    let trimmed_length = line.trim_end_matches(['\n', '\r']).len();
+    line.truncate(trimmed_length);
+    Ok(line)
+}
+
+#[cfg(unix)]
+fn disable_echo() -> Option<libc::termios> {
+    // SAFETY: `termios` is plain data filled in by `tcgetattr` before it is read.
+    unsafe {
+        let mut termios: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) != 0 {
+            return None;
+        }
+
+        let saved = termios;
+        termios.c_lflag &= !libc::ECHO;
+        if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) != 0 {
+            return None;
+        }
+
+        Some(saved)
+    }
+}
@@ -6,9 +6,13 @@ use crate::gui::{
         welcome::{is_first_run, show_welcome_dialog},
     },
     sidebar::create_paned_layout_with_callbacks,
-    views::home_view::HomeView,
+    views::{
+        home_view::HomeView,
+        vault_view::{opened_vaults, run_auto_backups},
+    },
 };
-use adw::{Application, ApplicationWindow, HeaderBar};
+use crate::storage::{auto_backup::BackupTrigger, profile::current_home};
+use adw::{Application, ApplicationWindow, HeaderBar, ToastOverlay};
 use gpgme::{Context, Protocol};
 use gtk4::{
     Box, ButtonsType, CssProvider, DialogFlags, Label, MessageDialog, MessageType, Orientation,
@@ -17,17 +21,39 @@ use gtk4::{
 use std::cell::RefCell;
 use std::rc::Rc;
 
+/// How often vaults opened in this session are checked for due automatic backups.
+const AUTO_BACKUP_CHECK_INTERVAL_SECONDS: u32 = 60 * 60;
+
 pub fn run_gui() {
     crate::crypto::disable_core_dumps();
 
+    // Another profile's window must not be raised in place of this one
+    let flags = if current_home().is_default() {
+        gio::ApplicationFlags::empty()
+    } else {
+        gio::ApplicationFlags::NON_UNIQUE
+    };
     let application = adw::Application::builder()
         .application_id("org.codeberg.fmp")
+        .flags(flags)
         .build();
     application.connect_activate(|app| {
         run_ui(app);
     });
 
-    application.run();
+    // The data home options were already handled, so GTK is given no arguments to parse
+    let program = std::env::args().next().unwrap_or_default();
+    application.run_with_args(&[program]);
+}
+
+/// The window title, naming the profile or data directory when it is not the default one
+fn window_title() -> String {
+    let home = current_home();
+    if home.is_default() {
+        "Forgot My Password".to_string()
+    } else {
+        format!("Forgot My Password — {}", home.describe())
+    }
 }
 
 fn run_ui(app: &Application) {
@@ -85,12 +111,36 @@ fn run_ui(app: &Application) {
 
     let window = ApplicationWindow::builder()
         .application(app)
-        .title("Forgot My Password")
+        .title(window_title())
         .default_width(900)
         .default_height(600)
         .build();
 
-    window.set_content(Some(&paned_layout));
+    // Toasts raised anywhere in the content area show over the whole window
+    let toast_overlay = ToastOverlay::new();
+    toast_overlay.set_child(Some(&paned_layout));
+
+    window.set_content(Some(&toast_overlay));
+
+    // Back up vaults that ask for it when closing, with the window hidden so closing never waits
+    window.connect_close_request(|window| {
+        let vaults = opened_vaults();
+        if vaults.is_empty() {
+            return glib::Propagation::Proceed;
+        }
+
+        window.set_visible(false);
+        let window = window.clone();
+        run_auto_backups(vaults, BackupTrigger::Exit, move |_| window.destroy());
+        glib::Propagation::Stop
+    });
+
+    // Daily backups are checked hourly, so they still happen if fmp is left open
+    glib::timeout_add_seconds_local(AUTO_BACKUP_CHECK_INTERVAL_SECONDS, || {
+        run_auto_backups(opened_vaults(), BackupTrigger::Activity, |_| {});
+        glib::ControlFlow::Continue
+    });
+
     window.present();
 
     if is_first_run() {
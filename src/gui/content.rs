@@ -1,16 +1,30 @@
 use crate::{
-    gui::{dialogs::totp::show_totp_authentication_dialog, views::vault_view::VaultView},
+    gui::{
+        dialogs::{common::show_confirmation_dialog, totp::show_totp_authentication_dialog},
+        views::vault_view::VaultView,
+    },
+    storage::manifest::{ManifestProblem, check_vault_manifest, sign_vault_manifest},
     totp::is_totp_required,
     vault::{Account, warm_up_gpg_blocking, warm_up_gpg_finalize},
 };
-use adw::{ActionRow, ButtonContent, glib::closure::IntoClosureReturnValue, prelude::*};
+use adw::{
+    ActionRow, ButtonContent, Toast, ToastOverlay, glib::closure::IntoClosureReturnValue,
+    prelude::*,
+};
 use gpgme::Context;
 use gtk4::{
-    Align, Box, Button, Entry, Label, Orientation, PolicyType, ScrolledWindow, pango::EllipsizeMode,
+    Align, Box, Button, Entry, Label, Orientation, PolicyType, ScrolledWindow, gio, glib,
+    pango::EllipsizeMode,
+};
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
 };
-use std::{cell::RefCell, rc::Rc};
 use zeroize::Zeroize;
 
+static TOAST_ACTION_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
 /// Creates a field row with label and value
 pub fn create_field_row(label_text: &str, value_text: &str, copyable: bool) -> Box {
     let row_box = Box::new(Orientation::Horizontal, 16);
@@ -115,6 +129,7 @@ pub fn create_editable_field_row(
                 text.zeroize();
             }
             "notes" => account.notes = text,
+            "tags" => account.tags = Account::parse_tags(&text),
             _ => {}
         }
     });
@@ -125,6 +140,70 @@ pub fn create_editable_field_row(
     row_box
 }
 
+/// Shows a toast over the window containing `widget`
+pub fn show_toast(widget: &impl IsA<gtk4::Widget>, toast: Toast) {
+    match find_toast_overlay(widget) {
+        Some(overlay) => overlay.add_toast(toast),
+        None => log::warn!(
+            "No toast overlay to show '{}'",
+            toast.title().unwrap_or_default()
+        ),
+    }
+}
+
+/// Shows a toast with a button over the window containing `widget`
+/// Takes a callback that will be executed if the button is clicked
+pub fn show_toast_with_button<F>(
+    widget: &impl IsA<gtk4::Widget>,
+    toast: Toast,
+    button_label: &str,
+    on_clicked: F,
+) where
+    F: Fn() + 'static,
+{
+    let Some(overlay) = find_toast_overlay(widget) else {
+        log::warn!(
+            "No toast overlay to show '{}'",
+            toast.title().unwrap_or_default()
+        );
+        return;
+    };
+
+    // Each toast gets its own action group, so toasts shown together do not share a callback
+    let group_name = format!(
+        "toast{}",
+        TOAST_ACTION_COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    let action = gio::SimpleAction::new("clicked", None);
+    action.connect_activate(move |_, _| on_clicked());
+    let group = gio::SimpleActionGroup::new();
+    group.add_action(&action);
+    overlay.insert_action_group(&group_name, Some(&group));
+
+    toast.set_button_label(Some(button_label));
+    toast.set_action_name(Some(&format!("{group_name}.clicked")));
+    toast.connect_dismissed({
+        let overlay = overlay.clone();
+        move |_| {
+            // Clicking the button dismisses the toast, so wait for the action to run first
+            let overlay = overlay.clone();
+            let group_name = group_name.clone();
+            glib::idle_add_local_once(move || {
+                overlay.insert_action_group(&group_name, None::<&gio::SimpleActionGroup>);
+            });
+        }
+    });
+
+    overlay.add_toast(toast);
+}
+
+/// Finds the toast overlay of the window containing `widget`
+fn find_toast_overlay(widget: &impl IsA<gtk4::Widget>) -> Option<ToastOverlay> {
+    widget
+        .ancestor(ToastOverlay::static_type())
+        .and_then(|overlay| overlay.downcast::<ToastOverlay>().ok())
+}
+
 /// Clears all content from the content area
 pub fn clear_content(content_area: &Box) {
     while let Some(child) = content_area.first_child() {
@@ -176,7 +255,27 @@ fn proceed_with_gpg_warmup(content_area: &Box, vault_name: &str, ctx: Rc<RefCell
             Ok(encrypted) => {
                 // finalize on main thread using Rc<RefCell<Context>>
                 match warm_up_gpg_finalize(encrypted, ctx_main.clone()) {
-                    Ok(()) => VaultView::new(&content_area, &vault_name).create(ctx_main),
+                    Ok(()) => {
+                        // Checked before the vault view opens, since it may purge the trash and
+                        // sign the vault again
+                        let vault_bg = vault_name.clone();
+                        let check =
+                            gtk4::gio::spawn_blocking(move || check_vault_manifest(&vault_bg))
+                                .await
+                                .expect("Manifest check task panicked");
+
+                        VaultView::new(&content_area, &vault_name).create(ctx_main);
+
+                        match check {
+                            Ok(check) if !check.is_ok() => {
+                                show_tampering_warning(&content_area, &vault_name, &check.problems);
+                            }
+                            Ok(_) => {}
+                            Err(e) => {
+                                log::warn!("Failed to check the manifest of '{vault_name}': {e}");
+                            }
+                        }
+                    }
                     Err(e) => show_error_message(
                         &content_area,
                         "Failed to Access Vault",
@@ -195,6 +294,55 @@ fn proceed_with_gpg_warmup(content_area: &Box, vault_name: &str, ctx: Rc<RefCell
     });
 }
 
+/// Most problems listed in the tampering warning before the rest are only counted
+const MAX_LISTED_PROBLEMS: usize = 8;
+
+/// Warns that a vault does not match its manifest, offering to sign it as it is now
+fn show_tampering_warning(content_area: &Box, vault_name: &str, problems: &[ManifestProblem]) {
+    let mut message = format!(
+        "Vault '{vault_name}' was changed outside fmp since it was last used, or an older copy of it was put back:\n"
+    );
+    for problem in problems.iter().take(MAX_LISTED_PROBLEMS) {
+        message.push_str(&format!("\n• {problem}"));
+    }
+    if problems.len() > MAX_LISTED_PROBLEMS {
+        message.push_str(&format!(
+            "\n• and {} more",
+            problems.len() - MAX_LISTED_PROBLEMS
+        ));
+    }
+    message.push_str(
+        "\n\nIf you did not make these changes, restore the vault from a backup you trust. \
+         Otherwise, trust the vault as it is now to stop this warning. Until then, fmp does \
+         not sign changes to this vault.",
+    );
+
+    let content_area = content_area.clone();
+    let vault_name = vault_name.to_string();
+    show_confirmation_dialog(
+        "Vault May Have Been Tampered With",
+        &message,
+        "Trust Current State",
+        None::<&gtk4::Window>,
+        move || {
+            let content_area = content_area.clone();
+            let vault_name = vault_name.clone();
+            glib::spawn_future_local(async move {
+                let vault_bg = vault_name.clone();
+                let result = gio::spawn_blocking(move || sign_vault_manifest(&vault_bg))
+                    .await
+                    .expect("Manifest signing task panicked");
+
+                let text = match result {
+                    Ok(_) => format!("Vault '{vault_name}' is trusted as it is now"),
+                    Err(e) => format!("Failed to sign vault '{vault_name}': {e}"),
+                };
+                show_toast(&content_area, Toast::new(&glib::markup_escape_text(&text)));
+            });
+        },
+    );
+}
+
 /// Shows an error message in the content area
 fn show_error_message(content_area: &Box, title: &str, message: &str) {
     clear_content(content_area);
@@ -1,9 +1,17 @@
 use crate::{
-    gui::dialogs::common::show_error_dialog, storage::filesystem::rename_account, vault::Account,
+    gui::{content::clear_content, dialogs::common::show_error_dialog},
+    storage::filesystem::rename_account,
+    vault::{
+        Account,
+        revisions::{FieldChange, Revision, diff_accounts, get_account_revision},
+    },
 };
-use adw::prelude::*;
+use adw::{ActionRow, PreferencesGroup, prelude::*};
 use gpgme::Context;
-use gtk4::{Box as GtkBox, Button, Dialog, Entry, Label, Orientation};
+use gtk4::{
+    Box as GtkBox, Button, Dialog, DropDown, Entry, Label, Orientation, PolicyType, ScrolledWindow,
+    glib,
+};
 use std::{cell::RefCell, rc::Rc};
 
 /// Shows the rename account dialog
@@ -63,22 +71,41 @@ pub fn show_rename_account_dialog(
     rename_button.connect_clicked(move |_| {
         let new_name = name_entry_clone.text().to_string();
         if !new_name.is_empty() && new_name != account_name_clone {
-            match rename_account(&vault_name_clone, &account_name_clone, &new_name) {
-                Ok(()) => {
-                    dialog_clone.close();
-                    crate::gui::views::account_view::AccountView::new(
-                        &content_area_clone,
-                        &vault_name_clone,
-                        &new_name,
-                        false,
-                    )
-                    .create(ctx.clone());
-                }
-                Err(e) => {
-                    log::error!("Failed to rename account: {e}");
-                    show_error_dialog("Rename Failed", &format!("Could not rename account: {e}"));
+            let dialog = dialog_clone.clone();
+            let content_area = content_area_clone.clone();
+            let vault_name = vault_name_clone.clone();
+            let account_name = account_name_clone.clone();
+            let ctx = ctx.clone();
+
+            glib::spawn_future_local(async move {
+                let vault_task = vault_name.clone();
+                let new_name_task = new_name.clone();
+                let result = gtk4::gio::spawn_blocking(move || {
+                    rename_account(&vault_task, &account_name, &new_name_task)
+                })
+                .await
+                .expect("rename account task panicked");
+
+                match result {
+                    Ok(()) => {
+                        dialog.close();
+                        crate::gui::views::account_view::AccountView::new(
+                            &content_area,
+                            &vault_name,
+                            &new_name,
+                            false,
+                        )
+                        .create(ctx);
+                    }
+                    Err(e) => {
+                        log::error!("Failed to rename account: {e}");
+                        show_error_dialog(
+                            "Rename Failed",
+                            &format!("Could not rename account: {e}"),
+                        );
+                    }
                 }
-            }
+            });
         }
     });
 
@@ -181,29 +208,42 @@ pub fn show_add_field_dialog(
 
             account.additional_fields.insert(field_name, field_value);
             account.update_modified_time();
-            let account_name = account.name.clone();
-
-            match crate::vault::update_account(&vault_name_clone, &account) {
-                Ok(()) => {
-                    drop(account);
-                    dialog_clone.close();
-
-                    crate::gui::views::account_view::AccountView::new(
-                        &content_area_clone,
-                        &vault_name_clone,
-                        &account_name,
-                        true,
-                    ).create(ctx.clone());
+            let account = account.clone();
+            let dialog = dialog_clone.clone();
+            let content_area = content_area_clone.clone();
+            let vault_name = vault_name_clone.clone();
+            let ctx = ctx.clone();
+
+            glib::spawn_future_local(async move {
+                let account_name = account.name.clone();
+                let vault_task = vault_name.clone();
+                let result = gtk4::gio::spawn_blocking(move || {
+                    crate::vault::update_account(&vault_task, &account)
+                })
+                .await
+                .expect("update account task panicked");
+
+                match result {
+                    Ok(()) => {
+                        dialog.close();
+
+                        crate::gui::views::account_view::AccountView::new(
+                            &content_area,
+                            &vault_name,
+                            &account_name,
+                            true,
+                        )
+                        .create(ctx);
+                    }
+                    Err(e) => {
+                        log::error!("Failed to save account: {e}");
+                        show_error_dialog(
+                            "Save Error",
+                            "Failed to save the new field. Please try again.",
+                        );
+                    }
                 }
-                Err(e) => {
-                    drop(account);
-                    log::error!("Failed to save account: {e}");
-                    show_error_dialog(
-                        "Save Error",
-                        "Failed to save the new field. Please try again.",
-                    );
-                }
-            }
+            });
         } else {
             show_error_dialog("Invalid Input", "Both field name and value are required.");
         }
@@ -334,29 +374,42 @@ pub fn show_edit_field_dialog(
                 .insert(new_field_name, new_field_value);
 
             account.update_modified_time();
-            let account_name = account.name.clone();
-
-            match crate::vault::update_account(&vault_name_clone, &account) {
-                Ok(()) => {
-                    drop(account);
-                    dialog_clone.close();
-
-                    crate::gui::views::account_view::AccountView::new(
-                        &content_area_clone,
-                        &vault_name_clone,
-                        &account_name,
-                        true, // Keep in edit mode
-                    ).create(ctx.clone());
+            let account = account.clone();
+            let dialog = dialog_clone.clone();
+            let content_area = content_area_clone.clone();
+            let vault_name = vault_name_clone.clone();
+            let ctx = ctx.clone();
+
+            glib::spawn_future_local(async move {
+                let account_name = account.name.clone();
+                let vault_task = vault_name.clone();
+                let result = gtk4::gio::spawn_blocking(move || {
+                    crate::vault::update_account(&vault_task, &account)
+                })
+                .await
+                .expect("update account task panicked");
+
+                match result {
+                    Ok(()) => {
+                        dialog.close();
+
+                        crate::gui::views::account_view::AccountView::new(
+                            &content_area,
+                            &vault_name,
+                            &account_name,
+                            true, // Keep in edit mode
+                        )
+                        .create(ctx);
+                    }
+                    Err(e) => {
+                        log::error!("Failed to save account: {e}");
+                        show_error_dialog(
+                            "Save Error",
+                            "Failed to save the field changes. Please try again.",
+                        );
+                    }
                 }
-                Err(e) => {
-                    drop(account);
-                    log::error!("Failed to save account: {e}");
-                    show_error_dialog(
-                        "Save Error",
-                        "Failed to save the field changes. Please try again.",
-                    );
-                }
-            }
+            });
         } else {
             show_error_dialog("Invalid Input", "Both field name and value are required.");
         }
@@ -440,34 +493,48 @@ pub fn show_delete_field_dialog(
     let vault_name_clone = vault_name.to_string();
     let field_name_owned = field_name.to_string();
     delete_button.connect_clicked(move |_| {
-        let mut account = account_rc_clone.borrow_mut();
-        account.additional_fields.remove(&field_name_owned);
-        account.update_modified_time();
-        let account_name = account.name.clone();
+        let account = {
+            let mut account = account_rc_clone.borrow_mut();
+            account.additional_fields.remove(&field_name_owned);
+            account.update_modified_time();
+            account.clone()
+        };
+        let dialog = dialog_clone.clone();
+        let content_area = content_area_clone.clone();
+        let vault_name = vault_name_clone.clone();
+        let ctx = ctx.clone();
+
+        glib::spawn_future_local(async move {
+            let account_name = account.name.clone();
+            let vault_task = vault_name.clone();
+            let result = gtk4::gio::spawn_blocking(move || {
+                crate::vault::update_account(&vault_task, &account)
+            })
+            .await
+            .expect("update account task panicked");
+
+            match result {
+                Ok(()) => {
+                    dialog.close();
 
-        match crate::vault::update_account(&vault_name_clone, &account) {
-            Ok(()) => {
-                drop(account);
-                dialog_clone.close();
-
-                // Refresh the account view
-                crate::gui::views::account_view::AccountView::new(
-                    &content_area_clone,
-                    &vault_name_clone,
-                    &account_name,
-                    true, // Keep in edit mode
-                )
-                .create(ctx.clone());
-            }
-            Err(e) => {
-                drop(account);
-                log::error!("Failed to save account: {e}");
-                show_error_dialog(
-                    "Save Error",
-                    "Failed to delete the field. Please try again.",
-                );
+                    // Refresh the account view
+                    crate::gui::views::account_view::AccountView::new(
+                        &content_area,
+                        &vault_name,
+                        &account_name,
+                        true, // Keep in edit mode
+                    )
+                    .create(ctx);
+                }
+                Err(e) => {
+                    log::error!("Failed to save account: {e}");
+                    show_error_dialog(
+                        "Save Error",
+                        "Failed to delete the field. Please try again.",
+                    );
+                }
             }
-        }
+        });
     });
 
     button_box.append(&cancel_button);
@@ -477,3 +544,168 @@ pub fn show_delete_field_dialog(
     dialog.set_child(Some(&content_box));
     dialog.present();
 }
+
+/// Shows a field-level comparison between two versions of an account.
+///
+/// The versions to compare are picked from the account's revisions and its current version,
+/// starting with `from_id` compared to the current version.
+pub fn show_revision_diff_dialog(
+    vault_name: &str,
+    current: &Account,
+    revisions: &[Revision],
+    from_id: u32,
+) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some("Compare Revisions"));
+    dialog.set_modal(true);
+    dialog.set_default_size(520, 480);
+
+    let content_box = GtkBox::new(Orientation::Vertical, 16);
+    content_box.set_margin_top(20);
+    content_box.set_margin_bottom(20);
+    content_box.set_margin_start(20);
+    content_box.set_margin_end(20);
+
+    let title = Label::new(Some(&format!("Changes to '{}'", current.name)));
+    title.add_css_class("title-2");
+    title.set_halign(gtk4::Align::Center);
+    content_box.append(&title);
+
+    // Index 0 is the current version, the rest follow `revisions` (newest first).
+    let mut version_names = vec![format!("Current ({})", current.modified_at)];
+    version_names.extend(
+        revisions
+            .iter()
+            .map(|revision| format!("Revision {} ({})", revision.id, revision.saved_at)),
+    );
+    let version_names: Vec<&str> = version_names.iter().map(String::as_str).collect();
+
+    let from_dropdown = DropDown::from_strings(&version_names);
+    from_dropdown.set_hexpand(true);
+    let from_index = revisions
+        .iter()
+        .position(|revision| revision.id == from_id)
+        .map_or(0, |position| position + 1);
+    from_dropdown.set_selected(from_index as u32);
+
+    let to_dropdown = DropDown::from_strings(&version_names);
+    to_dropdown.set_hexpand(true);
+    to_dropdown.set_selected(0);
+
+    let pickers = GtkBox::new(Orientation::Horizontal, 12);
+    let from_label = Label::new(Some("From"));
+    from_label.add_css_class("dim-label");
+    let to_label = Label::new(Some("To"));
+    to_label.add_css_class("dim-label");
+    pickers.append(&from_label);
+    pickers.append(&from_dropdown);
+    pickers.append(&to_label);
+    pickers.append(&to_dropdown);
+    content_box.append(&pickers);
+
+    let changes_box = GtkBox::new(Orientation::Vertical, 12);
+    let scrolled_window = ScrolledWindow::new();
+    scrolled_window.set_policy(PolicyType::Never, PolicyType::Automatic);
+    scrolled_window.set_vexpand(true);
+    scrolled_window.set_child(Some(&changes_box));
+    content_box.append(&scrolled_window);
+
+    let refresh = {
+        let vault_name = vault_name.to_string();
+        let current = current.clone();
+        let revision_ids: Vec<u32> = revisions.iter().map(|revision| revision.id).collect();
+        let from_dropdown = from_dropdown.clone();
+        let to_dropdown = to_dropdown.clone();
+        let changes_box = changes_box.clone();
+
+        Rc::new(move || {
+            let version_id = |index: u32| {
+                index
+                    .checked_sub(1)
+                    .map(|index| revision_ids[index as usize])
+            };
+            let from = version_id(from_dropdown.selected());
+            let to = version_id(to_dropdown.selected());
+
+            let vault_name = vault_name.clone();
+            let current = current.clone();
+            let changes_box = changes_box.clone();
+            glib::spawn_future_local(async move {
+                let account_name = current.name.clone();
+                let result = gtk4::gio::spawn_blocking(move || {
+                    let load = |id: Option<u32>| match id {
+                        Some(id) => get_account_revision(&vault_name, &account_name, id),
+                        None => Ok(current.clone()),
+                    };
+                    Ok::<_, anyhow::Error>(diff_accounts(&load(from)?, &load(to)?))
+                })
+                .await
+                .expect("decrypt task panicked");
+
+                clear_content(&changes_box);
+                match result {
+                    Ok(changes) if changes.is_empty() => {
+                        let label = Label::new(Some("These versions are the same."));
+                        label.add_css_class("dim-label");
+                        changes_box.append(&label);
+                    }
+                    Ok(changes) => {
+                        let group = PreferencesGroup::new();
+                        group.add_css_class("group_background");
+                        for change in &changes {
+                            group.add(&field_change_row(change));
+                        }
+                        changes_box.append(&group);
+                    }
+                    Err(e) => {
+                        log::error!("Failed to compare revisions: {e}");
+                        let label = Label::new(Some(&format!("Could not compare versions: {e}")));
+                        label.set_wrap(true);
+                        label.add_css_class("error");
+                        changes_box.append(&label);
+                    }
+                }
+            });
+        })
+    };
+
+    let refresh_from = refresh.clone();
+    from_dropdown.connect_selected_notify(move |_| refresh_from());
+    let refresh_to = refresh.clone();
+    to_dropdown.connect_selected_notify(move |_| refresh_to());
+    refresh();
+
+    let button_box = GtkBox::new(Orientation::Horizontal, 12);
+    button_box.set_halign(gtk4::Align::End);
+
+    let close_button = Button::new();
+    close_button.set_label("Close");
+    let dialog_clone = dialog.clone();
+    close_button.connect_clicked(move |_| {
+        dialog_clone.close();
+    });
+    button_box.append(&close_button);
+    content_box.append(&button_box);
+
+    dialog.set_child(Some(&content_box));
+    dialog.present();
+}
+
+/// Builds the row describing one changed field. Secrets are only ever shown as changed.
+pub fn field_change_row(change: &FieldChange) -> ActionRow {
+    let row = ActionRow::new();
+    // Titles and subtitles are markup, so escape the values before showing them
+    row.set_title(&glib::markup_escape_text(&change.field));
+
+    let subtitle = match (&change.old, &change.new) {
+        _ if change.sensitive => "Changed".to_string(),
+        (Some(old), Some(new)) => format!("{old} → {new}"),
+        (None, Some(new)) => format!("Added: {new}"),
+        (Some(old), None) => format!("Removed: {old}"),
+        (None, None) => "Changed".to_string(),
+    };
+    row.set_subtitle(&glib::markup_escape_text(&subtitle));
+    row.set_margin_start(8);
+    row.set_margin_end(8);
+    row
+}
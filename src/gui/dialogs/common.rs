@@ -1,7 +1,7 @@
 use adw::prelude::*;
 use gtk4::{
-    Box as GtkBox, Button, ButtonsType, Dialog, Label, MessageDialog, MessageType, Orientation,
-    ResponseType,
+    Box as GtkBox, Button, ButtonsType, Dialog, FileChooserAction, FileChooserDialog, FileFilter,
+    Label, MessageDialog, MessageType, Orientation, ResponseType, gio,
 };
 
 /// Shows a confirmation dialog for dangerous actions
@@ -45,6 +45,50 @@ pub fn show_confirmation_dialog<F>(
     dialog.present();
 }
 
+/// Shows a file chooser for opening or saving a single file
+/// Takes a callback that will be executed with the chosen file
+pub fn show_file_chooser_dialog<F>(
+    title: &str,
+    action: FileChooserAction,
+    filter: &FileFilter,
+    initial_name: Option<&str>,
+    parent: Option<&impl IsA<gtk4::Window>>,
+    on_chosen: F,
+) where
+    F: Fn(gio::File) + 'static,
+{
+    let accept_label = match action {
+        FileChooserAction::Save => "Save",
+        _ => "Open",
+    };
+    let dialog = FileChooserDialog::new(
+        Some(title),
+        parent,
+        action,
+        &[
+            ("Cancel", ResponseType::Cancel),
+            (accept_label, ResponseType::Accept),
+        ],
+    );
+    dialog.set_modal(true);
+    dialog.add_filter(filter);
+    dialog.set_filter(filter);
+    if let Some(name) = initial_name {
+        dialog.set_current_name(name);
+    }
+
+    dialog.connect_response(move |dialog, response| {
+        if response == ResponseType::Accept
+            && let Some(file) = dialog.file()
+        {
+            on_chosen(file);
+        }
+        dialog.close();
+    });
+
+    dialog.present();
+}
+
 /// Shows a simple error dialog
 pub fn show_error_dialog(title: &str, message: &str) {
     let dialog = Dialog::new();
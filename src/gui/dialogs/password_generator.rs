@@ -1,8 +1,10 @@
 use crate::{
+    breach::{BreachStatus, check_password_breached},
     password::{
-        PasswordConfig, calculate_password_strength, generate_password, get_strength_color_class,
-        get_strength_description,
+        MAX_PASSPHRASE_WORDS, PasswordConfig, PasswordMode, generate_password,
+        get_strength_color_class, get_strength_description, passphrase_entropy_bits,
     },
+    strength::{StrengthEstimate, estimate_password_strength},
     vault::Account,
 };
 
@@ -47,7 +49,31 @@ pub fn show_password_generator_dialog(
     page.add(&custom_group);
     page.add(&display_group);
 
+    // Passphrases get their own page, and so their own configuration
+    let passphrase_config = Rc::new(RefCell::new(PasswordConfig {
+        mode: PasswordMode::Passphrase,
+        ..PasswordConfig::default()
+    }));
+
+    let passphrase_page = adw::PreferencesPage::new();
+    passphrase_page.set_title("Passphrase");
+    passphrase_page.set_icon_name(Some("format-justify-fill-symbolic"));
+
+    let words_group = create_passphrase_words_preferences_group(&passphrase_config);
+    let options_group = create_passphrase_options_preferences_group(&passphrase_config);
+    let passphrase_display_group = create_password_display_preferences_group(
+        &passphrase_config,
+        target_entry,
+        account_ref,
+        Some(&generator_window),
+    );
+
+    passphrase_page.add(&words_group);
+    passphrase_page.add(&options_group);
+    passphrase_page.add(&passphrase_display_group);
+
     generator_window.add(&page);
+    generator_window.add(&passphrase_page);
     generator_window.present();
 }
 
@@ -199,6 +225,131 @@ fn create_character_types_preferences_group(
     group
 }
 
+/// Creates the passphrase word count and separator preferences group
+fn create_passphrase_words_preferences_group(
+    password_config: &Rc<RefCell<PasswordConfig>>,
+) -> PreferencesGroup {
+    let group = PreferencesGroup::new();
+    group.set_title("Words");
+    group.set_description(Some(
+        "Passphrases are random words from the EFF long wordlist",
+    ));
+
+    let count_row = ActionRow::new();
+    count_row.set_title("Word Count");
+    count_row.set_subtitle("Each word adds about 12.9 bits of entropy");
+    count_row.set_margin_start(8);
+    count_row.set_margin_end(8);
+
+    let default_count = password_config.borrow().passphrase.word_count;
+    let count_adjustment = Adjustment::new(
+        default_count as f64,
+        1.0,
+        MAX_PASSPHRASE_WORDS as f64,
+        1.0,
+        2.0,
+        0.0,
+    );
+    let count_spinner = SpinButton::new(Some(&count_adjustment), 1.0, 0);
+    count_spinner.set_tooltip_text(Some(&format!(
+        "Set the number of words (1-{MAX_PASSPHRASE_WORDS})"
+    )));
+    count_spinner.set_valign(gtk4::Align::Center);
+
+    let config_weak = Rc::downgrade(password_config);
+    count_spinner.connect_value_changed(move |spinner| {
+        if let Some(config_ref) = config_weak.upgrade() {
+            config_ref.borrow_mut().passphrase.word_count = spinner.value() as usize;
+        }
+    });
+
+    count_row.add_suffix(&count_spinner);
+    group.add(&count_row);
+
+    let separator_row = ActionRow::new();
+    separator_row.set_title("Separator");
+    separator_row.set_subtitle("Text placed between words");
+    separator_row.set_margin_start(8);
+    separator_row.set_margin_end(8);
+
+    let separator_entry = Entry::new();
+    separator_entry.set_text(&password_config.borrow().passphrase.separator);
+    separator_entry.set_placeholder_text(Some("None"));
+    separator_entry.set_max_length(8);
+    separator_entry.set_valign(gtk4::Align::Center);
+    separator_entry.set_size_request(80, -1);
+
+    let config_weak = Rc::downgrade(password_config);
+    separator_entry.connect_changed(move |entry| {
+        if let Some(config_ref) = config_weak.upgrade() {
+            config_ref.borrow_mut().passphrase.separator = entry.text().to_string();
+        }
+    });
+
+    separator_row.add_suffix(&separator_entry);
+    group.add(&separator_row);
+
+    group
+}
+
+/// Creates the passphrase capitalisation and suffix preferences group
+fn create_passphrase_options_preferences_group(
+    password_config: &Rc<RefCell<PasswordConfig>>,
+) -> PreferencesGroup {
+    let group = PreferencesGroup::new();
+    group.set_title("Options");
+    group.set_description(Some("For sites that require capitals, numbers or symbols"));
+
+    let passphrase_options = [
+        (
+            "Capitalize Words",
+            "Start every word with a capital letter",
+            "capitalize",
+        ),
+        (
+            "Append Number",
+            "Add a random digit to the end",
+            "append_digit",
+        ),
+        (
+            "Append Symbol",
+            "Add a random symbol to the end",
+            "append_symbol",
+        ),
+    ];
+
+    for (title, subtitle, field_name) in passphrase_options {
+        let row = ActionRow::new();
+        row.set_title(title);
+        row.set_subtitle(subtitle);
+        row.set_margin_start(8);
+        row.set_margin_end(8);
+
+        let switch = Switch::new();
+        switch.set_valign(gtk4::Align::Center);
+
+        let config_weak = Rc::downgrade(password_config);
+        switch.connect_state_set(move |_, state| {
+            if let Some(config_ref) = config_weak.upgrade() {
+                let mut config = config_ref.borrow_mut();
+                match field_name {
+                    "capitalize" => config.passphrase.capitalize = state,
+                    "append_digit" => config.passphrase.append_digit = state,
+                    "append_symbol" => config.passphrase.append_symbol = state,
+                    _ => {}
+                }
+            }
+            glib::Propagation::Proceed
+        });
+
+        row.add_suffix(&switch);
+        row.set_activatable_widget(Some(&switch));
+        group.add(&row);
+    }
+
+    group
+}
+
 /// Creates the custom characters configuration preferences group
 fn create_custom_characters_preferences_group(
     password_config: &Rc<RefCell<PasswordConfig>>,
@@ -321,6 +472,8 @@ fn create_password_display_preferences_group(
     strength_description.add_css_class("caption");
     strength_description.set_halign(gtk4::Align::Start);
     strength_description.set_valign(gtk4::Align::Center);
+    strength_description.set_wrap(true);
+    strength_description.set_max_width_chars(32);
 
     // Update strength indicator for initial password
     let buffer = password_display.buffer();
@@ -330,7 +483,12 @@ fn create_password_display_preferences_group(
     if !initial_password.is_empty()
         && initial_password != "Click 'Generate Password' to create a password"
     {
-        update_strength_indicator(&strength_progress, &strength_description, &initial_password);
+        update_strength_indicator(
+            &strength_progress,
+            &strength_description,
+            &initial_password,
+            &password_config.borrow(),
+        );
     }
 
     let strength_box = GtkBox::new(Orientation::Vertical, 4);
@@ -370,6 +528,7 @@ fn create_password_display_preferences_group(
                     &strength_progress,
                     &strength_description,
                     &generated_password,
+                    &config,
                 );
             }
             Err(error_message) => {
@@ -380,6 +539,7 @@ fn create_password_display_preferences_group(
                 strength_progress_ref.set_fraction(0.0);
                 strength_progress_ref.set_text(Some("0%"));
                 strength_desc_ref.set_text("");
+                strength_desc_ref.set_tooltip_text(None);
             }
         }
     });
@@ -475,11 +635,48 @@ fn create_password_display_preferences_group(
     group
 }
 
-fn update_strength_indicator(progress: &ProgressBar, desc: &Label, password: &str) {
-    let strength = calculate_password_strength(password);
+fn update_strength_indicator(
+    progress: &ProgressBar,
+    desc: &Label,
+    password: &str,
+    config: &PasswordConfig,
+) {
+    // Passphrase entropy is known exactly, so report it instead of estimating from the text
+    let (estimate, mut description) = if config.mode == PasswordMode::Passphrase {
+        let bits = passphrase_entropy_bits(&config.passphrase);
+        let estimate = StrengthEstimate::from_guesses(bits.exp2());
+        let description = format!("{} · {bits:.1} bits", get_strength_description(&estimate));
+        (estimate, description)
+    } else {
+        let estimate = estimate_password_strength(password);
+        let description = get_strength_description(&estimate);
+        (estimate, description)
+    };
+    let mut strength = estimate.percent;
+
+    if let Some(warning) = estimate.warning {
+        description.push('\n');
+        description.push_str(warning);
+    }
+
+    match check_password_breached(password) {
+        Ok(BreachStatus::Found(count)) => {
+            // A breached password is in every attacker's wordlist, however random it looks
+            strength = 0;
+            description.push_str(&format!("\nFound in {count} known data breaches"));
+        }
+        Ok(BreachStatus::NotFound | BreachStatus::Unavailable) => {}
+        Err(e) => log::warn!("Failed to check password against breach list: {e}"),
+    }
+    if estimate.suggestions.is_empty() {
+        desc.set_tooltip_text(None);
+    } else {
+        desc.set_tooltip_text(Some(&estimate.suggestions.join("\n")));
+    }
+
     progress.set_fraction(strength as f64 / 100.0);
     progress.set_text(Some(&format!("{strength}%")));
-    desc.set_text(get_strength_description(strength));
+    desc.set_text(&description);
     for cls in [
         "strength-weak",
         "strength-fair",
@@ -1,7 +1,12 @@
 use crate::{
-    gui::{dialogs::common::show_confirmation_dialog, views::vault_view::VaultView},
+    gui::{
+        dialogs::common::{show_confirmation_dialog, show_file_chooser_dialog},
+        views::vault_view::VaultView,
+    },
+    models::AccountTotp,
     totp::{
-        confirm_totp_setup, disable_totp, get_totp_qr_info, prepare_totp_setup, verify_totp_code,
+        TotpAlgorithm, TotpParams, confirm_totp_setup, disable_totp, get_totp_qr_info,
+        parse_otpauth_uri, prepare_totp_setup, read_otpauth_qr_image, verify_totp_code,
         verify_totp_code_with_secret,
     },
 };
@@ -10,7 +15,8 @@ use std::{cell::RefCell, rc::Rc};
 
 use adw::{ActionRow, PreferencesGroup, PreferencesWindow, prelude::*};
 use gtk4::{
-    Box as GtkBox, Button, Dialog, Entry, Image, Label, Orientation,
+    Box as GtkBox, Button, Dialog, DropDown, Entry, FileChooserAction, FileFilter, Image, Label,
+    Orientation, SpinButton,
     gdk_pixbuf::{Colorspace, Pixbuf},
     glib::{self, Bytes},
 };
@@ -19,6 +25,16 @@ use qrcode::QrCode;
 
 /// Shows the TOTP setup dialog for enabling 2FA on a vault
 pub fn show_totp_setup_dialog(vault_name: &str, content_area: &GtkBox, ctx: Rc<RefCell<Context>>) {
+    show_totp_setup_dialog_with_params(vault_name, content_area, ctx, TotpParams::default());
+}
+
+/// Shows the TOTP setup dialog with a new secret generated for `params`
+fn show_totp_setup_dialog_with_params(
+    vault_name: &str,
+    content_area: &GtkBox,
+    ctx: Rc<RefCell<Context>>,
+    params: TotpParams,
+) {
     let totp_window = PreferencesWindow::new();
     totp_window.set_title(Some("Enable Two-Factor Authentication"));
     totp_window.set_modal(true);
@@ -40,6 +56,54 @@ pub fn show_totp_setup_dialog(vault_name: &str, content_area: &GtkBox, ctx: Rc<R
     );
     setup_group.add(&instructions_row);
 
+    // Code settings: changing one reopens the dialog with a fresh secret and QR code
+    let settings_group = PreferencesGroup::new();
+    settings_group.set_title("Code Settings");
+    settings_group.set_description(Some(
+        "Keep the defaults unless your authenticator app supports other settings",
+    ));
+
+    let algorithms = [
+        TotpAlgorithm::Sha1,
+        TotpAlgorithm::Sha256,
+        TotpAlgorithm::Sha512,
+    ];
+    let digit_options = [6u32, 7, 8];
+
+    let algorithm_dropdown = DropDown::from_strings(&algorithms.map(TotpAlgorithm::as_str));
+    let digits_dropdown = DropDown::from_strings(&["6", "7", "8"]);
+    let period_spin = SpinButton::with_range(1.0, 300.0, 1.0);
+    period_spin.set_tooltip_text(Some("Seconds each code lasts"));
+    let skew_spin = SpinButton::with_range(0.0, 10.0, 1.0);
+    skew_spin.set_tooltip_text(Some(
+        "Codes this many steps early or late are still accepted",
+    ));
+
+    let selected_index =
+        |position: Option<usize>| u32::try_from(position.unwrap_or(0)).unwrap_or(0);
+    algorithm_dropdown.set_selected(selected_index(
+        algorithms.iter().position(|a| *a == params.algorithm),
+    ));
+    digits_dropdown.set_selected(selected_index(
+        digit_options.iter().position(|d| *d == params.digits),
+    ));
+    #[allow(clippy::cast_precision_loss)]
+    period_spin.set_value(params.period as f64);
+    skew_spin.set_value(f64::from(params.skew));
+
+    for (title, control) in [
+        ("Algorithm", algorithm_dropdown.upcast_ref::<gtk4::Widget>()),
+        ("Digits", digits_dropdown.upcast_ref()),
+        ("Period", period_spin.upcast_ref()),
+        ("Allowed Skew", skew_spin.upcast_ref()),
+    ] {
+        let row = ActionRow::new();
+        row.set_title(title);
+        control.set_valign(gtk4::Align::Center);
+        row.add_suffix(control);
+        settings_group.add(&row);
+    }
+
     // QR Code group (will be populated after setup)
     let qr_group = PreferencesGroup::new();
     qr_group.set_title("QR Code");
@@ -57,14 +121,61 @@ pub fn show_totp_setup_dialog(vault_name: &str, content_area: &GtkBox, ctx: Rc<R
     verification_group.set_description(Some("Enter the code from your authenticator app"));
 
     page.add(&setup_group);
+    page.add(&settings_group);
     page.add(&qr_group);
     page.add(&verification_group);
 
     totp_window.add(&page);
     totp_window.present();
 
+    let reopen_with_settings = {
+        let totp_window = totp_window.clone();
+        let vault_name = vault_name.to_string();
+        let content_area = content_area.clone();
+        let ctx = ctx.clone();
+        let algorithm_dropdown = algorithm_dropdown.clone();
+        let digits_dropdown = digits_dropdown.clone();
+        let period_spin = period_spin.clone();
+        let skew_spin = skew_spin.clone();
+        move || {
+            if !totp_window.is_visible() {
+                return;
+            }
+            #[allow(clippy::cast_sign_loss)]
+            let new_params = TotpParams {
+                algorithm: algorithms[algorithm_dropdown.selected() as usize],
+                digits: digit_options[digits_dropdown.selected() as usize],
+                period: period_spin.value_as_int().max(1) as u64,
+                skew: skew_spin.value_as_int().max(0) as u32,
+            };
+            totp_window.close();
+            show_totp_setup_dialog_with_params(&vault_name, &content_area, ctx.clone(), new_params);
+        }
+    };
+    for dropdown in [&algorithm_dropdown, &digits_dropdown] {
+        let reopen_with_settings = reopen_with_settings.clone();
+        dropdown.connect_selected_notify(move |_| reopen_with_settings());
+    }
+
+    // The period is part of the QR code, so wait for the spinner to settle before reopening
+    let pending_reopen: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
+    period_spin.connect_value_changed(move |_| {
+        if let Some(source) = pending_reopen.borrow_mut().take() {
+            source.remove();
+        }
+        let reopen_with_settings = reopen_with_settings.clone();
+        let pending_reopen_clone = pending_reopen.clone();
+        *pending_reopen.borrow_mut() = Some(glib::timeout_add_local_once(
+            std::time::Duration::from_millis(800),
+            move || {
+                pending_reopen_clone.borrow_mut().take();
+                reopen_with_settings();
+            },
+        ));
+    });
+
     // Prepare TOTP secret and QR code after showing dialog (without enabling yet)
-    match prepare_totp_setup(vault_name, ctx.clone()) {
+    match prepare_totp_setup(vault_name, &params, ctx.clone()) {
         Ok((secret_buf, secret_b32, otpauth_uri)) => {
             // Remove loading message
             qr_group.remove(&loading_row);
@@ -84,11 +195,14 @@ pub fn show_totp_setup_dialog(vault_name: &str, content_area: &GtkBox, ctx: Rc<R
             // Add verification input
             let verification_row = ActionRow::new();
             verification_row.set_title("Verification Code");
-            verification_row.set_subtitle("Enter the 6-digit code from your authenticator app");
+            verification_row.set_subtitle(&format!(
+                "Enter the {}-digit code from your authenticator app",
+                params.digits
+            ));
 
             let code_entry = Entry::new();
-            code_entry.set_placeholder_text(Some("000000"));
-            code_entry.set_max_length(6);
+            code_entry.set_placeholder_text(Some(&"0".repeat(params.digits as usize)));
+            code_entry.set_max_length(i32::try_from(params.digits).unwrap_or(8));
             code_entry.set_input_purpose(gtk4::InputPurpose::Digits);
             code_entry.set_valign(gtk4::Align::Center);
             code_entry.set_size_request(120, -1);
@@ -109,13 +223,22 @@ pub fn show_totp_setup_dialog(vault_name: &str, content_area: &GtkBox, ctx: Rc<R
             let content_area_clone = content_area.clone();
 
             verify_button.connect_clicked(move |_| {
+                // The skew is not part of the QR code, so it is read when the code is checked
+                #[allow(clippy::cast_sign_loss)]
+                let params = TotpParams {
+                    skew: skew_spin.value_as_int().max(0) as u32,
+                    ..params
+                };
                 let code = code_entry.text();
-                if code.len() == 6 {
-                    match verify_totp_code_with_secret(&secret_clone, &code) {
+                if code.len() == params.digits as usize {
+                    match verify_totp_code_with_secret(&secret_clone, &code, &params) {
                         Ok(true) => {
-                            if let Err(e) =
-                                confirm_totp_setup(&vault_name_clone, &secret_clone, ctx.clone())
-                            {
+                            if let Err(e) = confirm_totp_setup(
+                                &vault_name_clone,
+                                &secret_clone,
+                                &params,
+                                ctx.clone(),
+                            ) {
                                 log::error!("Failed to confirm TOTP setup: {e}");
                             } else {
                                 VaultView::new(&content_area_clone, &vault_name_clone)
@@ -297,6 +420,120 @@ fn generate_qr_code_image(otpauth_uri: &str) -> Result<Pixbuf, Box<dyn std::erro
     Ok(pixbuf)
 }
 
+/// Shows a dialog for importing an account's one-time code from an otpauth URI or QR code image
+pub fn show_import_otp_dialog<F>(on_import: F)
+where
+    F: Fn(AccountTotp) + 'static,
+{
+    let dialog = Dialog::new();
+    dialog.set_title(Some("Import One-Time Code"));
+    dialog.set_modal(true);
+    dialog.set_default_size(450, 250);
+
+    let content_box = GtkBox::new(Orientation::Vertical, 16);
+    content_box.set_margin_top(20);
+    content_box.set_margin_bottom(20);
+    content_box.set_margin_start(20);
+    content_box.set_margin_end(20);
+
+    let title_label = Label::new(Some(
+        "Paste an otpauth:// link or open a QR code screenshot",
+    ));
+    title_label.add_css_class("title-4");
+    title_label.set_wrap(true);
+    content_box.append(&title_label);
+
+    let uri_entry = Entry::new();
+    uri_entry.set_visibility(false);
+    uri_entry.set_invisible_char(Some('•'));
+    uri_entry.set_placeholder_text(Some("otpauth://totp/..."));
+    content_box.append(&uri_entry);
+
+    let status_label = Label::new(None);
+    status_label.add_css_class("error");
+    status_label.set_wrap(true);
+    status_label.set_visible(false);
+    content_box.append(&status_label);
+
+    let button_box = GtkBox::new(Orientation::Horizontal, 12);
+    button_box.set_halign(gtk4::Align::Center);
+    button_box.set_margin_top(16);
+
+    let cancel_button = Button::new();
+    cancel_button.set_label("Cancel");
+    cancel_button.add_css_class("flat");
+
+    let open_image_button = Button::new();
+    open_image_button.set_label("Open QR Image…");
+
+    let import_button = Button::new();
+    import_button.set_label("Import");
+    import_button.add_css_class("suggested-action");
+
+    let on_import = Rc::new(on_import);
+    let finish = {
+        let dialog = dialog.clone();
+        let status_label = status_label.clone();
+        move |result: Result<AccountTotp, anyhow::Error>| match result {
+            Ok(totp) => {
+                on_import(totp);
+                dialog.close();
+            }
+            Err(e) => {
+                status_label.set_text(&e.to_string());
+                status_label.set_visible(true);
+            }
+        }
+    };
+
+    let dialog_clone = dialog.clone();
+    cancel_button.connect_clicked(move |_| {
+        dialog_clone.close();
+    });
+
+    let finish_clone = finish.clone();
+    let uri_entry_clone = uri_entry.clone();
+    import_button.connect_clicked(move |_| {
+        finish_clone(parse_otpauth_uri(&uri_entry_clone.text()));
+    });
+
+    let import_button_clone = import_button.clone();
+    uri_entry.connect_activate(move |_| {
+        import_button_clone.emit_clicked();
+    });
+
+    let dialog_clone = dialog.clone();
+    open_image_button.connect_clicked(move |_| {
+        let filter = FileFilter::new();
+        filter.set_name(Some("Images (PNG, JPEG)"));
+        filter.add_mime_type("image/png");
+        filter.add_mime_type("image/jpeg");
+
+        let finish = finish.clone();
+        show_file_chooser_dialog(
+            "Open QR Code Image",
+            FileChooserAction::Open,
+            &filter,
+            None,
+            Some(&dialog_clone),
+            move |file| match file.path() {
+                Some(path) => finish(read_otpauth_qr_image(&path)),
+                None => finish(Err(anyhow::anyhow!(
+                    "Only local image files can be opened."
+                ))),
+            },
+        );
+    });
+
+    button_box.append(&cancel_button);
+    button_box.append(&open_image_button);
+    button_box.append(&import_button);
+    content_box.append(&button_box);
+
+    dialog.set_child(Some(&content_box));
+    dialog.present();
+}
+
 /// Shows the TOTP authentication dialog for vault access
 pub fn show_totp_authentication_dialog<F>(
     vault_name: &str,
@@ -321,7 +558,7 @@ pub fn show_totp_authentication_dialog<F>(
     title.set_halign(gtk4::Align::Center);
     content_box.append(&title);
 
-    let instructions = Label::new(Some("Enter the 6-digit code from your authenticator app"));
+    let instructions = Label::new(Some("Enter the code from your authenticator app"));
     instructions.add_css_class("body");
     instructions.set_halign(gtk4::Align::Center);
     instructions.set_margin_bottom(12);
@@ -332,7 +569,7 @@ pub fn show_totp_authentication_dialog<F>(
 
     let code_entry = Entry::new();
     code_entry.set_placeholder_text(Some("000000"));
-    code_entry.set_max_length(6);
+    code_entry.set_max_length(8);
     code_entry.set_width_chars(10);
     code_entry.set_halign(gtk4::Align::Center);
     code_entry.add_css_class("totp-code-entry");
@@ -1,12 +1,40 @@
-use crate::gui::dialogs::common::show_error_dialog;
-use crate::storage::filesystem::{
-    create_backup, delete_backup, delete_vault, install_backup, rename_vault,
+use crate::gui::content::{CreateActionRow, clear_content, show_toast};
+use crate::gui::dialogs::account_management::field_change_row;
+use crate::gui::dialogs::common::{
+    show_confirmation_dialog, show_error_dialog, show_file_chooser_dialog,
+};
+use crate::gui::widgets::loading_spinner::{create_loading_button, set_button_loading_state};
+use crate::storage::{
+    account_names::{hide_account_names, show_account_names, vault_hides_names},
+    archive::{
+        ARCHIVE_EXTENSION, ArchiveManifest, ImportMode, export_vault, import_vault, verify_archive,
+    },
+    auto_backup::AutoBackupPolicy,
+    backup::{
+        BackupSnapshot, BackupVerification, FileCheck, create_backup, delete_backup,
+        install_backup, list_backups, load_backup_verification, verify_backup,
+    },
+    container::{convert_to_directories, convert_to_single_file, is_single_file},
+    filesystem::{delete_vault, get_available_accounts, get_available_vaults, rename_vault},
+    manifest::update_vault_manifest,
+};
+use crate::vault::{
+    Account, add_vault_recipient,
+    compare::{AccountDifference, AccountStatus, diff_vault_with_backup},
+    get_vault_recipients, rekey_vault, remove_vault_recipient,
+    restore::{
+        RestoreTarget, get_backup_account, list_backup_accounts, restore_account_from_backup,
+    },
+    settings::{VaultSettings, load_vault_settings, save_vault_settings},
 };
 use gpgme::Context;
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
 
-use adw::prelude::*;
-use gtk4::{Box as GtkBox, Button, Dialog, Entry, Label, Orientation};
+use adw::{ActionRow, PreferencesGroup, Toast, prelude::*};
+use gtk4::{
+    Adjustment, Box as GtkBox, Button, Dialog, Entry, FileChooserAction, FileFilter, Label,
+    ListBox, Orientation, PolicyType, ScrolledWindow, SelectionMode, SpinButton, Switch, glib,
+};
 
 /// Shows the backup vault dialog
 pub fn show_backup_vault_dialog(
@@ -31,7 +59,7 @@ pub fn show_backup_vault_dialog(
     content_box.append(&title);
 
     let description = Label::new(Some(
-        "This will create a backup of your vault that can be restored later.",
+        "This will add a backup of your vault that can be restored later, then check that every account in it decrypts. Older backups are pruned using the vault's retention rules.",
     ));
     description.add_css_class("dim-label");
     description.set_wrap(true);
@@ -46,8 +74,7 @@ pub fn show_backup_vault_dialog(
     cancel_button.set_label("Cancel");
     cancel_button.add_css_class("flat");
 
-    let backup_button = Button::new();
-    backup_button.set_label("Create Backup");
+    let (backup_button, _) = create_loading_button("Create Backup", "Backing up...");
     backup_button.add_css_class("suggested-action");
 
     let dialog_clone = dialog.clone();
@@ -58,16 +85,47 @@ pub fn show_backup_vault_dialog(
     let dialog_clone = dialog.clone();
     let content_area_clone = content_area.clone();
     let vault_name_clone = vault_name.to_string();
-    backup_button.connect_clicked(move |_| match create_backup(&vault_name_clone) {
-        Ok(()) => {
-            dialog_clone.close();
-            crate::gui::views::vault_view::VaultView::new(&content_area_clone, &vault_name_clone)
-                .create(ctx.clone());
-        }
-        Err(e) => {
-            log::error!("Failed to create backup: {e}");
-            show_error_dialog("Backup Failed", &format!("Could not create backup: {e}"));
-        }
+    backup_button.connect_clicked(move |button| {
+        set_button_loading_state(button, true);
+
+        let button = button.clone();
+        let dialog = dialog_clone.clone();
+        let content_area = content_area_clone.clone();
+        let vault_name = vault_name_clone.clone();
+        let ctx = ctx.clone();
+        glib::spawn_future_local(async move {
+            let vault_name_task = vault_name.clone();
+            let result = gtk4::gio::spawn_blocking(move || create_backup(&vault_name_task))
+                .await
+                .expect("backup task panicked");
+
+            match result {
+                Ok(snapshot) => {
+                    dialog.close();
+                    crate::gui::views::vault_view::VaultView::new(&content_area, &vault_name)
+                        .create(ctx.clone());
+
+                    match load_backup_verification(&snapshot) {
+                        Some(verification) if verification.is_ok() => show_toast(
+                            &content_area,
+                            Toast::new(&format!("Backup created • {}", verification.summary())),
+                        ),
+                        Some(verification) => {
+                            show_backup_verification_dialog(&snapshot, &verification)
+                        }
+                        None => show_toast(
+                            &content_area,
+                            Toast::new("Backup created, but it could not be verified"),
+                        ),
+                    }
+                }
+                Err(e) => {
+                    log::error!("Failed to create backup: {e}");
+                    set_button_loading_state(&button, false);
+                    show_error_dialog("Backup Failed", &format!("Could not create backup: {e}"));
+                }
+            }
+        });
     });
 
     button_box.append(&cancel_button);
@@ -78,16 +136,361 @@ pub fn show_backup_vault_dialog(
     dialog.present();
 }
 
-/// Shows the restore vault dialog
-pub fn show_restore_vault_dialog(
+/// Shows the backups dialog, listing every snapshot of the vault with restore and delete actions
+pub fn show_backups_dialog(vault_name: &str, content_area: &GtkBox, ctx: Rc<RefCell<Context>>) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some("Vault Backups"));
+    dialog.set_modal(true);
+    dialog.set_default_size(480, 520);
+
+    let content_box = GtkBox::new(Orientation::Vertical, 16);
+    content_box.set_margin_top(20);
+    content_box.set_margin_bottom(20);
+    content_box.set_margin_start(20);
+    content_box.set_margin_end(20);
+
+    let title = Label::new(Some(&format!("Backups of '{vault_name}'")));
+    title.add_css_class("title-2");
+    title.set_halign(gtk4::Align::Center);
+    content_box.append(&title);
+
+    let description = Label::new(Some(
+        "Restoring a backup replaces all current vault data with it. Compare a backup to see what changed since, or browse it to restore single accounts instead.",
+    ));
+    description.add_css_class("dim-label");
+    description.set_wrap(true);
+    description.set_halign(gtk4::Align::Center);
+    content_box.append(&description);
+
+    let snapshots_list = ListBox::new();
+    snapshots_list.add_css_class("boxed-list");
+    snapshots_list.set_selection_mode(SelectionMode::None);
+
+    let snapshots = list_backups(vault_name).unwrap_or_else(|e| {
+        log::error!("Failed to list backups: {e}");
+        Vec::new()
+    });
+    if snapshots.is_empty() {
+        let row = ActionRow::new();
+        row.set_title("No backups yet");
+        snapshots_list.append(&row);
+    }
+
+    for snapshot in snapshots {
+        let row = ActionRow::new();
+        row.set_title(&snapshot.created_at_display());
+        row.set_subtitle(&verification_subtitle(
+            load_backup_verification(&snapshot).as_ref(),
+        ));
+
+        let (verify_button, _) = create_loading_button("Verify", "Verifying...");
+        verify_button.set_valign(gtk4::Align::Center);
+
+        let vault_name_clone = vault_name.to_string();
+        let snapshot_clone = snapshot.clone();
+        let row_clone = row.clone();
+        verify_button.connect_clicked(move |button| {
+            set_button_loading_state(button, true);
+
+            let button = button.clone();
+            let vault_name = vault_name_clone.clone();
+            let snapshot = snapshot_clone.clone();
+            let row = row_clone.clone();
+            glib::spawn_future_local(async move {
+                let vault_name_task = vault_name.clone();
+                let snapshot_id = snapshot.id.clone();
+                let result = gtk4::gio::spawn_blocking(move || {
+                    verify_backup(&vault_name_task, &snapshot_id)
+                })
+                .await
+                .expect("verify task panicked");
+
+                set_button_loading_state(&button, false);
+                match result {
+                    Ok(verification) => {
+                        row.set_subtitle(&verification_subtitle(Some(&verification)));
+                        show_backup_verification_dialog(&snapshot, &verification);
+                    }
+                    Err(e) => {
+                        log::error!("Failed to verify backup {}: {e}", snapshot.id);
+                        show_error_dialog(
+                            "Verification Failed",
+                            &format!("Could not verify backup: {e}"),
+                        );
+                    }
+                }
+            });
+        });
+
+        let browse_button = Button::with_label("Browse");
+        browse_button.set_valign(gtk4::Align::Center);
+
+        let content_area_clone = content_area.clone();
+        let vault_name_clone = vault_name.to_string();
+        let ctx_clone = ctx.clone();
+        let snapshot_clone = snapshot.clone();
+        browse_button.connect_clicked(move |_| {
+            show_backup_accounts_dialog(
+                &vault_name_clone,
+                &snapshot_clone,
+                &content_area_clone,
+                ctx_clone.clone(),
+            );
+        });
+
+        let compare_button = Button::with_label("Compare");
+        compare_button.set_valign(gtk4::Align::Center);
+
+        let vault_name_clone = vault_name.to_string();
+        let snapshot_clone = snapshot.clone();
+        compare_button.connect_clicked(move |_| {
+            show_backup_diff_dialog(&vault_name_clone, &snapshot_clone);
+        });
+
+        let restore_button = Button::with_label("Restore");
+        restore_button.add_css_class("suggested-action");
+        restore_button.set_valign(gtk4::Align::Center);
+
+        let dialog_clone = dialog.clone();
+        let content_area_clone = content_area.clone();
+        let vault_name_clone = vault_name.to_string();
+        let ctx_clone = ctx.clone();
+        let snapshot_clone = snapshot.clone();
+        restore_button.connect_clicked(move |_| {
+            let dialog = dialog_clone.clone();
+            let content_area = content_area_clone.clone();
+            let vault_name = vault_name_clone.clone();
+            let ctx = ctx_clone.clone();
+            let snapshot_id = snapshot_clone.id.clone();
+            show_confirmation_dialog(
+                "Restore Backup",
+                &format!(
+                    "Replace all current data in '{vault_name}' with the backup from {}?\n\nThis action cannot be undone.",
+                    snapshot_clone.created_at_display()
+                ),
+                "Restore",
+                None::<&gtk4::Window>,
+                move || {
+                    let dialog = dialog.clone();
+                    let content_area = content_area.clone();
+                    let vault_name = vault_name.clone();
+                    let ctx = ctx.clone();
+                    let snapshot_id = snapshot_id.clone();
+                    glib::spawn_future_local(async move {
+                        let vault_name_task = vault_name.clone();
+                        let result = gtk4::gio::spawn_blocking(move || {
+                            install_backup(&vault_name_task, &snapshot_id)
+                        })
+                        .await
+                        .expect("restore task panicked");
+
+                        match result {
+                            Ok(()) => {
+                                dialog.close();
+                                crate::gui::views::vault_view::VaultView::new(
+                                    &content_area,
+                                    &vault_name,
+                                )
+                                .create(ctx);
+                            }
+                            Err(e) => {
+                                log::error!("Failed to restore backup: {e}");
+                                show_error_dialog(
+                                    "Restore Failed",
+                                    &format!("Could not restore backup: {e}"),
+                                );
+                            }
+                        }
+                    });
+                },
+            );
+        });
+
+        let delete_button = Button::with_label("Delete");
+        delete_button.add_css_class("destructive-action");
+        delete_button.set_valign(gtk4::Align::Center);
+
+        let dialog_clone = dialog.clone();
+        let content_area_clone = content_area.clone();
+        let vault_name_clone = vault_name.to_string();
+        let ctx_clone = ctx.clone();
+        delete_button.connect_clicked(move |_| {
+            match delete_backup(&vault_name_clone, &snapshot.id) {
+                Ok(()) => {
+                    dialog_clone.close();
+                    show_backups_dialog(&vault_name_clone, &content_area_clone, ctx_clone.clone());
+                }
+                Err(e) => {
+                    log::error!("Failed to delete backup: {e}");
+                    show_error_dialog("Delete Failed", &format!("Could not delete backup: {e}"));
+                }
+            }
+        });
+
+        row.add_suffix(&verify_button);
+        row.add_suffix(&browse_button);
+        row.add_suffix(&compare_button);
+        row.add_suffix(&restore_button);
+        row.add_suffix(&delete_button);
+        snapshots_list.append(&row);
+    }
+
+    let scrolled_window = ScrolledWindow::new();
+    scrolled_window.set_policy(PolicyType::Never, PolicyType::Automatic);
+    scrolled_window.set_vexpand(true);
+    scrolled_window.set_child(Some(&snapshots_list));
+    content_box.append(&scrolled_window);
+
+    content_box.append(&create_auto_backup_group(vault_name));
+    content_box.append(&create_backup_retention_group(vault_name));
+
+    let button_box = GtkBox::new(Orientation::Horizontal, 12);
+    button_box.set_halign(gtk4::Align::Center);
+
+    let close_button = Button::new();
+    close_button.set_label("Close");
+    close_button.add_css_class("flat");
+
+    let dialog_clone = dialog.clone();
+    let content_area_clone = content_area.clone();
+    let vault_name_clone = vault_name.to_string();
+    close_button.connect_clicked(move |_| {
+        dialog_clone.close();
+        // The vault view shows the latest backup, which may have been deleted
+        crate::gui::views::vault_view::VaultView::new(&content_area_clone, &vault_name_clone)
+            .create(ctx.clone());
+    });
+
+    button_box.append(&close_button);
+    content_box.append(&button_box);
+
+    dialog.set_child(Some(&content_box));
+    dialog.present();
+}
+
+/// Describes the last verification of a backup for its row in the backups dialog
+fn verification_subtitle(verification: Option<&BackupVerification>) -> String {
+    match verification {
+        None => "Not verified".to_string(),
+        Some(verification) if verification.is_ok() => format!(
+            "✓ {} • {}",
+            verification.summary(),
+            verification.verified_at
+        ),
+        Some(verification) => format!(
+            "⚠ {} • {}",
+            verification.summary(),
+            verification.verified_at
+        ),
+    }
+}
+
+/// Shows the per-file results of verifying a backup
+fn show_backup_verification_dialog(snapshot: &BackupSnapshot, verification: &BackupVerification) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some("Backup Verification"));
+    dialog.set_modal(true);
+    dialog.set_default_size(460, 480);
+
+    let content_box = GtkBox::new(Orientation::Vertical, 16);
+    content_box.set_margin_top(20);
+    content_box.set_margin_bottom(20);
+    content_box.set_margin_start(20);
+    content_box.set_margin_end(20);
+
+    let title = Label::new(Some(&format!(
+        "Backup from {}",
+        snapshot.created_at_display()
+    )));
+    title.add_css_class("title-2");
+    title.set_halign(gtk4::Align::Center);
+    content_box.append(&title);
+
+    let summary = Label::new(Some(&verification.summary()));
+    summary.add_css_class(if verification.is_ok() {
+        "success"
+    } else {
+        "error"
+    });
+    summary.set_wrap(true);
+    summary.set_halign(gtk4::Align::Center);
+    content_box.append(&summary);
+
+    let checks_box = GtkBox::new(Orientation::Vertical, 12);
+
+    let vault_group = PreferencesGroup::new();
+    vault_group.set_title("Vault Files");
+    vault_group.add(&file_check_row(&verification.gate));
+    if let Some(totp) = &verification.totp {
+        vault_group.add(&file_check_row(totp));
+    }
+    checks_box.append(&vault_group);
+
+    let accounts_group = PreferencesGroup::new();
+    accounts_group.set_title("Accounts");
+    if verification.accounts.is_empty() {
+        accounts_group.add(
+            &CreateActionRow::<fn()>::new()
+                .title("This backup has no accounts")
+                .add_button(false)
+                .activatable(false)
+                .build(),
+        );
+    }
+    // Failures first, so they are not lost in a long list
+    let mut accounts: Vec<_> = verification.accounts.iter().collect();
+    accounts.sort_by_key(|check| check.is_ok());
+    for check in accounts {
+        accounts_group.add(&file_check_row(check));
+    }
+    checks_box.append(&accounts_group);
+
+    let scrolled_window = ScrolledWindow::new();
+    scrolled_window.set_policy(PolicyType::Never, PolicyType::Automatic);
+    scrolled_window.set_vexpand(true);
+    scrolled_window.set_child(Some(&checks_box));
+    content_box.append(&scrolled_window);
+
+    let button_box = GtkBox::new(Orientation::Horizontal, 12);
+    button_box.set_halign(gtk4::Align::End);
+
+    let close_button = Button::with_label("Close");
+    let dialog_clone = dialog.clone();
+    close_button.connect_clicked(move |_| {
+        dialog_clone.close();
+    });
+    button_box.append(&close_button);
+    content_box.append(&button_box);
+
+    dialog.set_child(Some(&content_box));
+    dialog.present();
+}
+
+fn file_check_row(check: &FileCheck) -> ActionRow {
+    let row = ActionRow::new();
+    row.set_title(&glib::markup_escape_text(&check.name));
+    match &check.error {
+        None => row.set_subtitle("Decrypts correctly"),
+        Some(error) => {
+            row.set_subtitle(&glib::markup_escape_text(error));
+            row.add_css_class("error");
+        }
+    }
+    row
+}
+
+/// Shows the accounts in a backup, with a preview of the selected one and actions to restore it
+/// over the current account or as a copy
+fn show_backup_accounts_dialog(
     vault_name: &str,
+    snapshot: &BackupSnapshot,
     content_area: &GtkBox,
     ctx: Rc<RefCell<Context>>,
 ) {
     let dialog = Dialog::new();
-    dialog.set_title(Some("Restore Vault Backup"));
+    dialog.set_title(Some("Restore Account"));
     dialog.set_modal(true);
-    dialog.set_default_size(400, 250);
+    dialog.set_default_size(520, 600);
 
     let content_box = GtkBox::new(Orientation::Vertical, 16);
     content_box.set_margin_top(20);
@@ -95,75 +498,727 @@ pub fn show_restore_vault_dialog(
     content_box.set_margin_start(20);
     content_box.set_margin_end(20);
 
-    let title = Label::new(Some(&format!("Restore Backup for '{vault_name}'")));
+    let title = Label::new(Some(&format!(
+        "Backup from {}",
+        snapshot.created_at_display()
+    )));
     title.add_css_class("title-2");
     title.set_halign(gtk4::Align::Center);
     content_box.append(&title);
 
-    let warning = Label::new(Some(
-        "This will replace all current vault data with the backup.\nThis action cannot be undone.",
+    let description = Label::new(Some(
+        "Select an account to preview it. Only that account is restored; the rest of the vault is left as it is.",
     ));
-    warning.add_css_class("error");
-    warning.set_wrap(true);
-    warning.set_halign(gtk4::Align::Center);
-    content_box.append(&warning);
+    description.add_css_class("dim-label");
+    description.set_wrap(true);
+    description.set_halign(gtk4::Align::Center);
+    content_box.append(&description);
+
+    let accounts_list = ListBox::new();
+    accounts_list.add_css_class("boxed-list");
+    accounts_list.set_selection_mode(SelectionMode::Single);
+
+    let accounts = list_backup_accounts(vault_name, &snapshot.id).unwrap_or_else(|e| {
+        log::error!("Failed to list the accounts in backup {}: {e}", snapshot.id);
+        Vec::new()
+    });
+    if accounts.is_empty() {
+        let row = ActionRow::new();
+        row.set_title("This backup has no accounts");
+        row.set_selectable(false);
+        accounts_list.append(&row);
+    }
+
+    let current_accounts = get_available_accounts(vault_name);
+    for account in &accounts {
+        let row = ActionRow::new();
+        row.set_title(&glib::markup_escape_text(account));
+        if !current_accounts.contains(account) {
+            row.set_subtitle("Not in the vault anymore");
+        }
+        accounts_list.append(&row);
+    }
+
+    let scrolled_window = ScrolledWindow::new();
+    scrolled_window.set_policy(PolicyType::Never, PolicyType::Automatic);
+    scrolled_window.set_min_content_height(160);
+    scrolled_window.set_child(Some(&accounts_list));
+    content_box.append(&scrolled_window);
+
+    let preview_box = GtkBox::new(Orientation::Vertical, 12);
+    let preview_window = ScrolledWindow::new();
+    preview_window.set_policy(PolicyType::Never, PolicyType::Automatic);
+    preview_window.set_vexpand(true);
+    preview_window.set_child(Some(&preview_box));
+    content_box.append(&preview_window);
+
+    let copy_name_entry = Entry::new();
+    copy_name_entry.set_placeholder_text(Some("Name for the copy"));
+    copy_name_entry.set_sensitive(false);
+    content_box.append(&copy_name_entry);
 
     let button_box = GtkBox::new(Orientation::Horizontal, 12);
     button_box.set_halign(gtk4::Align::Center);
-    button_box.set_margin_top(16);
 
-    let cancel_button = Button::new();
-    cancel_button.set_label("Cancel");
-    cancel_button.add_css_class("flat");
+    let close_button = Button::with_label("Close");
+    close_button.add_css_class("flat");
 
-    let restore_button = Button::new();
-    restore_button.set_label("Restore Backup");
-    restore_button.add_css_class("destructive-action");
+    let copy_button = Button::with_label("Restore as Copy");
+    copy_button.set_sensitive(false);
 
-    let dialog_clone = dialog.clone();
-    cancel_button.connect_clicked(move |_| {
-        dialog_clone.close();
-    });
+    let overwrite_button = Button::with_label("Overwrite Current");
+    overwrite_button.add_css_class("destructive-action");
+    overwrite_button.set_sensitive(false);
+
+    let selected: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(None));
 
-    let dialog_clone = dialog.clone();
-    let content_area_clone = content_area.clone();
     let vault_name_clone = vault_name.to_string();
-    restore_button.connect_clicked(move |_| {
-        match install_backup(&vault_name_clone) {
-            Ok(()) => {
-                dialog_clone.close();
-                // Refresh the vault view
-                crate::gui::views::vault_view::VaultView::new(
-                    &content_area_clone,
-                    &vault_name_clone,
-                )
-                .create(ctx.clone());
+    let snapshot_id = snapshot.id.clone();
+    let selected_clone = selected.clone();
+    let copy_name_entry_clone = copy_name_entry.clone();
+    let copy_button_clone = copy_button.clone();
+    let overwrite_button_clone = overwrite_button.clone();
+    accounts_list.connect_row_selected(move |_, row| {
+        let account_name = row.and_then(|row| accounts.get(row.index() as usize).cloned());
+        selected_clone.replace(account_name.clone());
+
+        let has_selection = account_name.is_some();
+        copy_name_entry_clone.set_sensitive(has_selection);
+        copy_button_clone.set_sensitive(has_selection);
+        overwrite_button_clone.set_sensitive(has_selection);
+
+        clear_content(&preview_box);
+        let Some(account_name) = account_name else {
+            return;
+        };
+        copy_name_entry_clone.set_text(&format!("{account_name} (restored)"));
+
+        let loading = Label::new(Some("Decrypting..."));
+        loading.add_css_class("dim-label");
+        preview_box.append(&loading);
+
+        let vault_name = vault_name_clone.clone();
+        let snapshot_id = snapshot_id.clone();
+        let preview_box = preview_box.clone();
+        let selected = selected_clone.clone();
+        glib::spawn_future_local(async move {
+            let account_name_task = account_name.clone();
+            let result = gtk4::gio::spawn_blocking(move || {
+                get_backup_account(&vault_name, &snapshot_id, &account_name_task)
+            })
+            .await
+            .expect("decrypt task panicked");
+
+            // Another account may have been selected while this one was decrypting
+            if selected.borrow().as_deref() != Some(account_name.as_str()) {
+                return;
             }
-            Err(e) => {
-                log::error!("Failed to restore backup: {e}");
-                show_error_dialog("Restore Failed", &format!("Could not restore backup: {e}"));
+
+            clear_content(&preview_box);
+            match result {
+                Ok(account) => preview_box.append(&backup_account_preview(&account)),
+                Err(e) => {
+                    log::error!("Failed to decrypt '{account_name}' from the backup: {e}");
+                    let label = Label::new(Some(&format!("Could not decrypt account: {e}")));
+                    label.set_wrap(true);
+                    label.add_css_class("error");
+                    preview_box.append(&label);
+                }
             }
+        });
+    });
+
+    let restore = {
+        let dialog = dialog.clone();
+        let content_area = content_area.clone();
+        let vault_name = vault_name.to_string();
+        let snapshot_id = snapshot.id.clone();
+
+        Rc::new(move |account_name: String, target: RestoreTarget| {
+            let dialog = dialog.clone();
+            let content_area = content_area.clone();
+            let vault_name = vault_name.clone();
+            let snapshot_id = snapshot_id.clone();
+            let ctx = ctx.clone();
+            glib::spawn_future_local(async move {
+                let vault_name_task = vault_name.clone();
+                let account_name_task = account_name.clone();
+                let result = gtk4::gio::spawn_blocking(move || {
+                    restore_account_from_backup(
+                        &vault_name_task,
+                        &snapshot_id,
+                        &account_name_task,
+                        &target,
+                    )
+                })
+                .await
+                .expect("restore task panicked");
+
+                match result {
+                    Ok(restored) => {
+                        dialog.close();
+                        crate::gui::views::vault_view::VaultView::new(&content_area, &vault_name)
+                            .create(ctx);
+                        show_toast(
+                            &content_area,
+                            Toast::new(&glib::markup_escape_text(&format!(
+                                "Restored '{restored}' from the backup"
+                            ))),
+                        );
+                    }
+                    Err(e) => {
+                        log::error!("Failed to restore '{account_name}' from the backup: {e}");
+                        show_error_dialog(
+                            "Restore Failed",
+                            &format!("Could not restore account: {e}"),
+                        );
+                    }
+                }
+            });
+        })
+    };
+
+    let selected_clone = selected.clone();
+    let restore_clone = restore.clone();
+    copy_button.connect_clicked(move |_| {
+        let Some(account_name) = selected_clone.borrow().clone() else {
+            return;
+        };
+        let new_name = copy_name_entry.text().trim().to_string();
+        if new_name.is_empty() {
+            show_error_dialog("Invalid Name", "Enter a name for the restored copy.");
+            return;
         }
+        restore_clone(account_name, RestoreTarget::Copy(new_name));
     });
 
-    button_box.append(&cancel_button);
-    button_box.append(&restore_button);
+    let vault_name_clone = vault_name.to_string();
+    let snapshot_display = snapshot.created_at_display();
+    overwrite_button.connect_clicked(move |_| {
+        let Some(account_name) = selected.borrow().clone() else {
+            return;
+        };
+        let message = if get_available_accounts(&vault_name_clone).contains(&account_name) {
+            format!(
+                "Replace '{account_name}' with its version from {snapshot_display}?\n\nThe current version is kept in the account's revisions."
+            )
+        } else {
+            format!("Bring '{account_name}' back from the backup made {snapshot_display}?")
+        };
+
+        let restore = restore.clone();
+        show_confirmation_dialog(
+            "Overwrite Account",
+            &message,
+            "Restore",
+            None::<&gtk4::Window>,
+            move || restore(account_name.clone(), RestoreTarget::Overwrite),
+        );
+    });
+
+    let dialog_clone = dialog.clone();
+    close_button.connect_clicked(move |_| {
+        dialog_clone.close();
+    });
+
+    button_box.append(&close_button);
+    button_box.append(&copy_button);
+    button_box.append(&overwrite_button);
     content_box.append(&button_box);
 
     dialog.set_child(Some(&content_box));
     dialog.present();
 }
 
-/// Shows the delete backup dialog
-pub fn show_delete_backup_dialog(
-    vault_name: &str,
+/// Shows the accounts added, removed and modified since a backup was made. Changed passwords are
+/// only shown as changed until revealed.
+fn show_backup_diff_dialog(vault_name: &str, snapshot: &BackupSnapshot) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some("Compare with Backup"));
+    dialog.set_modal(true);
+    dialog.set_default_size(480, 560);
+
+    let content_box = GtkBox::new(Orientation::Vertical, 16);
+    content_box.set_margin_top(20);
+    content_box.set_margin_bottom(20);
+    content_box.set_margin_start(20);
+    content_box.set_margin_end(20);
+
+    let title = Label::new(Some(&format!(
+        "Changes since {}",
+        snapshot.created_at_display()
+    )));
+    title.add_css_class("title-2");
+    title.set_halign(gtk4::Align::Center);
+    content_box.append(&title);
+
+    let summary = Label::new(Some("Comparing..."));
+    summary.add_css_class("dim-label");
+    summary.set_wrap(true);
+    summary.set_halign(gtk4::Align::Center);
+    content_box.append(&summary);
+
+    let changes_box = GtkBox::new(Orientation::Vertical, 12);
+    let scrolled_window = ScrolledWindow::new();
+    scrolled_window.set_policy(PolicyType::Never, PolicyType::Automatic);
+    scrolled_window.set_vexpand(true);
+    scrolled_window.set_child(Some(&changes_box));
+    content_box.append(&scrolled_window);
+
+    let button_box = GtkBox::new(Orientation::Horizontal, 12);
+    button_box.set_halign(gtk4::Align::End);
+
+    let close_button = Button::with_label("Close");
+    let dialog_clone = dialog.clone();
+    close_button.connect_clicked(move |_| {
+        dialog_clone.close();
+    });
+    button_box.append(&close_button);
+    content_box.append(&button_box);
+
+    dialog.set_child(Some(&content_box));
+    dialog.present();
+
+    let vault_name = vault_name.to_string();
+    let snapshot_id = snapshot.id.clone();
+    glib::spawn_future_local(async move {
+        let snapshot_id_task = snapshot_id.clone();
+        let result = gtk4::gio::spawn_blocking(move || {
+            diff_vault_with_backup(&vault_name, &snapshot_id_task)
+        })
+        .await
+        .expect("compare task panicked");
+
+        let differences = match result {
+            Ok(differences) => differences,
+            Err(e) => {
+                log::error!("Failed to compare with backup {snapshot_id}: {e}");
+                summary.set_text(&format!("Could not compare with the backup: {e}"));
+                summary.remove_css_class("dim-label");
+                summary.add_css_class("error");
+                return;
+            }
+        };
+
+        if differences.is_empty() {
+            summary.set_text("No accounts have changed since this backup.");
+            return;
+        }
+
+        let count = |status| {
+            differences
+                .iter()
+                .filter(|difference| difference.status == status)
+                .count()
+        };
+        summary.set_text(&format!(
+            "{} added • {} removed • {} modified",
+            count(AccountStatus::Added),
+            count(AccountStatus::Removed),
+            count(AccountStatus::Modified)
+        ));
+
+        for (status, heading, description) in [
+            (
+                AccountStatus::Added,
+                "Added",
+                "Not in the backup; restoring it would remove these.",
+            ),
+            (
+                AccountStatus::Removed,
+                "Removed",
+                "Only in the backup; they can be restored from it.",
+            ),
+        ] {
+            if count(status) == 0 {
+                continue;
+            }
+
+            let group = PreferencesGroup::new();
+            group.set_title(heading);
+            group.set_description(Some(description));
+            for difference in differences
+                .iter()
+                .filter(|difference| difference.status == status)
+            {
+                group.add(
+                    &CreateActionRow::<fn()>::new()
+                        .title(glib::markup_escape_text(&difference.account_name).as_str())
+                        .add_button(false)
+                        .activatable(false)
+                        .build(),
+                );
+            }
+            changes_box.append(&group);
+        }
+
+        for difference in differences
+            .iter()
+            .filter(|difference| difference.status == AccountStatus::Modified)
+        {
+            changes_box.append(&modified_account_group(difference));
+        }
+    });
+}
+
+/// Creates a group listing the changed fields of a modified account. A changed password can be
+/// revealed, showing its backed up and current values.
+fn modified_account_group(difference: &AccountDifference) -> PreferencesGroup {
+    let group = PreferencesGroup::new();
+    group.add_css_class("group_background");
+    group.set_title(&glib::markup_escape_text(&difference.account_name));
+    group.set_description(Some("Modified"));
+
+    for change in &difference.changes {
+        let row = field_change_row(change);
+
+        if change.field == "Password"
+            && let Some((backup, current)) = &difference.versions
+        {
+            // Only expose the passwords while they are shown
+            let backup_password = backup.password.clone();
+            let current_password = current.password.clone();
+
+            let reveal_button = Button::from_icon_name("view-reveal-symbolic");
+            reveal_button.add_css_class("flat");
+            reveal_button.set_valign(gtk4::Align::Center);
+            reveal_button.set_tooltip_text(Some("Show/Hide Passwords"));
+
+            let row_clone = row.clone();
+            let is_revealed = Rc::new(RefCell::new(false));
+            reveal_button.connect_clicked(move |_| {
+                let mut revealed = is_revealed.borrow_mut();
+                *revealed = !*revealed;
+                if *revealed {
+                    // Reserve room for the worst case, so growing never leaves a stray copy
+                    let length = backup_password.with_exposed(str::len)
+                        + current_password.with_exposed(str::len);
+                    let mut passwords = String::with_capacity(length * 6 + 5);
+                    backup_password.with_exposed(|old| push_markup_escaped(&mut passwords, old));
+                    passwords.push_str(" → ");
+                    current_password.with_exposed(|new| push_markup_escaped(&mut passwords, new));
+                    row_clone.set_subtitle(&passwords);
+                    use zeroize::Zeroize;
+                    passwords.zeroize();
+                } else {
+                    row_clone.set_subtitle("Changed");
+                }
+            });
+            row.add_suffix(&reveal_button);
+        }
+
+        group.add(&row);
+    }
+
+    group
+}
+
+/// Appends `text` to `markup` with markup characters escaped, without the intermediate copies
+/// `glib::markup_escape_text` would leave behind for a secret
+fn push_markup_escaped(markup: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => markup.push_str("&amp;"),
+            '<' => markup.push_str("&lt;"),
+            '>' => markup.push_str("&gt;"),
+            '"' => markup.push_str("&quot;"),
+            '\'' => markup.push_str("&apos;"),
+            _ => markup.push(c),
+        }
+    }
+}
+
+/// Creates a read-only summary of a backed up account, with its password masked
+fn backup_account_preview(account: &Account) -> PreferencesGroup {
+    let group = PreferencesGroup::new();
+    group.add_css_class("group_background");
+    group.set_title(&glib::markup_escape_text(&account.name));
+    group.set_description(Some(&format!("Last modified {}", account.modified_at)));
+
+    let mut fields = vec![
+        ("Type", account.account_type.clone()),
+        ("Website", account.website.clone()),
+        ("Username", account.username.clone()),
+        ("Password", account.password.masked(8)),
+        ("Notes", account.notes.clone()),
+    ];
+    if account.totp.is_some() {
+        fields.push(("Two-factor", "Configured".to_string()));
+    }
+
+    let mut additional: Vec<(&String, &String)> = account.additional_fields.iter().collect();
+    additional.sort();
+
+    for (field, value) in fields
+        .iter()
+        .map(|(field, value)| (*field, value.as_str()))
+        .chain(
+            additional
+                .iter()
+                .map(|(field, value)| (field.as_str(), value.as_str())),
+        )
+        .filter(|(_, value)| !value.is_empty())
+    {
+        let row = ActionRow::new();
+        row.set_title(&glib::markup_escape_text(field));
+        row.set_subtitle(&glib::markup_escape_text(value));
+        row.set_margin_start(8);
+        row.set_margin_end(8);
+        group.add(&row);
+    }
+
+    group
+}
+
+/// Creates the preferences controlling when backups are made automatically
+fn create_auto_backup_group(vault_name: &str) -> PreferencesGroup {
+    let group = PreferencesGroup::new();
+    group.set_title("Automatic Backups");
+    group.set_description(Some(
+        "Backups are made in the background, and only if the vault changed since the last one",
+    ));
+
+    let policy = load_vault_settings(vault_name)
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to load the settings of '{vault_name}': {e}");
+            Default::default()
+        })
+        .auto_backup;
+
+    let changes_row = ActionRow::new();
+    changes_row.set_title("After Changes");
+    changes_row.set_subtitle("Back up after this many changes to accounts, or 0 for never");
+
+    let changes_adjustment =
+        Adjustment::new(f64::from(policy.every_changes), 0.0, 1000.0, 1.0, 10.0, 0.0);
+    let changes_spinner = SpinButton::new(Some(&changes_adjustment), 1.0, 0);
+    changes_spinner.set_valign(gtk4::Align::Center);
+    changes_spinner.connect_value_changed({
+        let vault_name = vault_name.to_string();
+        move |spinner| {
+            update_auto_backup_policy(&vault_name, |policy| {
+                policy.every_changes = spinner.value() as u32
+            });
+        }
+    });
+    changes_row.add_suffix(&changes_spinner);
+    group.add(&changes_row);
+
+    let switches = [
+        ("Daily", "Back up at most once a day", policy.daily),
+        ("When Closing", "Back up when fmp is closed", policy.on_exit),
+    ];
+
+    for (index, (title, subtitle, active)) in switches.into_iter().enumerate() {
+        let row = ActionRow::new();
+        row.set_title(title);
+        row.set_subtitle(subtitle);
+
+        let switch = Switch::new();
+        switch.set_active(active);
+        switch.set_valign(gtk4::Align::Center);
+
+        let vault_name = vault_name.to_string();
+        switch.connect_state_set(move |_, state| {
+            update_auto_backup_policy(&vault_name, |policy| match index {
+                0 => policy.daily = state,
+                _ => policy.on_exit = state,
+            });
+            glib::Propagation::Proceed
+        });
+
+        row.add_suffix(&switch);
+        row.set_activatable_widget(Some(&switch));
+        group.add(&row);
+    }
+
+    group
+}
+
+/// Changes the automatic backup policy saved for a vault
+fn update_auto_backup_policy(vault_name: &str, change: impl FnOnce(&mut AutoBackupPolicy)) {
+    let mut settings = load_vault_settings(vault_name).unwrap_or_default();
+    change(&mut settings.auto_backup);
+    save_settings(vault_name, &settings);
+}
+
+/// Saves a vault's settings, then signs its manifest again off the GTK main thread
+fn save_settings(vault_name: &str, settings: &VaultSettings) {
+    if let Err(e) = save_vault_settings(vault_name, settings) {
+        log::error!("Failed to save the settings of '{vault_name}': {e}");
+        return;
+    }
+
+    let vault_task = vault_name.to_string();
+    gtk4::gio::spawn_blocking(move || update_vault_manifest(&vault_task));
+}
+
+/// Creates the preferences controlling which backups are kept when a new one is made
+fn create_backup_retention_group(vault_name: &str) -> PreferencesGroup {
+    let group = PreferencesGroup::new();
+    group.set_title("Retention");
+    group.set_description(Some(
+        "Older backups are deleted when a new one is made, unless a rule keeps them",
+    ));
+
+    let policy = load_vault_settings(vault_name)
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to load the settings of '{vault_name}': {e}");
+            Default::default()
+        })
+        .backup_retention;
+
+    let rows = [
+        (
+            "Latest Backups",
+            "Always keep this many of the newest backups",
+            1.0,
+            policy.keep_last as f64,
+        ),
+        (
+            "Daily Backups",
+            "Keep the newest backup of each of the last this many days",
+            0.0,
+            f64::from(policy.keep_daily_days),
+        ),
+        (
+            "Weekly Backups",
+            "Keep the newest backup of each of the last this many weeks",
+            0.0,
+            f64::from(policy.keep_weekly_weeks),
+        ),
+    ];
+
+    for (index, (title, subtitle, minimum, value)) in rows.into_iter().enumerate() {
+        let row = ActionRow::new();
+        row.set_title(title);
+        row.set_subtitle(subtitle);
+
+        let adjustment = Adjustment::new(value, minimum, 365.0, 1.0, 7.0, 0.0);
+        let spinner = SpinButton::new(Some(&adjustment), 1.0, 0);
+        spinner.set_valign(gtk4::Align::Center);
+
+        let vault_name = vault_name.to_string();
+        spinner.connect_value_changed(move |spinner| {
+            let mut settings = load_vault_settings(&vault_name).unwrap_or_default();
+            let policy = &mut settings.backup_retention;
+            match index {
+                0 => policy.keep_last = spinner.value() as usize,
+                1 => policy.keep_daily_days = spinner.value() as u32,
+                _ => policy.keep_weekly_weeks = spinner.value() as u32,
+            }
+            save_settings(&vault_name, &settings);
+        });
+        row.add_suffix(&spinner);
+        group.add(&row);
+    }
+
+    group
+}
+
+/// Creates a file filter matching vault archives
+fn archive_file_filter() -> FileFilter {
+    let filter = FileFilter::new();
+    filter.set_name(Some("Vault Archives"));
+    filter.add_pattern(&format!("*.{ARCHIVE_EXTENSION}"));
+    filter
+}
+
+/// Asks where to save an archive of the vault, then exports it there
+pub fn show_export_vault_dialog(vault_name: &str, content_area: &GtkBox) {
+    let initial_name = format!("{vault_name}.{ARCHIVE_EXTENSION}");
+    let vault_name = vault_name.to_string();
+    let content_area = content_area.clone();
+    show_file_chooser_dialog(
+        "Export Vault",
+        FileChooserAction::Save,
+        &archive_file_filter(),
+        Some(&initial_name),
+        None::<&gtk4::Window>,
+        move |file| {
+            let Some(path) = file.path() else {
+                show_error_dialog("Export Failed", "Only local files can be exported to.");
+                return;
+            };
+
+            let vault_name = vault_name.clone();
+            let content_area = content_area.clone();
+            glib::spawn_future_local(async move {
+                let vault_name_task = vault_name.clone();
+                let path_task = path.clone();
+                let result =
+                    gtk4::gio::spawn_blocking(move || export_vault(&vault_name_task, &path_task))
+                        .await
+                        .expect("export task panicked");
+
+                match result {
+                    Ok(manifest) => show_toast(
+                        &content_area,
+                        Toast::new(&format!(
+                            "Exported {} account(s) to {}",
+                            manifest.account_names().len(),
+                            glib::markup_escape_text(&path.display().to_string())
+                        )),
+                    ),
+                    Err(e) => {
+                        log::error!("Failed to export '{vault_name}': {e}");
+                        show_error_dialog("Export Failed", &format!("Could not export vault: {e}"));
+                    }
+                }
+            });
+        },
+    );
+}
+
+/// Asks for an archive file, verifies it, then shows the import dialog for it
+pub fn show_import_vault_dialog(content_area: &GtkBox, ctx: Rc<RefCell<Context>>) {
+    let content_area = content_area.clone();
+    show_file_chooser_dialog(
+        "Import Vault",
+        FileChooserAction::Open,
+        &archive_file_filter(),
+        None,
+        None::<&gtk4::Window>,
+        move |file| {
+            let Some(path) = file.path() else {
+                show_error_dialog("Import Failed", "Only local files can be imported.");
+                return;
+            };
+
+            let content_area = content_area.clone();
+            let ctx = ctx.clone();
+            glib::spawn_future_local(async move {
+                let path_task = path.clone();
+                let result = gtk4::gio::spawn_blocking(move || verify_archive(&path_task))
+                    .await
+                    .expect("verify task panicked");
+
+                match result {
+                    Ok(manifest) => show_import_archive_dialog(&content_area, path, manifest, ctx),
+                    Err(e) => {
+                        log::error!("Archive '{}' failed verification: {e}", path.display());
+                        show_error_dialog(
+                            "Invalid Archive",
+                            &format!("This archive cannot be imported: {e}"),
+                        );
+                    }
+                }
+            });
+        },
+    );
+}
+
+/// Shows what a verified archive contains and imports it as a new vault, or over an existing one
+fn show_import_archive_dialog(
     content_area: &GtkBox,
+    archive_path: PathBuf,
+    manifest: ArchiveManifest,
     ctx: Rc<RefCell<Context>>,
 ) {
     let dialog = Dialog::new();
-    dialog.set_title(Some("Delete Vault Backup"));
+    dialog.set_title(Some("Import Vault"));
     dialog.set_modal(true);
-    dialog.set_default_size(400, 200);
+    dialog.set_default_size(440, 360);
 
     let content_box = GtkBox::new(Orientation::Vertical, 16);
     content_box.set_margin_top(20);
@@ -171,30 +1226,69 @@ pub fn show_delete_backup_dialog(
     content_box.set_margin_start(20);
     content_box.set_margin_end(20);
 
-    let title = Label::new(Some(&format!("Delete Backup for '{vault_name}'")));
+    let title = Label::new(Some("Import Vault"));
     title.add_css_class("title-2");
     title.set_halign(gtk4::Align::Center);
     content_box.append(&title);
 
-    let warning = Label::new(Some(
-        "Are you sure you want to delete the backup?\nThis action cannot be undone.",
-    ));
-    warning.add_css_class("error");
-    warning.set_wrap(true);
-    warning.set_halign(gtk4::Align::Center);
-    content_box.append(&warning);
+    let summary = PreferencesGroup::new();
+    summary.set_description(Some("Every file in the archive matches its checksum."));
+    for (field, value) in [
+        ("Vault", manifest.vault_name.clone()),
+        ("Exported", manifest.created_at.clone()),
+        ("Accounts", manifest.account_names().len().to_string()),
+        (
+            "Files",
+            format!(
+                "{} ({} KiB)",
+                manifest.files.len(),
+                manifest.total_size().div_ceil(1024)
+            ),
+        ),
+    ] {
+        let row = ActionRow::new();
+        row.set_title(field);
+        row.set_subtitle(&glib::markup_escape_text(&value));
+        summary.add(&row);
+    }
+    content_box.append(&summary);
+
+    let name_label = Label::new(Some("Import As"));
+    name_label.set_halign(gtk4::Align::Start);
+    content_box.append(&name_label);
+
+    let name_entry = Entry::new();
+    name_entry.set_text(&manifest.vault_name);
+    content_box.append(&name_entry);
+
+    let replace_label = Label::new(None);
+    replace_label.add_css_class("dim-label");
+    replace_label.set_wrap(true);
+    replace_label.set_halign(gtk4::Align::Start);
+    content_box.append(&replace_label);
 
     let button_box = GtkBox::new(Orientation::Horizontal, 12);
     button_box.set_halign(gtk4::Align::Center);
-    button_box.set_margin_top(16);
 
-    let cancel_button = Button::new();
-    cancel_button.set_label("Cancel");
-    cancel_button.add_css_class("flat");
+    let cancel_button = Button::with_label("Cancel");
+    let (import_button, _) = create_loading_button("Import", "Importing...");
+    import_button.add_css_class("suggested-action");
 
-    let delete_button = Button::new();
-    delete_button.set_label("Delete Backup");
-    delete_button.add_css_class("destructive-action");
+    let update_mode = {
+        let replace_label = replace_label.clone();
+        let import_button = import_button.clone();
+        move |name: &str| {
+            let exists = get_available_vaults().iter().any(|vault| vault == name);
+            replace_label.set_text(if exists {
+                "A vault with this name exists. Importing replaces its data; a backup of it is made first."
+            } else {
+                "The archive is imported as a new vault."
+            });
+            import_button.set_sensitive(!name.trim().is_empty());
+        }
+    };
+    update_mode(&manifest.vault_name);
+    name_entry.connect_changed(move |entry| update_mode(&entry.text()));
 
     let dialog_clone = dialog.clone();
     cancel_button.connect_clicked(move |_| {
@@ -202,22 +1296,83 @@ pub fn show_delete_backup_dialog(
     });
 
     let dialog_clone = dialog.clone();
-    let content_area_clone = content_area.clone();
-    let vault_name_clone = vault_name.to_string();
-    delete_button.connect_clicked(move |_| match delete_backup(&vault_name_clone) {
-        Ok(()) => {
-            dialog_clone.close();
-            crate::gui::views::vault_view::VaultView::new(&content_area_clone, &vault_name_clone)
-                .create(ctx.clone());
-        }
-        Err(e) => {
-            log::error!("Failed to delete backup: {e}");
-            show_error_dialog("Delete Failed", &format!("Could not delete backup: {e}"));
+    let content_area = content_area.clone();
+    import_button.connect_clicked(move |button| {
+        let vault_name = name_entry.text().trim().to_string();
+        let exists = get_available_vaults().contains(&vault_name);
+        let mode = if exists {
+            ImportMode::Replace
+        } else {
+            ImportMode::New
+        };
+
+        let run_import = {
+            let button = button.clone();
+            let dialog = dialog_clone.clone();
+            let content_area = content_area.clone();
+            let archive_path = archive_path.clone();
+            let ctx = ctx.clone();
+            move || {
+                set_button_loading_state(&button, true);
+
+                let button = button.clone();
+                let dialog = dialog.clone();
+                let content_area = content_area.clone();
+                let archive_path = archive_path.clone();
+                let vault_name = vault_name.clone();
+                let ctx = ctx.clone();
+                glib::spawn_future_local(async move {
+                    let vault_name_task = vault_name.clone();
+                    let result = gtk4::gio::spawn_blocking(move || {
+                        import_vault(&archive_path, &vault_name_task, mode)
+                    })
+                    .await
+                    .expect("import task panicked");
+
+                    match result {
+                        Ok(_) => {
+                            dialog.close();
+                            crate::gui::sidebar::refresh_sidebar_from_content_area(
+                                &content_area,
+                                ctx.clone(),
+                            );
+                            crate::gui::content::proceed_with_gate_warmup(
+                                &content_area,
+                                &vault_name,
+                                ctx,
+                            );
+                        }
+                        Err(e) => {
+                            log::error!("Failed to import '{vault_name}': {e}");
+                            set_button_loading_state(&button, false);
+                            show_error_dialog(
+                                "Import Failed",
+                                &format!("Could not import vault: {e}"),
+                            );
+                        }
+                    }
+                });
+            }
+        };
+
+        if exists {
+            show_confirmation_dialog(
+                "Replace Vault",
+                &format!(
+                    "Replace all data in '{}' with the archive?\n\nA backup of the current vault is made first.",
+                    name_entry.text().trim()
+                ),
+                "Replace",
+                None::<&gtk4::Window>,
+                run_import,
+            );
+        } else {
+            run_import();
         }
     });
 
     button_box.append(&cancel_button);
-    button_box.append(&delete_button);
+    button_box.append(&import_button);
     content_box.append(&button_box);
 
     dialog.set_child(Some(&content_box));
@@ -405,3 +1560,354 @@ pub fn show_delete_vault_dialog(
     dialog.set_child(Some(&content_box));
     dialog.present();
 }
+
+/// Shows the vault members dialog for sharing a vault with other GPG keys
+pub fn show_vault_members_dialog(
+    vault_name: &str,
+    content_area: &GtkBox,
+    ctx: Rc<RefCell<Context>>,
+) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some("Vault Members"));
+    dialog.set_modal(true);
+    dialog.set_default_size(450, 350);
+
+    let content_box = GtkBox::new(Orientation::Vertical, 16);
+    content_box.set_margin_top(20);
+    content_box.set_margin_bottom(20);
+    content_box.set_margin_start(20);
+    content_box.set_margin_end(20);
+
+    let title = Label::new(Some(&format!("Members of '{vault_name}'")));
+    title.add_css_class("title-2");
+    title.set_halign(gtk4::Align::Center);
+    content_box.append(&title);
+
+    let description = Label::new(Some(
+        "Every account is encrypted to each member's GPG key. Adding, removing or re-keying re-encrypts the whole vault.",
+    ));
+    description.add_css_class("dim-label");
+    description.set_wrap(true);
+    description.set_halign(gtk4::Align::Center);
+    content_box.append(&description);
+
+    let members_list = ListBox::new();
+    members_list.add_css_class("boxed-list");
+    members_list.set_selection_mode(SelectionMode::None);
+
+    let recipients = get_vault_recipients(vault_name).unwrap_or_else(|e| {
+        log::error!("Failed to read vault members: {e}");
+        Vec::new()
+    });
+    let is_last_member = recipients.len() <= 1;
+
+    for recipient in recipients {
+        let row = ActionRow::new();
+        row.set_title(&recipient);
+
+        let remove_button = Button::with_label("Remove");
+        remove_button.add_css_class("destructive-action");
+        remove_button.set_valign(gtk4::Align::Center);
+        remove_button.set_sensitive(!is_last_member);
+
+        let dialog_clone = dialog.clone();
+        let content_area_clone = content_area.clone();
+        let vault_name_clone = vault_name.to_string();
+        let ctx_clone = ctx.clone();
+        remove_button.connect_clicked(move |button| {
+            button.set_sensitive(false);
+            let vault_name = vault_name_clone.clone();
+            let recipient = recipient.clone();
+            run_member_change(
+                &dialog_clone,
+                &content_area_clone,
+                ctx_clone.clone(),
+                vault_name.clone(),
+                move || remove_vault_recipient(&vault_name, &recipient),
+            );
+        });
+
+        row.add_suffix(&remove_button);
+        members_list.append(&row);
+    }
+
+    content_box.append(&members_list);
+
+    let entry_label = Label::new(Some("Add member:"));
+    entry_label.set_halign(gtk4::Align::Start);
+    content_box.append(&entry_label);
+
+    let recipient_entry = Entry::new();
+    recipient_entry.set_placeholder_text(Some("Enter GPG key ID or email"));
+    content_box.append(&recipient_entry);
+
+    let rekey_label = Label::new(Some(
+        "Re-key vault (replaces every member, e.g. after a key expires or is compromised):",
+    ));
+    rekey_label.set_halign(gtk4::Align::Start);
+    rekey_label.set_wrap(true);
+    content_box.append(&rekey_label);
+
+    let rekey_entry = Entry::new();
+    rekey_entry.set_placeholder_text(Some("New GPG key IDs or emails, separated by spaces"));
+    content_box.append(&rekey_entry);
+
+    let button_box = GtkBox::new(Orientation::Horizontal, 12);
+    button_box.set_halign(gtk4::Align::Center);
+    button_box.set_margin_top(16);
+
+    let close_button = Button::new();
+    close_button.set_label("Close");
+    close_button.add_css_class("flat");
+
+    let (add_button, _) = create_loading_button("Add Member", "Re-encrypting...");
+    add_button.add_css_class("suggested-action");
+
+    let dialog_clone = dialog.clone();
+    close_button.connect_clicked(move |_| {
+        dialog_clone.close();
+    });
+
+    let dialog_clone = dialog.clone();
+    let content_area_clone = content_area.clone();
+    let vault_name_clone = vault_name.to_string();
+    let recipient_entry_clone = recipient_entry.clone();
+    let ctx_clone = ctx.clone();
+    add_button.connect_clicked(move |button| {
+        let recipient = recipient_entry_clone.text().trim().to_string();
+        if recipient.is_empty() {
+            return;
+        }
+
+        set_button_loading_state(button, true);
+        let vault_name = vault_name_clone.clone();
+        run_member_change(
+            &dialog_clone,
+            &content_area_clone,
+            ctx_clone.clone(),
+            vault_name.clone(),
+            move || add_vault_recipient(&vault_name, &recipient),
+        );
+    });
+
+    let add_button_clone = add_button.clone();
+    recipient_entry.connect_activate(move |_| {
+        add_button_clone.emit_clicked();
+    });
+
+    let (rekey_button, _) = create_loading_button("Re-key Vault", "Re-encrypting...");
+    rekey_button.add_css_class("destructive-action");
+
+    let dialog_clone = dialog.clone();
+    let content_area_clone = content_area.clone();
+    let vault_name_clone = vault_name.to_string();
+    let rekey_entry_clone = rekey_entry.clone();
+    rekey_button.connect_clicked(move |button| {
+        let recipients: Vec<String> = rekey_entry_clone
+            .text()
+            .split([' ', ','])
+            .filter(|recipient| !recipient.is_empty())
+            .map(str::to_string)
+            .collect();
+        if recipients.is_empty() {
+            return;
+        }
+
+        set_button_loading_state(button, true);
+        let vault_name = vault_name_clone.clone();
+        run_member_change(
+            &dialog_clone,
+            &content_area_clone,
+            ctx.clone(),
+            vault_name.clone(),
+            move || rekey_vault(&vault_name, &recipients),
+        );
+    });
+
+    button_box.append(&close_button);
+    button_box.append(&rekey_button);
+    button_box.append(&add_button);
+    content_box.append(&button_box);
+
+    dialog.set_child(Some(&content_box));
+    dialog.present();
+}
+
+/// Asks before moving a vault's accounts into a single file, or back into one directory each, and
+/// converts it off the main thread
+pub fn show_convert_vault_dialog(
+    vault_name: &str,
+    content_area: &GtkBox,
+    ctx: Rc<RefCell<Context>>,
+) {
+    let single_file = is_single_file(vault_name);
+    let (title, message) = if single_file {
+        (
+            "Use Account Directories",
+            format!(
+                "Move the accounts in '{vault_name}' out of its single file into one directory each?\n\nAnyone who can see the vault's files will see how many accounts it has and what they are called. A backup is made first."
+            ),
+        )
+    } else {
+        (
+            "Use a Single File",
+            format!(
+                "Move every account in '{vault_name}' into one encrypted file?\n\nThe number and names of accounts are hidden on disk, and the vault is easier to sync or copy. A backup is made first."
+            ),
+        )
+    };
+
+    let vault_name = vault_name.to_string();
+    let content_area = content_area.clone();
+    show_confirmation_dialog(
+        title,
+        &message,
+        "Convert",
+        None::<&gtk4::Window>,
+        move || {
+            let vault_name = vault_name.clone();
+            let content_area = content_area.clone();
+            let ctx = ctx.clone();
+
+            glib::spawn_future_local(async move {
+                let vault = vault_name.clone();
+                let result = gtk4::gio::spawn_blocking(move || {
+                    if single_file {
+                        convert_to_directories(&vault)
+                    } else {
+                        convert_to_single_file(&vault)
+                    }
+                })
+                .await
+                .expect("vault conversion task panicked");
+
+                match result {
+                    Ok(_) => {
+                        crate::gui::views::vault_view::VaultView::new(&content_area, &vault_name)
+                            .create(ctx);
+                        show_toast(
+                            &content_area,
+                            Toast::new(&glib::markup_escape_text(&format!(
+                                "Converted '{vault_name}'"
+                            ))),
+                        );
+                    }
+                    Err(e) => {
+                        log::error!("Failed to convert vault: {e}");
+                        show_error_dialog(
+                            "Conversion Failed",
+                            &format!("Could not convert vault: {e}"),
+                        );
+                    }
+                }
+            });
+        },
+    );
+}
+
+/// Asks before storing a vault's accounts under opaque IDs, or back under their names, and moves
+/// them off the main thread
+pub fn show_account_names_dialog(
+    vault_name: &str,
+    content_area: &GtkBox,
+    ctx: Rc<RefCell<Context>>,
+) {
+    let hidden = vault_hides_names(vault_name);
+    let (title, message, confirm_label) = if hidden {
+        (
+            "Show Account Names",
+            format!(
+                "Store the accounts in '{vault_name}' under their names again?\n\nAnyone who can see the vault's files will see what its accounts are called. A backup is made first."
+            ),
+            "Show Names",
+        )
+    } else {
+        (
+            "Hide Account Names",
+            format!(
+                "Store the accounts in '{vault_name}' under opaque IDs?\n\nTheir names are kept in an encrypted index, so they cannot be read from the vault's files. A backup is made first."
+            ),
+            "Hide Names",
+        )
+    };
+
+    let vault_name = vault_name.to_string();
+    let content_area = content_area.clone();
+    show_confirmation_dialog(
+        title,
+        &message,
+        confirm_label,
+        None::<&gtk4::Window>,
+        move || {
+            let vault_name = vault_name.clone();
+            let content_area = content_area.clone();
+            let ctx = ctx.clone();
+
+            glib::spawn_future_local(async move {
+                let vault = vault_name.clone();
+                let result = gtk4::gio::spawn_blocking(move || {
+                    if hidden {
+                        show_account_names(&vault)
+                    } else {
+                        hide_account_names(&vault)
+                    }
+                })
+                .await
+                .expect("account name migration task panicked");
+
+                match result {
+                    Ok(_) => {
+                        crate::gui::views::vault_view::VaultView::new(&content_area, &vault_name)
+                            .create(ctx);
+                        show_toast(
+                            &content_area,
+                            Toast::new(&glib::markup_escape_text(&format!(
+                                "Account names in '{vault_name}' are now {}",
+                                if hidden { "shown on disk" } else { "hidden" }
+                            ))),
+                        );
+                    }
+                    Err(e) => {
+                        log::error!("Failed to move accounts: {e}");
+                        show_error_dialog(
+                            "Moving Accounts Failed",
+                            &format!("Could not move the vault's accounts: {e}"),
+                        );
+                    }
+                }
+            });
+        },
+    );
+}
+
+/// Runs a member change off the main thread, then reopens the members dialog with the new list
+fn run_member_change<F>(
+    dialog: &Dialog,
+    content_area: &GtkBox,
+    ctx: Rc<RefCell<Context>>,
+    vault_name: String,
+    change: F,
+) where
+    F: FnOnce() -> Result<(), anyhow::Error> + Send + 'static,
+{
+    let dialog = dialog.clone();
+    let content_area = content_area.clone();
+
+    glib::spawn_future_local(async move {
+        let result = gtk4::gio::spawn_blocking(change)
+            .await
+            .expect("member change task panicked");
+
+        dialog.close();
+
+        if let Err(e) = result {
+            log::error!("Failed to change vault members: {e}");
+            show_error_dialog(
+                "Member Change Failed",
+                &format!("Could not update vault members: {e}"),
+            );
+        }
+
+        show_vault_members_dialog(&vault_name, &content_area, ctx);
+    });
+}
@@ -1,3 +1,4 @@
+use crate::storage::profile::current_home;
 use adw::{HeaderBar, Window as AdwWindow, prelude::*};
 use anyhow::Error;
 use gtk4::{Box as GtkBox, Button, Entry, Label, Orientation, glib};
@@ -30,8 +31,7 @@ pub fn mark_first_run_complete() -> Result<(), std::io::Error> {
 
 /// Gets the path to the first-run marker file
 fn get_config_file_path() -> PathBuf {
-    let config_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
-    config_dir.join("fmp").join("fmp-ran")
+    current_home().config.join("fmp-ran")
 }
 
 pub fn show_welcome_dialog(parent: &adw::ApplicationWindow) {
@@ -1,35 +1,14 @@
 use crate::{
     gui::{content::proceed_with_gate_warmup, views::home_view::HomeView},
-    storage::filesystem::read_directory,
-    vault::Locations,
+    storage::filesystem::get_available_vaults,
 };
 use adw::{ButtonContent, Clamp, HeaderBar, PreferencesGroup, prelude::*};
 use gpgme::Context;
 use gtk4::{Box, Button, Label, Orientation, Paned, PolicyType, ScrolledWindow, SearchEntry};
-use std::{cell::RefCell, path::PathBuf, rc::Rc};
+use std::{cell::RefCell, rc::Rc};
 
 const SIDEBAR_WIDTH: i32 = 250;
 
-/// Gets the vaults directory path
-fn get_vaults_directory() -> PathBuf {
-    let locations = Locations::new("", "");
-    locations.fmp.join("vaults")
-}
-
-/// Reads available vaults from the vaults directory
-pub fn get_available_vaults() -> Vec<String> {
-    let vaults_dir = get_vaults_directory();
-
-    read_directory(&vaults_dir).unwrap_or_else(|e| {
-        log::error!(
-            "Failed to read vaults directory: {} - Error: {}",
-            vaults_dir.display(),
-            e
-        );
-        Vec::new()
-    })
-}
-
 /// Creates a responsive paned layout with sidebar that can update the main content
 pub fn create_paned_layout_with_callbacks(
     main_content: &Box,
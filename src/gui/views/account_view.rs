@@ -1,26 +1,36 @@
 use crate::{
+    breach::{BreachStatus, check_password_breached},
     gui::{
         content::{
             CreateBox, CreateScrollableView, clear_content, create_editable_field_row,
-            create_field_row,
+            create_field_row, show_toast_with_button,
         },
         dialogs::{
             account_management::{
                 show_add_field_dialog, show_delete_field_dialog, show_edit_field_dialog,
-                show_rename_account_dialog,
+                show_rename_account_dialog, show_revision_diff_dialog,
             },
-            common::show_confirmation_dialog,
+            common::{show_confirmation_dialog, show_error_dialog},
             password_generator::show_password_generator_dialog,
+            totp::show_import_otp_dialog,
         },
         views::vault_view::VaultView,
     },
-    vault::{Account, create_account, delete_account, get_full_account_details, update_account},
+    models::AccountTotp,
+    security::SecurePassword,
+    totp::{TotpAlgorithm, current_account_totp_code, decode_totp_secret},
+    vault::{
+        Account, create_account, delete_account, get_full_account_details,
+        revisions::{Revision, list_revisions, rollback_account},
+        trash::restore_account_from_trash,
+        update_account,
+    },
 };
-use adw::{ButtonContent, PreferencesGroup, prelude::*};
+use adw::{ActionRow, ButtonContent, PreferencesGroup, Toast, prelude::*};
 use gpgme::Context;
 use gtk4::{
-    Align, Box, Button, Entry, Label, Orientation, PolicyType, ScrolledWindow, Separator, TextView,
-    pango::EllipsizeMode,
+    Align, Box, Button, DropDown, Entry, Label, Orientation, PolicyType, ScrolledWindow, Separator,
+    SpinButton, TextView, pango::EllipsizeMode,
 };
 use std::{cell::RefCell, rc::Rc};
 
@@ -85,6 +95,10 @@ pub fn show_new_account_view(content_area: &Box, vault_name: &str, ctx: Rc<RefCe
     let username_row = create_editable_field_row("Username", "", &new_account, "username");
     form_box.append(&username_row);
 
+    // Tags
+    let tags_row = create_editable_field_row("Tags", "", &new_account, "tags");
+    form_box.append(&tags_row);
+
     // Password (with show/hide functionality)
     let password_row = create_password_field_row("Password", "", &new_account);
     form_box.append(&password_row);
@@ -238,9 +252,29 @@ impl AccountView {
             main_box.append(&this.header_section(&account_rc, ctx_clone.clone()));
             main_box.append(&this.details_section(&account_rc));
             main_box.append(&this.password_section(&account_rc));
+            if !edit_mode && !account_rc.borrow().password_history.is_empty() {
+                main_box.append(&this.password_history_section(&account_rc, ctx_clone.clone()));
+            }
+            if edit_mode || account_rc.borrow().totp.is_some() {
+                main_box.append(&this.one_time_code_section(&account_rc));
+            }
             main_box.append(&this.additional_fields_section(&account_rc, ctx_clone.clone()));
             main_box.append(&this.notes_section(&account_rc));
 
+            if !edit_mode {
+                let revisions = list_revisions(&vault_name, &account_name).unwrap_or_else(|e| {
+                    log::warn!("Failed to list revisions of '{account_name}': {e}");
+                    Vec::new()
+                });
+                if !revisions.is_empty() {
+                    main_box.append(&this.revisions_section(
+                        &account_rc,
+                        revisions,
+                        ctx_clone.clone(),
+                    ));
+                }
+            }
+
             if edit_mode {
                 main_box.append(&this.actions_section(&account_rc, ctx_clone));
             }
@@ -344,7 +378,7 @@ impl AccountView {
 
             delete_button.connect_clicked(move |_| {
                 let message = format!(
-                    "Are you sure you want to delete the account '{account_name_delete}'?\n\nThis action cannot be undone and will permanently remove all data associated with this account.");
+                    "Are you sure you want to delete the account '{account_name_delete}'?\n\nIt will be moved to the vault's trash, where it can be restored until the trash is emptied.");
 
                 let content_area_confirm = content_area_delete.clone();
                 let vault_name_confirm = vault_name_delete.clone();
@@ -357,14 +391,61 @@ impl AccountView {
                     "Delete",
                     None::<&gtk4::Window>,
                     move || {
-                        match delete_account(&vault_name_confirm, &account_name_confirm) {
-                            Ok(()) => {
-                                VaultView::new(&content_area_confirm, &vault_name_confirm).create(ctx_clone3.clone());
+                        let content_area_confirm = content_area_confirm.clone();
+                        let vault_name_confirm = vault_name_confirm.clone();
+                        let account_name_confirm = account_name_confirm.clone();
+                        let ctx_clone3 = ctx_clone3.clone();
+
+                        glib::spawn_future_local(async move {
+                            let vault_task = vault_name_confirm.clone();
+                            let account_task = account_name_confirm.clone();
+                            let result = gtk4::gio::spawn_blocking(move || {
+                                delete_account(&vault_task, &account_task)
+                            })
+                            .await
+                            .expect("delete account task panicked");
+
+                            match result {
+                                Ok(trashed) => {
+                                    VaultView::new(&content_area_confirm, &vault_name_confirm).create(ctx_clone3.clone());
+
+                                    let toast = Toast::new(&format!("Moved '{account_name_confirm}' to the trash"));
+
+                                    let content_area_undo = content_area_confirm.clone();
+                                    let vault_name_undo = vault_name_confirm.clone();
+                                    let ctx_undo = ctx_clone3.clone();
+                                    show_toast_with_button(&content_area_confirm, toast, "Undo", move || {
+                                        let content_area_undo = content_area_undo.clone();
+                                        let vault_name_undo = vault_name_undo.clone();
+                                        let ctx_undo = ctx_undo.clone();
+                                        let trashed = trashed.clone();
+
+                                        glib::spawn_future_local(async move {
+                                            let vault_task = vault_name_undo.clone();
+                                            let id = trashed.id.clone();
+                                            let result = gtk4::gio::spawn_blocking(move || {
+                                                restore_account_from_trash(&vault_task, &id)
+                                            })
+                                            .await
+                                            .expect("restore account task panicked");
+
+                                            match result {
+                                                Ok(_) => {
+                                                    VaultView::new(&content_area_undo, &vault_name_undo).create(ctx_undo.clone());
+                                                }
+                                                Err(e) => {
+                                                    log::error!("Failed to restore '{}': {e}", trashed.account_name);
+                                                    show_error_dialog("Restore Failed", &format!("Could not restore the account: {e}"));
+                                                }
+                                            }
+                                        });
+                                    });
+                                }
+                                Err(e) => {
+                                    log::error!("Failed to delete account '{account_name_confirm}': {e}");
+                                }
                             }
-                            Err(e) => {
-                                log::error!("Failed to delete account '{account_name_confirm}': {e}");
-                            }
-                        }
+                        });
                     },
                 );
             });
@@ -424,12 +505,23 @@ impl AccountView {
                 &account.username,
                 account_rc,
                 "username",
+            ));
+
+            group.add(&create_editable_field_row(
+                "Tags",
+                &account.tags_display(),
+                account_rc,
+                "tags",
             ))
         } else {
             // Read-only fields in view mode
             group.add(&create_field_row("Website", &account.website, true));
 
-            group.add(&create_field_row("Username", &account.username, true))
+            group.add(&create_field_row("Username", &account.username, true));
+
+            if !account.tags.is_empty() {
+                group.add(&create_field_row("Tags", &account.tags_display(), false));
+            }
         }
 
         group.add(&create_field_row("Created", &account.created_at, false));
@@ -484,13 +576,24 @@ impl AccountView {
         password_entry.set_size_request(250, -1); // Reduced width for better responsiveness
         password_entry.add_css_class("password-field");
 
+        let breach_label = Label::new(None);
+        breach_label.add_css_class("error");
+        breach_label.add_css_class("caption");
+        breach_label.set_wrap(true);
+        breach_label.set_margin_bottom(12);
+        account
+            .password
+            .with_exposed(|password| update_breach_label(&breach_label, password));
+
         // Connect password changes in edit mode
         if self.edit_mode {
             let account_rc_edit = account_rc.clone();
+            let breach_label_edit = breach_label.clone();
             password_entry.connect_changed(move |entry| {
                 let mut text = entry.text().to_string();
                 let mut account = account_rc_edit.borrow_mut();
                 account.password.update(&text);
+                update_breach_label(&breach_label_edit, &text);
                 use zeroize::Zeroize;
                 text.zeroize();
             });
@@ -572,11 +675,492 @@ impl AccountView {
         password_box.append(&copy_button);
 
         group.add(&password_box);
+        group.add(&breach_label);
         section.append(&group);
 
         section
     }
 
+    fn password_history_section(
+        &self,
+        account_rc: &Rc<RefCell<Account>>,
+        ctx: Rc<RefCell<Context>>,
+    ) -> Box {
+        let section = Box::new(Orientation::Vertical, 20);
+        section.add_css_class("account-section");
+
+        let group = PreferencesGroup::new();
+        group.set_title("Password History");
+        group.set_description(Some("Previous passwords, newest first"));
+        group.add_css_class("group_background");
+
+        let account = account_rc.borrow();
+        for (index, entry) in account.password_history.iter().enumerate() {
+            let row = ActionRow::new();
+            row.set_title(&entry.password.masked(8));
+            row.set_subtitle(&format!("Replaced {}", entry.replaced_at));
+            row.set_margin_start(8);
+            row.set_margin_end(8);
+            row.add_css_class("password-field");
+
+            let reveal_button = Button::new();
+            reveal_button.set_child(Some(
+                &ButtonContent::builder()
+                    .icon_name("view-reveal-symbolic")
+                    .build(),
+            ));
+            reveal_button.add_css_class("flat");
+            reveal_button.set_valign(Align::Center);
+            reveal_button.set_tooltip_text(Some("Show/Hide Password"));
+
+            let row_reveal = row.clone();
+            let account_rc_reveal = account_rc.clone();
+            let is_revealed = Rc::new(RefCell::new(false));
+            reveal_button.connect_clicked(move |_| {
+                let account = account_rc_reveal.borrow();
+                let Some(entry) = account.password_history.get(index) else {
+                    return;
+                };
+
+                let mut revealed = is_revealed.borrow_mut();
+                if *revealed {
+                    row_reveal.set_title(&entry.password.masked(8));
+                } else {
+                    entry.password.with_exposed(|password| {
+                        // Titles are markup, so escape the password before showing it
+                        row_reveal.set_title(&glib::markup_escape_text(password));
+                    });
+                }
+                *revealed = !*revealed;
+            });
+
+            let copy_button = Button::new();
+            copy_button.set_child(Some(
+                &ButtonContent::builder()
+                    .icon_name("edit-copy-symbolic")
+                    .build(),
+            ));
+            copy_button.add_css_class("flat");
+            copy_button.set_valign(Align::Center);
+            copy_button.set_tooltip_text(Some("Copy Password"));
+
+            let account_rc_copy = account_rc.clone();
+            copy_button.connect_clicked(move |button| {
+                let account = account_rc_copy.borrow();
+                let Some(entry) = account.password_history.get(index) else {
+                    return;
+                };
+
+                let clipboard = button.display().clipboard();
+                let password_copy = entry.password.expose_for_clipboard();
+                clipboard.set_text(&password_copy);
+
+                glib::timeout_add_seconds_local(30, move || {
+                    clipboard.set_text("");
+                    log::info!("Clipboard cleared for security");
+                    glib::ControlFlow::Break
+                });
+            });
+
+            let restore_button = Button::new();
+            restore_button.set_label("Restore");
+            restore_button.add_css_class("suggested-action");
+            restore_button.set_valign(Align::Center);
+            restore_button.set_tooltip_text(Some("Make this the current password again"));
+
+            let account_rc_restore = account_rc.clone();
+            let content_area_restore = self.content_area.clone();
+            let vault_name_restore = self.vault_name.clone();
+            let ctx_restore = ctx.clone();
+            let replaced_at = entry.replaced_at.clone();
+            restore_button.connect_clicked(move |_| {
+                let account_rc = account_rc_restore.clone();
+                let content_area = content_area_restore.clone();
+                let vault_name = vault_name_restore.clone();
+                let ctx = ctx_restore.clone();
+
+                show_confirmation_dialog(
+                    "Restore Password",
+                    &format!(
+                        "Restore the password that was replaced on {replaced_at}?\n\nThe current password will be kept in the history."
+                    ),
+                    "Restore",
+                    None::<&gtk4::Window>,
+                    move || {
+                        let mut account = account_rc.borrow_mut();
+                        if !account.restore_password_from_history(index) {
+                            return;
+                        }
+                        let account = account.clone();
+                        let content_area = content_area.clone();
+                        let vault_name = vault_name.clone();
+                        let ctx = ctx.clone();
+
+                        glib::spawn_future_local(async move {
+                            let account_name = account.name.clone();
+                            let vault_task = vault_name.clone();
+                            let result =
+                                gtk4::gio::spawn_blocking(move || update_account(&vault_task, &account))
+                                    .await
+                                    .expect("update account task panicked");
+
+                            match result {
+                                Ok(()) => {
+                                    AccountView::new(&content_area, &vault_name, &account_name, false)
+                                        .create(ctx.clone());
+                                }
+                                Err(e) => {
+                                    log::error!("Failed to restore password for '{account_name}': {e}");
+                                }
+                            }
+                        });
+                    },
+                );
+            });
+
+            row.add_suffix(&reveal_button);
+            row.add_suffix(&copy_button);
+            row.add_suffix(&restore_button);
+            group.add(&row);
+        }
+
+        section.append(&group);
+        section
+    }
+
+    fn revisions_section(
+        &self,
+        account_rc: &Rc<RefCell<Account>>,
+        revisions: Vec<Revision>,
+        ctx: Rc<RefCell<Context>>,
+    ) -> Box {
+        let section = Box::new(Orientation::Vertical, 20);
+        section.add_css_class("account-section");
+
+        let group = PreferencesGroup::new();
+        group.set_title("Revisions");
+        group.set_description(Some("Earlier versions of this account, newest first"));
+        group.add_css_class("group_background");
+
+        let revisions = Rc::new(revisions);
+        for revision in revisions.iter() {
+            let row = ActionRow::new();
+            row.set_title(&format!("Revision {}", revision.id));
+            row.set_subtitle(&format!("Saved {}", revision.saved_at));
+            row.set_margin_start(8);
+            row.set_margin_end(8);
+
+            let compare_button = Button::new();
+            compare_button.set_label("Compare");
+            compare_button.add_css_class("flat");
+            compare_button.set_valign(Align::Center);
+            compare_button.set_tooltip_text(Some("Show what changed since this revision"));
+
+            let account_rc_compare = account_rc.clone();
+            let vault_name_compare = self.vault_name.clone();
+            let revisions_compare = revisions.clone();
+            let id = revision.id;
+            compare_button.connect_clicked(move |_| {
+                show_revision_diff_dialog(
+                    &vault_name_compare,
+                    &account_rc_compare.borrow(),
+                    &revisions_compare,
+                    id,
+                );
+            });
+
+            let rollback_button = Button::new();
+            rollback_button.set_label("Roll Back");
+            rollback_button.add_css_class("suggested-action");
+            rollback_button.set_valign(Align::Center);
+            rollback_button.set_tooltip_text(Some("Make this revision the current version"));
+
+            let content_area_rollback = self.content_area.clone();
+            let vault_name_rollback = self.vault_name.clone();
+            let account_name_rollback = self.account_name.clone();
+            let ctx_rollback = ctx.clone();
+            let saved_at = revision.saved_at.clone();
+            rollback_button.connect_clicked(move |_| {
+                let content_area = content_area_rollback.clone();
+                let vault_name = vault_name_rollback.clone();
+                let account_name = account_name_rollback.clone();
+                let ctx = ctx_rollback.clone();
+
+                show_confirmation_dialog(
+                    "Roll Back Account",
+                    &format!(
+                        "Roll back to the version saved on {saved_at}?\n\nThe current version will be kept as a new revision."
+                    ),
+                    "Roll Back",
+                    None::<&gtk4::Window>,
+                    move || match rollback_account(&vault_name, &account_name, id) {
+                        Ok(()) => {
+                            AccountView::new(&content_area, &vault_name, &account_name, false)
+                                .create(ctx.clone());
+                        }
+                        Err(e) => {
+                            log::error!("Failed to roll back '{account_name}': {e}");
+                        }
+                    },
+                );
+            });
+
+            row.add_suffix(&compare_button);
+            row.add_suffix(&rollback_button);
+            group.add(&row);
+        }
+
+        section.append(&group);
+        section
+    }
+
+    fn one_time_code_section(&self, account_rc: &Rc<RefCell<Account>>) -> Box {
+        let section = Box::new(Orientation::Vertical, 20);
+        section.add_css_class("account-section");
+
+        let group = PreferencesGroup::new();
+        group.set_title("One-Time Code");
+        group.set_description(Some("Two-factor authentication code for this account"));
+        group.add_css_class("group_background");
+
+        let code_box = CreateBox::new()
+            .new_box(Box::new(Orientation::Horizontal, 12))
+            .margins(0, 24, 24, 24)
+            .halign(Align::Center)
+            .build();
+
+        if self.edit_mode {
+            let secret_entry = Entry::new();
+            secret_entry.set_visibility(false);
+            secret_entry.set_invisible_char(Some('•'));
+            secret_entry.set_hexpand(true);
+            secret_entry.set_size_request(250, -1);
+            secret_entry.set_placeholder_text(Some("Base32 secret from the service's 2FA setup"));
+
+            let algorithms = [
+                TotpAlgorithm::Sha1,
+                TotpAlgorithm::Sha256,
+                TotpAlgorithm::Sha512,
+            ];
+            let algorithm_dropdown = DropDown::from_strings(&algorithms.map(TotpAlgorithm::as_str));
+            algorithm_dropdown.set_valign(gtk4::Align::Center);
+            algorithm_dropdown.set_tooltip_text(Some("Hash algorithm"));
+
+            let digits_dropdown = DropDown::from_strings(&["6 digits", "7 digits", "8 digits"]);
+            digits_dropdown.set_valign(gtk4::Align::Center);
+
+            let period_spin = SpinButton::with_range(1.0, 300.0, 1.0);
+            period_spin.set_valign(gtk4::Align::Center);
+            period_spin.set_tooltip_text(Some("Seconds each code lasts"));
+
+            let existing_params = account_rc
+                .borrow()
+                .totp
+                .as_ref()
+                .map(|totp| totp.params)
+                .unwrap_or_default();
+            if let Some(totp) = &account_rc.borrow().totp {
+                totp.secret
+                    .with_exposed(|secret| secret_entry.set_text(secret));
+            }
+            let algorithm_index = algorithms
+                .iter()
+                .position(|algorithm| *algorithm == existing_params.algorithm)
+                .unwrap_or(0);
+            algorithm_dropdown.set_selected(u32::try_from(algorithm_index).unwrap_or(0));
+            digits_dropdown.set_selected(existing_params.digits.clamp(6, 8) - 6);
+            #[allow(clippy::cast_precision_loss)]
+            period_spin.set_value(existing_params.period as f64);
+
+            let update_totp = {
+                let account_rc = account_rc.clone();
+                let secret_entry = secret_entry.clone();
+                let algorithm_dropdown = algorithm_dropdown.clone();
+                let digits_dropdown = digits_dropdown.clone();
+                let period_spin = period_spin.clone();
+                move || {
+                    let mut secret = secret_entry.text().trim().to_string();
+                    let mut account = account_rc.borrow_mut();
+
+                    account.totp = if secret.is_empty() {
+                        None
+                    } else {
+                        let is_valid = decode_totp_secret(&secret).is_ok();
+                        if is_valid {
+                            secret_entry.remove_css_class("error");
+                        } else {
+                            secret_entry.add_css_class("error");
+                        }
+
+                        // Start from the current secret so settings not shown here, like skew or the
+                        // imported issuer, are kept.
+                        let existing = account.totp.take().unwrap_or_default();
+                        let mut params = existing.params;
+                        params.algorithm = algorithms
+                            .get(algorithm_dropdown.selected() as usize)
+                            .copied()
+                            .unwrap_or_default();
+                        params.digits = digits_dropdown.selected() + 6;
+                        #[allow(clippy::cast_sign_loss)]
+                        {
+                            params.period = period_spin.value_as_int().max(1) as u64;
+                        }
+
+                        Some(AccountTotp {
+                            secret: SecurePassword::new(secret.clone()),
+                            params,
+                            ..existing
+                        })
+                    };
+
+                    use zeroize::Zeroize;
+                    secret.zeroize();
+                }
+            };
+
+            let update_on_change = update_totp.clone();
+            secret_entry.connect_changed(move |_| update_on_change());
+            let update_on_change = update_totp.clone();
+            algorithm_dropdown.connect_selected_notify(move |_| update_on_change());
+            let update_on_change = update_totp.clone();
+            digits_dropdown.connect_selected_notify(move |_| update_on_change());
+            period_spin.connect_value_changed(move |_| update_totp());
+
+            let import_button = Button::new();
+            let import_button_content = ButtonContent::builder()
+                .icon_name("document-open-symbolic")
+                .label("Import")
+                .build();
+            import_button.set_child(Some(&import_button_content));
+            import_button.set_valign(gtk4::Align::Center);
+            import_button.set_tooltip_text(Some("Import from an otpauth link or QR code image"));
+
+            let account_rc_clone = account_rc.clone();
+            let secret_entry_clone = secret_entry.clone();
+            let algorithm_dropdown_clone = algorithm_dropdown.clone();
+            let digits_dropdown_clone = digits_dropdown.clone();
+            let period_spin_clone = period_spin.clone();
+            import_button.connect_clicked(move |_| {
+                let account_rc = account_rc_clone.clone();
+                let secret_entry = secret_entry_clone.clone();
+                let algorithm_dropdown = algorithm_dropdown_clone.clone();
+                let digits_dropdown = digits_dropdown_clone.clone();
+                let period_spin = period_spin_clone.clone();
+                show_import_otp_dialog(move |totp| {
+                    // Filling the widgets fires their change handlers, so store the import afterwards.
+                    totp.secret
+                        .with_exposed(|secret| secret_entry.set_text(secret));
+                    let algorithm_index = algorithms
+                        .iter()
+                        .position(|algorithm| *algorithm == totp.params.algorithm)
+                        .unwrap_or(0);
+                    algorithm_dropdown.set_selected(u32::try_from(algorithm_index).unwrap_or(0));
+                    digits_dropdown.set_selected(totp.params.digits.clamp(6, 8) - 6);
+                    #[allow(clippy::cast_precision_loss)]
+                    period_spin.set_value(totp.params.period as f64);
+                    secret_entry.remove_css_class("error");
+
+                    account_rc.borrow_mut().totp = Some(totp);
+                });
+            });
+
+            code_box.append(&secret_entry);
+            code_box.append(&algorithm_dropdown);
+            code_box.append(&digits_dropdown);
+            code_box.append(&period_spin);
+            code_box.append(&import_button);
+        } else {
+            if let Some(totp) = &account_rc.borrow().totp {
+                let source = match (totp.issuer.is_empty(), totp.label.is_empty()) {
+                    (false, false) => Some(format!("{} ({})", totp.issuer, totp.label)),
+                    (false, true) => Some(totp.issuer.clone()),
+                    (true, false) => Some(totp.label.clone()),
+                    (true, true) => None,
+                };
+                if let Some(source) = source {
+                    group.set_description(Some(&format!("Two-factor code for {source}")));
+                }
+            }
+
+            let code_label = Label::new(None);
+            code_label.add_css_class("title-2");
+            code_label.add_css_class("monospace");
+
+            let countdown_label = Label::new(None);
+            countdown_label.add_css_class("dim-label");
+            countdown_label.set_width_chars(4);
+
+            let copy_button = Button::new();
+            let copy_button_content = ButtonContent::builder()
+                .icon_name("edit-copy-symbolic")
+                .build();
+            copy_button.set_child(Some(&copy_button_content));
+            copy_button.add_css_class("flat");
+            copy_button.set_tooltip_text(Some("Copy Code"));
+
+            let refresh = {
+                let account_rc = account_rc.clone();
+                move |code_label: &Label, countdown_label: &Label| {
+                    let Some(totp) = account_rc.borrow().totp.clone() else {
+                        return;
+                    };
+                    match current_account_totp_code(&totp) {
+                        Ok((code, seconds_remaining)) => {
+                            code_label.set_text(&code);
+                            countdown_label.set_text(&format!("{seconds_remaining}s"));
+                        }
+                        Err(e) => {
+                            code_label.set_text("Invalid secret");
+                            countdown_label.set_text("");
+                            log::warn!("Failed to generate one-time code: {e}");
+                        }
+                    }
+                }
+            };
+            refresh(&code_label, &countdown_label);
+
+            // Tick every second until the view is replaced and the labels are dropped. Only weak
+            // references are held here, so the timer does not keep the labels alive itself.
+            let code_label_weak = code_label.downgrade();
+            let countdown_label_weak = countdown_label.downgrade();
+            glib::timeout_add_seconds_local(1, move || {
+                let (Some(code_label), Some(countdown_label)) =
+                    (code_label_weak.upgrade(), countdown_label_weak.upgrade())
+                else {
+                    return glib::ControlFlow::Break;
+                };
+                refresh(&code_label, &countdown_label);
+                glib::ControlFlow::Continue
+            });
+
+            let account_rc_copy = account_rc.clone();
+            copy_button.connect_clicked(move |button| {
+                let Some(totp) = account_rc_copy.borrow().totp.clone() else {
+                    return;
+                };
+                let Ok((code, _)) = current_account_totp_code(&totp) else {
+                    return;
+                };
+
+                let clipboard = button.display().clipboard();
+                clipboard.set_text(&code);
+
+                glib::timeout_add_seconds_local(30, move || {
+                    clipboard.set_text("");
+                    glib::ControlFlow::Break
+                });
+            });
+
+            code_box.append(&code_label);
+            code_box.append(&countdown_label);
+            code_box.append(&copy_button);
+        }
+
+        group.add(&code_box);
+        section.append(&group);
+        section
+    }
+
     fn additional_fields_section(
         &self,
         account_rc: &Rc<RefCell<Account>>,
@@ -820,21 +1404,34 @@ impl AccountView {
         let ctx_clone = ctx.clone();
 
         save_button.connect_clicked(move |_| {
-            let mut account = account_rc_clone.borrow_mut();
-            account.update_modified_time();
+            let account = {
+                let mut account = account_rc_clone.borrow_mut();
+                account.update_modified_time();
+                account.clone()
+            };
             let account_name = account.name.clone();
-
-            match update_account(&vault_name_clone, &account) {
-                Ok(()) => {
-                    drop(account);
-                    // Exit edit mode and show the updated account
-                    AccountView::new(&content_area_clone, &vault_name_clone, &account_name, false)
-                        .create(ctx_clone.clone());
+            let vault_name = vault_name_clone.clone();
+            let content_area = content_area_clone.clone();
+            let ctx = ctx_clone.clone();
+
+            glib::spawn_future_local(async move {
+                let vault_task = vault_name.clone();
+                let result =
+                    gtk4::gio::spawn_blocking(move || update_account(&vault_task, &account))
+                        .await
+                        .expect("update account task panicked");
+
+                match result {
+                    Ok(()) => {
+                        // Exit edit mode and show the updated account
+                        AccountView::new(&content_area, &vault_name, &account_name, false)
+                            .create(ctx.clone());
+                    }
+                    Err(e) => {
+                        log::error!("Failed to save account: {e}");
+                    }
                 }
-                Err(e) => {
-                    log::error!("Failed to save account: {e}");
-                }
-            }
+            });
         });
 
         let cancel_button = Button::new();
@@ -863,6 +1460,28 @@ impl AccountView {
     }
 }
 
+/// Shows a warning under the password when it appears in the local breached password list
+fn update_breach_label(label: &Label, password: &str) {
+    let status = if password.is_empty() {
+        BreachStatus::NotFound
+    } else {
+        check_password_breached(password).unwrap_or_else(|e| {
+            log::warn!("Failed to check password against breach list: {e}");
+            BreachStatus::Unavailable
+        })
+    };
+
+    match status {
+        BreachStatus::Found(count) => {
+            label.set_text(&format!(
+                "This password has appeared in {count} known data breaches. Change it."
+            ));
+            label.set_visible(true);
+        }
+        BreachStatus::NotFound | BreachStatus::Unavailable => label.set_visible(false),
+    }
+}
+
 /// Creates a password field row with show/hide functionality for account creation
 fn create_password_field_row(
     label_text: &str,
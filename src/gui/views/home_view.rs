@@ -4,10 +4,15 @@ use crate::{
             CreateActionRow, CreateBox, CreateScrollableView, clear_content,
             proceed_with_gate_warmup,
         },
-        dialogs::password_generator::show_password_generator_dialog,
+        dialogs::{
+            password_generator::show_password_generator_dialog,
+            vault_management::show_import_vault_dialog,
+        },
         widgets::loading_spinner::{create_loading_button, set_button_loading_state},
     },
-    storage::filesystem::{get_available_accounts, get_most_used_vault, get_recent_vaults},
+    storage::filesystem::{
+        get_available_accounts, get_available_vaults, get_most_used_vault, get_recent_vaults,
+    },
     vault::{create_vault_finalize, create_vault_prepare},
 };
 use adw::{PreferencesGroup, prelude::*};
@@ -32,7 +37,7 @@ impl<'a> HomeView<'a> {
 
         glib::spawn_future_local(async move {
             let (vaults, total_accounts, most_used, recent) = gtk4::gio::spawn_blocking(|| {
-                let vaults = crate::gui::sidebar::get_available_vaults();
+                let vaults = get_available_vaults();
                 let total_accounts: usize = vaults
                     .iter()
                     .map(|vault_name| get_available_accounts(vault_name).len())
@@ -138,11 +143,25 @@ impl<'a> HomeView<'a> {
                 .css_class("suggested-action")
                 .callback({
                     let content_area = content_area.clone();
+                    let ctx = ctx.clone();
                     move || show_create_vault_view(&content_area, ctx.clone())
                 })
                 .build(),
         );
 
+        group.add(
+            &CreateActionRow::new()
+                .title("Import Vault")
+                .subtitle("Restore a vault from an exported archive file")
+                .button_label("Import")
+                .css_class("suggested-action")
+                .callback({
+                    let content_area = content_area.clone();
+                    move || show_import_vault_dialog(&content_area, ctx.clone())
+                })
+                .build(),
+        );
+
         group.add(
             &CreateActionRow::new()
                 .title("Generate Password")
@@ -1,31 +1,57 @@
 use crate::{
     gui::{
-        content::{CreateActionRow, CreateBox, CreateScrollableView, clear_content},
+        content::{CreateActionRow, CreateBox, CreateScrollableView, clear_content, show_toast},
         dialogs::{
+            common::{show_confirmation_dialog, show_error_dialog},
             totp::{show_totp_management_dialog, show_totp_setup_dialog},
             vault_management::{
-                show_backup_vault_dialog, show_delete_backup_dialog, show_delete_vault_dialog,
-                show_rename_vault_dialog, show_restore_vault_dialog,
+                show_account_names_dialog, show_backup_vault_dialog, show_backups_dialog,
+                show_convert_vault_dialog, show_delete_vault_dialog, show_export_vault_dialog,
+                show_rename_vault_dialog, show_vault_members_dialog,
             },
         },
         views::{account_view::AccountView, account_view::show_new_account_view},
         widgets::loading_spinner::LoadingOverlay,
     },
-    storage::filesystem::{
-        backup_exists, get_available_accounts, increment_vault_usage, record_recent_vault,
+    storage::{
+        Locations,
+        account_names::vault_hides_names,
+        auto_backup::{BackupTrigger, run_auto_backup},
+        backup::{BackupSnapshot, list_backups},
+        container::is_single_file,
+        filesystem::{get_available_accounts, increment_vault_usage, record_recent_vault},
+        manifest::update_vault_manifest,
     },
     totp::is_totp_enabled,
+    vault::{
+        audit::{AuditOptions, VaultAudit, audit_vault},
+        metadata::{AccountMetadata, AccountSort, load_account_metadata, sort_account_metadata},
+        settings::{load_vault_settings, save_vault_settings},
+        trash::{
+            delete_from_trash, empty_trash, list_trash, purge_expired_trash,
+            restore_account_from_trash,
+        },
+    },
 };
-use adw::{PreferencesGroup, prelude::*};
+use adw::{ActionRow, PreferencesGroup, Toast, prelude::*};
+use anyhow::Error;
 use gpgme::Context;
-use gtk4::{Box, Label, ListBox, Orientation, SelectionMode, StringObject, gio::ListStore};
+use gtk4::{
+    Adjustment, Align, Box, Button, DropDown, Label, ListBox, Orientation, SearchEntry,
+    SelectionMode, SpinButton, StringObject, gio::ListStore,
+};
 use std::{
     cell::RefCell,
     rc::Rc,
-    sync::atomic::{AtomicUsize, Ordering},
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
 };
 
 static VAULT_LOADING_COUNTER: AtomicUsize = AtomicUsize::new(0);
+/// Vaults opened since fmp started, which are checked for due automatic backups
+static OPENED_VAULTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
 
 pub struct VaultView<'a> {
     content_area: &'a Box,
@@ -46,6 +72,30 @@ impl<'a> VaultView<'a> {
         increment_vault_usage(&self.vault_name);
         record_recent_vault(&self.vault_name);
 
+        let mut opened_vaults = OPENED_VAULTS.lock().unwrap_or_else(|e| e.into_inner());
+        if !opened_vaults.contains(&self.vault_name) {
+            opened_vaults.push(self.vault_name.clone());
+        }
+        drop(opened_vaults);
+
+        glib::spawn_future_local({
+            let vault_name = self.vault_name.clone();
+            async move {
+                let vault = vault_name.clone();
+                let result = gtk4::gio::spawn_blocking(move || purge_expired_trash(&vault))
+                    .await
+                    .expect("purge trash task panicked");
+
+                match result {
+                    Ok(0) => {}
+                    Ok(purged) => log::info!(
+                        "Purged {purged} expired account(s) from the trash of '{vault_name}'"
+                    ),
+                    Err(e) => log::warn!("Failed to purge the trash of '{vault_name}': {e}"),
+                }
+            }
+        });
+
         let loading_overlay = Rc::new(LoadingOverlay::new());
         self.content_area.append(loading_overlay.widget());
         loading_overlay.show("Loading accounts...");
@@ -83,11 +133,21 @@ impl<'a> VaultView<'a> {
                 &vault_name_clone,
                 ctx_clone.clone(),
             ));
+            main_box.append(&create_security_section(
+                &content_area_clone,
+                &vault_name_clone,
+                ctx_clone.clone(),
+            ));
             main_box.append(&create_totp_management_section(
                 &content_area_clone,
                 &vault_name_clone,
                 ctx_clone2.clone(),
             ));
+            main_box.append(&create_trash_section(
+                &content_area_clone,
+                &vault_name_clone,
+                ctx_clone2.clone(),
+            ));
             main_box.append(&create_vault_management_section(
                 &content_area_clone,
                 &vault_name_clone,
@@ -144,6 +204,67 @@ impl<'a> VaultView<'a> {
     }
 }
 
+/// Describes a vault's backups, including when the last one was made and its automatic backup policy
+fn backups_subtitle(vault_name: &str) -> String {
+    let backups = list_backups(vault_name).unwrap_or_else(|e| {
+        log::error!("Failed to list backups of '{vault_name}': {e}");
+        Vec::new()
+    });
+    let auto_backup = load_vault_settings(vault_name)
+        .map(|settings| settings.auto_backup.describe())
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to load the settings of '{vault_name}': {e}");
+            "off".to_string()
+        });
+
+    match backups.first() {
+        None => format!("No backups yet • automatic: {auto_backup}"),
+        Some(latest) => format!(
+            "{} backup(s) • last backup {} • automatic: {auto_backup}",
+            backups.len(),
+            latest.created_at_display()
+        ),
+    }
+}
+
+/// Returns the vaults opened since fmp started that still exist
+pub fn opened_vaults() -> Vec<String> {
+    OPENED_VAULTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .filter(|vault_name| Locations::new(vault_name, "").vault.exists())
+        .cloned()
+        .collect()
+}
+
+/// Runs the automatic backups that are due for the given vaults in the background, then calls
+/// `on_done` with the backups that were attempted
+pub fn run_auto_backups<F>(vault_names: Vec<String>, trigger: BackupTrigger, on_done: F)
+where
+    F: FnOnce(Vec<(String, Result<BackupSnapshot, Error>)>) + 'static,
+{
+    glib::spawn_future_local(async move {
+        let results = gtk4::gio::spawn_blocking(move || {
+            vault_names
+                .into_iter()
+                .filter_map(|vault_name| match run_auto_backup(&vault_name, trigger) {
+                    Ok(None) => None,
+                    Ok(Some(snapshot)) => Some((vault_name, Ok(snapshot))),
+                    Err(e) => {
+                        log::error!("Automatic backup of '{vault_name}' failed: {e}");
+                        Some((vault_name, Err(e)))
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .await
+        .expect("automatic backup task panicked");
+
+        on_done(results);
+    });
+}
+
 /// Creates the vault management section with backup and vault operations
 pub fn create_vault_management_section(
     content_area: &Box,
@@ -152,7 +273,9 @@ pub fn create_vault_management_section(
 ) -> PreferencesGroup {
     let group = PreferencesGroup::new();
     group.set_title("Vault Management");
-    group.set_description(Some("Backup, restore, rename, and delete vault operations"));
+    group.set_description(Some(
+        "Backup, restore, export, members, storage, account names, rename, and delete vault operations",
+    ));
 
     let vault_name_clone = vault_name.to_string();
     let content_area_clone = content_area.clone();
@@ -173,53 +296,128 @@ pub fn create_vault_management_section(
             .build(),
     );
 
-    let has_backup = backup_exists(vault_name);
-
     let ctx_clone = ctx.clone();
     let ctx_clone2 = ctx_clone.clone();
-    let ctx_clone3 = ctx_clone2.clone();
-    let ctx_clone4 = ctx_clone3.clone();
+    let ctx_clone4 = ctx_clone2.clone();
+
+    let backups_row = CreateActionRow::new()
+        .title("Backups")
+        .subtitle(backups_subtitle(vault_name))
+        .button_label("Manage")
+        .css_class("suggested-action")
+        .activatable(true)
+        .callback({
+            let vault_name = vault_name_clone.clone();
+            let content_area = content_area_clone.clone();
+            move || show_backups_dialog(&vault_name, &content_area, ctx_clone.clone())
+        })
+        .build();
+    group.add(&backups_row);
+
+    // Opening or changing the vault may make an automatic backup due
+    let backups_row_weak = backups_row.downgrade();
+    let content_area_backup = content_area.clone();
+    run_auto_backups(
+        vec![vault_name.to_string()],
+        BackupTrigger::Activity,
+        move |results| {
+            for (vault_name, result) in results {
+                let message = match result {
+                    Ok(_) => format!("Backed up '{vault_name}' automatically"),
+                    Err(e) => format!("Automatic backup of '{vault_name}' failed: {e}"),
+                };
+                show_toast(
+                    &content_area_backup,
+                    Toast::new(&glib::markup_escape_text(&message)),
+                );
+
+                if let Some(row) = backups_row_weak.upgrade() {
+                    row.set_subtitle(&backups_subtitle(&vault_name));
+                }
+            }
+        },
+    );
 
     group.add(
         &CreateActionRow::new()
-            .title("Restore Backup")
-            .subtitle("Restore vault from backup")
-            .button_label("Restore")
+            .title("Export Vault")
+            .subtitle("Save the encrypted vault as one archive file, such as on a USB drive")
+            .button_label("Export")
             .css_class("suggested-action")
-            .activatable(has_backup)
             .callback({
                 let vault_name = vault_name_clone.clone();
                 let content_area = content_area_clone.clone();
-                move || show_restore_vault_dialog(&vault_name, &content_area, ctx_clone.clone())
+                move || show_export_vault_dialog(&vault_name, &content_area)
             })
             .build(),
     );
 
+    let ctx_clone5 = ctx.clone();
     group.add(
         &CreateActionRow::new()
-            .title("Rename Vault")
-            .subtitle("Change the name of this vault")
-            .button_label("Rename")
+            .title("Members")
+            .subtitle("Share this vault with other GPG keys or re-key it")
+            .button_label("Manage")
             .css_class("suggested-action")
             .callback({
                 let vault_name = vault_name_clone.clone();
                 let content_area = content_area_clone.clone();
-                move || show_rename_vault_dialog(&vault_name, &content_area, ctx_clone2.clone())
+                move || show_vault_members_dialog(&vault_name, &content_area, ctx_clone5.clone())
             })
             .build(),
     );
 
+    let ctx_clone6 = ctx.clone();
     group.add(
         &CreateActionRow::new()
-            .title("Delete Backup")
-            .subtitle("Delete vault backup")
-            .button_label("Delete")
-            .css_class("destructive-action")
-            .activatable(has_backup)
+            .title("Storage")
+            .subtitle(if is_single_file(vault_name) {
+                "Kept in one encrypted file that hides the number and names of accounts"
+            } else if vault_hides_names(vault_name) {
+                "One directory per account, named by an opaque ID"
+            } else {
+                "One directory per account, which shows account names on disk"
+            })
+            .button_label("Convert")
+            .css_class("suggested-action")
+            .callback({
+                let vault_name = vault_name_clone.clone();
+                let content_area = content_area_clone.clone();
+                move || show_convert_vault_dialog(&vault_name, &content_area, ctx_clone6.clone())
+            })
+            .build(),
+    );
+
+    let hides_names = vault_hides_names(vault_name);
+    let ctx_clone7 = ctx.clone();
+    group.add(
+        &CreateActionRow::new()
+            .title("Account Names")
+            .subtitle(if hides_names {
+                "Kept in an encrypted index, with accounts stored under opaque IDs"
+            } else {
+                "Used as directory names, which anyone with access to the disk can read"
+            })
+            .button_label(if hides_names { "Show" } else { "Hide" })
+            .css_class("suggested-action")
+            .callback({
+                let vault_name = vault_name_clone.clone();
+                let content_area = content_area_clone.clone();
+                move || show_account_names_dialog(&vault_name, &content_area, ctx_clone7.clone())
+            })
+            .build(),
+    );
+
+    group.add(
+        &CreateActionRow::new()
+            .title("Rename Vault")
+            .subtitle("Change the name of this vault")
+            .button_label("Rename")
+            .css_class("suggested-action")
             .callback({
                 let vault_name = vault_name_clone.clone();
                 let content_area = content_area_clone.clone();
-                move || show_delete_backup_dialog(&vault_name, &content_area, ctx_clone3.clone())
+                move || show_rename_vault_dialog(&vault_name, &content_area, ctx_clone2.clone())
             })
             .build(),
     );
@@ -293,23 +491,53 @@ pub fn create_accounts_grid(
     outer
 }
 
+/// Lists a vault's accounts with a search box and a choice of order. Accounts are listed by name
+/// at first, then described from the vault's metadata index once it is read off the main thread.
 fn create_account_list(
     content_area: &Box,
     vault_name: &str,
     all_accounts: Vec<String>,
     ctx: Rc<RefCell<Context>>,
-) -> ListBox {
+) -> Box {
+    let container = Box::new(Orientation::Vertical, 8);
+
+    let toolbar = Box::new(Orientation::Horizontal, 8);
+    let search_entry = SearchEntry::new();
+    search_entry.set_placeholder_text(Some("Search accounts..."));
+    search_entry.set_tooltip_text(Some("Matches names, types, websites, usernames and tags"));
+    search_entry.set_hexpand(true);
+    let sort_labels: Vec<&str> = AccountSort::ALL.iter().map(|sort| sort.label()).collect();
+    let sort_dropdown = DropDown::from_strings(&sort_labels);
+    sort_dropdown.set_tooltip_text(Some("Sort accounts"));
+    toolbar.append(&search_entry);
+    toolbar.append(&sort_dropdown);
+    container.append(&toolbar);
+
+    let metadata: Rc<RefCell<Vec<AccountMetadata>>> = Rc::new(RefCell::new(
+        all_accounts
+            .into_iter()
+            .map(|name| AccountMetadata {
+                name,
+                ..AccountMetadata::default()
+            })
+            .collect(),
+    ));
+
     let store = ListStore::new::<StringObject>();
-    for name in &all_accounts {
-        store.append(&StringObject::new(name));
-    }
 
     let list_box = ListBox::new();
     list_box.add_css_class("boxed-list");
     list_box.set_selection_mode(SelectionMode::None);
 
+    let placeholder = Label::new(Some("No accounts match your search"));
+    placeholder.add_css_class("dim-label");
+    placeholder.set_margin_top(12);
+    placeholder.set_margin_bottom(12);
+    list_box.set_placeholder(Some(&placeholder));
+
     let content_area_clone = content_area.clone();
     let vault_name_clone = vault_name.to_string();
+    let metadata_clone = metadata.clone();
 
     list_box.bind_model(Some(&store), move |object| {
         let name = object
@@ -317,10 +545,16 @@ fn create_account_list(
             .unwrap()
             .string()
             .to_string();
+        let subtitle = metadata_clone
+            .borrow()
+            .iter()
+            .find(|account| account.name == name)
+            .map(account_subtitle)
+            .unwrap_or_default();
 
         CreateActionRow::new()
             .title(&name)
-            .subtitle("Password Account")
+            .subtitle(glib::markup_escape_text(&subtitle).as_str())
             .button_label("View")
             .css_class("suggested-action")
             .callback({
@@ -336,7 +570,80 @@ fn create_account_list(
             .upcast::<gtk4::Widget>()
     });
 
-    list_box
+    let loaded_metadata = metadata.clone();
+
+    // Lists the accounts matching the search, in the chosen order
+    let refresh = Rc::new({
+        let search_entry = search_entry.clone();
+        let sort_dropdown = sort_dropdown.clone();
+        move || {
+            let query = search_entry.text().to_string();
+            let sort = AccountSort::ALL
+                .get(sort_dropdown.selected() as usize)
+                .copied()
+                .unwrap_or(AccountSort::Name);
+
+            let mut listed: Vec<AccountMetadata> = metadata
+                .borrow()
+                .iter()
+                .filter(|account| account.matches(&query))
+                .cloned()
+                .collect();
+            sort_account_metadata(&mut listed, sort);
+
+            store.remove_all();
+            for account in &listed {
+                store.append(&StringObject::new(&account.name));
+            }
+        }
+    });
+    refresh();
+
+    search_entry.connect_search_changed({
+        let refresh = refresh.clone();
+        move |_| refresh()
+    });
+    sort_dropdown.connect_selected_notify({
+        let refresh = refresh.clone();
+        move |_| refresh()
+    });
+
+    glib::spawn_future_local({
+        let vault_name = vault_name.to_string();
+        async move {
+            let vault = vault_name.clone();
+            let result = gtk4::gio::spawn_blocking(move || load_account_metadata(&vault))
+                .await
+                .expect("account index task panicked");
+
+            match result {
+                Ok(loaded) => {
+                    *loaded_metadata.borrow_mut() = loaded;
+                    refresh();
+                }
+                Err(e) => log::warn!("Failed to read the account index of '{vault_name}': {e}"),
+            }
+        }
+    });
+
+    container.append(&list_box);
+    container
+}
+
+/// Describes an account from its metadata, such as "Email • alice • mail.example.com • work"
+fn account_subtitle(account: &AccountMetadata) -> String {
+    let tags = account.tags.join(", ");
+
+    [
+        account.account_type.as_str(),
+        account.username.as_str(),
+        account.website.as_str(),
+        tags.as_str(),
+    ]
+    .into_iter()
+    .filter(|part| !part.is_empty())
+    .collect::<Vec<_>>()
+    .join(" • ")
 }
 
 /// Creates the TOTP (2FA) management section for the vault view
@@ -390,3 +697,326 @@ pub fn create_totp_management_section(
 
     group
 }
+
+/// Creates the security section, which audits every account's password on request
+pub fn create_security_section(
+    content_area: &Box,
+    vault_name: &str,
+    ctx: Rc<RefCell<Context>>,
+) -> PreferencesGroup {
+    let group = PreferencesGroup::new();
+    group.set_title("Security");
+    group.set_description(Some("Check for empty, weak, reused and old passwords"));
+
+    let result_rows: Rc<RefCell<Vec<ActionRow>>> = Rc::new(RefCell::new(Vec::new()));
+
+    let audit_row = CreateActionRow::new()
+        .title("Password Health")
+        .subtitle("Decrypt every account and check its password")
+        .button_label("Run Audit")
+        .css_class("suggested-action")
+        .callback({
+            let group = group.clone();
+            let content_area = content_area.clone();
+            let vault_name = vault_name.to_string();
+            let result_rows = result_rows.clone();
+            move || {
+                let group = group.clone();
+                let content_area = content_area.clone();
+                let vault_name = vault_name.clone();
+                let result_rows = result_rows.clone();
+                let ctx = ctx.clone();
+
+                for row in result_rows.borrow_mut().drain(..) {
+                    group.remove(&row);
+                }
+                group.set_description(Some("Auditing accounts..."));
+
+                glib::spawn_future_local(async move {
+                    let vault_name_bg = vault_name.clone();
+                    let result = gtk4::gio::spawn_blocking(move || {
+                        audit_vault(&vault_name_bg, &AuditOptions::default())
+                    })
+                    .await
+                    .expect("audit task panicked");
+
+                    let audit = match result {
+                        Ok(audit) => audit,
+                        Err(e) => {
+                            log::error!("Failed to audit vault '{vault_name}': {e}");
+                            group.set_description(Some(&format!("Audit failed: {e}")));
+                            return;
+                        }
+                    };
+
+                    group.set_description(Some(&audit_summary(&audit)));
+
+                    let mut rows = result_rows.borrow_mut();
+                    for account in &audit.accounts {
+                        let descriptions: Vec<String> = account
+                            .issues
+                            .iter()
+                            .map(|issue| issue.description())
+                            .collect();
+                        let row = CreateActionRow::new()
+                            .title(&account.account_name)
+                            .subtitle(descriptions.join("\n"))
+                            .button_label("View")
+                            .css_class("flat")
+                            .callback({
+                                let content_area = content_area.clone();
+                                let vault_name = vault_name.clone();
+                                let account_name = account.account_name.clone();
+                                let ctx = ctx.clone();
+                                move || {
+                                    AccountView::new(
+                                        &content_area,
+                                        &vault_name,
+                                        &account_name,
+                                        false,
+                                    )
+                                    .create(ctx.clone())
+                                }
+                            })
+                            .build();
+                        row.add_prefix(&gtk4::Image::from_icon_name("dialog-warning-symbolic"));
+                        group.add(&row);
+                        rows.push(row);
+                    }
+
+                    for (account_name, error) in &audit.unreadable {
+                        let row = CreateActionRow::<fn()>::new()
+                            .title(account_name)
+                            .subtitle(format!("Could not be decrypted: {error}"))
+                            .add_button(false)
+                            .build();
+                        row.add_prefix(&gtk4::Image::from_icon_name("dialog-error-symbolic"));
+                        group.add(&row);
+                        rows.push(row);
+                    }
+                });
+            }
+        })
+        .build();
+
+    group.add(&audit_row);
+    group
+}
+
+/// Creates the trash section, listing deleted accounts with restore and permanent delete actions
+pub fn create_trash_section(
+    content_area: &Box,
+    vault_name: &str,
+    ctx: Rc<RefCell<Context>>,
+) -> PreferencesGroup {
+    let group = PreferencesGroup::new();
+    group.set_title("Trash");
+    group.set_description(Some(
+        "Deleted accounts can be restored until they are purged",
+    ));
+
+    let trashed = list_trash(vault_name).unwrap_or_else(|e| {
+        log::error!("Failed to read the trash of '{vault_name}': {e}");
+        Vec::new()
+    });
+
+    let empty_button = Button::with_label("Empty");
+    empty_button.add_css_class("destructive-action");
+    empty_button.set_valign(Align::Center);
+    empty_button.set_sensitive(!trashed.is_empty());
+    empty_button.connect_clicked({
+        let content_area = content_area.clone();
+        let vault_name = vault_name.to_string();
+        let ctx = ctx.clone();
+        move |_| {
+            let content_area = content_area.clone();
+            let vault_name = vault_name.clone();
+            let ctx = ctx.clone();
+            show_confirmation_dialog(
+                "Empty Trash",
+                "Permanently delete every account in the trash?\n\nThis action cannot be undone.",
+                "Empty Trash",
+                None::<&gtk4::Window>,
+                move || {
+                    let content_area = content_area.clone();
+                    let vault_name = vault_name.clone();
+                    let ctx = ctx.clone();
+
+                    glib::spawn_future_local(async move {
+                        let vault = vault_name.clone();
+                        let result = gtk4::gio::spawn_blocking(move || empty_trash(&vault))
+                            .await
+                            .expect("empty trash task panicked");
+
+                        if let Err(e) = result {
+                            log::error!("Failed to empty the trash of '{vault_name}': {e}");
+                            show_error_dialog("Empty Trash Failed", &format!("{e}"));
+                        }
+                        VaultView::new(&content_area, &vault_name).create(ctx);
+                    });
+                },
+            );
+        }
+    });
+    let empty_row = ActionRow::new();
+    empty_row.set_title("Empty Trash");
+    empty_row.set_subtitle("Permanently delete every account in the trash");
+    empty_row.set_margin_start(8);
+    empty_row.set_margin_end(8);
+    empty_row.add_suffix(&empty_button);
+
+    let settings = load_vault_settings(vault_name).unwrap_or_else(|e| {
+        log::warn!("Failed to load the settings of '{vault_name}': {e}");
+        Default::default()
+    });
+    let retention_row = ActionRow::new();
+    retention_row.set_title("Keep Deleted Accounts");
+    retention_row.set_subtitle("Days before they are purged, or 0 to keep them until emptied");
+    retention_row.set_margin_start(8);
+    retention_row.set_margin_end(8);
+
+    let retention_adjustment = Adjustment::new(
+        f64::from(settings.trash_retention_days),
+        0.0,
+        3650.0,
+        1.0,
+        30.0,
+        0.0,
+    );
+    let retention_spinner = SpinButton::new(Some(&retention_adjustment), 1.0, 0);
+    retention_spinner.set_valign(gtk4::Align::Center);
+    retention_spinner.connect_value_changed({
+        let vault_name = vault_name.to_string();
+        move |spinner| {
+            let mut settings = load_vault_settings(&vault_name).unwrap_or_default();
+            settings.trash_retention_days = spinner.value() as u32;
+            match save_vault_settings(&vault_name, &settings) {
+                Ok(()) => {
+                    let vault_task = vault_name.clone();
+                    gtk4::gio::spawn_blocking(move || update_vault_manifest(&vault_task));
+                }
+                Err(e) => log::error!("Failed to save the settings of '{vault_name}': {e}"),
+            }
+        }
+    });
+    retention_row.add_suffix(&retention_spinner);
+    group.add(&retention_row);
+    group.add(&empty_row);
+
+    if trashed.is_empty() {
+        group.add(
+            &CreateActionRow::<fn()>::new()
+                .title("Trash is empty")
+                .add_button(false)
+                .build(),
+        );
+    }
+
+    for account in trashed {
+        let delete_button = Button::with_label("Delete Forever");
+        delete_button.add_css_class("destructive-action");
+        delete_button.set_valign(Align::Center);
+        delete_button.connect_clicked({
+            let content_area = content_area.clone();
+            let vault_name = vault_name.to_string();
+            let ctx = ctx.clone();
+            let account = account.clone();
+            move |_| {
+                let content_area = content_area.clone();
+                let vault_name = vault_name.clone();
+                let ctx = ctx.clone();
+                let id = account.id.clone();
+                show_confirmation_dialog(
+                    "Delete Forever",
+                    &format!(
+                        "Permanently delete '{}'?\n\nThis action cannot be undone.",
+                        account.account_name
+                    ),
+                    "Delete",
+                    None::<&gtk4::Window>,
+                    move || {
+                        let content_area = content_area.clone();
+                        let vault_name = vault_name.clone();
+                        let ctx = ctx.clone();
+                        let id = id.clone();
+
+                        glib::spawn_future_local(async move {
+                            let vault = vault_name.clone();
+                            let id_task = id.clone();
+                            let result = gtk4::gio::spawn_blocking(move || {
+                                delete_from_trash(&vault, &id_task)
+                            })
+                            .await
+                            .expect("delete from trash task panicked");
+
+                            if let Err(e) = result {
+                                log::error!("Failed to delete '{id}' from the trash: {e}");
+                                show_error_dialog("Delete Failed", &format!("{e}"));
+                            }
+                            VaultView::new(&content_area, &vault_name).create(ctx);
+                        });
+                    },
+                );
+            }
+        });
+
+        group.add(
+            &CreateActionRow::new()
+                .title(&account.account_name)
+                .subtitle(format!("Deleted {}", account.deleted_at_display()))
+                .button_label("Restore")
+                .css_class("suggested-action")
+                .suffix(&delete_button)
+                .callback({
+                    let content_area = content_area.clone();
+                    let vault_name = vault_name.to_string();
+                    let ctx = ctx.clone();
+                    move || {
+                        let content_area = content_area.clone();
+                        let vault_name = vault_name.clone();
+                        let ctx = ctx.clone();
+                        let account = account.clone();
+
+                        glib::spawn_future_local(async move {
+                            let vault = vault_name.clone();
+                            let id = account.id.clone();
+                            let result = gtk4::gio::spawn_blocking(move || {
+                                restore_account_from_trash(&vault, &id)
+                            })
+                            .await
+                            .expect("restore account task panicked");
+
+                            match result {
+                                Ok(_) => VaultView::new(&content_area, &vault_name).create(ctx),
+                                Err(e) => {
+                                    log::error!(
+                                        "Failed to restore '{}': {e}",
+                                        account.account_name
+                                    );
+                                    show_error_dialog("Restore Failed", &format!("{e}"));
+                                }
+                            }
+                        });
+                    }
+                })
+                .build(),
+        );
+    }
+
+    group
+}
+
+fn audit_summary(audit: &VaultAudit) -> String {
+    let checked = match audit.accounts_checked {
+        1 => "1 account checked".to_string(),
+        n => format!("{n} accounts checked"),
+    };
+
+    match (audit.issue_count(), audit.unreadable.len()) {
+        (0, 0) => format!("{checked} • No issues found"),
+        (issues, 0) => format!("{checked} • {issues} issue(s) found"),
+        (issues, unreadable) => {
+            format!("{checked} • {issues} issue(s) found • {unreadable} could not be decrypted")
+        }
+    }
+}
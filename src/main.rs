@@ -1,9 +1,12 @@
+mod breach;
+mod cli;
 mod crypto;
 mod gui;
 mod models;
 mod password;
 mod security;
 mod storage;
+mod strength;
 mod totp;
 mod vault;
 
@@ -11,7 +14,47 @@ mod vault;
 mod tests;
 
 use crate::gui::application::run_gui;
+use crate::storage::{
+    profile::{select_home, selection_from_env},
+    store::recover_unfinished_writes,
+};
 
 fn main() {
-    run_gui();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let (selection, args) = match cli::parse_global_options(&args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("fmp: {e}");
+            eprintln!("Run `fmp help` for usage.");
+            std::process::exit(2);
+        }
+    };
+    let home = match select_home(&selection.unwrap_or_else(selection_from_env)) {
+        Ok(home) => home,
+        Err(e) => {
+            eprintln!("fmp: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    // A crash mid-write leaves a temporary file next to the untouched original
+    let mut roots = vec![&home.data];
+    if home.config != home.data {
+        roots.push(&home.config);
+    }
+    for root in roots {
+        if let Err(e) = recover_unfinished_writes(root) {
+            eprintln!(
+                "fmp: failed to clean up unfinished writes in `{}`: {e}",
+                root.display()
+            );
+        }
+    }
+
+    if args.is_empty() {
+        run_gui();
+    } else {
+        std::process::exit(cli::run(&args));
+    }
 }
@@ -18,9 +18,37 @@ Copyright (C) 2025  Luke Wilkinson
 */
 
 use crate::security::SecurePassword;
+use crate::totp::TotpParams;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// A one-time password secret for the service this account logs in to.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AccountTotp {
+    /// The Base32-encoded shared secret, as shown by the service during 2FA setup.
+    pub secret: SecurePassword,
+    /// The algorithm, digits, period and skew used to generate codes from the secret.
+    #[serde(default)]
+    pub params: TotpParams,
+    /// The service that issued the secret, such as "GitHub".
+    #[serde(default)]
+    pub issuer: String,
+    /// The account name the service gave the secret, usually a username or email.
+    #[serde(default)]
+    pub label: String,
+}
+
+/// How many previous passwords an account keeps; older ones are dropped.
+pub const MAX_PASSWORD_HISTORY: usize = 20;
+
+/// A password an account used before, kept so it can be recovered after a failed rotation.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PasswordHistoryEntry {
+    pub password: SecurePassword,
+    /// When this password was replaced, in the same format as `Account::modified_at`.
+    pub replaced_at: String,
+}
+
 /// Represents a comprehensive account with all fields supported by the GUI
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Account {
@@ -32,8 +60,16 @@ pub struct Account {
     pub password: SecurePassword,
     pub notes: String,
     pub additional_fields: HashMap<String, String>,
+    /// Labels for grouping and finding accounts, such as "work" or "finance".
+    #[serde(default)]
+    pub tags: Vec<String>,
     pub created_at: String,
     pub modified_at: String,
+    #[serde(default)]
+    pub totp: Option<AccountTotp>,
+    /// Previous passwords, newest first. Stored inside the encrypted account file.
+    #[serde(default)]
+    pub password_history: Vec<PasswordHistoryEntry>,
 }
 
 impl Default for Account {
@@ -47,8 +83,11 @@ impl Default for Account {
             password: SecurePassword::empty(),
             notes: String::new(),
             additional_fields: HashMap::new(),
+            tags: Vec::new(),
             created_at: now.clone(),
             modified_at: now,
+            totp: None,
+            password_history: Vec::new(),
         }
     }
 }
@@ -58,4 +97,70 @@ impl Account {
     pub fn update_modified_time(&mut self) {
         self.modified_at = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
     }
+
+    /// Parses comma-separated tags, trimming each and dropping empty and repeated ones.
+    ///
+    /// # Arguments
+    /// * `text` - The tags as typed, such as "work, finance".
+    ///
+    /// # Returns
+    /// * `Vec<String>` - The tags, in the order given.
+    pub fn parse_tags(text: &str) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+        for tag in text.split(',').map(str::trim) {
+            if !tag.is_empty() && !tags.iter().any(|existing| existing == tag) {
+                tags.push(tag.to_string());
+            }
+        }
+        tags
+    }
+
+    /// Returns the tags joined with commas, as `parse_tags` reads them.
+    pub fn tags_display(&self) -> String {
+        self.tags.join(", ")
+    }
+
+    /// Adds the previous version's password to the history if this version changed it. An older
+    /// entry with the same password is dropped, so each password is listed once.
+    ///
+    /// # Arguments
+    /// * `previous` - The account as it was last saved.
+    ///
+    /// # Returns
+    /// * `bool` - Returns `true` if a history entry was added.
+    pub fn record_password_change(&mut self, previous: &Account) -> bool {
+        if self.password.matches(&previous.password) || previous.password.is_empty() {
+            return false;
+        }
+
+        self.password_history
+            .retain(|entry| !entry.password.matches(&previous.password));
+        self.password_history.insert(
+            0,
+            PasswordHistoryEntry {
+                password: previous.password.clone(),
+                replaced_at: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            },
+        );
+        self.password_history.truncate(MAX_PASSWORD_HISTORY);
+        true
+    }
+
+    /// Makes a previous password current again and removes it from the history. The replaced
+    /// password is added to the history when the account is next saved.
+    ///
+    /// # Arguments
+    /// * `index` - The position of the entry in `password_history`.
+    ///
+    /// # Returns
+    /// * `bool` - Returns `false` if there is no entry at `index`.
+    pub fn restore_password_from_history(&mut self, index: usize) -> bool {
+        if index >= self.password_history.len() {
+            return false;
+        }
+
+        self.password = self.password_history.remove(index).password;
+        self.update_modified_time();
+        true
+    }
 }
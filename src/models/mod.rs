@@ -22,4 +22,4 @@ Copyright (C) 2025  Luke Wilkinson
 
 pub mod account;
 
-pub use account::Account;
+pub use account::{Account, AccountTotp};
@@ -1,8 +1,10 @@
+use crate::strength::{StrengthEstimate, display_crack_time, estimate_password_strength};
 use rand::{Rng, rng};
 use std::collections::HashSet;
+use std::sync::LazyLock;
 
 /// Configuration for password generation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct PasswordConfig {
     pub length: usize,
@@ -14,6 +16,38 @@ pub struct PasswordConfig {
     pub include_extended: bool,
     pub additional_characters: String,
     pub excluded_characters: String,
+    pub mode: PasswordMode,
+    pub passphrase: PassphraseConfig,
+}
+
+/// Whether to generate a password from random characters or random words
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PasswordMode {
+    #[default]
+    Characters,
+    Passphrase,
+}
+
+/// Configuration for diceware passphrase generation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassphraseConfig {
+    pub word_count: usize,
+    pub separator: String,
+    pub capitalize: bool,
+    pub append_digit: bool,
+    pub append_symbol: bool,
+}
+
+impl Default for PassphraseConfig {
+    fn default() -> Self {
+        Self {
+            word_count: 6,
+            separator: "-".to_string(),
+            capitalize: false,
+            append_digit: false,
+            append_symbol: false,
+        }
+    }
 }
 
 impl Default for PasswordConfig {
@@ -28,6 +62,8 @@ impl Default for PasswordConfig {
             include_extended: false,
             additional_characters: String::new(),
             excluded_characters: String::new(),
+            mode: PasswordMode::Characters,
+            passphrase: PassphraseConfig::default(),
         }
     }
 }
@@ -39,6 +75,26 @@ const NUMBERS: &str = "0123456789";
 pub const SYMBOLS: &str = "!\"#%&'()*+,-./:;<=>?@[\\]^_`{|}$~";
 const EXTENDED: &str = "áÁàÀâÂäÄãÃåÅæÆçÇéÉèÈêÊëËíÍìÌîÎïÏñÑóÓòÒôÔöÖõÕøØœŒßúÚùÙûÛüÜ";
 
+/// Largest number of words a passphrase may have
+pub const MAX_PASSPHRASE_WORDS: usize = 20;
+
+// The EFF long wordlist (CC BY 3.0 US), one "<dice rolls>\t<word>" entry per line
+const EFF_LARGE_WORDLIST: &str = include_str!("wordlists/eff_large_wordlist.txt");
+
+static EFF_WORDS: LazyLock<Vec<&'static str>> = LazyLock::new(|| {
+    EFF_LARGE_WORDLIST
+        .lines()
+        .filter_map(|line| line.split('\t').nth(1))
+        .map(str::trim)
+        .filter(|word| !word.is_empty())
+        .collect()
+});
+
+/// Returns the words passphrases are drawn from.
+pub fn eff_wordlist() -> &'static [&'static str] {
+    &EFF_WORDS
+}
+
 // TODO: Proper errors
 /// Generates a random password based on the provided configuration.
 ///
@@ -48,6 +104,10 @@ const EXTENDED: &str = "áÁàÀâÂäÄãÃåÅæÆçÇéÉèÈêÊëËíÍìÌ
 /// # Returns
 /// * `Result<String, String>` - The generated password or an error message
 pub fn generate_password(password_config: &PasswordConfig) -> Result<String, String> {
+    if password_config.mode == PasswordMode::Passphrase {
+        return generate_passphrase(&password_config.passphrase);
+    }
+
     if password_config.length == 0 {
         return Err("Password length must be greater than 0".to_string());
     }
@@ -104,46 +164,89 @@ pub fn generate_password(password_config: &PasswordConfig) -> Result<String, Str
     Ok(password)
 }
 
-/// Calculates password strength score (0-100) based on estimated bit entropy.
+/// Generates a diceware passphrase from the EFF long wordlist.
+///
+/// Words are joined with the separator; the optional digit and symbol are appended to the end.
 ///
-/// Entropy = log2(pool_size) * length, capped at 128 bits = 100.
+/// # Arguments
+/// * `passphrase_config` - The passphrase generation configuration
+///
+/// # Returns
+/// * `Result<String, String>` - The generated passphrase or an error message
+pub fn generate_passphrase(passphrase_config: &PassphraseConfig) -> Result<String, String> {
+    if !(1..=MAX_PASSPHRASE_WORDS).contains(&passphrase_config.word_count) {
+        return Err(format!(
+            "Passphrase must have between 1 and {MAX_PASSPHRASE_WORDS} words"
+        ));
+    }
+
+    let words = eff_wordlist();
+    if words.is_empty() {
+        return Err("Passphrase wordlist is empty".to_string());
+    }
+
+    let mut rng = rng();
+    let mut passphrase = String::new();
+
+    for index in 0..passphrase_config.word_count {
+        if index > 0 {
+            passphrase.push_str(&passphrase_config.separator);
+        }
+
+        let word = words[rng.random_range(0..words.len())];
+        if passphrase_config.capitalize {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                passphrase.extend(first.to_uppercase());
+                passphrase.push_str(chars.as_str());
+            }
+        } else {
+            passphrase.push_str(word);
+        }
+    }
+
+    if passphrase_config.append_digit {
+        let digits: Vec<char> = NUMBERS.chars().collect();
+        passphrase.push(digits[rng.random_range(0..digits.len())]);
+    }
+    if passphrase_config.append_symbol {
+        let symbols: Vec<char> = SYMBOLS.chars().collect();
+        passphrase.push(symbols[rng.random_range(0..symbols.len())]);
+    }
+
+    Ok(passphrase)
+}
+
+/// Calculates the exact entropy of passphrases generated with a configuration.
+///
+/// Each word contributes log2(wordlist size) bits and the appended digit and symbol contribute
+/// log2(10) and log2(symbol count). Capitalisation and the separator are fixed, so they add nothing.
+///
+/// # Arguments
+/// * `passphrase_config` - The passphrase generation configuration
+///
+/// # Returns
+/// * `f64` - The entropy in bits
+pub fn passphrase_entropy_bits(passphrase_config: &PassphraseConfig) -> f64 {
+    let mut bits = passphrase_config.word_count as f64 * (eff_wordlist().len() as f64).log2();
+
+    if passphrase_config.append_digit {
+        bits += (NUMBERS.chars().count() as f64).log2();
+    }
+    if passphrase_config.append_symbol {
+        bits += (SYMBOLS.chars().count() as f64).log2();
+    }
+
+    bits
+}
+
+/// Calculates password strength score (0-100) from the estimated number of guesses.
+///
+/// Dictionary words, common passwords, keyboard walks, repeats, sequences and dates are all
+/// recognised, so `Password1!Password1!` scores far lower than its length suggests.
+#[allow(dead_code)] // Used for testing only as of current
 pub fn calculate_password_strength(password: &str) -> u8 {
-    if password.is_empty() {
-        return 0;
-    }
-
-    let length = password.len();
-
-    // Detect which character classes are present to estimate pool size
-    let has_lower = password.chars().any(|c| c.is_ascii_lowercase());
-    let has_upper = password.chars().any(|c| c.is_ascii_uppercase());
-    let has_digit = password.chars().any(|c| c.is_ascii_digit());
-    let has_special = password
-        .chars()
-        .any(|c| !c.is_alphanumeric() && c.is_ascii());
-    let has_extended = password.chars().any(|c| !c.is_ascii());
-
-    let pool_size: f32 = [
-        (has_lower, 26.0f32),
-        (has_upper, 26.0),
-        (has_digit, 10.0),
-        (has_special, 32.0),
-        (has_extended, 64.0),
-    ]
-    .iter()
-    .filter(|(present, _)| *present)
-    .map(|(_, size)| size)
-    .sum();
-
-    if pool_size == 0.0 {
-        return 0;
-    }
-
-    // bits of entropy = log2(pool_size) * length
-    // cap at 128 bits → 100 score
-    let bits = pool_size.log2() * length as f32;
-    let score = ((bits / 128.0) * 100.0).min(100.0) as u8;
-    score
+    estimate_password_strength(password).percent
 }
 
 /// Returns a color class based on password strength
@@ -156,12 +259,21 @@ pub fn get_strength_color_class(strength: u8) -> &'static str {
     }
 }
 
-/// Returns a human-readable strength description
-pub fn get_strength_description(strength: u8) -> &'static str {
-    match strength {
-        0..=25 => "Weak",
-        26..=50 => "Fair",
-        51..=75 => "Good",
+/// Returns a human-readable strength description, including how long an offline attack
+/// against a slow hash would take, such as "Weak · cracked in 3 hours".
+pub fn get_strength_description(estimate: &StrengthEstimate) -> String {
+    let label = match estimate.score {
+        0 => "Very weak",
+        1 => "Weak",
+        2 => "Fair",
+        3 => "Good",
         _ => "Strong",
+    };
+    let crack_time = display_crack_time(estimate.crack_times.offline_slow);
+
+    if crack_time == "centuries" {
+        format!("{label} · would take centuries to crack")
+    } else {
+        format!("{label} · cracked in {crack_time}")
     }
 }
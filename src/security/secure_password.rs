@@ -74,6 +74,16 @@ impl SecurePassword {
         self.inner_secret.expose_secret().len()
     }
 
+    /// Returns true if the password is empty
+    pub fn is_empty(&self) -> bool {
+        self.inner_secret.expose_secret().is_empty()
+    }
+
+    /// Returns true if both passwords hold the same secret
+    pub fn matches(&self, other: &SecurePassword) -> bool {
+        self.inner_secret.expose_secret() == other.inner_secret.expose_secret()
+    }
+
     /// Updates the password with a new value
     pub fn update(&mut self, new_password_input: &str) {
         // Regenerate obfuscation data on update
@@ -0,0 +1,504 @@
+//! Keeping account names off the disk.
+//!
+//! A vault normally stores each account in a directory named after it. A vault with a `names.gpg`
+//! index stores each account under an opaque ID instead: a keyed hash of its name, so the ID of a
+//! new account is known before it is recorded. The index holds the hashing key and every ID's
+//! name, and is encrypted to the vault's recipients. Trashed accounts keep the same ID after their
+//! timestamp, and backup snapshots copy the index along with the accounts.
+
+/*
+Copyright (C) 2025  Luke Wilkinson
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::storage::{
+    Locations,
+    backup::create_backup,
+    locations::TRASH_DIRECTORY,
+    manifest::update_vault_manifest,
+    store::{find_recipient_keys, read_recipient_file, write_private_file},
+    vault_files,
+};
+use crate::vault::recover_interrupted_rekey;
+use anyhow::Error;
+use gpgme::{Context, Protocol};
+use hmac::{Hmac, Mac, digest::KeyInit};
+use rand::{RngCore, rng};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{metadata, read, remove_file};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use zeroize::Zeroize;
+
+/// Name of the encrypted index of account names inside a vault or backup snapshot.
+pub const NAMES_FILE: &str = "names.gpg";
+
+/// Length of an account ID in bytes, before it is written as hex.
+const ID_BYTES: usize = 16;
+/// Length of the key account IDs are derived with.
+const KEY_BYTES: usize = 32;
+
+/// Indexes read since they last changed, by file.
+static CACHE: Mutex<BTreeMap<PathBuf, CachedIndex>> = Mutex::new(BTreeMap::new());
+
+/// A decrypted index, with the length and modification time of the file it was read from.
+struct CachedIndex {
+    len: u64,
+    modified: SystemTime,
+    index: NameIndex,
+}
+
+/// The decrypted contents of `names.gpg`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct NameIndex {
+    /// The key account IDs are derived with.
+    pub key: Vec<u8>,
+    /// The name of each account, trashed ones included, by ID.
+    pub names: BTreeMap<String, String>,
+}
+
+impl NameIndex {
+    /// Creates an empty index with a new random key.
+    pub fn generate() -> Self {
+        let mut key = vec![0u8; KEY_BYTES];
+        rng().fill_bytes(&mut key);
+
+        Self {
+            key,
+            names: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the ID an account is stored under. The same name always gets the same ID.
+    ///
+    /// # Arguments
+    /// * `account_name` - The name of the account.
+    ///
+    /// # Returns
+    /// * `String` - The ID, as hex.
+    pub fn id_for(&self, account_name: &str) -> String {
+        let mut mac = <Hmac<Sha256> as KeyInit>::new_from_slice(&self.key)
+            .expect("HMAC accepts keys of any length");
+        mac.update(account_name.as_bytes());
+
+        mac.finalize().into_bytes()[..ID_BYTES]
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    /// Returns the name of the account stored under an ID.
+    ///
+    /// # Arguments
+    /// * `id` - The account's directory name.
+    ///
+    /// # Returns
+    /// * `Option<&str>` - The account's name, or `None` if the ID is not in the index.
+    pub fn name_for(&self, id: &str) -> Option<&str> {
+        self.names.get(id).map(String::as_str)
+    }
+
+    /// Records an account's name and returns its ID.
+    ///
+    /// # Arguments
+    /// * `account_name` - The name of the account.
+    ///
+    /// # Returns
+    /// * `String` - The ID the account is stored under.
+    pub fn insert(&mut self, account_name: &str) -> String {
+        let id = self.id_for(account_name);
+        self.names.insert(id.clone(), account_name.to_string());
+        id
+    }
+}
+
+impl Drop for NameIndex {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+/// Whether a vault or backup snapshot stores its accounts under opaque IDs.
+///
+/// # Arguments
+/// * `root` - The vault or snapshot directory.
+///
+/// # Returns
+/// * `bool` - `true` if it has a names index.
+pub fn hides_names(root: &Path) -> bool {
+    root.join(NAMES_FILE).is_file()
+}
+
+/// Whether a vault stores its accounts under opaque IDs.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+///
+/// # Returns
+/// * `bool` - `true` if the vault hides account names.
+pub fn vault_hides_names(vault_name: &str) -> bool {
+    hides_names(&Locations::new(vault_name, "").vault)
+}
+
+/// Returns an account's directory in a vault or backup snapshot.
+///
+/// In a vault that hides names, a directory still named after the account is used if there is no
+/// directory under its ID, so a migration that was interrupted leaves every account reachable.
+///
+/// # Arguments
+/// * `root` - The vault or snapshot directory.
+/// * `account_name` - The name of the account.
+///
+/// # Returns
+/// * `Result<PathBuf, Error>` - Returns the account directory, which may not exist yet.
+///
+/// # Errors
+/// * If the vault hides names and its index cannot be read.
+pub fn account_dir(root: &Path, account_name: &str) -> Result<PathBuf, Error> {
+    if account_name.is_empty() || !hides_names(root) {
+        return Ok(root.join(account_name));
+    }
+
+    let id = read_index(root, |index| index.id_for(account_name))?;
+    let hidden = root.join(&id);
+    let plain = root.join(account_name);
+    if !vault_files::exists(&hidden) && vault_files::exists(&plain) {
+        return Ok(plain);
+    }
+
+    Ok(hidden)
+}
+
+/// Returns the name of the account stored in a directory of a vault or backup snapshot.
+///
+/// # Arguments
+/// * `root` - The vault or snapshot directory.
+/// * `dir_name` - The account directory's name.
+///
+/// # Returns
+/// * `String` - The account's name, or `dir_name` if it is not an ID in the index. An index that
+///   cannot be read is logged and treated the same way.
+pub fn account_name_for_dir(root: &Path, dir_name: &str) -> String {
+    account_names_for_dirs(root, vec![dir_name.to_string()]).remove(0)
+}
+
+/// Returns the names of the accounts stored in directories of a vault or backup snapshot, in the
+/// same order, reading the index once.
+///
+/// # Arguments
+/// * `root` - The vault or snapshot directory.
+/// * `dir_names` - The account directories' names.
+///
+/// # Returns
+/// * `Vec<String>` - Each account's name, or the directory's name if it is not an ID in the index.
+pub fn account_names_for_dirs(root: &Path, dir_names: Vec<String>) -> Vec<String> {
+    if !hides_names(root) {
+        return dir_names;
+    }
+
+    read_index(root, |index| {
+        dir_names
+            .iter()
+            .map(|dir_name| index.name_for(dir_name).unwrap_or(dir_name).to_string())
+            .collect()
+    })
+    .unwrap_or_else(|e| {
+        log::warn!("Failed to read account names in `{}`: {e}", root.display());
+        dir_names
+    })
+}
+
+/// Records an account's name in a vault's index before its directory is created under its ID.
+/// Nothing is done for a vault that does not hide names.
+///
+/// # Arguments
+/// * `root` - The vault directory.
+/// * `account_name` - The name of the account.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the index cannot be read or written.
+pub fn record_account_name(root: &Path, account_name: &str) -> Result<(), Error> {
+    if !hides_names(root) {
+        return Ok(());
+    }
+
+    let Some(mut index) = load_index(root)? else {
+        return Ok(());
+    };
+    if index.name_for(&index.id_for(account_name)).is_some() {
+        return Ok(());
+    }
+
+    index.insert(account_name);
+    save_index(root, &index)
+}
+
+/// Removes the names of accounts that are no longer in a vault or its trash from its index.
+/// Nothing is done for a vault that does not hide names.
+///
+/// # Arguments
+/// * `root` - The vault directory.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault, its trash or its index cannot be read, or the index cannot be written.
+pub fn forget_removed_accounts(root: &Path) -> Result<(), Error> {
+    let Some(mut index) = load_index(root)? else {
+        return Ok(());
+    };
+
+    let (accounts, trashed) = (account_dirs(root)?, trashed_dirs(root)?);
+    let stored: BTreeSet<String> = accounts
+        .into_iter()
+        .chain(trashed.into_iter().map(|(_, dir_name)| dir_name))
+        .collect();
+
+    let before = index.names.len();
+    index.names.retain(|id, _| stored.contains(id));
+    if index.names.len() == before {
+        return Ok(());
+    }
+
+    save_index(root, &index)
+}
+
+/// Moves every account in a vault, trashed ones included, under an opaque ID. The names are
+/// recorded in the index before any directory is renamed, and running this again finishes a
+/// migration that was interrupted.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+///
+/// # Returns
+/// * `Result<usize, Error>` - Returns how many account directories were renamed, or an error on
+///   failure.
+///
+/// # Errors
+/// * If the vault does not exist, cannot be backed up, or an account cannot be renamed.
+pub fn hide_account_names(vault_name: &str) -> Result<usize, Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+    recover_interrupted_rekey(vault_name)?;
+    create_backup(vault_name)?;
+
+    let mut index = load_index(&locations.vault)?.unwrap_or_else(NameIndex::generate);
+    let (accounts, trashed) = (
+        account_dirs(&locations.vault)?,
+        trashed_dirs(&locations.vault)?,
+    );
+
+    let mut renames = Vec::new();
+    for dir_name in accounts {
+        if index.name_for(&dir_name).is_none() {
+            let id = index.insert(&dir_name);
+            renames.push((locations.vault.join(dir_name), locations.vault.join(id)));
+        }
+    }
+    for (timestamp, dir_name) in trashed {
+        if index.name_for(&dir_name).is_none() {
+            let id = index.insert(&dir_name);
+            renames.push((
+                locations.trash.join(format!("{timestamp}-{dir_name}")),
+                locations.trash.join(format!("{timestamp}-{id}")),
+            ));
+        }
+    }
+
+    save_index(&locations.vault, &index)?;
+    rename_all(&renames)?;
+    update_vault_manifest(vault_name);
+
+    Ok(renames.len())
+}
+
+/// Moves every account in a vault, trashed ones included, back into a directory named after it
+/// and removes the index. Running this again finishes a migration that was interrupted.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+///
+/// # Returns
+/// * `Result<usize, Error>` - Returns how many account directories were renamed, or an error on
+///   failure.
+///
+/// # Errors
+/// * If the vault does not exist or does not hide names, its index cannot be read, it cannot be
+///   backed up, or an account cannot be renamed.
+pub fn show_account_names(vault_name: &str) -> Result<usize, Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+    let Some(index) = load_index(&locations.vault)? else {
+        return Err(anyhow::anyhow!(
+            "Vault `{}` already stores accounts under their names",
+            vault_name
+        ));
+    };
+    recover_interrupted_rekey(vault_name)?;
+    create_backup(vault_name)?;
+
+    let (accounts, trashed) = (
+        account_dirs(&locations.vault)?,
+        trashed_dirs(&locations.vault)?,
+    );
+    let mut renames = Vec::new();
+    for dir_name in accounts {
+        if let Some(name) = index.name_for(&dir_name) {
+            renames.push((locations.vault.join(&dir_name), locations.vault.join(name)));
+        }
+    }
+    for (timestamp, dir_name) in trashed {
+        if let Some(name) = index.name_for(&dir_name) {
+            renames.push((
+                locations.trash.join(format!("{timestamp}-{dir_name}")),
+                locations.trash.join(format!("{timestamp}-{name}")),
+            ));
+        }
+    }
+
+    if let Some((_, taken)) = renames.iter().find(|(_, to)| vault_files::exists(to)) {
+        return Err(anyhow::anyhow!(
+            "`{}` already exists. Rename that account first.",
+            taken.display()
+        ));
+    }
+
+    rename_all(&renames)?;
+    let names_file = locations.vault.join(NAMES_FILE);
+    remove_file(&names_file)?;
+    forget_index(&names_file);
+    update_vault_manifest(vault_name);
+
+    Ok(renames.len())
+}
+
+/// Reads a vault's or snapshot's index, or `None` if it does not hide names.
+fn load_index(root: &Path) -> Result<Option<NameIndex>, Error> {
+    if !hides_names(root) {
+        return Ok(None);
+    }
+
+    read_index(root, NameIndex::clone).map(Some)
+}
+
+/// Runs `f` on a vault's or snapshot's index, decrypting it only if it changed since it was last
+/// read.
+fn read_index<T>(root: &Path, f: impl FnOnce(&NameIndex) -> T) -> Result<T, Error> {
+    let names_file = root.join(NAMES_FILE);
+    let file = metadata(&names_file)?;
+    let (len, modified) = (file.len(), file.modified()?);
+
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let current = cache
+        .get(&names_file)
+        .is_some_and(|cached| cached.len == len && cached.modified == modified);
+    if !current {
+        let mut ctx = Context::from_protocol(Protocol::OpenPgp)?;
+        let mut decrypted = Vec::new();
+        ctx.decrypt(read(&names_file)?, &mut decrypted)
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to decrypt the account names in `{}`. Error: {}",
+                    names_file.display(),
+                    e
+                )
+            })?;
+        let parsed = serde_json::from_slice(&decrypted);
+        decrypted.zeroize();
+
+        let index: NameIndex = parsed.map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to parse the account names in `{}`: {}",
+                names_file.display(),
+                e
+            )
+        })?;
+        cache.insert(
+            names_file.clone(),
+            CachedIndex {
+                len,
+                modified,
+                index,
+            },
+        );
+    }
+
+    Ok(f(&cache[&names_file].index))
+}
+
+/// Encrypts an index to a vault's recipients and writes it.
+fn save_index(root: &Path, index: &NameIndex) -> Result<(), Error> {
+    let recipients = read_recipient_file(&root.join("recipient"))?;
+    let mut ctx = Context::from_protocol(Protocol::OpenPgp)?;
+    let keys = find_recipient_keys(&recipients, &mut ctx)?;
+
+    let mut json = serde_json::to_vec(index)?;
+    let mut encrypted = Vec::new();
+    let result = ctx.encrypt(&keys, &json, &mut encrypted);
+    json.zeroize();
+    result.map_err(|e| anyhow::anyhow!("Failed to encrypt the account names. Error: {}", e))?;
+
+    let names_file = root.join(NAMES_FILE);
+    write_private_file(&names_file, &encrypted)?;
+    forget_index(&names_file);
+
+    Ok(())
+}
+
+fn forget_index(names_file: &Path) {
+    CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(names_file);
+}
+
+/// Lists the account directories in a vault.
+fn account_dirs(root: &Path) -> Result<Vec<String>, Error> {
+    Ok(vault_files::list_dirs(root)?
+        .into_iter()
+        .filter(|dir_name| dir_name != TRASH_DIRECTORY)
+        .collect())
+}
+
+/// Lists the timestamp and directory name of each account in a vault's trash.
+fn trashed_dirs(root: &Path) -> Result<Vec<(String, String)>, Error> {
+    let trash = root.join(TRASH_DIRECTORY);
+    if !vault_files::exists(&trash) {
+        return Ok(Vec::new());
+    }
+
+    Ok(vault_files::list_dirs(&trash)?
+        .into_iter()
+        .filter_map(|id| {
+            let (timestamp, dir_name) = id.split_once('-')?;
+            Some((timestamp.to_string(), dir_name.to_string()))
+        })
+        .collect())
+}
+
+fn rename_all(renames: &[(PathBuf, PathBuf)]) -> Result<(), Error> {
+    for (from, to) in renames {
+        vault_files::rename(from, to)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,542 @@
+//! Portable vault archives.
+//!
+//! An archive packs a whole vault directory (its recipient, gate, settings and the encrypted
+//! account files) into one file that can be kept anywhere, such as a USB drive. Nothing is
+//! decrypted: the account data in the archive is the same GPG ciphertext as in the vault.
+//!
+//! The file starts with `ARCHIVE_MAGIC`, followed by the length of the manifest as a
+//! little-endian `u64`, the manifest as JSON, and then the contents of every file listed in the
+//! manifest, in order. The manifest records each file's size and SHA-256 checksum, so an archive
+//! is verified in full before anything is imported from it.
+
+/*
+Copyright (C) 2025  Luke Wilkinson
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::storage::{
+    backup::create_backup,
+    filesystem::validate_path_new,
+    locations::Locations,
+    locations::TRASH_DIRECTORY,
+    manifest::sign_vault_manifest,
+    store::{TEMP_SUFFIX, write_private_file},
+};
+use crate::vault::recover_interrupted_rekey;
+use anyhow::Error;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::{File, create_dir_all, read, read_dir, remove_dir_all, remove_file, rename};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// The first bytes of every archive.
+pub const ARCHIVE_MAGIC: &[u8; 8] = b"FMPARCH\0";
+/// The manifest format written by this version of fmp.
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+/// The file extension suggested for archives.
+pub const ARCHIVE_EXTENSION: &str = "fmparchive";
+/// The format `ArchiveManifest::created_at` is stored in, matching `Account::modified_at`.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+/// Manifests larger than this are rejected rather than read into memory.
+const MAX_MANIFEST_SIZE: u64 = 16 * 1024 * 1024;
+/// Files every vault has, so an archive without them is not a vault.
+const REQUIRED_FILES: &[&str] = &["recipient", "gate.gpg"];
+/// Files that only matter while an operation is running, and are not archived.
+const SKIPPED_FILES: &[&str] = &["rekey.journal"];
+
+/// Describes the contents of an archive.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    /// The name of the vault the archive was exported from.
+    pub vault_name: String,
+    /// When the archive was made, in the same format as `Account::modified_at`.
+    pub created_at: String,
+    pub files: Vec<ArchiveEntry>,
+}
+
+/// One file in an archive.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// The file's path inside the vault, with `/` separators.
+    pub path: String,
+    pub size: u64,
+    /// The SHA-256 checksum of the file, as lowercase hex.
+    pub sha256: String,
+}
+
+impl ArchiveManifest {
+    /// Returns the names of the accounts in the archive, sorted. Trashed accounts are not included.
+    pub fn account_names(&self) -> Vec<String> {
+        let mut accounts: Vec<String> = self
+            .files
+            .iter()
+            .filter_map(|entry| entry.path.strip_suffix("/data.gpg"))
+            .filter(|account| !account.contains('/') && *account != TRASH_DIRECTORY)
+            .map(str::to_string)
+            .collect();
+        accounts.sort();
+        accounts
+    }
+
+    /// Returns the total size of the archived files in bytes.
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().map(|entry| entry.size).sum()
+    }
+}
+
+/// How an archive is imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Create a new vault; fails if the name is taken.
+    New,
+    /// Replace an existing vault, which is backed up first.
+    Replace,
+}
+
+/// Writes a vault directory to an archive file.
+///
+/// The archive is written next to `archive_path` and moved into place once complete, so a
+/// failed export never leaves a truncated archive behind.
+///
+/// # Arguments
+/// * `vault_dir` - The vault's directory.
+/// * `vault_name` - The name recorded in the manifest.
+/// * `archive_path` - Where to write the archive; an existing file is replaced.
+/// * `created_at` - When the archive was made.
+///
+/// # Returns
+/// * `Result<ArchiveManifest, Error>` - Returns the archive's manifest on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault cannot be read, is missing its recipient or gate, or the archive cannot be written.
+pub fn write_archive(
+    vault_dir: &Path,
+    vault_name: &str,
+    archive_path: &Path,
+    created_at: NaiveDateTime,
+) -> Result<ArchiveManifest, Error> {
+    let mut paths = Vec::new();
+    collect_files(vault_dir, "", &mut paths)?;
+    paths.sort();
+
+    // Read every file once, so the checksums always match the data written
+    let mut contents = Vec::with_capacity(paths.len());
+    let mut files = Vec::with_capacity(paths.len());
+    for path in paths {
+        let data = read(vault_dir.join(&path))?;
+        files.push(ArchiveEntry {
+            path,
+            size: data.len() as u64,
+            sha256: sha256_hex(&data),
+        });
+        contents.push(data);
+    }
+
+    let manifest = ArchiveManifest {
+        format_version: ARCHIVE_FORMAT_VERSION,
+        vault_name: vault_name.to_string(),
+        created_at: created_at.format(TIMESTAMP_FORMAT).to_string(),
+        files,
+    };
+    check_required_files(&manifest)?;
+
+    let partial = partial_path(archive_path);
+    let result = (|| -> Result<(), Error> {
+        let file = File::create(&partial)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        }
+
+        let manifest_json = serde_json::to_vec(&manifest)?;
+        let mut writer = BufWriter::new(file);
+        writer.write_all(ARCHIVE_MAGIC)?;
+        writer.write_all(&(manifest_json.len() as u64).to_le_bytes())?;
+        writer.write_all(&manifest_json)?;
+        for data in &contents {
+            writer.write_all(data)?;
+        }
+        writer.into_inner()?.sync_all()?;
+
+        rename(&partial, archive_path)?;
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        let _ = remove_file(&partial);
+        return Err(e);
+    }
+
+    Ok(manifest)
+}
+
+/// Checks that an archive is complete and undamaged, by comparing every file in it against the
+/// size and checksum in its manifest.
+///
+/// # Arguments
+/// * `archive_path` - The archive file.
+///
+/// # Returns
+/// * `Result<ArchiveManifest, Error>` - Returns the manifest of the verified archive, or an error on failure.
+///
+/// # Errors
+/// * If the archive cannot be read, its manifest is invalid, or any file is truncated, altered or missing.
+pub fn verify_archive(archive_path: &Path) -> Result<ArchiveManifest, Error> {
+    read_entries(archive_path, |_, _| Ok(()))
+}
+
+/// Verifies an archive and unpacks it into a new directory.
+///
+/// # Arguments
+/// * `archive_path` - The archive file.
+/// * `destination` - The directory to unpack into, which must not exist yet.
+///
+/// # Returns
+/// * `Result<ArchiveManifest, Error>` - Returns the archive's manifest on success, or an error on failure.
+///
+/// # Errors
+/// * If the destination exists, the archive fails verification, or a file cannot be written.
+///   Nothing is left in `destination` on failure.
+pub fn extract_archive(archive_path: &Path, destination: &Path) -> Result<ArchiveManifest, Error> {
+    if destination.exists() {
+        return Err(anyhow::anyhow!(
+            "`{}` already exists",
+            destination.display()
+        ));
+    }
+
+    // Nothing is written until the whole archive is known to be intact
+    verify_archive(archive_path)?;
+
+    create_private_dir(destination)?;
+    let result = read_entries(archive_path, |entry, data| {
+        let path = entry
+            .path
+            .split('/')
+            .fold(destination.to_path_buf(), |path, component| {
+                path.join(component)
+            });
+        if let Some(parent) = path.parent() {
+            create_private_dir(parent)?;
+        }
+        write_private_file(&path, data)
+    });
+
+    if result.is_err() {
+        let _ = remove_dir_all(destination);
+    }
+    result
+}
+
+/// Exports a vault to an archive file.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+/// * `archive_path` - Where to write the archive; an existing file is replaced.
+///
+/// # Returns
+/// * `Result<ArchiveManifest, Error>` - Returns the archive's manifest on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault does not exist, an interrupted re-key cannot be recovered, or the archive cannot be written.
+pub fn export_vault(vault_name: &str, archive_path: &Path) -> Result<ArchiveManifest, Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+
+    // Do not archive a vault that is halfway through being re-encrypted
+    recover_interrupted_rekey(vault_name)?;
+
+    write_archive(
+        &locations.vault,
+        vault_name,
+        archive_path,
+        chrono::Utc::now().naive_utc(),
+    )
+}
+
+/// Imports an archive as a vault.
+///
+/// When replacing a vault, a backup snapshot of it is made first, and it is only swapped out
+/// once the archive has been unpacked in full. The imported files are then signed into the vault's
+/// manifest.
+///
+/// # Arguments
+/// * `archive_path` - The archive file.
+/// * `vault_name` - The name of the vault to create or replace.
+/// * `mode` - Whether to create a new vault or replace an existing one.
+///
+/// # Returns
+/// * `Result<ArchiveManifest, Error>` - Returns the imported archive's manifest on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault name is invalid, the vault exists when creating or is missing when replacing,
+///   the archive fails verification, the vault cannot be written, or its manifest cannot be signed.
+pub fn import_vault(
+    archive_path: &Path,
+    vault_name: &str,
+    mode: ImportMode,
+) -> Result<ArchiveManifest, Error> {
+    let locations = Locations::new(vault_name, "");
+    if vault_name.is_empty() || vault_name.contains(['/', '\\']) {
+        return Err(anyhow::anyhow!("Invalid vault name `{}`", vault_name));
+    }
+
+    match mode {
+        ImportMode::New if !validate_path_new(&locations.vault) => {
+            return Err(anyhow::anyhow!(
+                "Vault `{}` already exists or is not a valid name.",
+                vault_name
+            ));
+        }
+        ImportMode::Replace => {
+            locations.does_vault_exist()?;
+        }
+        ImportMode::New => {}
+    }
+
+    let staged = locations.fmp.join(format!(".import-{vault_name}"));
+    let previous = locations.fmp.join(format!(".import-{vault_name}-old"));
+    for leftover in [&staged, &previous] {
+        if leftover.exists() {
+            remove_dir_all(leftover)?;
+        }
+    }
+
+    let manifest = extract_archive(archive_path, &staged)?;
+
+    if mode == ImportMode::Replace {
+        if let Err(e) = create_backup(vault_name) {
+            let _ = remove_dir_all(&staged);
+            return Err(anyhow::anyhow!(
+                "Could not back up vault `{}` before replacing it: {}",
+                vault_name,
+                e
+            ));
+        }
+        if let Err(e) = rename(&locations.vault, &previous) {
+            let _ = remove_dir_all(&staged);
+            return Err(e.into());
+        }
+    } else if let Some(vaults_dir) = locations.vault.parent() {
+        create_dir_all(vaults_dir)?;
+    }
+
+    if let Err(e) = rename(&staged, &locations.vault) {
+        if previous.exists() {
+            rename(&previous, &locations.vault)?;
+        }
+        let _ = remove_dir_all(&staged);
+        return Err(e.into());
+    }
+    if previous.exists() {
+        remove_dir_all(&previous)?;
+    }
+    // An archive carries the manifest it was exported with, which may be older than one already
+    // seen, so the import the user chose is trusted explicitly
+    sign_vault_manifest(vault_name).map_err(|e| {
+        anyhow::anyhow!(
+            "Imported vault `{}` but could not sign its manifest: {}",
+            vault_name,
+            e
+        )
+    })?;
+
+    Ok(manifest)
+}
+
+/// Reads the magic bytes and manifest from the start of an archive, and validates the manifest.
+fn read_manifest(reader: &mut impl Read, archive_path: &Path) -> Result<ArchiveManifest, Error> {
+    let not_an_archive =
+        || anyhow::anyhow!("`{}` is not an fmp vault archive", archive_path.display());
+
+    let mut magic = [0u8; 8];
+    reader
+        .read_exact(&mut magic)
+        .map_err(|_| not_an_archive())?;
+    if &magic != ARCHIVE_MAGIC {
+        return Err(not_an_archive());
+    }
+
+    let mut length = [0u8; 8];
+    reader
+        .read_exact(&mut length)
+        .map_err(|_| not_an_archive())?;
+    let length = u64::from_le_bytes(length);
+    if length > MAX_MANIFEST_SIZE {
+        return Err(anyhow::anyhow!(
+            "The manifest of `{}` is too large",
+            archive_path.display()
+        ));
+    }
+
+    let mut manifest_json = vec![0u8; length as usize];
+    reader.read_exact(&mut manifest_json).map_err(|_| {
+        anyhow::anyhow!("The manifest of `{}` is truncated", archive_path.display())
+    })?;
+    let manifest: ArchiveManifest = serde_json::from_slice(&manifest_json).map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to parse the manifest of `{}`: {}",
+            archive_path.display(),
+            e
+        )
+    })?;
+
+    if manifest.format_version > ARCHIVE_FORMAT_VERSION {
+        return Err(anyhow::anyhow!(
+            "`{}` was made by a newer version of fmp (archive format {})",
+            archive_path.display(),
+            manifest.format_version
+        ));
+    }
+
+    let mut seen = HashSet::new();
+    for entry in &manifest.files {
+        validate_entry_path(&entry.path)?;
+        if !seen.insert(entry.path.as_str()) {
+            return Err(anyhow::anyhow!(
+                "`{}` is listed twice in the archive",
+                entry.path
+            ));
+        }
+    }
+    check_required_files(&manifest)?;
+
+    Ok(manifest)
+}
+
+/// Reads every file in an archive, checking it against the manifest before passing it to `visit`.
+fn read_entries<F>(archive_path: &Path, mut visit: F) -> Result<ArchiveManifest, Error>
+where
+    F: FnMut(&ArchiveEntry, &[u8]) -> Result<(), Error>,
+{
+    let mut reader = BufReader::new(File::open(archive_path)?);
+    let manifest = read_manifest(&mut reader, archive_path)?;
+
+    for entry in &manifest.files {
+        let mut data = Vec::new();
+        (&mut reader).take(entry.size).read_to_end(&mut data)?;
+        if data.len() as u64 != entry.size {
+            return Err(anyhow::anyhow!(
+                "The archive is truncated: `{}` is incomplete",
+                entry.path
+            ));
+        }
+        if sha256_hex(&data) != entry.sha256.to_lowercase() {
+            return Err(anyhow::anyhow!(
+                "`{}` does not match its checksum; the archive is damaged",
+                entry.path
+            ));
+        }
+
+        visit(entry, &data)?;
+    }
+
+    if reader.read(&mut [0u8; 1])? != 0 {
+        return Err(anyhow::anyhow!(
+            "The archive has unexpected data after its last file"
+        ));
+    }
+
+    Ok(manifest)
+}
+
+/// Adds the paths of all files under `dir` to `paths`, relative to the vault and `/` separated.
+fn collect_files(dir: &Path, prefix: &str, paths: &mut Vec<String>) -> Result<(), Error> {
+    for entry in read_dir(dir)? {
+        let entry = entry?;
+        let name = entry
+            .file_name()
+            .into_string()
+            .map_err(|_| anyhow::anyhow!("Failed to convert file name to string."))?;
+        let path = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{prefix}/{name}")
+        };
+
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_files(&entry.path(), &path, paths)?;
+        } else if file_type.is_file()
+            && !(prefix.is_empty() && SKIPPED_FILES.contains(&name.as_str()))
+            && !name.ends_with(&format!(".{TEMP_SUFFIX}"))
+        {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Rejects manifest paths that could escape the directory an archive is unpacked into.
+fn validate_entry_path(path: &str) -> Result<(), Error> {
+    let valid = !path.is_empty()
+        && !path.contains('\\')
+        && path
+            .split('/')
+            .all(|component| !component.is_empty() && component != "." && component != "..");
+
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("Invalid path `{}` in the archive", path))
+    }
+}
+
+fn check_required_files(manifest: &ArchiveManifest) -> Result<(), Error> {
+    for required in REQUIRED_FILES {
+        if !manifest.files.iter().any(|entry| entry.path == *required) {
+            return Err(anyhow::anyhow!(
+                "The archive is missing the vault's `{}` file",
+                required
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates a directory, and any missing parents, that only the owner can open.
+pub fn create_private_dir(path: &Path) -> Result<(), Error> {
+    create_dir_all(path)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o700))?;
+    }
+
+    Ok(())
+}
+
+fn partial_path(archive_path: &Path) -> PathBuf {
+    let mut file_name = archive_path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".partial");
+    archive_path.with_file_name(file_name)
+}
+
+/// Returns the SHA-256 of some data as lowercase hex.
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
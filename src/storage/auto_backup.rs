@@ -0,0 +1,319 @@
+//! Automatic backups.
+//!
+//! Each vault can ask for a backup after a number of changes to its accounts, once a day, or when
+//! the app is closed. Changes are counted in `auto-backup.json` in the vault's backup directory,
+//! and the count is cleared whenever a backup is made, automatic or not.
+
+/*
+Copyright (C) 2025  Luke Wilkinson
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::storage::{
+    Locations,
+    backup::{BackupSnapshot, create_backup, list_backups},
+    manifest::update_vault_manifest,
+    store::write_private_file,
+};
+use crate::vault::settings::VaultSettings;
+use anyhow::Error;
+use chrono::{NaiveDateTime, TimeDelta};
+use serde::{Deserialize, Serialize};
+use std::fs::{create_dir_all, read_to_string};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Serialises updates to the change counters, which are written from the UI and backup threads.
+static STATE_LOCK: Mutex<()> = Mutex::new(());
+/// Vaults with an automatic backup in progress, so a vault is never backed up twice at once.
+static RUNNING: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// When a vault is backed up without being asked. Every rule is off by default.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct AutoBackupPolicy {
+    /// Back up after this many changes to accounts; 0 turns this off.
+    pub every_changes: u32,
+    /// Back up when the app is closed, if anything changed since the last backup.
+    pub on_exit: bool,
+    /// Back up at most once a day, if anything changed since the last backup.
+    pub daily: bool,
+}
+
+impl AutoBackupPolicy {
+    /// Whether any rule is turned on.
+    pub fn is_enabled(&self) -> bool {
+        self.every_changes > 0 || self.on_exit || self.daily
+    }
+
+    /// Returns a short description, such as "after every 10 changes and daily".
+    pub fn describe(&self) -> String {
+        let mut rules = Vec::new();
+        match self.every_changes {
+            0 => {}
+            1 => rules.push("after every change".to_string()),
+            n => rules.push(format!("after every {n} changes")),
+        }
+        if self.daily {
+            rules.push("daily".to_string());
+        }
+        if self.on_exit {
+            rules.push("when closing".to_string());
+        }
+
+        match rules.len() {
+            0 => "off".to_string(),
+            1 => rules.remove(0),
+            _ => {
+                let last = rules.pop().unwrap_or_default();
+                format!("{} and {last}", rules.join(", "))
+            }
+        }
+    }
+}
+
+/// What prompted a check for a due automatic backup.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackupTrigger {
+    /// The vault was opened or changed, or a periodic check ran.
+    Activity,
+    /// The app is closing.
+    Exit,
+}
+
+/// The changes made to a vault since its last backup.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct AutoBackupState {
+    pub changes: u32,
+}
+
+impl AutoBackupState {
+    /// Reads the state from a file, falling back to no changes if it does not exist.
+    ///
+    /// # Arguments
+    /// * `path` - The state file.
+    ///
+    /// # Returns
+    /// * `Result<AutoBackupState, Error>` - Returns the state on success, or an error on failure.
+    ///
+    /// # Errors
+    /// * If the file exists but cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        serde_json::from_str(&read_to_string(path)?).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to parse automatic backup state `{}`: {}",
+                path.display(),
+                e
+            )
+        })
+    }
+
+    /// Writes the state to a file, creating its directory if needed.
+    ///
+    /// # Arguments
+    /// * `path` - The state file.
+    ///
+    /// # Returns
+    /// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+    ///
+    /// # Errors
+    /// * If the directory or file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        if let Some(parent) = path.parent()
+            && !parent.exists()
+        {
+            create_dir_all(parent)?;
+
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(parent, std::fs::Permissions::from_mode(0o700))?;
+            }
+        }
+
+        write_private_file(path, serde_json::to_string_pretty(self)?.as_bytes())
+    }
+}
+
+/// Decides whether an automatic backup is due.
+///
+/// Nothing is backed up if nothing changed since the last backup, except that a vault with no
+/// backups at all is backed up by any rule that applies.
+///
+/// # Arguments
+/// * `policy` - The vault's automatic backup policy.
+/// * `changes` - How many changes were made since the last backup.
+/// * `latest` - When the newest backup was made, or `None` if there are none.
+/// * `now` - The current time.
+/// * `trigger` - What prompted the check.
+///
+/// # Returns
+/// * `bool` - Whether a backup should be made.
+pub fn backup_due(
+    policy: &AutoBackupPolicy,
+    changes: u32,
+    latest: Option<NaiveDateTime>,
+    now: NaiveDateTime,
+    trigger: BackupTrigger,
+) -> bool {
+    if changes == 0 && latest.is_some() {
+        return false;
+    }
+
+    let after_changes = policy.every_changes > 0 && changes >= policy.every_changes;
+    let daily = policy.daily && latest.is_none_or(|latest| now - latest >= TimeDelta::days(1));
+    let on_exit = policy.on_exit && trigger == BackupTrigger::Exit;
+
+    after_changes || daily || on_exit
+}
+
+/// Adds one change to the count kept in a state file.
+///
+/// # Arguments
+/// * `state_path` - The state file.
+///
+/// # Returns
+/// * `Result<u32, Error>` - Returns the new count on success, or an error on failure.
+///
+/// # Errors
+/// * If the state file cannot be read or written.
+pub fn count_change(state_path: &Path) -> Result<u32, Error> {
+    let _lock = STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let mut state = AutoBackupState::load(state_path)?;
+    state.changes = state.changes.saturating_add(1);
+    state.save(state_path)?;
+
+    Ok(state.changes)
+}
+
+/// Removes changes that a backup now covers from the count kept in a state file.
+///
+/// Only the changes counted when the backup started are removed, so changes made while it ran
+/// still count towards the next one.
+///
+/// # Arguments
+/// * `state_path` - The state file.
+/// * `covered` - How many changes had been counted when the backup started.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the state file cannot be read or written.
+pub fn clear_changes(state_path: &Path, covered: u32) -> Result<(), Error> {
+    let _lock = STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let mut state = AutoBackupState::load(state_path)?;
+    if state.changes == 0 {
+        return Ok(());
+    }
+    state.changes = state.changes.saturating_sub(covered);
+    state.save(state_path)
+}
+
+/// Records a change to the accounts of a vault, counting it for automatic backups and signing the
+/// vault's manifest again. Failures are logged, since they should never stop the change itself.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+pub fn record_vault_change(vault_name: &str) {
+    let locations = Locations::new(vault_name, "");
+    if let Err(e) = count_change(&locations.backup_state) {
+        log::warn!("Failed to count a change to '{vault_name}' for automatic backups: {e}");
+    }
+    update_vault_manifest(vault_name);
+}
+
+/// Backs up a vault if its automatic backup policy says one is due.
+///
+/// This copies and verifies the whole vault, so call it away from the UI thread.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+/// * `trigger` - What prompted the check.
+///
+/// # Returns
+/// * `Result<Option<BackupSnapshot>, Error>` - Returns the new snapshot, or `None` if no backup was due
+///   or one is already running.
+///
+/// # Errors
+/// * If the vault does not exist, its settings, state or backups cannot be read, or the backup fails.
+pub fn run_auto_backup(
+    vault_name: &str,
+    trigger: BackupTrigger,
+) -> Result<Option<BackupSnapshot>, Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+
+    let policy = VaultSettings::load(&locations.settings)?.auto_backup;
+    if !policy.is_enabled() {
+        return Ok(None);
+    }
+
+    let Some(_running) = RunningBackup::start(vault_name) else {
+        return Ok(None);
+    };
+
+    let changes = AutoBackupState::load(&locations.backup_state)?.changes;
+    let latest = list_backups(vault_name)?
+        .first()
+        .map(|snapshot| snapshot.created_at);
+    if !backup_due(
+        &policy,
+        changes,
+        latest,
+        chrono::Utc::now().naive_utc(),
+        trigger,
+    ) {
+        return Ok(None);
+    }
+
+    log::info!("Backing up '{vault_name}' automatically after {changes} change(s)");
+    create_backup(vault_name).map(Some)
+}
+
+/// Marks a vault as being backed up until dropped.
+struct RunningBackup {
+    vault_name: String,
+}
+
+impl RunningBackup {
+    fn start(vault_name: &str) -> Option<Self> {
+        let mut running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+        if running.iter().any(|name| name == vault_name) {
+            return None;
+        }
+        running.push(vault_name.to_string());
+
+        Some(Self {
+            vault_name: vault_name.to_string(),
+        })
+    }
+}
+
+impl Drop for RunningBackup {
+    fn drop(&mut self) {
+        RUNNING
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|name| name != &self.vault_name);
+    }
+}
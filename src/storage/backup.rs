@@ -0,0 +1,712 @@
+//! Timestamped vault backups with a retention policy.
+//!
+//! Each backup is a snapshot: a full copy of the vault directory, still encrypted, stored under
+//! `backups/<vault>/<timestamp>`. Creating a snapshot prunes older ones that fall outside the
+//! vault's retention policy, then verifies the new snapshot by decrypting every account in it.
+//! The result of the last verification is kept next to the snapshot as
+//! `<timestamp>.verification.json`.
+
+/*
+Copyright (C) 2025  Luke Wilkinson
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::crypto::LockedBuffer;
+use crate::storage::{
+    Store,
+    account_names::account_dir,
+    auto_backup::{AutoBackupState, clear_changes},
+    locations::{AUTO_BACKUP_STATE, Locations},
+    manifest::sign_vault_manifest,
+    store::write_private_file,
+    vault_files,
+};
+use crate::vault::{restore::read_snapshot_accounts, settings::VaultSettings};
+use anyhow::Error;
+use chrono::{Datelike, NaiveDateTime, SubsecRound, TimeDelta};
+use fs_extra::dir::{CopyOptions, copy};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{
+    create_dir_all, metadata, read_dir, read_to_string, remove_dir_all, remove_file, rename,
+};
+use std::path::{Path, PathBuf};
+
+/// Snapshot directory names, down to the millisecond so quick successive backups do not collide.
+const SNAPSHOT_TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3f";
+/// The format `BackupSnapshot::created_at` is displayed in, matching `Account::modified_at`.
+const DISPLAY_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+/// A file every vault has, used to recognise a backup made before snapshots existed.
+const LEGACY_BACKUP_MARKER: &str = "recipient";
+/// Where a snapshot is copied to while it is being restored, inside the vault's backup directory.
+const STAGED_RESTORE: &str = "restore-new";
+/// Where the vault being replaced is moved to while a snapshot is restored.
+const PREVIOUS_VAULT: &str = "restore-old";
+/// Appended to a snapshot's id to name the file holding its last verification.
+const VERIFICATION_SUFFIX: &str = ".verification.json";
+
+/// A full copy of a vault taken at one point in time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupSnapshot {
+    /// The snapshot's directory name, used to restore or delete it.
+    pub id: String,
+    pub created_at: NaiveDateTime,
+    pub path: PathBuf,
+}
+
+impl BackupSnapshot {
+    /// Returns when the snapshot was taken, formatted like `Account::modified_at`.
+    pub fn created_at_display(&self) -> String {
+        self.created_at.format(DISPLAY_TIMESTAMP_FORMAT).to_string()
+    }
+}
+
+/// Whether one encrypted file in a snapshot could be read back.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FileCheck {
+    /// The account name, or the file name for the gate and two-factor secret.
+    pub name: String,
+    /// Why the file could not be decrypted or parsed, or `None` if it is fine.
+    pub error: Option<String>,
+}
+
+impl FileCheck {
+    fn new(name: &str, result: Result<(), Error>) -> Self {
+        Self {
+            name: name.to_string(),
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// The result of decrypting every encrypted file in a snapshot.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BackupVerification {
+    /// When the snapshot was verified, in the same format as `Account::modified_at`.
+    pub verified_at: String,
+    pub gate: FileCheck,
+    /// The two-factor secret, or `None` if the vault does not use two-factor authentication.
+    pub totp: Option<FileCheck>,
+    /// One check per account, sorted by name.
+    pub accounts: Vec<FileCheck>,
+}
+
+impl BackupVerification {
+    /// Whether every file in the snapshot could be read back.
+    pub fn is_ok(&self) -> bool {
+        self.failures().is_empty()
+    }
+
+    /// Returns the checks that failed, vault files first.
+    pub fn failures(&self) -> Vec<&FileCheck> {
+        std::iter::once(&self.gate)
+            .chain(&self.totp)
+            .chain(&self.accounts)
+            .filter(|check| !check.is_ok())
+            .collect()
+    }
+
+    /// Returns a one-line summary, such as "All 12 account(s) verified".
+    pub fn summary(&self) -> String {
+        let failed_accounts = self.accounts.iter().filter(|check| !check.is_ok()).count();
+
+        match self.failures().len() {
+            0 => format!("All {} account(s) verified", self.accounts.len()),
+            failures if failures == failed_accounts => format!(
+                "{failed_accounts} of {} account(s) failed to decrypt",
+                self.accounts.len()
+            ),
+            failures => format!("{failures} file(s) failed to decrypt, including vault files"),
+        }
+    }
+}
+
+/// Which snapshots survive pruning. A snapshot is kept if any rule keeps it.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Always keep this many of the newest snapshots. At least one is always kept.
+    pub keep_last: usize,
+    /// Keep the newest snapshot of each of the last this many days.
+    pub keep_daily_days: u32,
+    /// Keep the newest snapshot of each of the last this many weeks.
+    pub keep_weekly_weeks: u32,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_last: 5,
+            keep_daily_days: 7,
+            keep_weekly_weeks: 4,
+        }
+    }
+}
+
+/// Works out which snapshots a retention policy would delete.
+///
+/// # Arguments
+/// * `snapshots` - The snapshots, newest first.
+/// * `policy` - The retention policy.
+/// * `now` - The current time.
+///
+/// # Returns
+/// * `Vec<BackupSnapshot>` - The snapshots to delete, newest first.
+pub fn snapshots_to_prune(
+    snapshots: &[BackupSnapshot],
+    policy: &RetentionPolicy,
+    now: NaiveDateTime,
+) -> Vec<BackupSnapshot> {
+    let daily_cutoff = now - TimeDelta::days(i64::from(policy.keep_daily_days));
+    let weekly_cutoff = now - TimeDelta::weeks(i64::from(policy.keep_weekly_weeks));
+
+    let mut days_kept = HashSet::new();
+    let mut weeks_kept = HashSet::new();
+    let mut pruned = Vec::new();
+
+    for (index, snapshot) in snapshots.iter().enumerate() {
+        let day = snapshot.created_at.date();
+        let week = day.iso_week();
+
+        // Snapshots are newest first, so the first one seen for a day or week is its newest.
+        let kept_as_daily = snapshot.created_at > daily_cutoff && days_kept.insert(day);
+        let kept_as_weekly =
+            snapshot.created_at > weekly_cutoff && weeks_kept.insert((week.year(), week.week()));
+
+        if index >= policy.keep_last.max(1) && !kept_as_daily && !kept_as_weekly {
+            pruned.push(snapshot.clone());
+        }
+    }
+
+    pruned
+}
+
+/// Reads a vault's backup directory, newest snapshot first. Entries not named like snapshots are
+/// ignored.
+///
+/// # Arguments
+/// * `backup_dir` - The vault's backup directory.
+///
+/// # Returns
+/// * `Result<Vec<BackupSnapshot>, Error>` - Returns the snapshots on success, or an error on failure.
+///
+/// # Errors
+/// * If the backup directory exists but cannot be read.
+pub fn read_snapshots(backup_dir: &Path) -> Result<Vec<BackupSnapshot>, Error> {
+    if !backup_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut snapshots = Vec::new();
+    for entry in read_dir(backup_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let Ok(id) = entry.file_name().into_string() else {
+            continue;
+        };
+        let Ok(created_at) = NaiveDateTime::parse_from_str(&id, SNAPSHOT_TIMESTAMP_FORMAT) else {
+            continue;
+        };
+
+        snapshots.push(BackupSnapshot {
+            id,
+            created_at,
+            path: entry.path(),
+        });
+    }
+
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.created_at));
+    Ok(snapshots)
+}
+
+/// Copies a vault directory into a new snapshot.
+///
+/// # Arguments
+/// * `vault_dir` - The vault's directory.
+/// * `backup_dir` - The vault's backup directory, created if needed.
+/// * `created_at` - When the snapshot is taken.
+///
+/// # Returns
+/// * `Result<BackupSnapshot, Error>` - Returns the new snapshot on success, or an error on failure.
+///
+/// # Errors
+/// * If a snapshot with the same timestamp exists, or the vault cannot be copied.
+pub fn create_snapshot(
+    vault_dir: &Path,
+    backup_dir: &Path,
+    created_at: NaiveDateTime,
+) -> Result<BackupSnapshot, Error> {
+    create_dir_all(backup_dir)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(backup_dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+
+    // Only milliseconds survive in the directory name
+    let created_at = created_at.trunc_subsecs(3);
+    let id = created_at.format(SNAPSHOT_TIMESTAMP_FORMAT).to_string();
+    let path = backup_dir.join(&id);
+    if path.exists() {
+        return Err(anyhow::anyhow!("Backup `{}` already exists", id));
+    }
+
+    if let Err(e) = copy(vault_dir, &path, &CopyOptions::new().content_only(true)) {
+        // Do not leave a partial copy that would look like a usable snapshot
+        let _ = remove_dir_all(&path);
+        return Err(e.into());
+    }
+
+    Ok(BackupSnapshot {
+        id,
+        created_at,
+        path,
+    })
+}
+
+/// Replaces a vault directory with the contents of a snapshot.
+///
+/// The snapshot is copied into the backup directory first, so a failed copy leaves the vault
+/// untouched.
+///
+/// # Arguments
+/// * `snapshot` - The snapshot to restore.
+/// * `vault_dir` - The vault's directory.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the snapshot cannot be copied, or the vault directory cannot be replaced.
+pub fn install_snapshot(snapshot: &BackupSnapshot, vault_dir: &Path) -> Result<(), Error> {
+    let backup_dir = snapshot
+        .path
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("Invalid backup `{}`", snapshot.path.display()))?;
+    let staged = backup_dir.join(STAGED_RESTORE);
+    let previous = backup_dir.join(PREVIOUS_VAULT);
+    for leftover in [&staged, &previous] {
+        if leftover.exists() {
+            remove_dir_all(leftover)?;
+        }
+    }
+
+    if let Err(e) = copy(
+        &snapshot.path,
+        &staged,
+        &CopyOptions::new().content_only(true),
+    ) {
+        let _ = remove_dir_all(&staged);
+        return Err(e.into());
+    }
+
+    if vault_dir.exists() {
+        rename(vault_dir, &previous)?;
+    }
+    if let Err(e) = rename(&staged, vault_dir) {
+        if previous.exists() {
+            rename(&previous, vault_dir)?;
+        }
+        return Err(e.into());
+    }
+    if previous.exists() {
+        remove_dir_all(&previous)?;
+    }
+
+    Ok(())
+}
+
+/// Checks that every account in a snapshot, and its gate and two-factor secret, can be decrypted,
+/// and that each account parses.
+///
+/// A file that fails is recorded in the result rather than stopping the check, so every problem
+/// is reported at once.
+///
+/// # Arguments
+/// * `snapshot_dir` - The snapshot's directory.
+/// * `verified_at` - When the check is run.
+/// * `decrypt` - Decrypts one file, such as `Store::decrypt_file`.
+///
+/// # Returns
+/// * `Result<BackupVerification, Error>` - Returns the per-file results, or an error on failure.
+///
+/// # Errors
+/// * If the snapshot's accounts cannot be listed.
+pub fn verify_snapshot<F>(
+    snapshot_dir: &Path,
+    verified_at: NaiveDateTime,
+    mut decrypt: F,
+) -> Result<BackupVerification, Error>
+where
+    F: FnMut(&Path) -> Result<LockedBuffer, Error>,
+{
+    let mut check_file = |path: &Path, parse: &dyn Fn(&LockedBuffer) -> Result<(), Error>| {
+        if !vault_files::exists(path) {
+            return Err(anyhow::anyhow!("`{}` is missing", path.display()));
+        }
+        parse(&decrypt(path)?)
+    };
+
+    let gate = FileCheck::new(
+        "gate.gpg",
+        check_file(&snapshot_dir.join("gate.gpg"), &|_| Ok(())),
+    );
+
+    let totp_path = snapshot_dir.join("totp.gpg");
+    let totp = totp_path.exists().then(|| {
+        FileCheck::new(
+            "totp.gpg",
+            check_file(&totp_path, &|secret| {
+                if secret.as_slice().is_empty() {
+                    return Err(anyhow::anyhow!("The two-factor secret is empty"));
+                }
+                Ok(())
+            }),
+        )
+    });
+
+    let accounts = read_snapshot_accounts(snapshot_dir)?
+        .iter()
+        .map(|account| {
+            FileCheck::new(
+                account,
+                account_dir(snapshot_dir, account).and_then(|dir| {
+                    check_file(&dir.join("data.gpg"), &|data| {
+                        Store::parse_account(data).map(|_| ())
+                    })
+                }),
+            )
+        })
+        .collect();
+
+    Ok(BackupVerification {
+        verified_at: verified_at.format(DISPLAY_TIMESTAMP_FORMAT).to_string(),
+        gate,
+        totp,
+        accounts,
+    })
+}
+
+/// Moves a backup made before snapshots existed into a snapshot of its own.
+///
+/// Such backups are a plain copy of the vault directly in the vault's backup directory. The
+/// snapshot is dated from when the backup's recipient file was last modified.
+///
+/// # Arguments
+/// * `backup_dir` - The vault's backup directory.
+///
+/// # Returns
+/// * `Result<Option<BackupSnapshot>, Error>` - Returns the migrated snapshot, or `None` if there was nothing to migrate.
+///
+/// # Errors
+/// * If the old backup cannot be moved.
+pub fn migrate_legacy_backup(backup_dir: &Path) -> Result<Option<BackupSnapshot>, Error> {
+    let marker = backup_dir.join(LEGACY_BACKUP_MARKER);
+    if !marker.is_file() {
+        return Ok(None);
+    }
+
+    let created_at = metadata(&marker)?
+        .modified()
+        .map(|modified| chrono::DateTime::<chrono::Utc>::from(modified).naive_utc())
+        .unwrap_or_else(|_| chrono::Utc::now().naive_utc())
+        .trunc_subsecs(3);
+    let id = created_at.format(SNAPSHOT_TIMESTAMP_FORMAT).to_string();
+    let path = backup_dir.join(&id);
+    create_dir_all(&path)?;
+
+    for entry in read_dir(backup_dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let is_snapshot = name.to_str().is_some_and(|name| {
+            NaiveDateTime::parse_from_str(
+                name.strip_suffix(VERIFICATION_SUFFIX).unwrap_or(name),
+                SNAPSHOT_TIMESTAMP_FORMAT,
+            )
+            .is_ok()
+        });
+        if !is_snapshot && name != AUTO_BACKUP_STATE {
+            rename(entry.path(), path.join(name))?;
+        }
+    }
+
+    log::info!(
+        "Moved the backup in `{}` into snapshot {id}",
+        backup_dir.display()
+    );
+
+    Ok(Some(BackupSnapshot {
+        id,
+        created_at,
+        path,
+    }))
+}
+
+/// Creates a new backup snapshot of a vault, prunes old snapshots using the vault's retention
+/// policy, and verifies the new snapshot.
+///
+/// A snapshot that fails verification is still kept, since it may hold accounts that do decrypt;
+/// the result can be read with `load_backup_verification`. The changes counted for automatic
+/// backups are cleared once the snapshot exists.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault to back up.
+///
+/// # Returns
+/// * `Result<BackupSnapshot, Error>` - Returns the new snapshot on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault does not exist, or the snapshot cannot be created.
+pub fn create_backup(vault_name: &str) -> Result<BackupSnapshot, Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+    migrate_legacy_backup(&locations.vault_backups)?;
+
+    let covered = AutoBackupState::load(&locations.backup_state)
+        .map(|state| state.changes)
+        .unwrap_or_default();
+    let snapshot = create_snapshot(
+        &locations.vault,
+        &locations.vault_backups,
+        chrono::Utc::now().naive_utc(),
+    )?;
+
+    if let Err(e) = clear_changes(&locations.backup_state, covered) {
+        log::warn!("Failed to reset the automatic backup count of '{vault_name}': {e}");
+    }
+
+    if let Err(e) = prune_backups(vault_name) {
+        log::warn!("Failed to prune old backups of '{vault_name}': {e}");
+    }
+
+    match verify_backup(vault_name, &snapshot.id) {
+        Ok(verification) if !verification.is_ok() => log::warn!(
+            "Backup {} of '{vault_name}' failed verification: {}",
+            snapshot.id,
+            verification.summary()
+        ),
+        Ok(_) => {}
+        Err(e) => log::warn!(
+            "Failed to verify backup {} of '{vault_name}': {e}",
+            snapshot.id
+        ),
+    }
+
+    Ok(snapshot)
+}
+
+/// Lists the backup snapshots of a vault, newest first.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+///
+/// # Returns
+/// * `Result<Vec<BackupSnapshot>, Error>` - Returns the snapshots on success, or an error on failure.
+///
+/// # Errors
+/// * If an old-style backup cannot be migrated, or the backup directory cannot be read.
+pub fn list_backups(vault_name: &str) -> Result<Vec<BackupSnapshot>, Error> {
+    let locations = Locations::new(vault_name, "");
+    migrate_legacy_backup(&locations.vault_backups)?;
+
+    read_snapshots(&locations.vault_backups)
+}
+
+/// Checks if any backup exists for the specified vault.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault to check.
+///
+/// # Returns
+/// * `bool` - Returns `true` if at least one backup exists, `false` otherwise.
+#[allow(dead_code)] // Used for testing only as of current
+pub fn backup_exists(vault_name: &str) -> bool {
+    list_backups(vault_name).is_ok_and(|snapshots| !snapshots.is_empty())
+}
+
+/// Replaces a vault with one of its backup snapshots, and signs the vault's manifest as restored.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault to restore.
+/// * `snapshot_id` - The `BackupSnapshot::id` of the snapshot to restore.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the snapshot does not exist, there are issues with file operations, or the restored vault's
+///   manifest cannot be signed.
+pub fn install_backup(vault_name: &str, snapshot_id: &str) -> Result<(), Error> {
+    let locations = Locations::new(vault_name, "");
+    let snapshot = find_backup(vault_name, snapshot_id)?;
+
+    install_snapshot(&snapshot, &locations.vault)?;
+    // The snapshot's manifest is older than one already seen, so the restore has to be trusted
+    // explicitly or the vault would be reported as rolled back
+    sign_vault_manifest(vault_name).map_err(|e| {
+        anyhow::anyhow!(
+            "Restored vault `{}` but could not sign its manifest: {}",
+            vault_name,
+            e
+        )
+    })?;
+
+    Ok(())
+}
+
+/// Deletes one backup snapshot of a vault.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault whose backup should be deleted.
+/// * `snapshot_id` - The `BackupSnapshot::id` of the snapshot to delete.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the snapshot does not exist or cannot be deleted, an error is returned.
+pub fn delete_backup(vault_name: &str, snapshot_id: &str) -> Result<(), Error> {
+    let snapshot = find_backup(vault_name, snapshot_id)?;
+
+    remove_snapshot(&snapshot)
+}
+
+/// Deletes the snapshots of a vault that fall outside its retention policy.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+///
+/// # Returns
+/// * `Result<usize, Error>` - Returns how many snapshots were deleted, or an error on failure.
+///
+/// # Errors
+/// * If the vault settings or backups cannot be read, or a snapshot cannot be deleted.
+pub fn prune_backups(vault_name: &str) -> Result<usize, Error> {
+    let locations = Locations::new(vault_name, "");
+    let policy = VaultSettings::load(&locations.settings)?.backup_retention;
+
+    let pruned = snapshots_to_prune(
+        &list_backups(vault_name)?,
+        &policy,
+        chrono::Utc::now().naive_utc(),
+    );
+    for snapshot in &pruned {
+        remove_snapshot(snapshot)?;
+    }
+
+    Ok(pruned.len())
+}
+
+/// Verifies a backup snapshot of a vault by decrypting every account in it, and keeps the result
+/// next to the snapshot.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+/// * `snapshot_id` - The `BackupSnapshot::id` of the snapshot.
+///
+/// # Returns
+/// * `Result<BackupVerification, Error>` - Returns the per-file results, or an error on failure.
+///
+/// # Errors
+/// * If the snapshot does not exist, its accounts cannot be listed, or the result cannot be saved.
+///   Files that fail to decrypt are reported in the result, not as an error.
+pub fn verify_backup(vault_name: &str, snapshot_id: &str) -> Result<BackupVerification, Error> {
+    let snapshot = find_backup(vault_name, snapshot_id)?;
+    let mut store = Store::new(vault_name, "")?;
+
+    let verification = verify_snapshot(&snapshot.path, chrono::Utc::now().naive_utc(), |path| {
+        store.decrypt_file(path)
+    })?;
+    write_private_file(
+        &verification_path(&snapshot),
+        serde_json::to_string_pretty(&verification)?.as_bytes(),
+    )?;
+
+    Ok(verification)
+}
+
+/// Reads the result of the last verification of a snapshot.
+///
+/// # Arguments
+/// * `snapshot` - The snapshot.
+///
+/// # Returns
+/// * `Option<BackupVerification>` - The last result, or `None` if the snapshot was never verified.
+pub fn load_backup_verification(snapshot: &BackupSnapshot) -> Option<BackupVerification> {
+    let path = verification_path(snapshot);
+    if !path.exists() {
+        return None;
+    }
+
+    match read_to_string(&path)
+        .map_err(Error::from)
+        .and_then(|contents| serde_json::from_str(&contents).map_err(Error::from))
+    {
+        Ok(verification) => Some(verification),
+        Err(e) => {
+            log::warn!("Failed to read `{}`: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Deletes a snapshot and its verification result.
+fn remove_snapshot(snapshot: &BackupSnapshot) -> Result<(), Error> {
+    remove_dir_all(&snapshot.path)?;
+
+    let verification = verification_path(snapshot);
+    if verification.exists() {
+        remove_file(verification)?;
+    }
+
+    Ok(())
+}
+
+fn verification_path(snapshot: &BackupSnapshot) -> PathBuf {
+    snapshot
+        .path
+        .with_file_name(format!("{}{VERIFICATION_SUFFIX}", snapshot.id))
+}
+
+/// Finds one backup snapshot of a vault.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+/// * `snapshot_id` - The `BackupSnapshot::id` of the snapshot.
+///
+/// # Returns
+/// * `Result<BackupSnapshot, Error>` - Returns the snapshot on success, or an error on failure.
+///
+/// # Errors
+/// * If the backups cannot be read, or the snapshot does not exist.
+pub fn find_backup(vault_name: &str, snapshot_id: &str) -> Result<BackupSnapshot, Error> {
+    list_backups(vault_name)?
+        .into_iter()
+        .find(|snapshot| snapshot.id == snapshot_id)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Backup `{}` of vault `{}` does not exist.",
+                snapshot_id,
+                vault_name
+            )
+        })
+}
@@ -0,0 +1,552 @@
+//! The single-file vault format.
+//!
+//! A vault normally keeps one directory per account, which shows anyone who can list it how many
+//! accounts there are and what they are called. A single-file vault keeps every account directory,
+//! the trash included, in one container file, `vault.fmpv`, next to the vault's own files
+//! (`recipient`, `settings.json`, `gate.gpg` and `totp.gpg`).
+//!
+//! The container starts with a header: the magic bytes `FMPVAULT`, a little-endian `u32` format
+//! version and a little-endian `u64` giving the length of the index. The index follows, encrypted
+//! to the vault's recipients; it is JSON listing each file's path and where its bytes are. The
+//! files come last, exactly as they would be on disk, so account records stay encrypted on their
+//! own and never need re-encrypting when the container is rewritten.
+//!
+//! Code working with account files goes through `vault_files`, which looks inside the container
+//! when there is one, so the rest of fmp does not need to know how a vault is stored.
+
+/*
+Copyright (C) 2025  Luke Wilkinson
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::crypto::LockedBuffer;
+use crate::storage::{
+    Locations,
+    archive::create_private_dir,
+    backup::create_backup,
+    store::{TEMP_SUFFIX, find_recipient_keys, read_recipient_file, write_private_file},
+};
+use crate::vault::recover_interrupted_rekey;
+use anyhow::Error;
+use gpgme::{Context, Protocol};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{metadata, read, read_dir, remove_dir_all, remove_file};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Name of the container file inside a single-file vault.
+pub const CONTAINER_FILE: &str = "vault.fmpv";
+/// The bytes every container starts with.
+pub const MAGIC: &[u8; 8] = b"FMPVAULT";
+/// The newest container format this version of fmp reads and writes.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Length of the magic bytes, format version and index length.
+const HEADER_SIZE: usize = 8 + 4 + 8;
+
+/// Containers already read in this process, so the index is only decrypted again when the file
+/// changes. Also serialises changes made from different threads.
+static CACHE: Mutex<BTreeMap<PathBuf, CachedContainer>> = Mutex::new(BTreeMap::new());
+
+struct CachedContainer {
+    len: u64,
+    modified: SystemTime,
+    container: VaultContainer,
+}
+
+/// Where one file's bytes are in a container.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct IndexEntry {
+    /// The file's path inside the vault, with `/` separators, such as `bank/data.gpg`.
+    path: String,
+    /// Where the file starts, counted from the end of the index.
+    offset: u64,
+    length: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+struct ContainerIndex {
+    entries: Vec<IndexEntry>,
+}
+
+/// The files of a single-file vault, by their path inside the vault.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VaultContainer {
+    pub files: BTreeMap<String, Vec<u8>>,
+}
+
+impl VaultContainer {
+    /// Encodes the container, encrypting its index with `encrypt_index`.
+    ///
+    /// # Arguments
+    /// * `encrypt_index` - Encrypts the index, such as to the vault's recipients.
+    ///
+    /// # Returns
+    /// * `Result<Vec<u8>, Error>` - Returns the container file's contents, or an error on failure.
+    ///
+    /// # Errors
+    /// * If a path is invalid or the index cannot be encrypted.
+    pub fn to_bytes<F>(&self, encrypt_index: F) -> Result<Vec<u8>, Error>
+    where
+        F: FnOnce(&[u8]) -> Result<Vec<u8>, Error>,
+    {
+        let mut index = ContainerIndex::default();
+        let mut offset = 0;
+        for (path, contents) in &self.files {
+            validate_container_path(path)?;
+            let length = contents.len() as u64;
+            index.entries.push(IndexEntry {
+                path: path.clone(),
+                offset,
+                length,
+            });
+            offset += length;
+        }
+
+        let index_json = LockedBuffer::from_string(serde_json::to_string(&index)?);
+        let encrypted_index = encrypt_index(index_json.as_slice())?;
+
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + encrypted_index.len() + offset as usize);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(encrypted_index.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&encrypted_index);
+        for contents in self.files.values() {
+            bytes.extend_from_slice(contents);
+        }
+
+        Ok(bytes)
+    }
+
+    /// Decodes a container, decrypting its index with `decrypt_index`.
+    ///
+    /// # Arguments
+    /// * `bytes` - The container file's contents.
+    /// * `decrypt_index` - Decrypts the index, such as `Store::decrypt_file` would.
+    ///
+    /// # Returns
+    /// * `Result<VaultContainer, Error>` - Returns the container on success, or an error on failure.
+    ///
+    /// # Errors
+    /// * If the data is not a container, was written by a newer version of fmp, is truncated, or
+    ///   its index cannot be decrypted or lists an invalid, duplicate or out-of-range file.
+    pub fn from_bytes<F>(bytes: &[u8], decrypt_index: F) -> Result<Self, Error>
+    where
+        F: FnOnce(&[u8]) -> Result<LockedBuffer, Error>,
+    {
+        if bytes.len() < HEADER_SIZE || &bytes[..8] != MAGIC {
+            return Err(anyhow::anyhow!("Not a single-file vault"));
+        }
+
+        let version = u32::from_le_bytes(bytes[8..12].try_into()?);
+        if version > FORMAT_VERSION {
+            return Err(anyhow::anyhow!(
+                "The vault uses format version {version}, which needs a newer version of fmp"
+            ));
+        }
+
+        let index_length = u64::from_le_bytes(bytes[12..HEADER_SIZE].try_into()?);
+        let records_start = usize::try_from(index_length)
+            .ok()
+            .and_then(|length| HEADER_SIZE.checked_add(length))
+            .filter(|end| *end <= bytes.len())
+            .ok_or_else(|| anyhow::anyhow!("The vault's index is truncated"))?;
+
+        let index_json = decrypt_index(&bytes[HEADER_SIZE..records_start])?;
+        let index: ContainerIndex = serde_json::from_slice(index_json.as_slice())
+            .map_err(|e| anyhow::anyhow!("Failed to parse the vault's index: {}", e))?;
+
+        let records = &bytes[records_start..];
+        let mut files = BTreeMap::new();
+        for entry in index.entries {
+            validate_container_path(&entry.path)?;
+
+            let contents = usize::try_from(entry.offset)
+                .ok()
+                .zip(usize::try_from(entry.length).ok())
+                .and_then(|(start, length)| Some(start..start.checked_add(length)?))
+                .and_then(|range| records.get(range))
+                .ok_or_else(|| anyhow::anyhow!("`{}` lies outside the vault's data", entry.path))?;
+
+            if files
+                .insert(entry.path.clone(), contents.to_vec())
+                .is_some()
+            {
+                return Err(anyhow::anyhow!(
+                    "`{}` is listed twice in the vault's index",
+                    entry.path
+                ));
+            }
+        }
+
+        Ok(Self { files })
+    }
+}
+
+/// Returns the container file of a vault directory, if the vault is stored as a single file.
+///
+/// # Arguments
+/// * `vault_dir` - The vault's directory, or a backup snapshot of it.
+///
+/// # Returns
+/// * `Option<PathBuf>` - The container file, or `None` if the vault keeps one directory per account.
+pub fn container_path(vault_dir: &Path) -> Option<PathBuf> {
+    let path = vault_dir.join(CONTAINER_FILE);
+    path.is_file().then_some(path)
+}
+
+/// Whether a vault is stored as a single file.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+///
+/// # Returns
+/// * `bool` - `true` if the vault's accounts are in a container file.
+pub fn is_single_file(vault_name: &str) -> bool {
+    container_path(&Locations::new(vault_name, "").vault).is_some()
+}
+
+/// Runs `f` on a container, decrypting its index unless it was already read and has not changed.
+///
+/// # Arguments
+/// * `container` - The container file.
+/// * `f` - Reads what it needs from the container.
+///
+/// # Returns
+/// * `Result<R, Error>` - Returns what `f` returns, or an error on failure.
+///
+/// # Errors
+/// * If the container cannot be read or decoded.
+pub fn read_container<R>(
+    container: &Path,
+    f: impl FnOnce(&VaultContainer) -> R,
+) -> Result<R, Error> {
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(f(cached_container(&mut cache, container)?))
+}
+
+/// Changes the files in a container and writes it back, with its index encrypted to the vault's
+/// current recipients. Nothing is written if `f` fails.
+///
+/// # Arguments
+/// * `container` - The container file.
+/// * `f` - Changes the files.
+///
+/// # Returns
+/// * `Result<R, Error>` - Returns what `f` returns, or an error on failure.
+///
+/// # Errors
+/// * If the container cannot be read, `f` fails, or the container cannot be encrypted or written.
+pub fn update_container<R>(
+    container: &Path,
+    f: impl FnOnce(&mut VaultContainer) -> Result<R, Error>,
+) -> Result<R, Error> {
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+
+    let mut updated = cached_container(&mut cache, container)?.clone();
+    let result = f(&mut updated)?;
+
+    let recipients = read_recipient_file(&recipient_path(container))?;
+    let bytes = encode_for_recipients(&updated, &recipients)?;
+    write_private_file(container, &bytes)?;
+
+    let file = metadata(container)?;
+    cache.insert(
+        container.to_path_buf(),
+        CachedContainer {
+            len: file.len(),
+            modified: file.modified()?,
+            container: updated,
+        },
+    );
+
+    Ok(result)
+}
+
+/// Encodes a container with its index encrypted to the given recipients, without writing it.
+///
+/// # Arguments
+/// * `container` - The files to store.
+/// * `recipients` - The recipient key IDs to encrypt the index to.
+///
+/// # Returns
+/// * `Result<Vec<u8>, Error>` - Returns the container file's contents, or an error on failure.
+///
+/// # Errors
+/// * If a recipient key cannot be found or encryption fails.
+pub fn encode_for_recipients(
+    container: &VaultContainer,
+    recipients: &[String],
+) -> Result<Vec<u8>, Error> {
+    container.to_bytes(|index| {
+        let mut ctx = Context::from_protocol(Protocol::OpenPgp)?;
+        let keys = find_recipient_keys(recipients, &mut ctx)?;
+
+        let mut encrypted = Vec::new();
+        ctx.encrypt(&keys, index, &mut encrypted)
+            .map_err(|e| anyhow::anyhow!("Failed to encrypt the vault's index. Error: {}", e))?;
+        Ok(encrypted)
+    })
+}
+
+/// Moves a vault's account directories, trash included, into a container file.
+///
+/// The vault is backed up first. The container is read back and compared with the directories
+/// before they are removed, so a failure at any point leaves every account readable.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+///
+/// # Returns
+/// * `Result<usize, Error>` - Returns how many files were moved into the container, or an error on failure.
+///
+/// # Errors
+/// * If the vault does not exist or is already a single file, the backup fails, or the container
+///   cannot be written or does not read back the same.
+pub fn convert_to_single_file(vault_name: &str) -> Result<usize, Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+    if is_single_file(vault_name) {
+        return Err(anyhow::anyhow!(
+            "Vault `{}` is already stored as a single file.",
+            vault_name
+        ));
+    }
+    recover_interrupted_rekey(vault_name)?;
+    create_backup(vault_name)?;
+
+    let mut container = VaultContainer::default();
+    let mut account_dirs = Vec::new();
+    for entry in read_dir(&locations.vault)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            let name = entry
+                .file_name()
+                .into_string()
+                .map_err(|_| anyhow::anyhow!("Failed to convert file name to string."))?;
+            collect_files(&entry.path(), &name, &mut container.files)?;
+            account_dirs.push(entry.path());
+        }
+    }
+
+    let container_file = locations.vault.join(CONTAINER_FILE);
+    let recipients = read_recipient_file(&locations.recipient)?;
+    let written = encode_for_recipients(&container, &recipients).and_then(|bytes| {
+        write_private_file(&container_file, &bytes)?;
+        forget_container(&container_file);
+        read_container(&container_file, |read_back| read_back == &container)
+    });
+    if !matches!(written, Ok(true)) {
+        let _ = remove_file(&container_file);
+        forget_container(&container_file);
+        return Err(written.err().unwrap_or_else(|| {
+            anyhow::anyhow!("The single-file vault did not read back the same")
+        }));
+    }
+
+    for account_dir in account_dirs {
+        remove_dir_all(account_dir)?;
+    }
+
+    Ok(container.files.len())
+}
+
+/// Moves the files in a vault's container back into one directory per account.
+///
+/// The vault is backed up first. Every file is written and read back before the container is
+/// removed, so a failure at any point leaves every account readable.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+///
+/// # Returns
+/// * `Result<usize, Error>` - Returns how many files were moved out of the container, or an error on failure.
+///
+/// # Errors
+/// * If the vault does not exist or is not a single file, a directory it would create already
+///   exists, the backup fails, or the files cannot be written.
+pub fn convert_to_directories(vault_name: &str) -> Result<usize, Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+    let Some(container_file) = container_path(&locations.vault) else {
+        return Err(anyhow::anyhow!(
+            "Vault `{}` is not stored as a single file.",
+            vault_name
+        ));
+    };
+    recover_interrupted_rekey(vault_name)?;
+    create_backup(vault_name)?;
+
+    let files = read_container(&container_file, |container| container.files.clone())?;
+    for path in files.keys() {
+        let account_dir = locations
+            .vault
+            .join(path.split('/').next().unwrap_or_default());
+        if account_dir.exists() {
+            return Err(anyhow::anyhow!(
+                "`{}` already exists. Move it out of the vault before converting.",
+                account_dir.display()
+            ));
+        }
+    }
+
+    let written: Result<(), Error> = files.iter().try_for_each(|(path, contents)| {
+        let target = locations.vault.join(path);
+        if let Some(parent) = target.parent() {
+            create_private_dir(parent)?;
+        }
+        write_private_file(&target, contents)?;
+
+        if &read(&target)? != contents {
+            return Err(anyhow::anyhow!(
+                "`{}` did not read back the same",
+                target.display()
+            ));
+        }
+        Ok(())
+    });
+    if let Err(e) = written {
+        for account_dir in files
+            .keys()
+            .filter_map(|path| path.split('/').next())
+            .map(|name| locations.vault.join(name))
+        {
+            let _ = remove_dir_all(account_dir);
+        }
+        return Err(e);
+    }
+
+    remove_file(&container_file)?;
+    forget_container(&container_file);
+
+    Ok(files.len())
+}
+
+/// Returns the bytes of a container with some of its files replaced and its index encrypted to new
+/// recipients, without writing anything. Used when re-keying a vault.
+///
+/// # Arguments
+/// * `container` - The container file.
+/// * `replacements` - Each file's path inside the vault with its new contents.
+/// * `recipients` - The recipient key IDs to encrypt the index to.
+///
+/// # Returns
+/// * `Result<Vec<u8>, Error>` - Returns the new container file's contents, or an error on failure.
+///
+/// # Errors
+/// * If the container cannot be read, or the index cannot be encrypted.
+pub fn repack_container(
+    container: &Path,
+    replacements: Vec<(String, Vec<u8>)>,
+    recipients: &[String],
+) -> Result<Vec<u8>, Error> {
+    let mut repacked = read_container(container, VaultContainer::clone)?;
+    repacked.files.extend(replacements);
+
+    encode_for_recipients(&repacked, recipients)
+}
+
+/// Returns the container for a file, from the cache if it has not changed since.
+fn cached_container<'a>(
+    cache: &'a mut BTreeMap<PathBuf, CachedContainer>,
+    container: &Path,
+) -> Result<&'a VaultContainer, Error> {
+    let file = metadata(container)?;
+    let (len, modified) = (file.len(), file.modified()?);
+
+    let current = cache
+        .get(container)
+        .is_some_and(|cached| cached.len == len && cached.modified == modified);
+    if !current {
+        let decoded = VaultContainer::from_bytes(&read(container)?, |index| {
+            let mut ctx = Context::from_protocol(Protocol::OpenPgp)?;
+            let mut decrypted = Vec::new();
+            ctx.decrypt(index, &mut decrypted).map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to decrypt the index of `{}`. Error: {}",
+                    container.display(),
+                    e
+                )
+            })?;
+            Ok(LockedBuffer::new(decrypted))
+        })?;
+
+        cache.insert(
+            container.to_path_buf(),
+            CachedContainer {
+                len,
+                modified,
+                container: decoded,
+            },
+        );
+    }
+
+    Ok(&cache[container].container)
+}
+
+/// Drops a container from the cache, so it is read from disk next time.
+fn forget_container(container: &Path) {
+    CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(container);
+}
+
+/// The recipient file of the vault or snapshot a container belongs to.
+fn recipient_path(container: &Path) -> PathBuf {
+    container.with_file_name("recipient")
+}
+
+/// Adds every file under `dir` to `files`, by its path inside the vault.
+fn collect_files(
+    dir: &Path,
+    prefix: &str,
+    files: &mut BTreeMap<String, Vec<u8>>,
+) -> Result<(), Error> {
+    for entry in read_dir(dir)? {
+        let entry = entry?;
+        let name = entry
+            .file_name()
+            .into_string()
+            .map_err(|_| anyhow::anyhow!("Failed to convert file name to string."))?;
+        let path = format!("{prefix}/{name}");
+
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_files(&entry.path(), &path, files)?;
+        } else if file_type.is_file() && !name.ends_with(&format!(".{TEMP_SUFFIX}")) {
+            files.insert(path, read(entry.path())?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Rejects paths that are not inside an account directory, or could escape the vault.
+fn validate_container_path(path: &str) -> Result<(), Error> {
+    let valid = path.contains('/')
+        && !path.contains('\\')
+        && path
+            .split('/')
+            .all(|component| !component.is_empty() && component != "." && component != "..");
+
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("Invalid path `{}` in the vault", path))
+    }
+}
@@ -17,15 +17,34 @@ Copyright (C) 2025  Luke Wilkinson
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use crate::{storage::locations::Locations, totp::update_totp_ledgers_on_rename};
+use crate::{
+    storage::{
+        account_names::{account_names_for_dirs, forget_removed_accounts, record_account_name},
+        auto_backup::record_vault_change,
+        locations::{Locations, TRASH_DIRECTORY},
+        manifest::{forget_manifest_version, update_manifest_versions_on_rename},
+        store::write_private_file,
+        vault_files,
+    },
+    totp::update_totp_ledgers_on_rename,
+    vault::metadata::rename_account_metadata,
+};
 use anyhow::Error;
-use fs_extra::dir::{CopyOptions, copy};
 use std::{
     collections::HashMap,
-    fs::{create_dir_all, read_dir, read_to_string, remove_dir_all, rename, write},
-    path::PathBuf,
+    fs::{create_dir_all, read_dir, read_to_string, remove_dir_all, remove_file, rename, write},
+    path::{Path, PathBuf},
 };
 
+/// Suffix of the replacement copy written next to a file during a transaction.
+const STAGED_SUFFIX: &str = "txn-new";
+/// Suffix the original file is moved to while a transaction is being committed.
+const PREVIOUS_SUFFIX: &str = "txn-old";
+/// Journal state written before any original file is touched.
+const JOURNAL_SWAPPING: &str = "swapping";
+/// Journal state written once every replacement is in place.
+const JOURNAL_COMMITTED: &str = "committed";
+
 /// Reads all directories in the specified directory and returns their names as a vector of strings.
 ///
 /// # Arguments
@@ -53,69 +72,6 @@ pub fn read_directory(directory: &std::path::Path) -> Result<Vec<String>, Error>
     Ok(directories)
 }
 
-/// Creates a backup of the specified vault.
-///
-/// # Arguments
-/// * `vault_name` - The name of the vault to back up.
-///
-/// # Returns
-/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
-///
-/// # Errors
-/// * If the backup location already exists and cannot be removed, or if there are issues with file operations.
-pub fn create_backup(vault_name: &str) -> Result<(), Error> {
-    let locations = Locations::new(vault_name, "");
-
-    let backup_path = locations.backup.join(vault_name);
-    if backup_path.exists() {
-        remove_dir_all(&backup_path)?;
-    }
-
-    if validate_path_new(&backup_path) {
-        create_dir_all(&locations.backup)?;
-
-        let options = CopyOptions::new();
-        copy(&locations.vault, &locations.backup, &options)?;
-
-        Ok(())
-    } else {
-        Err(anyhow::anyhow!("Invalid backup directory path"))
-    }
-}
-
-/// Installs a backup of the specified vault.
-///
-/// # Arguments
-/// * `vault_name` - The name of the vault to restore.
-///
-/// # Returns
-/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
-///
-/// # Errors
-/// * If the backup does not exist, or if there are issues with file operations.
-pub fn install_backup(vault_name: &str) -> Result<(), Error> {
-    let locations = Locations::new(vault_name, "");
-
-    if !locations.backup.exists() {
-        return Err(anyhow::anyhow!(
-            "Backup does not exist at {:?}",
-            locations.backup
-        ));
-    }
-
-    remove_dir_all(&locations.vault)?;
-
-    let options = CopyOptions::new();
-
-    copy(
-        locations.backup.join(vault_name),
-        locations.fmp.join("vaults"),
-        &options,
-    )?;
-
-    Ok(())
-}
-
 /// Deletes a vault and all its contents.
 ///
 /// # Arguments
@@ -135,13 +91,14 @@ pub fn delete_vault(vault_name: &str) -> Result<(), Error> {
 
     remove_dir_all(&locations.vault)?;
 
-    let backup_path = locations.backup.join(vault_name);
-    if backup_path.exists() {
-        remove_dir_all(&backup_path)?;
+    if locations.vault_backups.exists() {
+        remove_dir_all(&locations.vault_backups)?;
     }
 
     remove_vault_from_stats(vault_name)?;
 
+    forget_manifest_version(vault_name)?;
+
     Ok(())
 }
 
@@ -172,8 +129,8 @@ pub fn rename_vault(old_name: &str, new_name: &str) -> Result<(), Error> {
         rename(&old_locations.vault, &new_locations.vault)?;
 
         // Also rename backup if it exists
-        let old_backup = old_locations.backup.join(old_name);
-        let new_backup = new_locations.backup.join(new_name);
+        let old_backup = old_locations.vault_backups;
+        let new_backup = new_locations.vault_backups;
 
         if old_backup.exists() {
             if let Some(backup_parent) = new_backup.parent() {
@@ -186,6 +143,8 @@ pub fn rename_vault(old_name: &str, new_name: &str) -> Result<(), Error> {
 
         update_totp_ledgers_on_rename(old_name, new_name)?;
 
+        update_manifest_versions_on_rename(old_name, new_name)?;
+
         Ok(())
     } else {
         return Err(anyhow::anyhow!("Invalid new vault directory path"));
@@ -210,7 +169,7 @@ pub fn rename_account(vault_name: &str, old_name: &str, new_name: &str) -> Resul
 
     old_locations.does_vault_exist()?;
 
-    if !old_locations.account.exists() {
+    if !vault_files::exists(&old_locations.account) {
         return Err(anyhow::anyhow!(
             "Account `{}` does not exist in vault `{}`.",
             old_name,
@@ -218,7 +177,7 @@ pub fn rename_account(vault_name: &str, old_name: &str, new_name: &str) -> Resul
         ));
     }
 
-    if new_locations.account.exists() {
+    if vault_files::exists(&new_locations.account) {
         return Err(anyhow::anyhow!(
             "Account `{}` already exists in vault `{}`.",
             new_name,
@@ -226,45 +185,18 @@ pub fn rename_account(vault_name: &str, old_name: &str, new_name: &str) -> Resul
         ));
     }
 
-    rename(&old_locations.account, &new_locations.account)?;
-
-    Ok(())
-}
-
-/// Checks if a backup exists for the specified vault.
-///
-/// # Arguments
-/// * `vault_name` - The name of the vault to check.
-///
-/// # Returns
-/// * `bool` - Returns `true` if a backup exists, `false` otherwise.
-pub fn backup_exists(vault_name: &str) -> bool {
-    let locations = Locations::new(vault_name, "");
-    locations.backup.join(vault_name).exists()
-}
-
-/// Deletes the backup for the specified vault.
-///
-/// # Arguments
-/// * `vault_name` - The name of the vault whose backup should be deleted.
-///
-/// # Returns
-/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
-///
-/// # Errors
-/// * If the backup does not exist or cannot be deleted, an error is returned.
-pub fn delete_backup(vault_name: &str) -> Result<(), Error> {
-    let locations = Locations::new(vault_name, "");
-    let backup_path = locations.backup.join(vault_name);
-
-    if !backup_path.exists() {
+    if new_name == TRASH_DIRECTORY {
         return Err(anyhow::anyhow!(
-            "Backup for vault `{}` does not exist.",
-            vault_name
+            "`{}` is reserved for the vault's trash",
+            TRASH_DIRECTORY
         ));
     }
 
-    remove_dir_all(&backup_path)?;
+    record_account_name(&old_locations.vault, new_name)?;
+    vault_files::rename(&old_locations.account, &new_locations.account)?;
+    forget_removed_accounts(&old_locations.vault)?;
+    rename_account_metadata(vault_name, old_name, new_name);
+    record_vault_change(vault_name);
 
     Ok(())
 }
@@ -461,15 +393,33 @@ pub fn get_most_used_vault() -> String {
 }
 
 /// Reads available vaults from the vaults directory
+pub fn get_available_vaults() -> Vec<String> {
+    let vaults_dir = Locations::new("", "").fmp.join("vaults");
+
+    read_directory(&vaults_dir).unwrap_or_else(|e| {
+        log::error!(
+            "Failed to read vaults directory: {} - Error: {}",
+            vaults_dir.display(),
+            e
+        );
+        Vec::new()
+    })
+}
+
+/// Reads available accounts from the vault directory
 pub fn get_available_accounts(vault_name: &str) -> Vec<String> {
     let account_dir = get_account_directory(vault_name);
-    read_directory(&account_dir).unwrap_or_else(|_| {
+    let mut accounts = vault_files::list_dirs(&account_dir).unwrap_or_else(|_| {
         log::error!(
             "Failed to read account directory: {}",
             account_dir.display()
         );
         Vec::new()
-    })
+    });
+
+    // The trash lives alongside the accounts but is not one
+    accounts.retain(|name| name != TRASH_DIRECTORY);
+    account_names_for_dirs(&account_dir, accounts)
 }
 
 /// Gets the account directory path
@@ -485,3 +435,160 @@ pub fn validate_path_new(path: &PathBuf) -> bool {
         _ => true,
     }
 }
+
+/// Returns `path` with `.suffix` appended to its file name.
+fn sibling_with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+/// Writes the journal for a file transaction: a state line followed by one path per line.
+fn write_journal(journal: &Path, state: &str, paths: &[&Path]) -> Result<(), Error> {
+    let mut contents = format!("{state}\n");
+    for path in paths {
+        let path = path
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("Path `{}` is not valid UTF-8.", path.display()))?;
+        contents.push_str(path);
+        contents.push('\n');
+    }
+
+    // Written to the side and renamed so the journal itself is never half-written.
+    let staged = sibling_with_suffix(journal, STAGED_SUFFIX);
+    write_private_file(&staged, contents.as_bytes())?;
+    rename(&staged, journal)?;
+
+    Ok(())
+}
+
+/// Replaces the contents of several files so that either all of them change or none do.
+///
+/// Every replacement is first written next to its target. Only when all of them are on disk are the
+/// originals swapped out, one rename per file, and the originals are restored if any swap fails. A
+/// journal records progress so that `recover_file_transaction` can finish the job after a crash.
+///
+/// # Arguments
+/// * `replacements` - Each target path with its new contents, swapped in the order given.
+/// * `journal` - Where to keep the journal while the transaction runs.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` once every file holds its new contents, or an error if nothing changed.
+///
+/// # Errors
+/// * If a journal already exists, a replacement cannot be written, or a swap fails (after rolling back).
+pub fn replace_files_transactionally(
+    replacements: &[(PathBuf, Vec<u8>)],
+    journal: &Path,
+) -> Result<(), Error> {
+    if journal.exists() {
+        return Err(anyhow::anyhow!(
+            "An interrupted transaction is pending at `{}`.",
+            journal.display()
+        ));
+    }
+
+    let paths: Vec<&Path> = replacements
+        .iter()
+        .map(|(path, _)| path.as_path())
+        .collect();
+
+    for (path, contents) in replacements {
+        if let Err(e) = write_private_file(&sibling_with_suffix(path, STAGED_SUFFIX), contents) {
+            remove_staged_files(&paths);
+            return Err(e);
+        }
+    }
+
+    if let Err(e) = write_journal(journal, JOURNAL_SWAPPING, &paths) {
+        remove_staged_files(&paths);
+        return Err(e);
+    }
+
+    for (swapped, path) in paths.iter().enumerate() {
+        if let Err(e) = swap_in_staged_file(path) {
+            roll_back_swaps(&paths[..=swapped]);
+            remove_staged_files(&paths);
+            if let Err(journal_error) = remove_file(journal) {
+                log::error!("Failed to remove transaction journal: {journal_error}");
+            }
+            return Err(e);
+        }
+    }
+
+    write_journal(journal, JOURNAL_COMMITTED, &paths)?;
+    remove_previous_files(&paths);
+    remove_file(journal)?;
+
+    Ok(())
+}
+
+/// Moves the original file aside and the staged replacement into its place.
+fn swap_in_staged_file(path: &Path) -> Result<(), Error> {
+    if path.exists() {
+        rename(path, sibling_with_suffix(path, PREVIOUS_SUFFIX))?;
+    }
+    rename(sibling_with_suffix(path, STAGED_SUFFIX), path)?;
+    Ok(())
+}
+
+/// Puts back every original that was moved aside, leaving untouched files alone.
+fn roll_back_swaps(paths: &[&Path]) {
+    for path in paths {
+        let previous = sibling_with_suffix(path, PREVIOUS_SUFFIX);
+        if previous.exists()
+            && let Err(e) = rename(&previous, path)
+        {
+            log::error!("Failed to restore `{}`: {e}", path.display());
+        }
+    }
+}
+
+fn remove_staged_files(paths: &[&Path]) {
+    for path in paths {
+        let _ = remove_file(sibling_with_suffix(path, STAGED_SUFFIX));
+    }
+}
+
+fn remove_previous_files(paths: &[&Path]) {
+    for path in paths {
+        let _ = remove_file(sibling_with_suffix(path, PREVIOUS_SUFFIX));
+    }
+}
+
+/// Finishes a transaction interrupted by a crash, if its journal is still present.
+///
+/// A transaction that had not finished swapping is rolled back to the original files; one that had
+/// committed is cleaned up.
+///
+/// # Arguments
+/// * `journal` - The journal path given to `replace_files_transactionally`.
+///
+/// # Returns
+/// * `Result<bool, Error>` - Returns `true` if an interrupted transaction was recovered, or an error on failure.
+///
+/// # Errors
+/// * If the journal cannot be read or removed.
+pub fn recover_file_transaction(journal: &Path) -> Result<bool, Error> {
+    let _ = remove_file(sibling_with_suffix(journal, STAGED_SUFFIX));
+
+    if !journal.exists() {
+        return Ok(false);
+    }
+
+    let contents = read_to_string(journal)?;
+    let mut lines = contents.lines();
+    let state = lines.next().unwrap_or_default();
+    let paths: Vec<&Path> = lines.map(Path::new).collect();
+
+    if state == JOURNAL_COMMITTED {
+        remove_previous_files(&paths);
+    } else {
+        roll_back_swaps(&paths);
+    }
+    remove_staged_files(&paths);
+    remove_file(journal)?;
+
+    Ok(true)
+}
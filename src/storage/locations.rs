@@ -17,26 +17,50 @@ Copyright (C) 2025  Luke Wilkinson
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use crate::storage::filesystem::validate_path_new;
+use crate::storage::{
+    account_names::{NAMES_FILE, account_dir},
+    filesystem::validate_path_new,
+    profile::current_home,
+    store::write_private_file,
+    vault_files,
+};
+use crate::vault::{metadata::METADATA_FILE, settings::SETTINGS_FILE};
 use anyhow::Error;
-use dirs::data_dir;
-use std::fs::{File, create_dir_all};
+use std::fs::create_dir_all;
 use std::path::PathBuf;
 
+/// Name of the directory inside a vault that deleted accounts are moved to.
+pub const TRASH_DIRECTORY: &str = ".trash";
+
+/// Name of the file inside a vault's backup directory counting changes for automatic backups.
+pub const AUTO_BACKUP_STATE: &str = "auto-backup.json";
+
 /// Represents the locations of various files and directories within a vault.
 pub struct Locations {
     pub fmp: PathBuf,
     pub vault: PathBuf,
+    #[allow(dead_code)] // Used for testing only as of current
     pub backup: PathBuf,
+    pub vault_backups: PathBuf,
+    pub backup_state: PathBuf,
     pub account: PathBuf,
     pub recipient: PathBuf,
     pub data: PathBuf,
+    pub revisions: PathBuf,
+    pub trash: PathBuf,
+    pub settings: PathBuf,
     pub totp: PathBuf,
     pub gate: PathBuf,
+    pub names: PathBuf,
+    pub metadata: PathBuf,
+    pub rekey_journal: PathBuf,
+    pub pwned_passwords: PathBuf,
 }
 
 impl Locations {
-    /// Creates a new `Locations` instance with paths based on the provided vault and account names.
+    /// Creates a new `Locations` instance with paths based on the provided vault and account names,
+    /// inside the data home chosen at startup. In a vault that hides account names, the account
+    /// directory is named by the account's ID.
     ///
     /// # Arguments
     /// * `vault_name` - The name of the vault.
@@ -45,26 +69,47 @@ impl Locations {
     /// # Returns
     /// * `Locations` - Returns a `Locations` instance.
     pub fn new(vault_name: &str, account_name: &str) -> Locations {
-        let base = data_dir().unwrap_or_else(|| PathBuf::from("."));
-        let fmp = base.join("fmp");
+        let fmp = current_home().data;
 
         let vault = fmp.join("vaults").join(vault_name);
         let backup = fmp.join("backups");
-        let account = vault.join(account_name);
+        let vault_backups = backup.join(vault_name);
+        let backup_state = vault_backups.join(AUTO_BACKUP_STATE);
+        let account = account_dir(&vault, account_name).unwrap_or_else(|e| {
+            log::error!("Failed to find account `{account_name}`: {e}");
+            // A path that cannot exist, so an unreadable index never points at another account
+            vault.join(NAMES_FILE).join(account_name)
+        });
         let recipient = vault.join("recipient");
         let data = account.join("data.gpg");
+        let revisions = account.join("revisions");
+        let trash = vault.join(TRASH_DIRECTORY);
+        let settings = vault.join(SETTINGS_FILE);
         let totp = vault.join("totp.gpg");
         let gate = vault.join("gate.gpg");
+        let names = vault.join(NAMES_FILE);
+        let metadata = vault.join(METADATA_FILE);
+        let rekey_journal = vault.join("rekey.journal");
+        let pwned_passwords = fmp.join("pwned-passwords-sha1-ordered-by-hash.txt");
 
         Self {
             fmp,
             vault,
             backup,
+            vault_backups,
+            backup_state,
             account,
             recipient,
             data,
+            revisions,
+            trash,
+            settings,
             totp,
             gate,
+            names,
+            metadata,
+            rekey_journal,
+            pwned_passwords,
         }
     }
 
@@ -85,15 +130,7 @@ impl Locations {
                 std::fs::set_permissions(&self.vault, std::fs::Permissions::from_mode(0o700))?;
             }
 
-            let recipient_file = File::create(&self.recipient)?;
-
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                recipient_file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
-            }
-
-            Ok(())
+            write_private_file(&self.recipient, &[])
         } else {
             Err(anyhow::anyhow!("Invalid vault directory path"))
         }
@@ -105,18 +142,25 @@ impl Locations {
     /// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
     ///
     /// # Errors
-    /// * If the account directory cannot be created, an error is returned.
+    /// * If the account name is reserved, the vault's account names cannot be read, or the account
+    ///   directory cannot be created, an error is returned.
     pub fn create_account_directory(&self) -> Result<(), Error> {
-        if validate_path_new(&self.account) {
-            create_dir_all(&self.account)?;
+        if self.account.ends_with(TRASH_DIRECTORY) {
+            return Err(anyhow::anyhow!(
+                "`{}` is reserved for the vault's trash",
+                TRASH_DIRECTORY
+            ));
+        }
 
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                std::fs::set_permissions(&self.account, std::fs::Permissions::from_mode(0o700))?;
-            }
+        if self.account.starts_with(&self.names) {
+            return Err(anyhow::anyhow!(
+                "The account names in vault `{:?}` could not be read",
+                self.vault
+            ));
+        }
 
-            Ok(())
+        if validate_path_new(&self.account) && !vault_files::exists(&self.account) {
+            vault_files::create_private_dir(&self.account)
         } else {
             Err(anyhow::anyhow!("Invalid account directory path"))
         }
@@ -145,7 +189,7 @@ impl Locations {
     /// # Errors
     /// * If the account directory does not exist, an error is returned.
     pub fn does_account_exist(&self) -> Result<(), Error> {
-        if !self.account.exists() {
+        if !vault_files::exists(&self.account) {
             return Err(anyhow::anyhow!(
                 "Account `{:?}` does not exist. Check for typos or create it.",
                 self.account
@@ -0,0 +1,739 @@
+//! Signed manifest of a vault's files, for noticing tampering and rollback.
+//!
+//! `manifest.gpg` lists a SHA-256 hash of the vault's `recipient`, `gate.gpg`, `totp.gpg`,
+//! `names.gpg`, `metadata.gpg` and `settings.json`, and of every file in its account directories and
+//! trash, with a version that goes up each time it is signed. It is signed, not encrypted, with the key of a vault member and signed
+//! again after every change fmp makes. The highest version seen for each vault is kept in the user
+//! config directory, outside the vault, so an older copy of the whole vault put back with its old
+//! manifest is noticed as well as a changed, removed or added file.
+//!
+//! Signing again after a change would also vouch for anything changed outside fmp, so a vault whose
+//! check found problems is marked as pending next to the versions seen. Its manifest is not signed
+//! again until the problems are gone or the user trusts the vault as it is now.
+
+/*
+Copyright (C) 2025  Luke Wilkinson
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::storage::{
+    Locations,
+    account_names::NAMES_FILE,
+    archive::sha256_hex,
+    profile::current_home,
+    store::{TEMP_SUFFIX, read_recipient_file, write_private_file},
+    vault_files,
+};
+use crate::vault::{metadata::METADATA_FILE, settings::SETTINGS_FILE};
+use anyhow::Error;
+use gpgme::{Context, Protocol};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Name of the signed manifest inside a vault.
+pub const MANIFEST_FILE: &str = "manifest.gpg";
+
+/// Name of the file in the user config directory with the highest manifest version seen per vault.
+pub const SEEN_VERSIONS_FILE: &str = "manifest_versions.json";
+
+/// Files directly inside a vault that the manifest covers when they exist.
+const VAULT_FILES: [&str; 6] = [
+    "recipient",
+    "gate.gpg",
+    "totp.gpg",
+    NAMES_FILE,
+    METADATA_FILE,
+    SETTINGS_FILE,
+];
+
+/// Keeps two changes from signing a manifest, or recording a version, at the same time.
+static MANIFEST_LOCK: Mutex<()> = Mutex::new(());
+
+/// The signed contents of `manifest.gpg`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct VaultManifest {
+    /// Goes up by at least one each time the manifest is signed.
+    pub version: u64,
+    pub signed_at: String,
+    /// SHA-256 of each covered file, by its path inside the vault.
+    pub files: BTreeMap<String, String>,
+}
+
+impl VaultManifest {
+    /// Hashes the files a manifest covers in a vault, however the vault is stored.
+    ///
+    /// # Arguments
+    /// * `vault_dir` - The vault's directory.
+    /// * `version` - The version to give the manifest.
+    ///
+    /// # Returns
+    /// * `Result<VaultManifest, Error>` - Returns the manifest on success, or an error on failure.
+    ///
+    /// # Errors
+    /// * If the vault's directories or files cannot be read.
+    pub fn for_vault(vault_dir: &Path, version: u64) -> Result<Self, Error> {
+        let mut files = BTreeMap::new();
+
+        for name in VAULT_FILES {
+            let path = vault_dir.join(name);
+            if path.is_file() {
+                files.insert(name.to_string(), sha256_hex(&std::fs::read(&path)?));
+            }
+        }
+
+        for dir in vault_files::list_dirs(vault_dir)? {
+            for (path, contents) in vault_files::files_under(&vault_dir.join(&dir))? {
+                // Writes still in progress, or left by a crash and removed at startup
+                if !path.ends_with(TEMP_SUFFIX) {
+                    files.insert(format!("{dir}/{path}"), sha256_hex(&contents));
+                }
+            }
+        }
+
+        Ok(Self {
+            version,
+            signed_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            files,
+        })
+    }
+
+    /// Compares the files this manifest lists with the files a vault has now.
+    ///
+    /// # Arguments
+    /// * `current` - A manifest of the vault as it is now.
+    ///
+    /// # Returns
+    /// * `Vec<ManifestProblem>` - Every file that changed, was removed or was added, by path.
+    pub fn differences(&self, current: &VaultManifest) -> Vec<ManifestProblem> {
+        let mut problems = Vec::new();
+
+        for (path, hash) in &self.files {
+            match current.files.get(path) {
+                Some(current_hash) if current_hash == hash => {}
+                Some(_) => problems.push(ManifestProblem::Changed(path.clone())),
+                None => problems.push(ManifestProblem::Removed(path.clone())),
+            }
+        }
+        for path in current.files.keys() {
+            if !self.files.contains_key(path) {
+                problems.push(ManifestProblem::Added(path.clone()));
+            }
+        }
+
+        problems
+    }
+}
+
+/// Something about a vault that its manifest does not vouch for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestProblem {
+    /// The vault had a manifest before, but has none now.
+    Missing,
+    /// The manifest's signature is bad, or is not from a member of the vault.
+    Unverified(String),
+    /// The manifest is older than one already seen for the vault.
+    RolledBack { version: u64, seen: u64 },
+    /// A file is not the one the manifest lists.
+    Changed(String),
+    /// A file the manifest lists is gone.
+    Removed(String),
+    /// A file is not in the manifest.
+    Added(String),
+}
+
+impl fmt::Display for ManifestProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestProblem::Missing => write!(f, "The vault's signed manifest was removed"),
+            ManifestProblem::Unverified(reason) => {
+                write!(f, "The vault's manifest cannot be trusted: {reason}")
+            }
+            ManifestProblem::RolledBack { version, seen } => write!(
+                f,
+                "The vault was rolled back to version {version} after version {seen} was seen"
+            ),
+            ManifestProblem::Changed(path) => write!(f, "`{path}` was changed outside fmp"),
+            ManifestProblem::Removed(path) => write!(f, "`{path}` was removed outside fmp"),
+            ManifestProblem::Added(path) => write!(f, "`{path}` was added outside fmp"),
+        }
+    }
+}
+
+/// The outcome of checking a vault against its manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifestCheck {
+    /// The version of the manifest the vault was checked against, or signed with if it had none.
+    /// `None` if it could not be verified, or none could be signed.
+    pub version: Option<u64>,
+    pub problems: Vec<ManifestProblem>,
+}
+
+impl ManifestCheck {
+    /// Whether the vault matches its manifest, or has none and never had one.
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// The highest manifest version seen for each vault, kept outside the vaults.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SeenVersions {
+    pub vaults: BTreeMap<String, u64>,
+    /// Vaults whose last check found problems the user has not trusted yet.
+    #[serde(default)]
+    pub pending: BTreeSet<String>,
+}
+
+impl SeenVersions {
+    /// Loads the versions from a file, or none if it does not exist.
+    ///
+    /// # Arguments
+    /// * `path` - The versions file.
+    ///
+    /// # Returns
+    /// * `Result<SeenVersions, Error>` - Returns the versions on success, or an error on failure.
+    ///
+    /// # Errors
+    /// * If the file exists but cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        serde_json::from_slice(&std::fs::read(path)?)
+            .map_err(|e| anyhow::anyhow!("Failed to parse `{}`: {}", path.display(), e))
+    }
+
+    /// Saves the versions to a file, creating its directory if needed.
+    ///
+    /// # Arguments
+    /// * `path` - The versions file.
+    ///
+    /// # Returns
+    /// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+    ///
+    /// # Errors
+    /// * If the directory or file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir)?;
+        }
+        // A truncated file would forget every version, so it is replaced atomically
+        write_private_file(path, serde_json::to_string_pretty(self)?.as_bytes())
+    }
+
+    /// Records a version seen for a vault, keeping the highest.
+    ///
+    /// # Arguments
+    /// * `vault_name` - The name of the vault.
+    /// * `version` - The version seen.
+    ///
+    /// # Returns
+    /// * `bool` - `true` if the version is higher than any seen before.
+    pub fn record(&mut self, vault_name: &str, version: u64) -> bool {
+        let seen = self.vaults.entry(vault_name.to_string()).or_default();
+        if version <= *seen {
+            return false;
+        }
+        *seen = version;
+        true
+    }
+
+    /// Whether a vault has problems the user has not trusted yet.
+    ///
+    /// # Arguments
+    /// * `vault_name` - The name of the vault.
+    pub fn is_pending(&self, vault_name: &str) -> bool {
+        self.pending.contains(vault_name)
+    }
+
+    /// Marks a vault as having problems the user has not trusted yet, or as having none.
+    ///
+    /// # Arguments
+    /// * `vault_name` - The name of the vault.
+    /// * `pending` - Whether problems are pending.
+    ///
+    /// # Returns
+    /// * `bool` - `true` if this changed the vault's mark.
+    pub fn set_pending(&mut self, vault_name: &str, pending: bool) -> bool {
+        if pending {
+            self.pending.insert(vault_name.to_string())
+        } else {
+            self.pending.remove(vault_name)
+        }
+    }
+}
+
+/// Signs a manifest of a vault as it is now and writes it to `manifest.gpg`.
+///
+/// # Arguments
+/// * `vault_dir` - The vault's directory.
+/// * `version` - The version to give the manifest.
+/// * `sign` - Signs the manifest, returning the signed message, such as with a member's GPG key.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault cannot be read, signing fails or the manifest cannot be written.
+pub fn write_manifest<F>(vault_dir: &Path, version: u64, sign: F) -> Result<(), Error>
+where
+    F: FnOnce(&[u8]) -> Result<Vec<u8>, Error>,
+{
+    let manifest = VaultManifest::for_vault(vault_dir, version)?;
+    let signed = sign(&serde_json::to_vec_pretty(&manifest)?)?;
+    write_private_file(&vault_dir.join(MANIFEST_FILE), &signed)
+}
+
+/// Checks a vault against its manifest.
+///
+/// # Arguments
+/// * `vault_dir` - The vault's directory.
+/// * `seen` - The highest manifest version seen for the vault before, if any.
+/// * `verify` - Checks the manifest's signature, returning the signed contents.
+///
+/// # Returns
+/// * `Result<ManifestCheck, Error>` - Returns what was found on success, or an error on failure. A
+///   vault without a manifest has no problems unless one was seen for it before.
+///
+/// # Errors
+/// * If the manifest or the vault's files cannot be read.
+pub fn check_manifest<F>(
+    vault_dir: &Path,
+    seen: Option<u64>,
+    verify: F,
+) -> Result<ManifestCheck, Error>
+where
+    F: FnOnce(&[u8]) -> Result<Vec<u8>, Error>,
+{
+    let (manifest, mut problems) = read_verified_manifest(vault_dir, seen, verify)?;
+    let Some(manifest) = manifest else {
+        return Ok(ManifestCheck {
+            version: None,
+            problems,
+        });
+    };
+
+    problems.extend(manifest.differences(&VaultManifest::for_vault(vault_dir, 0)?));
+
+    Ok(ManifestCheck {
+        version: Some(manifest.version),
+        problems,
+    })
+}
+
+/// Checks a vault against its manifest, then records the manifest's version if the vault matches
+/// it, or marks the vault as pending if it does not.
+///
+/// # Arguments
+/// * `vault_dir` - The vault's directory.
+/// * `vault_name` - The name of the vault.
+/// * `versions` - The versions seen, updated with the outcome.
+/// * `verify` - Checks the manifest's signature, returning the signed contents.
+///
+/// # Returns
+/// * `Result<ManifestCheck, Error>` - Returns what was found on success, or an error on failure.
+///
+/// # Errors
+/// * If the manifest or the vault's files cannot be read.
+pub fn check_and_record_manifest<F>(
+    vault_dir: &Path,
+    vault_name: &str,
+    versions: &mut SeenVersions,
+    verify: F,
+) -> Result<ManifestCheck, Error>
+where
+    F: FnOnce(&[u8]) -> Result<Vec<u8>, Error>,
+{
+    let check = check_manifest(vault_dir, versions.vaults.get(vault_name).copied(), verify)?;
+
+    if let Some(version) = check.version
+        && check.is_ok()
+    {
+        versions.record(vault_name, version);
+    }
+    versions.set_pending(vault_name, !check.is_ok());
+
+    Ok(check)
+}
+
+/// Signs a manifest of a vault as it is now, accepting every file in it, with a version above both
+/// the one seen and the one in the vault, so a change made elsewhere to a shared vault is never
+/// signed with a lower version. Any pending problems are cleared.
+///
+/// # Arguments
+/// * `vault_dir` - The vault's directory.
+/// * `vault_name` - The name of the vault.
+/// * `versions` - The versions seen, updated with the new version.
+/// * `sign` - Signs the manifest, returning the signed message.
+/// * `verify` - Checks the current manifest's signature, returning the signed contents.
+///
+/// # Returns
+/// * `Result<u64, Error>` - Returns the new manifest's version on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault cannot be read, signing fails or the manifest cannot be written.
+pub fn sign_manifest<S, V>(
+    vault_dir: &Path,
+    vault_name: &str,
+    versions: &mut SeenVersions,
+    sign: S,
+    verify: V,
+) -> Result<u64, Error>
+where
+    S: FnOnce(&[u8]) -> Result<Vec<u8>, Error>,
+    V: FnOnce(&[u8]) -> Result<Vec<u8>, Error>,
+{
+    let seen = versions.vaults.get(vault_name).copied();
+    // A manifest that cannot be verified is being replaced, so only its version matters here
+    let (current, _) = read_verified_manifest(vault_dir, None, verify)?;
+    let version = seen
+        .unwrap_or(0)
+        .max(current.map_or(0, |manifest| manifest.version))
+        + 1;
+
+    write_manifest(vault_dir, version, sign)?;
+    versions.record(vault_name, version);
+    versions.set_pending(vault_name, false);
+
+    Ok(version)
+}
+
+/// Signs a vault's manifest again after fmp changes it. Nothing is signed while the vault has
+/// pending problems, or if its manifest turns out to be missing, forged or rolled back, since that
+/// would vouch for changes made outside fmp. The vault is marked as pending in the latter case.
+///
+/// # Arguments
+/// * `vault_dir` - The vault's directory.
+/// * `vault_name` - The name of the vault.
+/// * `versions` - The versions seen, updated with the outcome.
+/// * `sign` - Signs the manifest, returning the signed message.
+/// * `verify` - Checks the current manifest's signature, returning the signed contents.
+///
+/// # Returns
+/// * `Result<Option<u64>, Error>` - Returns the new manifest's version, or `None` if it was not
+///   signed, on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault cannot be read, signing fails or the manifest cannot be written.
+pub fn resign_manifest<S, V>(
+    vault_dir: &Path,
+    vault_name: &str,
+    versions: &mut SeenVersions,
+    sign: S,
+    verify: V,
+) -> Result<Option<u64>, Error>
+where
+    S: FnOnce(&[u8]) -> Result<Vec<u8>, Error>,
+    V: FnOnce(&[u8]) -> Result<Vec<u8>, Error>,
+{
+    if versions.is_pending(vault_name) {
+        return Ok(None);
+    }
+
+    // The files were just changed, so only the manifest itself can be checked
+    let seen = versions.vaults.get(vault_name).copied();
+    let (current, problems) = read_verified_manifest(vault_dir, seen, verify)?;
+    if !problems.is_empty() {
+        versions.set_pending(vault_name, true);
+        return Ok(None);
+    }
+
+    let version = seen
+        .unwrap_or(0)
+        .max(current.map_or(0, |manifest| manifest.version))
+        + 1;
+    write_manifest(vault_dir, version, sign)?;
+    versions.record(vault_name, version);
+
+    Ok(Some(version))
+}
+
+/// Reads a vault's manifest and checks its signature and that it is not older than one seen
+/// before, without comparing it with the vault's files.
+fn read_verified_manifest<F>(
+    vault_dir: &Path,
+    seen: Option<u64>,
+    verify: F,
+) -> Result<(Option<VaultManifest>, Vec<ManifestProblem>), Error>
+where
+    F: FnOnce(&[u8]) -> Result<Vec<u8>, Error>,
+{
+    let path = vault_dir.join(MANIFEST_FILE);
+    if !path.exists() {
+        let problems = match seen {
+            Some(_) => vec![ManifestProblem::Missing],
+            None => Vec::new(),
+        };
+        return Ok((None, problems));
+    }
+
+    let manifest: VaultManifest = match verify(&std::fs::read(&path)?).and_then(|contents| {
+        serde_json::from_slice(&contents)
+            .map_err(|e| anyhow::anyhow!("Failed to parse the manifest: {}", e))
+    }) {
+        Ok(manifest) => manifest,
+        Err(e) => return Ok((None, vec![ManifestProblem::Unverified(e.to_string())])),
+    };
+
+    let mut problems = Vec::new();
+    if let Some(seen) = seen
+        && manifest.version < seen
+    {
+        problems.push(ManifestProblem::RolledBack {
+            version: manifest.version,
+            seen,
+        });
+    }
+
+    Ok((Some(manifest), problems))
+}
+
+/// Checks a vault against its signed manifest when it is opened, and records the manifest's version
+/// if it matches or marks the vault as pending if it does not. A vault that has never had a
+/// manifest, such as one made by an older version of fmp, is given one.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+///
+/// # Returns
+/// * `Result<ManifestCheck, Error>` - Returns what was found on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault does not exist, or its files or the versions seen cannot be read.
+pub fn check_vault_manifest(vault_name: &str) -> Result<ManifestCheck, Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+
+    let _lock = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let versions_path = seen_versions_path();
+    let mut versions = SeenVersions::load(&versions_path)?;
+    let before = versions.clone();
+
+    let mut check =
+        check_and_record_manifest(&locations.vault, vault_name, &mut versions, |signed| {
+            verify_member_signature(&locations, signed)
+        })?;
+
+    if check.version.is_none() && check.is_ok() {
+        log::info!("Signing a manifest for '{vault_name}', which has none");
+        match sign_locked(vault_name, &locations, &mut versions) {
+            Ok(version) => check.version = Some(version),
+            Err(e) => log::warn!("Failed to sign a manifest for '{vault_name}': {e}"),
+        }
+    }
+    for problem in &check.problems {
+        log::warn!("Vault '{vault_name}': {problem}");
+    }
+
+    if versions != before {
+        versions.save(&versions_path)?;
+    }
+    Ok(check)
+}
+
+/// Signs a manifest of a vault as it is now, accepting every file in it and clearing any pending
+/// problems.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+///
+/// # Returns
+/// * `Result<u64, Error>` - Returns the new manifest's version on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault does not exist, none of its members has a secret key here, or the manifest or
+///   the versions seen cannot be written.
+pub fn sign_vault_manifest(vault_name: &str) -> Result<u64, Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+
+    let _lock = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let versions_path = seen_versions_path();
+    let mut versions = SeenVersions::load(&versions_path)?;
+    let version = sign_locked(vault_name, &locations, &mut versions)?;
+    versions.save(&versions_path)?;
+
+    Ok(version)
+}
+
+/// Signs a vault's manifest again after fmp changes it, unless the vault has problems the user has
+/// not trusted yet. Failures are logged, since they should never stop the change itself; the next
+/// change, or opening the vault, tries again.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+pub fn update_vault_manifest(vault_name: &str) {
+    if let Err(e) = resign_vault_manifest(vault_name) {
+        log::warn!("Failed to sign the manifest of '{vault_name}': {e}");
+    }
+}
+
+fn resign_vault_manifest(vault_name: &str) -> Result<(), Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+
+    let _lock = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let versions_path = seen_versions_path();
+    let mut versions = SeenVersions::load(&versions_path)?;
+    let signed = resign_manifest(
+        &locations.vault,
+        vault_name,
+        &mut versions,
+        |manifest| sign_as_member(&locations, manifest),
+        |signed| verify_member_signature(&locations, signed),
+    )?;
+    versions.save(&versions_path)?;
+
+    if signed.is_none() {
+        log::warn!(
+            "Not signing the manifest of '{vault_name}' until its problems are trusted or fixed"
+        );
+    }
+    Ok(())
+}
+
+/// Moves the version seen for a vault to its new name after it is renamed.
+///
+/// # Arguments
+/// * `old_name` - The old name of the vault.
+/// * `new_name` - The new name of the vault.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the versions file cannot be read or written.
+pub fn update_manifest_versions_on_rename(old_name: &str, new_name: &str) -> Result<(), Error> {
+    let _lock = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let path = seen_versions_path();
+    let mut versions = SeenVersions::load(&path)?;
+    let before = versions.clone();
+    if let Some(version) = versions.vaults.remove(old_name) {
+        versions.vaults.insert(new_name.to_string(), version);
+    }
+    if versions.set_pending(old_name, false) {
+        versions.set_pending(new_name, true);
+    }
+    if versions != before {
+        versions.save(&path)?;
+    }
+    Ok(())
+}
+
+/// Forgets the version seen for a deleted vault, so a new vault with its name starts again.
+///
+/// # Arguments
+/// * `vault_name` - The name of the deleted vault.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the versions file cannot be read or written.
+pub fn forget_manifest_version(vault_name: &str) -> Result<(), Error> {
+    let _lock = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let path = seen_versions_path();
+    let mut versions = SeenVersions::load(&path)?;
+    let removed = versions.vaults.remove(vault_name).is_some();
+    if versions.set_pending(vault_name, false) || removed {
+        versions.save(&path)?;
+    }
+    Ok(())
+}
+
+fn seen_versions_path() -> PathBuf {
+    current_home().config.join(SEEN_VERSIONS_FILE)
+}
+
+/// Signs the vault's manifest with the key of a vault member. The caller saves `versions`.
+fn sign_locked(
+    vault_name: &str,
+    locations: &Locations,
+    versions: &mut SeenVersions,
+) -> Result<u64, Error> {
+    sign_manifest(
+        &locations.vault,
+        vault_name,
+        versions,
+        |manifest| sign_as_member(locations, manifest),
+        |signed| verify_member_signature(locations, signed),
+    )
+}
+
+/// Signs with the key of the first vault member whose secret key is in the keyring.
+fn sign_as_member(locations: &Locations, manifest: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut ctx = Context::from_protocol(Protocol::OpenPgp)?;
+    let key = read_recipient_file(&locations.recipient)?
+        .iter()
+        .find_map(|recipient| {
+            ctx.get_secret_key(recipient)
+                .ok()
+                .filter(|key| key.can_sign())
+        })
+        .ok_or_else(|| {
+            anyhow::anyhow!("No member of the vault has a signing key in this keyring")
+        })?;
+
+    ctx.add_signer(&key)?;
+    let mut signed = Vec::new();
+    ctx.sign_normal(manifest, &mut signed)
+        .map_err(|e| anyhow::anyhow!("Failed to sign the manifest: {}", e))?;
+
+    Ok(signed)
+}
+
+/// Verifies the manifest's signature and that it was made by a key of a vault member.
+fn verify_member_signature(locations: &Locations, signed: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut ctx = Context::from_protocol(Protocol::OpenPgp)?;
+    let mut contents = Vec::new();
+    let result = ctx
+        .verify_opaque(signed, &mut contents)
+        .map_err(|e| anyhow::anyhow!("Failed to read the signature: {}", e))?;
+
+    let signer = result
+        .signatures()
+        .find(|signature| signature.status().is_ok())
+        .and_then(|signature| signature.fingerprint().ok().map(str::to_string))
+        .ok_or_else(|| anyhow::anyhow!("it has no good signature from a key in this keyring"))?;
+
+    // The signing key may be a subkey, so every subkey of each member is compared
+    let is_member = read_recipient_file(&locations.recipient)?
+        .iter()
+        .filter_map(|recipient| ctx.get_key(recipient).ok())
+        .any(|key| {
+            key.subkeys().any(|subkey| {
+                subkey
+                    .fingerprint()
+                    .is_ok_and(|fingerprint| fingerprint.eq_ignore_ascii_case(&signer))
+            })
+        });
+    if !is_member {
+        return Err(anyhow::anyhow!(
+            "it was signed by {signer}, which is not a member of the vault"
+        ));
+    }
+
+    Ok(contents)
+}
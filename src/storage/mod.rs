@@ -20,9 +20,17 @@ Copyright (C) 2025  Luke Wilkinson
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+pub mod account_names;
+pub mod archive;
+pub mod auto_backup;
+pub mod backup;
+pub mod container;
 pub mod filesystem;
 pub mod locations;
+pub mod manifest;
+pub mod profile;
 pub mod store;
+pub mod vault_files;
 
 pub use locations::Locations;
 pub use store::Store;
@@ -0,0 +1,281 @@
+//! Choosing where fmp keeps its files.
+//!
+//! By default vaults and backups live in the user data directory and the first-run marker,
+//! two-factor ledger and seen manifest versions in the user config directory. `--home <dir>` or
+//! `FMP_HOME` keeps everything in one directory instead, with the same split between its `data`
+//! and `config` subdirectories. Named profiles, such as "work" and "personal", each get their own
+//! directory laid out the same way and optionally their own GnuPG home; they are described in
+//! `profiles.json` in the default config directory, and a profile that is not described there is
+//! kept under `profiles/<name>` in the default data directory.
+
+/*
+Copyright (C) 2025  Luke Wilkinson
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use anyhow::Error;
+use gpgme::Protocol;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Environment variable naming a directory to keep all of fmp's files in.
+pub const HOME_VARIABLE: &str = "FMP_HOME";
+/// Environment variable naming the profile to use.
+pub const PROFILE_VARIABLE: &str = "FMP_PROFILE";
+/// Name of the file in the default config directory describing the profiles.
+pub const PROFILES_FILE: &str = "profiles.json";
+/// Subdirectory of a chosen home or profile directory that holds its data.
+pub const DATA_DIRECTORY: &str = "data";
+/// Subdirectory of a chosen home or profile directory that holds its config files.
+pub const CONFIG_DIRECTORY: &str = "config";
+
+/// The data home chosen at startup, or `None` until one is chosen.
+static SELECTED: RwLock<Option<DataHome>> = RwLock::new(None);
+
+/// How the data home was asked for, from the command line or the environment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HomeSelection {
+    /// The user data and config directories.
+    Default,
+    /// A directory holding everything.
+    Home(PathBuf),
+    /// A named profile.
+    Profile(String),
+}
+
+/// How a profile is set up in `profiles.json`. Both fields are optional.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ProfileConfig {
+    /// The directory holding the profile's `data` and `config` subdirectories.
+    pub home: Option<PathBuf>,
+    /// The GnuPG home to use instead of `GNUPGHOME` or `~/.gnupg`.
+    pub gnupg_home: Option<PathBuf>,
+}
+
+/// Where fmp keeps its files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataHome {
+    /// The profile in use, or `None` when none was chosen.
+    pub profile: Option<String>,
+    /// The directory holding vaults, backups and the breach list, used as `Locations::fmp`.
+    pub data: PathBuf,
+    /// The directory holding the first-run marker, two-factor ledger and seen manifest versions.
+    /// It is never the data directory, so these stay apart from the vaults they vouch for.
+    pub config: PathBuf,
+    /// The GnuPG home to use, or `None` to leave GnuPG's own choice.
+    pub gnupg_home: Option<PathBuf>,
+}
+
+impl DataHome {
+    /// Whether this is the default home, shared with installs that never chose one.
+    pub fn is_default(&self) -> bool {
+        self == &default_home()
+    }
+
+    /// Returns a short description for showing which data is in use, such as "profile work".
+    pub fn describe(&self) -> String {
+        match &self.profile {
+            Some(profile) => format!("profile {profile}"),
+            None => self.data.display().to_string(),
+        }
+    }
+}
+
+/// Reads the selection from `FMP_HOME` or `FMP_PROFILE`. Empty values are ignored, and
+/// `FMP_HOME` wins if both are set.
+///
+/// # Returns
+/// * `HomeSelection` - Returns the selection, or `HomeSelection::Default` if neither is set.
+pub fn selection_from_env() -> HomeSelection {
+    let value = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+
+    if let Some(home) = value(HOME_VARIABLE) {
+        HomeSelection::Home(PathBuf::from(home))
+    } else if let Some(profile) = value(PROFILE_VARIABLE) {
+        HomeSelection::Profile(profile.to_string_lossy().into_owned())
+    } else {
+        HomeSelection::Default
+    }
+}
+
+/// Checks that a profile name can be used as a directory name.
+///
+/// # Arguments
+/// * `name` - The profile name.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` if the name is valid, or an error otherwise.
+///
+/// # Errors
+/// * If the name is empty, `.` or `..`, or contains a path separator.
+pub fn validate_profile_name(name: &str) -> Result<(), Error> {
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        return Err(anyhow::anyhow!(
+            "Invalid profile name `{name}`. Profile names cannot be empty or contain `/` or `\\`."
+        ));
+    }
+
+    Ok(())
+}
+
+/// Reads the profiles described in a `profiles.json` file, which maps names to `ProfileConfig`s.
+///
+/// # Arguments
+/// * `path` - The profiles file.
+///
+/// # Returns
+/// * `Result<BTreeMap<String, ProfileConfig>, Error>` - Returns the profiles by name, or none if
+///   the file does not exist.
+///
+/// # Errors
+/// * If the file exists but cannot be read or parsed, or names an invalid profile.
+pub fn load_profiles(path: &Path) -> Result<BTreeMap<String, ProfileConfig>, Error> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let profiles: BTreeMap<String, ProfileConfig> = serde_json::from_str(&read_to_string(path)?)
+        .map_err(|e| anyhow::anyhow!("Failed to parse profiles `{}`: {}", path.display(), e))?;
+    for name in profiles.keys() {
+        validate_profile_name(name)?;
+    }
+
+    Ok(profiles)
+}
+
+/// Works out the data home for a selection.
+///
+/// # Arguments
+/// * `selection` - How the home was asked for.
+/// * `profiles` - The profiles described in `profiles.json`.
+/// * `default` - The default home, which profiles without their own directory are kept under.
+///
+/// # Returns
+/// * `Result<DataHome, Error>` - Returns the data home on success, or an error on failure.
+///
+/// # Errors
+/// * If a profile name is invalid.
+pub fn resolve_home(
+    selection: &HomeSelection,
+    profiles: &BTreeMap<String, ProfileConfig>,
+    default: &DataHome,
+) -> Result<DataHome, Error> {
+    match selection {
+        HomeSelection::Default => Ok(default.clone()),
+        HomeSelection::Home(home) => Ok(DataHome {
+            profile: None,
+            data: home.join(DATA_DIRECTORY),
+            config: home.join(CONFIG_DIRECTORY),
+            gnupg_home: None,
+        }),
+        HomeSelection::Profile(name) => {
+            validate_profile_name(name)?;
+
+            let config = profiles.get(name).cloned().unwrap_or_default();
+            let home = config
+                .home
+                .unwrap_or_else(|| default.data.join("profiles").join(name));
+
+            Ok(DataHome {
+                profile: Some(name.clone()),
+                data: home.join(DATA_DIRECTORY),
+                config: home.join(CONFIG_DIRECTORY),
+                gnupg_home: config.gnupg_home,
+            })
+        }
+    }
+}
+
+/// Returns the home used when none is chosen: `fmp` in the user data and config directories.
+///
+/// # Returns
+/// * `DataHome` - The default data home.
+pub fn default_home() -> DataHome {
+    let data = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+    let config = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+
+    DataHome {
+        profile: None,
+        data: data.join("fmp"),
+        config: config.join("fmp"),
+        gnupg_home: None,
+    }
+}
+
+/// Returns the path of `profiles.json`, which is always in the default config directory.
+///
+/// # Returns
+/// * `PathBuf` - The profiles file.
+pub fn profiles_path() -> PathBuf {
+    default_home().config.join(PROFILES_FILE)
+}
+
+/// Chooses where fmp keeps its files for the rest of the process, and points GnuPG at the
+/// profile's GnuPG home if it has one. Call this once at startup, before any vault is opened.
+///
+/// # Arguments
+/// * `selection` - How the home was asked for.
+///
+/// # Returns
+/// * `Result<DataHome, Error>` - Returns the chosen home on success, or an error on failure.
+///
+/// # Errors
+/// * If `profiles.json` cannot be read, the profile name is invalid, or GnuPG rejects the home.
+pub fn select_home(selection: &HomeSelection) -> Result<DataHome, Error> {
+    let profiles = match selection {
+        HomeSelection::Profile(_) => load_profiles(&profiles_path())?,
+        _ => BTreeMap::new(),
+    };
+    let home = resolve_home(selection, &profiles, &default_home())?;
+
+    if let Some(gnupg_home) = &home.gnupg_home {
+        gpgme::init()
+            .set_engine_home_dir(Protocol::OpenPgp, gnupg_home.to_string_lossy().into_owned())
+            .map_err(|e| {
+                anyhow::anyhow!("Failed to use GnuPG home `{}`: {}", gnupg_home.display(), e)
+            })?;
+    }
+
+    *SELECTED.write().unwrap_or_else(|e| e.into_inner()) = Some(home.clone());
+    Ok(home)
+}
+
+/// Returns where fmp keeps its files. If no home was chosen with `select_home`, `FMP_HOME` and
+/// `FMP_PROFILE` are still honoured, so tests can run away from real data.
+///
+/// # Returns
+/// * `DataHome` - The data home in use.
+pub fn current_home() -> DataHome {
+    if let Some(home) = SELECTED.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return home.clone();
+    }
+
+    let selection = selection_from_env();
+    let profiles = match selection {
+        HomeSelection::Profile(_) => load_profiles(&profiles_path()).unwrap_or_else(|e| {
+            log::warn!("Failed to read profiles: {e}");
+            BTreeMap::new()
+        }),
+        _ => BTreeMap::new(),
+    };
+    resolve_home(&selection, &profiles, &default_home()).unwrap_or_else(|e| {
+        log::warn!("{e}. Using the default data directory.");
+        default_home()
+    })
+}
@@ -20,13 +20,15 @@ Copyright (C) 2025  Luke Wilkinson
 use crate::crypto::LockedBuffer;
 use crate::models::Account;
 use crate::security::SecurePassword;
-use crate::storage::Locations;
+use crate::storage::{Locations, vault_files};
 use anyhow::Error;
 use gpgme::{Context, Protocol};
 use std::cell::RefCell;
-use std::fs::{File, read_to_string};
-use std::io::{BufReader, Read, Write};
+use std::fs::{File, OpenOptions, read_dir, read_to_string, remove_file, rename};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use zeroize::Zeroize;
 
 /// Handles GPG encryption and decryption operations for account data
@@ -72,68 +74,124 @@ impl Store {
         let serialized_json = serde_json::to_string(account_data)?;
         let account_buf = LockedBuffer::from_string(serialized_json);
 
-        let recipient_id = read_to_string(&self.storage_locations.recipient)?
-            .trim()
-            .to_string();
-        let recipient_key = self.gpg_context.get_key(&recipient_id).map_err(|error| {
-            anyhow::anyhow!(
-                "Failed to find recipient `{}` for encryption. Error: {}",
-                recipient_id,
-                error
-            )
-        })?;
-
-        let mut output_file = File::create(&self.storage_locations.data)?;
-
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            output_file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
-        }
+        let encrypted_output = self.encrypt_for_vault(account_buf.as_slice())?;
+        vault_files::write(&self.storage_locations.data, &encrypted_output)?;
+
+        Ok(())
+    }
+
+    /// Encrypts data to every recipient listed in the vault's recipient file.
+    ///
+    /// # Arguments
+    /// * `plaintext` - The data to encrypt.
+    ///
+    /// # Returns
+    /// * `Result<Vec<u8>, Error>` - Returns the encrypted data on success, or an error on failure.
+    ///
+    /// # Errors
+    /// * If the recipient file cannot be read, if a recipient key cannot be found, or if encryption fails.
+    pub fn encrypt_for_vault(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let recipients = read_recipients(&self.storage_locations)?;
+        self.encrypt_to_recipients(&recipients, plaintext)
+    }
+
+    /// Encrypts data to an explicit list of recipients, ignoring the vault's recipient file.
+    ///
+    /// # Arguments
+    /// * `recipients` - The recipient key IDs to encrypt to.
+    /// * `plaintext` - The data to encrypt.
+    ///
+    /// # Returns
+    /// * `Result<Vec<u8>, Error>` - Returns the encrypted data on success, or an error on failure.
+    ///
+    /// # Errors
+    /// * If a recipient key cannot be found or if encryption fails.
+    pub fn encrypt_to_recipients(
+        &mut self,
+        recipients: &[String],
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let recipient_keys = find_recipient_keys(recipients, &mut self.gpg_context)?;
 
         let mut encrypted_output = Vec::new();
         self.gpg_context
-            .encrypt(
-                [&recipient_key],
-                account_buf.as_slice(),
-                &mut encrypted_output,
-            )
+            .encrypt(&recipient_keys, plaintext, &mut encrypted_output)
             .map_err(|encryption_error| {
                 anyhow::anyhow!(
-                    "Failed to encrypt data for recipient `{}`. Error: {}",
-                    recipient_id,
+                    "Failed to encrypt data for recipients `{}`. Error: {}",
+                    recipients.join(", "),
                     encryption_error
                 )
             })?;
 
-        output_file.write_all(&encrypted_output)?;
-
-        Ok(())
+        Ok(encrypted_output)
     }
 
-    /// Decrypts data from data.gpg file in the vault and returns an `Account` struct.
+    /// Decrypts any encrypted file belonging to the vault.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the encrypted file.
     ///
     /// # Returns
-    /// * `Result<Account, Error>` - Returns an `Account` struct containing all decrypted account data on success, or an error on failure.
+    /// * `Result<LockedBuffer, Error>` - Returns the decrypted data on success, or an error on failure.
     ///
     /// # Errors
-    /// * If the file cannot be opened, if decryption fails, or if JSON parsing fails.
-    pub fn decrypt_account_from_file(&mut self) -> Result<Account, Error> {
-        let mut encrypted_file_data = Vec::new();
-        let input_file = File::open(&self.storage_locations.data)?;
-
-        let mut file_reader = BufReader::new(input_file);
-        file_reader.read_to_end(&mut encrypted_file_data)?;
+    /// * If the file cannot be read or if decryption fails.
+    pub fn decrypt_file(&mut self, path: &Path) -> Result<LockedBuffer, Error> {
+        let encrypted_file_data = vault_files::read(path)?;
 
         let mut decrypted_output = Vec::new();
         self.gpg_context
             .decrypt(&encrypted_file_data, &mut decrypted_output)
             .map_err(|decryption_error| {
-                anyhow::anyhow!("Failed to decrypt data. Error: {}", decryption_error)
+                anyhow::anyhow!(
+                    "Failed to decrypt `{}`. Error: {}",
+                    path.display(),
+                    decryption_error
+                )
             })?;
 
-        let decrypted_buf = LockedBuffer::new(decrypted_output);
+        Ok(LockedBuffer::new(decrypted_output))
+    }
+
+    /// Decrypts data from data.gpg file in the vault and returns an `Account` struct.
+    ///
+    /// # Returns
+    /// * `Result<Account, Error>` - Returns an `Account` struct containing all decrypted account data on success, or an error on failure.
+    ///
+    /// # Errors
+    /// * If the file cannot be opened, if decryption fails, or if JSON parsing fails.
+    pub fn decrypt_account_from_file(&mut self) -> Result<Account, Error> {
+        let data_path = self.storage_locations.data.clone();
+        self.decrypt_account_from_path(&data_path)
+    }
 
+    /// Decrypts an account stored at any path, such as a saved revision of `data.gpg`.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the encrypted account file.
+    ///
+    /// # Returns
+    /// * `Result<Account, Error>` - Returns the decrypted `Account` on success, or an error on failure.
+    ///
+    /// # Errors
+    /// * If the file cannot be opened, if decryption fails, or if JSON parsing fails.
+    pub fn decrypt_account_from_path(&mut self, path: &Path) -> Result<Account, Error> {
+        let decrypted_buf = self.decrypt_file(path)?;
+        Self::parse_account(&decrypted_buf)
+    }
+
+    /// Parses decrypted account data, in either the JSON or the legacy `username:password` format.
+    ///
+    /// # Arguments
+    /// * `decrypted_buf` - The decrypted contents of an account file.
+    ///
+    /// # Returns
+    /// * `Result<Account, Error>` - Returns the parsed `Account` on success, or an error on failure.
+    ///
+    /// # Errors
+    /// * If the data is not UTF-8, or is in neither format.
+    pub fn parse_account(decrypted_buf: &LockedBuffer) -> Result<Account, Error> {
         let account_data = if let Ok(json_str) = decrypted_buf.as_str() {
             if let Ok(parsed_account) = serde_json::from_str::<Account>(json_str) {
                 parsed_account
@@ -176,24 +234,288 @@ impl Store {
     }
 }
 
-pub fn get_recipient_key(
+/// Largest recipient file accepted, in bytes. Plenty for a team's worth of fingerprints.
+const MAX_RECIPIENT_FILE_SIZE: u64 = 16 * 1024;
+
+/// Parses the contents of a recipient file into key IDs, one per line.
+///
+/// Blank lines and lines starting with `#` are ignored, and duplicates are dropped.
+///
+/// # Arguments
+/// * `contents` - The text of the recipient file.
+///
+/// # Returns
+/// * `Vec<String>` - The recipients in file order.
+pub fn parse_recipients(contents: &str) -> Vec<String> {
+    let mut recipients: Vec<String> = Vec::new();
+
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if !recipients.iter().any(|existing| existing == line) {
+            recipients.push(line.to_string());
+        }
+    }
+
+    recipients
+}
+
+/// Reads the list of recipients for a vault.
+///
+/// # Arguments
+/// * `locations` - The `Locations` for the vault.
+///
+/// # Returns
+/// * `Result<Vec<String>, Error>` - Returns the recipient key IDs on success, or an error on failure.
+///
+/// # Errors
+/// * If the recipient file cannot be read, is too large, or lists no recipients.
+pub fn read_recipients(locations: &Locations) -> Result<Vec<String>, Error> {
+    read_recipient_file(&locations.recipient)
+}
+
+/// Reads the list of recipients from a recipient file, such as the one in a backup snapshot.
+///
+/// # Arguments
+/// * `path` - The recipient file.
+///
+/// # Returns
+/// * `Result<Vec<String>, Error>` - Returns the recipient key IDs on success, or an error on failure.
+///
+/// # Errors
+/// * If the file cannot be read, is too large, or lists no recipients.
+pub fn read_recipient_file(path: &Path) -> Result<Vec<String>, Error> {
+    if path.metadata()?.len() > MAX_RECIPIENT_FILE_SIZE {
+        return Err(anyhow::anyhow!("Recipient file is too large"));
+    }
+
+    let recipients = parse_recipients(&read_to_string(path)?);
+    if recipients.is_empty() {
+        return Err(anyhow::anyhow!("Vault has no recipients"));
+    }
+
+    Ok(recipients)
+}
+
+/// Writes the list of recipients for a vault, one per line.
+///
+/// # Arguments
+/// * `locations` - The `Locations` for the vault.
+/// * `recipients` - The recipient key IDs to store.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the list is empty or the file cannot be written.
+pub fn write_recipients(locations: &Locations, recipients: &[String]) -> Result<(), Error> {
+    if recipients.is_empty() {
+        return Err(anyhow::anyhow!("A vault needs at least one recipient"));
+    }
+
+    let mut contents = recipients.join("\n");
+    contents.push('\n');
+
+    write_private_file(&locations.recipient, contents.as_bytes())
+}
+
+/// Looks up the GPG key for every recipient.
+///
+/// # Arguments
+/// * `recipients` - The recipient key IDs, fingerprints or emails.
+/// * `ctx` - The GPGME context to search.
+///
+/// # Returns
+/// * `Result<Vec<gpgme::Key>, Error>` - Returns one key per recipient on success, or an error on failure.
+///
+/// # Errors
+/// * If any recipient is missing from the keyring or its key cannot encrypt.
+pub fn find_recipient_keys(
+    recipients: &[String],
+    ctx: &mut Context,
+) -> Result<Vec<gpgme::Key>, Error> {
+    recipients
+        .iter()
+        .map(|recipient| {
+            let key = ctx.get_key(recipient).map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to find recipient `{}` for encryption. Error: {}",
+                    recipient,
+                    e
+                )
+            })?;
+
+            if !key.can_encrypt() {
+                return Err(anyhow::anyhow!(
+                    "Key for recipient `{}` cannot be used for encryption (it may be expired or revoked).",
+                    recipient
+                ));
+            }
+
+            Ok(key)
+        })
+        .collect()
+}
+
+pub fn get_recipient_keys(
     locations: &Locations,
     ctx: Rc<RefCell<Context>>,
-) -> Result<gpgme::Key, Error> {
-    if locations.recipient.metadata()?.len() >= 256 {
-        return Err(anyhow::anyhow!("Recipient file is too large"));
+) -> Result<Vec<gpgme::Key>, Error> {
+    let recipients = read_recipients(locations)?;
+    find_recipient_keys(&recipients, &mut ctx.borrow_mut())
+}
+
+/// Suffix of the temporary file `write_private_file` writes before moving it into place.
+pub const TEMP_SUFFIX: &str = "fmp-tmp";
+
+/// Tells apart the temporary files of concurrent writes from the same process.
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Writes data to a file that only the owner can read, replacing it atomically.
+///
+/// The data is written to a temporary file next to `path`, created with 0600 permissions, synced to
+/// disk and then renamed over `path`. A crash or a full disk therefore leaves either the old file or
+/// the new one, never a truncated one. Leftover temporary files are removed at startup by
+/// `recover_unfinished_writes`.
+///
+/// # Arguments
+/// * `path` - The file to write.
+/// * `contents` - The bytes to write.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the temporary file cannot be created, written or synced, or cannot be moved into place.
+pub fn write_private_file(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    let temp = temp_path(path);
+
+    let result = write_synced(&temp, contents).and_then(|()| {
+        rename(&temp, path)?;
+        sync_parent_directory(path)
+    });
+    if result.is_err() {
+        let _ = remove_file(&temp);
     }
 
-    let recipient = std::fs::read_to_string(&locations.recipient)?
-        .trim()
-        .to_string();
-    let recipient_key = ctx.borrow_mut().get_key(&recipient).map_err(|e| {
-        anyhow::anyhow!(
-            "Failed to find recipient `{}` for encryption. Error: {}",
-            recipient,
-            e
-        )
-    })?;
+    result.map_err(|e| anyhow::anyhow!("Failed to write `{}`: {}", path.display(), e))
+}
+
+/// Returns the temporary file for a write to `path`, named after the file and this process so that
+/// recovery can tell whose write it was.
+fn temp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(
+        ".{}-{}.{TEMP_SUFFIX}",
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    path.with_file_name(file_name)
+}
+
+/// Creates a new file with owner-only permissions, writes to it and syncs it to disk.
+fn write_synced(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut output_file = options.open(path)?;
+    output_file.write_all(contents)?;
+    output_file.sync_all()?;
+
+    Ok(())
+}
+
+/// Syncs the directory holding `path`, so a rename into it survives a crash.
+fn sync_parent_directory(path: &Path) -> Result<(), Error> {
+    #[cfg(unix)]
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        File::open(parent)?.sync_all()?;
+    }
+
+    #[cfg(not(unix))]
+    let _ = path;
+
+    Ok(())
+}
+
+/// Removes temporary files left by writes that never finished, such as after a crash. The file
+/// each one was meant to replace still holds its previous contents.
+///
+/// Temporary files of processes that are still running are left alone, so a second copy of fmp
+/// starting up does not break the writes of the first.
+///
+/// # Arguments
+/// * `root` - The directory to search, such as the data home.
+///
+/// # Returns
+/// * `Result<Vec<PathBuf>, Error>` - Returns the temporary files that were removed, or an error on
+///   failure.
+///
+/// # Errors
+/// * If a directory cannot be read or a leftover file cannot be removed.
+pub fn recover_unfinished_writes(root: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut removed = Vec::new();
+    if !root.exists() {
+        return Ok(removed);
+    }
+
+    for entry in read_dir(root)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            removed.extend(recover_unfinished_writes(&entry.path())?);
+            continue;
+        }
+
+        let name = entry.file_name();
+        let Some(writer) = name
+            .to_str()
+            .and_then(|name| name.strip_suffix(&format!(".{TEMP_SUFFIX}")))
+            .and_then(|name| name.rsplit_once('.'))
+            .and_then(|(_, writer)| writer.split_once('-'))
+            .and_then(|(pid, _)| pid.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if is_process_running(writer) {
+            continue;
+        }
+
+        remove_file(entry.path())?;
+        log::warn!("Removed unfinished write `{}`", entry.path().display());
+        removed.push(entry.path());
+    }
+
+    Ok(removed)
+}
+
+/// Whether another process with this ID is running. This process's own writes count as finished,
+/// since recovery runs before it writes anything.
+fn is_process_running(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return false;
+    }
+
+    #[cfg(unix)]
+    {
+        let Ok(pid) = libc::pid_t::try_from(pid) else {
+            return false;
+        };
+        // SAFETY: signal 0 only checks whether the process exists; nothing is sent.
+        let result = unsafe { libc::kill(pid, 0) };
+        result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
 
-    Ok(recipient_key)
+    #[cfg(not(unix))]
+    false
 }
@@ -0,0 +1,413 @@
+//! Reading and writing the files in a vault's account directories, however the vault is stored.
+//!
+//! Paths are the ones `Locations` gives, such as `<vault>/bank/data.gpg`. When the vault, or the
+//! backup snapshot the path is in, has a `vault.fmpv` container, everything below its account
+//! directories is read from and written to the container instead of the filesystem. Files that
+//! belong to the vault itself, such as `recipient` and `gate.gpg`, are always real files.
+
+/*
+Copyright (C) 2025  Luke Wilkinson
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::storage::{
+    archive::create_private_dir as create_private_fs_dir,
+    container::{CONTAINER_FILE, read_container, update_container},
+    store::write_private_file,
+};
+use anyhow::Error;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+
+/// Returns the container a file is stored in, with the file's path inside it.
+///
+/// # Arguments
+/// * `path` - A file in an account directory, such as `Locations::data`.
+///
+/// # Returns
+/// * `Option<(PathBuf, String)>` - The container file and the path inside it, or `None` if the file
+///   is on the filesystem.
+pub fn container_entry(path: &Path) -> Option<(PathBuf, String)> {
+    locate(path).filter(|(_, entry)| entry.contains('/'))
+}
+
+/// Whether an account directory or a file exists.
+///
+/// # Arguments
+/// * `path` - The directory or file.
+///
+/// # Returns
+/// * `bool` - `true` if it exists. A container that cannot be read counts as empty.
+pub fn exists(path: &Path) -> bool {
+    match locate(path) {
+        Some((container, entry)) if !entry.is_empty() => {
+            let prefix = format!("{entry}/");
+            let in_container = read_container(&container, |container| {
+                container
+                    .files
+                    .keys()
+                    .any(|path| *path == entry || path.starts_with(&prefix))
+            })
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to read `{}`: {e}", container.display());
+                false
+            });
+
+            in_container || (!entry.contains('/') && path.exists())
+        }
+        _ => path.exists(),
+    }
+}
+
+/// Lists the names of the directories in a directory, such as the accounts in a vault.
+///
+/// # Arguments
+/// * `dir` - The directory to list.
+///
+/// # Returns
+/// * `Result<Vec<String>, Error>` - Returns the directory names on success, or an error on failure.
+///
+/// # Errors
+/// * If the directory or container cannot be read.
+pub fn list_dirs(dir: &Path) -> Result<Vec<String>, Error> {
+    list(dir, true)
+}
+
+/// Lists the names of the files in a directory, such as an account's revisions.
+///
+/// # Arguments
+/// * `dir` - The directory to list.
+///
+/// # Returns
+/// * `Result<Vec<String>, Error>` - Returns the file names on success, or an error on failure.
+///
+/// # Errors
+/// * If the directory or container cannot be read.
+pub fn list_files(dir: &Path) -> Result<Vec<String>, Error> {
+    list(dir, false)
+}
+
+/// Reads a file.
+///
+/// # Arguments
+/// * `path` - The file to read.
+///
+/// # Returns
+/// * `Result<Vec<u8>, Error>` - Returns the file's contents on success, or an error on failure.
+///
+/// # Errors
+/// * If the file does not exist or cannot be read.
+pub fn read(path: &Path) -> Result<Vec<u8>, Error> {
+    let Some((container, entry)) = container_entry(path) else {
+        return Ok(std::fs::read(path)?);
+    };
+
+    read_container(&container, |container| container.files.get(&entry).cloned())?
+        .ok_or_else(|| anyhow::anyhow!("`{}` does not exist", path.display()))
+}
+
+/// Writes a file that only the owner can read, replacing it atomically.
+///
+/// # Arguments
+/// * `path` - The file to write. On the filesystem its directory must already exist.
+/// * `contents` - The bytes to write.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the file or container cannot be written.
+pub fn write(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    let Some((container, entry)) = container_entry(path) else {
+        return write_private_file(path, contents);
+    };
+
+    update_container(&container, |container| {
+        container.files.insert(entry, contents.to_vec());
+        Ok(())
+    })
+}
+
+/// Creates a directory that only the owner can open. Directories in a container exist as soon as
+/// a file is written to them, so nothing is done there.
+///
+/// # Arguments
+/// * `dir` - The directory to create, with any missing parents.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the directory cannot be created.
+pub fn create_private_dir(dir: &Path) -> Result<(), Error> {
+    match locate(dir) {
+        Some((_, entry)) if !entry.is_empty() => Ok(()),
+        _ => create_private_fs_dir(dir),
+    }
+}
+
+/// Moves a file or directory, such as an account into the trash.
+///
+/// # Arguments
+/// * `from` - The file or directory to move.
+/// * `to` - Where to move it, which must be stored the same way.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If `from` does not exist, `to` is in a different container, or the move fails.
+pub fn rename(from: &Path, to: &Path) -> Result<(), Error> {
+    match (locate(from), locate(to)) {
+        (Some((container, from_entry)), Some((to_container, to_entry)))
+            if !from_entry.is_empty() && !to_entry.is_empty() =>
+        {
+            if container != to_container {
+                return Err(anyhow::anyhow!(
+                    "Cannot move `{}` to another vault",
+                    from.display()
+                ));
+            }
+
+            update_container(&container, |container| {
+                let moved = take_entries(&mut container.files, &from_entry);
+                if moved.is_empty() {
+                    return Err(anyhow::anyhow!("`{}` does not exist", from.display()));
+                }
+                for (suffix, contents) in moved {
+                    container
+                        .files
+                        .insert(format!("{to_entry}{suffix}"), contents);
+                }
+                Ok(())
+            })
+        }
+        (None, None) => Ok(std::fs::rename(from, to)?),
+        _ => Err(anyhow::anyhow!(
+            "Cannot move `{}` between a single-file vault and a directory",
+            from.display()
+        )),
+    }
+}
+
+/// Deletes a file.
+///
+/// # Arguments
+/// * `path` - The file to delete.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the file does not exist or cannot be deleted.
+pub fn remove_file(path: &Path) -> Result<(), Error> {
+    let Some((container, entry)) = container_entry(path) else {
+        return Ok(std::fs::remove_file(path)?);
+    };
+
+    update_container(&container, |container| {
+        container
+            .files
+            .remove(&entry)
+            .map(|_| ())
+            .ok_or_else(|| anyhow::anyhow!("`{}` does not exist", path.display()))
+    })
+}
+
+/// Deletes a directory and everything in it.
+///
+/// # Arguments
+/// * `dir` - The directory to delete.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the directory does not exist or cannot be deleted.
+pub fn remove_dir_all(dir: &Path) -> Result<(), Error> {
+    match locate(dir) {
+        Some((container, entry)) if !entry.is_empty() => {
+            update_container(&container, |container| {
+                if take_entries(&mut container.files, &entry).is_empty() {
+                    return Err(anyhow::anyhow!("`{}` does not exist", dir.display()));
+                }
+                Ok(())
+            })
+        }
+        _ => Ok(std::fs::remove_dir_all(dir)?),
+    }
+}
+
+/// Copies everything in a directory into another, such as an account from a backup into its vault.
+///
+/// # Arguments
+/// * `from` - The directory to copy.
+/// * `to` - The directory to copy into, created if needed.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If `from` cannot be read or the copies cannot be written.
+pub fn copy_dir(from: &Path, to: &Path) -> Result<(), Error> {
+    let files = files_under(from)?;
+
+    match locate(to) {
+        Some((container, entry)) if !entry.is_empty() => {
+            update_container(&container, |container| {
+                for (suffix, contents) in files {
+                    container
+                        .files
+                        .insert(format!("{entry}/{suffix}"), contents);
+                }
+                Ok(())
+            })
+        }
+        _ => {
+            for (suffix, contents) in files {
+                let target = to.join(suffix);
+                if let Some(parent) = target.parent() {
+                    create_private_fs_dir(parent)?;
+                }
+                write_private_file(&target, &contents)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Finds the container `path` is stored in: the nearest directory above it, or the path itself,
+/// holding a `vault.fmpv`. The path inside the container is empty for that directory itself.
+fn locate(path: &Path) -> Option<(PathBuf, String)> {
+    path.ancestors().find_map(|ancestor| {
+        let container = ancestor.join(CONTAINER_FILE);
+        if !container.is_file() {
+            return None;
+        }
+
+        let components: Option<Vec<&str>> = path
+            .strip_prefix(ancestor)
+            .ok()?
+            .components()
+            .map(|component| component.as_os_str().to_str())
+            .collect();
+        Some((container, components?.join("/")))
+    })
+}
+
+fn list(dir: &Path, dirs: bool) -> Result<Vec<String>, Error> {
+    if let Some((container, entry)) = locate(dir) {
+        let prefix = if entry.is_empty() {
+            String::new()
+        } else {
+            format!("{entry}/")
+        };
+
+        return read_container(&container, |container| {
+            let names: BTreeSet<String> = container
+                .files
+                .keys()
+                .filter_map(|path| path.strip_prefix(&prefix))
+                .filter_map(|rest| match rest.split_once('/') {
+                    Some((name, _)) if dirs => Some(name.to_string()),
+                    None if !dirs => Some(rest.to_string()),
+                    _ => None,
+                })
+                .collect();
+            names.into_iter().collect()
+        });
+    }
+
+    let mut names = Vec::new();
+    for entry in read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() != dirs {
+            continue;
+        }
+        names.push(
+            entry
+                .file_name()
+                .into_string()
+                .map_err(|_| anyhow::anyhow!("Failed to convert file name to string."))?,
+        );
+    }
+
+    Ok(names)
+}
+
+/// Reads every file below a directory, with its path relative to the directory.
+///
+/// # Arguments
+/// * `dir` - The directory to read, such as an account directory.
+///
+/// # Returns
+/// * `Result<Vec<(String, Vec<u8>)>, Error>` - Returns each file's path, with `/` between
+///   directories, and contents on success, or an error on failure.
+///
+/// # Errors
+/// * If the directory, a file in it or its container cannot be read.
+pub fn files_under(dir: &Path) -> Result<Vec<(String, Vec<u8>)>, Error> {
+    if let Some((container, entry)) = locate(dir).filter(|(_, entry)| !entry.is_empty()) {
+        let prefix = format!("{entry}/");
+        return read_container(&container, |container| {
+            container
+                .files
+                .iter()
+                .filter_map(|(path, contents)| {
+                    Some((path.strip_prefix(&prefix)?.to_string(), contents.clone()))
+                })
+                .collect()
+        });
+    }
+
+    let mut files = Vec::new();
+    for entry in read_dir(dir)? {
+        let entry = entry?;
+        let name = entry
+            .file_name()
+            .into_string()
+            .map_err(|_| anyhow::anyhow!("Failed to convert file name to string."))?;
+
+        if entry.file_type()?.is_dir() {
+            for (suffix, contents) in files_under(&entry.path())? {
+                files.push((format!("{name}/{suffix}"), contents));
+            }
+        } else {
+            files.push((name, std::fs::read(entry.path())?));
+        }
+    }
+
+    Ok(files)
+}
+
+/// Removes a file, or a directory's files, from a container's files. Returns what was removed,
+/// keyed by what followed `entry` in each path.
+fn take_entries(files: &mut BTreeMap<String, Vec<u8>>, entry: &str) -> Vec<(String, Vec<u8>)> {
+    let prefix = format!("{entry}/");
+    let paths: Vec<String> = files
+        .keys()
+        .filter(|path| *path == entry || path.starts_with(&prefix))
+        .cloned()
+        .collect();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let contents = files.remove(&path)?;
+            Some((path[entry.len()..].to_string(), contents))
+        })
+        .collect()
+}
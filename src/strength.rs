@@ -0,0 +1,1118 @@
+//! Pattern-aware password strength estimation, modelled on zxcvbn.
+//!
+//! A password is split into the cheapest sequence of guessable patterns (dictionary words,
+//! keyboard walks, repeats, sequences, dates and random runs), and the number of guesses an
+//! attacker trying those patterns would need is estimated from it.
+
+/*
+Copyright (C) 2025  Luke Wilkinson
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+*/
+
+use chrono::Datelike;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+const COMMON_PASSWORDS: &str = include_str!("wordlists/common_passwords.txt");
+const ENGLISH_WORDS: &str = include_str!("wordlists/english_words.txt");
+const NAMES: &str = include_str!("wordlists/names.txt");
+
+/// Characters beyond this are only matched as part of a repeat or sequence that runs past it, and
+/// otherwise count as random digits.
+const MAX_ANALYSED_LENGTH: usize = 64;
+/// Longest dictionary word looked up.
+const MAX_WORD_LENGTH: usize = 16;
+/// Cost of each extra pattern in a sequence, so that splitting into many small matches is not free.
+const MIN_GUESSES_BEFORE_GROWING_SEQUENCE: f64 = 10_000.0;
+const BRUTEFORCE_CARDINALITY: f64 = 10.0;
+const MIN_SUBMATCH_GUESSES_SINGLE_CHAR: f64 = 10.0;
+const MIN_SUBMATCH_GUESSES_MULTI_CHAR: f64 = 50.0;
+const MIN_YEAR_SPACE: i32 = 20;
+/// Largest step between characters still treated as a sequence, as in `aceg` or `9753`.
+const MAX_SEQUENCE_DELTA: i32 = 5;
+/// Upper bound of log10(guesses) for each score, and the percentage reached at that bound.
+/// Scores 0 and 1 share the bottom quarter so that percentages line up with the strength colours.
+const PERCENT_STEPS: &[(f64, f64)] = &[(6.0, 25.0), (8.0, 50.0), (10.0, 75.0), (14.0, 100.0)];
+
+/// Substitutions attackers try when guessing l33t-speak words.
+const L33T_TABLE: &[(char, &[char])] = &[
+    ('a', &['4', '@']),
+    ('b', &['8']),
+    ('c', &['(', '{', '[', '<']),
+    ('e', &['3']),
+    ('g', &['6', '9']),
+    ('i', &['1', '!', '|']),
+    ('l', &['1', '|', '7']),
+    ('o', &['0']),
+    ('s', &['$', '5']),
+    ('t', &['+', '7']),
+    ('x', &['%']),
+    ('z', &['2']),
+];
+
+/// Keyboard rows as (unshifted, shifted) characters and the horizontal offset of the row.
+const QWERTY_ROWS: &[(&str, &str, f64)] = &[
+    ("`1234567890-=", "~!@#$%^&*()_+", 0.0),
+    ("qwertyuiop[]\\", "QWERTYUIOP{}|", 1.5),
+    ("asdfghjkl;'", "ASDFGHJKL:\"", 1.75),
+    ("zxcvbnm,./", "ZXCVBNM<>?", 2.25),
+];
+
+/// Number pad keys and their (row, column) positions.
+const KEYPAD_KEYS: &[(char, i32, i32)] = &[
+    ('/', 0, 1),
+    ('*', 0, 2),
+    ('-', 0, 3),
+    ('7', 1, 0),
+    ('8', 1, 1),
+    ('9', 1, 2),
+    ('+', 1, 3),
+    ('4', 2, 0),
+    ('5', 2, 1),
+    ('6', 2, 2),
+    ('1', 3, 0),
+    ('2', 3, 1),
+    ('3', 3, 2),
+    ('0', 4, 0),
+    ('.', 4, 2),
+];
+
+/// The word lists a dictionary match can come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dictionary {
+    CommonPasswords,
+    EnglishWords,
+    Names,
+    Passphrase,
+}
+
+/// The kind of pattern a part of the password matched.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Dictionary {
+        dictionary: Dictionary,
+        word: String,
+        rank: usize,
+        reversed: bool,
+        l33t: bool,
+    },
+    Spatial {
+        keyboard: &'static str,
+        turns: usize,
+        shifted: usize,
+    },
+    Repeat {
+        base: String,
+        count: usize,
+    },
+    Sequence {
+        ascending: bool,
+    },
+    Year,
+    Date {
+        year: i32,
+        separator: bool,
+    },
+    Bruteforce,
+}
+
+/// A run of characters, `start..=end` in chars, matched to a pattern.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternMatch {
+    pub start: usize,
+    pub end: usize,
+    pub token: String,
+    pub pattern: Pattern,
+    pub guesses: f64,
+}
+
+/// Estimated seconds to guess a password under different attacks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CrackTimes {
+    /// A rate-limited login form, 100 guesses per hour.
+    pub online_throttled: f64,
+    /// An unlimited login form, 10 guesses per second.
+    pub online_unthrottled: f64,
+    /// A stolen database with a slow hash, 10,000 guesses per second.
+    pub offline_slow: f64,
+    /// A stolen database with a fast hash, 10 billion guesses per second.
+    pub offline_fast: f64,
+}
+
+/// The result of estimating a password's strength.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrengthEstimate {
+    pub guesses: f64,
+    pub guesses_log10: f64,
+    /// zxcvbn-style score from 0 (too guessable) to 4 (very unguessable).
+    pub score: u8,
+    /// Strength from 0 to 100, for progress bars.
+    pub percent: u8,
+    pub crack_times: CrackTimes,
+    pub warning: Option<&'static str>,
+    pub suggestions: Vec<&'static str>,
+    /// The patterns the estimate was based on, in password order.
+    pub sequence: Vec<PatternMatch>,
+}
+
+struct RankedDictionary {
+    dictionary: Dictionary,
+    ranks: HashMap<&'static str, usize>,
+}
+
+static DICTIONARIES: LazyLock<Vec<RankedDictionary>> = LazyLock::new(|| {
+    let ranked = |dictionary, list: &'static str| {
+        let mut ranks = HashMap::new();
+        let words = list.lines().map(str::trim).filter(|word| !word.is_empty());
+        for (index, word) in words.enumerate() {
+            ranks.entry(word).or_insert(index + 1);
+        }
+        RankedDictionary { dictionary, ranks }
+    };
+
+    // The EFF list is alphabetical, so every word is given the same rank: the size of the list.
+    let passphrase_words = crate::password::eff_wordlist();
+    let passphrase = RankedDictionary {
+        dictionary: Dictionary::Passphrase,
+        ranks: passphrase_words
+            .iter()
+            .map(|word| (*word, passphrase_words.len()))
+            .collect(),
+    };
+
+    vec![
+        ranked(Dictionary::CommonPasswords, COMMON_PASSWORDS),
+        ranked(Dictionary::EnglishWords, ENGLISH_WORDS),
+        ranked(Dictionary::Names, NAMES),
+        passphrase,
+    ]
+});
+
+struct KeyboardGraph {
+    name: &'static str,
+    /// Each character's key position and whether it needs shift.
+    keys: HashMap<char, ((i32, i32), bool)>,
+    starting_positions: f64,
+    average_degree: f64,
+    adjacent: fn((i32, i32), (i32, i32)) -> bool,
+}
+
+static KEYBOARDS: LazyLock<Vec<KeyboardGraph>> = LazyLock::new(|| {
+    // Qwerty keys are placed on a grid of quarter-key columns so that rows can be staggered.
+    let mut qwerty = HashMap::new();
+    for (row, (unshifted, shifted, offset)) in QWERTY_ROWS.iter().enumerate() {
+        for (column, (plain, shift)) in unshifted.chars().zip(shifted.chars()).enumerate() {
+            #[allow(clippy::cast_possible_truncation)]
+            let position = (row as i32, ((column as f64 + offset) * 4.0) as i32);
+            qwerty.insert(plain, (position, false));
+            qwerty.insert(shift, (position, true));
+        }
+    }
+    let qwerty_adjacent = |a: (i32, i32), b: (i32, i32)| {
+        let column_distance = (a.1 - b.1).abs();
+        match (a.0 - b.0).abs() {
+            0 => column_distance == 4,
+            1 => column_distance < 4,
+            _ => false,
+        }
+    };
+
+    let keypad = KEYPAD_KEYS
+        .iter()
+        .map(|(key, row, column)| (*key, ((*row, *column), false)))
+        .collect();
+    let keypad_adjacent =
+        |a: (i32, i32), b: (i32, i32)| a != b && (a.0 - b.0).abs() <= 1 && (a.1 - b.1).abs() <= 1;
+
+    [
+        ("qwerty", qwerty, qwerty_adjacent as fn(_, _) -> bool),
+        ("keypad", keypad, keypad_adjacent as fn(_, _) -> bool),
+    ]
+    .into_iter()
+    .map(|(name, keys, adjacent)| {
+        let mut positions: Vec<(i32, i32)> = keys
+            .values()
+            .map(|(position, _)| *position)
+            .collect::<Vec<_>>();
+        positions.sort_unstable();
+        positions.dedup();
+
+        let degrees: usize = positions
+            .iter()
+            .map(|a| positions.iter().filter(|b| adjacent(*a, **b)).count())
+            .sum();
+
+        KeyboardGraph {
+            name,
+            keys,
+            starting_positions: positions.len() as f64,
+            average_degree: degrees as f64 / positions.len() as f64,
+            adjacent,
+        }
+    })
+    .collect()
+});
+
+/// Estimates how hard a password is to guess.
+///
+/// # Arguments
+/// * `password` - The password to analyse.
+///
+/// # Returns
+/// * `StrengthEstimate` - Estimated guesses, crack times, a score and feedback.
+pub fn estimate_password_strength(password: &str) -> StrengthEstimate {
+    let chars: Vec<char> = password.chars().collect();
+    let (guesses, sequence) = long_guessable_sequence(&chars);
+
+    let estimate = StrengthEstimate::from_guesses(guesses);
+    let (warning, suggestions) = feedback(&sequence, estimate.score);
+
+    StrengthEstimate {
+        warning,
+        suggestions,
+        sequence,
+        ..estimate
+    }
+}
+
+impl StrengthEstimate {
+    /// Builds an estimate from a known number of guesses, such as 2^bits for generated passphrases.
+    ///
+    /// # Arguments
+    /// * `guesses` - The number of guesses needed.
+    ///
+    /// # Returns
+    /// * `StrengthEstimate` - An estimate without pattern feedback.
+    pub fn from_guesses(guesses: f64) -> Self {
+        let guesses = guesses.clamp(1.0, f64::MAX);
+        let guesses_log10 = guesses.log10();
+
+        let score = match guesses {
+            g if g < 1e3 + 5.0 => 0,
+            g if g < 1e6 + 5.0 => 1,
+            g if g < 1e8 + 5.0 => 2,
+            g if g < 1e10 + 5.0 => 3,
+            _ => 4,
+        };
+
+        let mut percent = 100.0;
+        let mut previous = (0.0, 0.0);
+        for &(log10, step_percent) in PERCENT_STEPS {
+            if guesses_log10 < log10 {
+                percent = previous.1
+                    + (guesses_log10 - previous.0) / (log10 - previous.0)
+                        * (step_percent - previous.1);
+                break;
+            }
+            previous = (log10, step_percent);
+        }
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let percent = percent.round().clamp(0.0, 100.0) as u8;
+
+        Self {
+            guesses,
+            guesses_log10,
+            score,
+            percent,
+            crack_times: CrackTimes {
+                online_throttled: guesses / (100.0 / 3600.0),
+                online_unthrottled: guesses / 10.0,
+                offline_slow: guesses / 1e4,
+                offline_fast: guesses / 1e10,
+            },
+            warning: None,
+            suggestions: Vec::new(),
+            sequence: Vec::new(),
+        }
+    }
+}
+
+/// Formats a crack time as rough human-readable text, such as "3 hours" or "centuries".
+///
+/// # Arguments
+/// * `seconds` - The crack time in seconds.
+///
+/// # Returns
+/// * `String` - The formatted duration.
+pub fn display_crack_time(seconds: f64) -> String {
+    const MINUTE: f64 = 60.0;
+    const HOUR: f64 = MINUTE * 60.0;
+    const DAY: f64 = HOUR * 24.0;
+    const MONTH: f64 = DAY * 31.0;
+    const YEAR: f64 = MONTH * 12.0;
+    const CENTURY: f64 = YEAR * 100.0;
+
+    let (amount, unit) = match seconds {
+        s if s < 1.0 => return "less than a second".to_string(),
+        s if s < MINUTE => (s, "second"),
+        s if s < HOUR => (s / MINUTE, "minute"),
+        s if s < DAY => (s / HOUR, "hour"),
+        s if s < MONTH => (s / DAY, "day"),
+        s if s < YEAR => (s / MONTH, "month"),
+        s if s < CENTURY => (s / YEAR, "year"),
+        _ => return "centuries".to_string(),
+    };
+
+    let amount = amount.round();
+    if (amount - 1.0).abs() < f64::EPSILON {
+        format!("1 {unit}")
+    } else {
+        format!("{amount} {unit}s")
+    }
+}
+
+/// Finds the sequence of non-overlapping matches covering the password with the fewest guesses.
+fn most_guessable_sequence(chars: &[char]) -> (f64, Vec<PatternMatch>) {
+    let length = chars.len();
+    if length == 0 {
+        return (1.0, Vec::new());
+    }
+
+    let mut matches_by_end: Vec<Vec<PatternMatch>> = vec![Vec::new(); length];
+    for found in find_matches(chars) {
+        matches_by_end[found.end].push(found);
+    }
+
+    // best[k] maps a sequence length to (total guesses, product of guesses, last match)
+    // for the best sequence of that length covering chars[..=k].
+    let mut best: Vec<HashMap<usize, (f64, f64, PatternMatch)>> = vec![HashMap::new(); length];
+
+    let consider = |best: &mut Vec<HashMap<usize, (f64, f64, PatternMatch)>>,
+                    candidate: PatternMatch,
+                    sequence_length: usize| {
+        let end = candidate.end;
+        let mut product = adjusted_guesses(&candidate, length);
+        if sequence_length > 1 {
+            let Some((_, previous_product, _)) =
+                best[candidate.start - 1].get(&(sequence_length - 1))
+            else {
+                return;
+            };
+            product *= previous_product;
+        }
+
+        let total = factorial(sequence_length) * product
+            + MIN_GUESSES_BEFORE_GROWING_SEQUENCE
+                .powi(i32::try_from(sequence_length - 1).unwrap_or(i32::MAX));
+
+        // A shorter sequence that is at least as cheap always wins.
+        let beaten = best[end].iter().any(|(other_length, (other_total, _, _))| {
+            *other_length <= sequence_length && *other_total <= total
+        });
+        if !beaten {
+            best[end].insert(sequence_length, (total, product, candidate));
+        }
+    };
+
+    for (end, ending_here) in matches_by_end.into_iter().enumerate() {
+        for found in ending_here {
+            if found.start == 0 {
+                consider(&mut best, found, 1);
+            } else {
+                let lengths: Vec<usize> = best[found.start - 1].keys().copied().collect();
+                for sequence_length in lengths {
+                    consider(&mut best, found.clone(), sequence_length + 1);
+                }
+            }
+        }
+
+        consider(&mut best, bruteforce_match(chars, 0, end), 1);
+        for start in 1..=end {
+            let lengths: Vec<usize> = best[start - 1]
+                .iter()
+                .filter(|(_, (_, _, last))| last.pattern != Pattern::Bruteforce)
+                .map(|(sequence_length, _)| *sequence_length)
+                .collect();
+            for sequence_length in lengths {
+                consider(
+                    &mut best,
+                    bruteforce_match(chars, start, end),
+                    sequence_length + 1,
+                );
+            }
+        }
+    }
+
+    let Some((&sequence_length, &(guesses, _, _))) = best[length - 1]
+        .iter()
+        .min_by(|a, b| a.1.0.total_cmp(&b.1.0))
+    else {
+        return (1.0, Vec::new());
+    };
+
+    let mut sequence = Vec::with_capacity(sequence_length);
+    let mut end = length - 1;
+    let mut remaining = sequence_length;
+    while remaining > 0 {
+        let found = best[end][&remaining].2.clone();
+        remaining -= 1;
+        if found.start > 0 {
+            end = found.start - 1;
+        }
+        sequence.push(found);
+    }
+    sequence.reverse();
+
+    (guesses, sequence)
+}
+
+/// Finds the least guessable sequence of a password of any length. Only the first
+/// `MAX_ANALYSED_LENGTH` characters are fully pattern-matched, but a repeat or sequence that starts
+/// there is followed to its end, so padding a weak password with more of the same stays weak.
+fn long_guessable_sequence(chars: &[char]) -> (f64, Vec<PatternMatch>) {
+    if chars.len() <= MAX_ANALYSED_LENGTH {
+        return most_guessable_sequence(chars);
+    }
+
+    let crossing = repeat_matches(chars)
+        .into_iter()
+        .chain(sequence_matches(chars))
+        .filter(|found| found.start < MAX_ANALYSED_LENGTH && found.end >= MAX_ANALYSED_LENGTH)
+        .max_by_key(|found| found.end);
+
+    let Some(crossing) = crossing else {
+        let (guesses, sequence) = most_guessable_sequence(&chars[..MAX_ANALYSED_LENGTH]);
+        // Anything past the analysed prefix is treated as random digits.
+        let tail = i32::try_from(chars.len() - MAX_ANALYSED_LENGTH).unwrap_or(i32::MAX);
+        return (guesses * BRUTEFORCE_CARDINALITY.powi(tail), sequence);
+    };
+
+    let (before_guesses, mut sequence) = most_guessable_sequence(&chars[..crossing.start]);
+    let (after_guesses, after) = long_guessable_sequence(&chars[crossing.end + 1..]);
+    let offset = crossing.end + 1;
+    let guesses = before_guesses * crossing.guesses * after_guesses;
+
+    sequence.push(crossing);
+    sequence.extend(after.into_iter().map(|found| PatternMatch {
+        start: found.start + offset,
+        end: found.end + offset,
+        ..found
+    }));
+
+    (guesses, sequence)
+}
+
+/// Applies the floor zxcvbn uses so that short matches inside longer passwords are never free.
+fn adjusted_guesses(found: &PatternMatch, password_length: usize) -> f64 {
+    let token_length = found.end - found.start + 1;
+    if token_length < password_length {
+        let floor = if token_length == 1 {
+            MIN_SUBMATCH_GUESSES_SINGLE_CHAR
+        } else {
+            MIN_SUBMATCH_GUESSES_MULTI_CHAR
+        };
+        found.guesses.max(floor)
+    } else {
+        found.guesses
+    }
+}
+
+fn bruteforce_match(chars: &[char], start: usize, end: usize) -> PatternMatch {
+    let token_length = end - start + 1;
+    let floor = if token_length == 1 {
+        MIN_SUBMATCH_GUESSES_SINGLE_CHAR + 1.0
+    } else {
+        MIN_SUBMATCH_GUESSES_MULTI_CHAR + 1.0
+    };
+
+    PatternMatch {
+        start,
+        end,
+        token: chars[start..=end].iter().collect(),
+        pattern: Pattern::Bruteforce,
+        guesses: BRUTEFORCE_CARDINALITY
+            .powi(i32::try_from(token_length).unwrap_or(i32::MAX))
+            .max(floor),
+    }
+}
+
+fn find_matches(chars: &[char]) -> Vec<PatternMatch> {
+    let mut matches = Vec::new();
+    matches.extend(dictionary_matches(chars));
+    matches.extend(reverse_dictionary_matches(chars));
+    matches.extend(l33t_matches(chars));
+    matches.extend(spatial_matches(chars));
+    matches.extend(repeat_matches(chars));
+    matches.extend(sequence_matches(chars));
+    matches.extend(date_matches(chars));
+    matches
+}
+
+fn dictionary_matches(chars: &[char]) -> Vec<PatternMatch> {
+    let lowered: Vec<char> = chars.iter().flat_map(|c| c.to_lowercase()).collect();
+    // Lowercasing can change the length of non-ASCII text; skip dictionaries rather than misalign.
+    if lowered.len() != chars.len() {
+        return Vec::new();
+    }
+
+    let mut matches = Vec::new();
+    for start in 0..chars.len() {
+        for end in (start + 2)..chars.len().min(start + MAX_WORD_LENGTH) {
+            let word: String = lowered[start..=end].iter().collect();
+            let token: String = chars[start..=end].iter().collect();
+
+            for dictionary in DICTIONARIES.iter() {
+                if let Some(&rank) = dictionary.ranks.get(word.as_str()) {
+                    matches.push(PatternMatch {
+                        start,
+                        end,
+                        guesses: rank as f64 * uppercase_variations(&token),
+                        token: token.clone(),
+                        pattern: Pattern::Dictionary {
+                            dictionary: dictionary.dictionary,
+                            word: word.clone(),
+                            rank,
+                            reversed: false,
+                            l33t: false,
+                        },
+                    });
+                }
+            }
+        }
+    }
+
+    matches
+}
+
+fn reverse_dictionary_matches(chars: &[char]) -> Vec<PatternMatch> {
+    let reversed: Vec<char> = chars.iter().rev().copied().collect();
+    let last = chars.len().saturating_sub(1);
+
+    dictionary_matches(&reversed)
+        .into_iter()
+        .filter_map(|mut found| {
+            let token: String = found.token.chars().rev().collect();
+            // Palindromes are already found forwards.
+            if token == found.token {
+                return None;
+            }
+
+            let (start, end) = (last - found.end, last - found.start);
+            found.start = start;
+            found.end = end;
+            found.token = token;
+            found.guesses *= 2.0;
+            if let Pattern::Dictionary { reversed, .. } = &mut found.pattern {
+                *reversed = true;
+            }
+            Some(found)
+        })
+        .collect()
+}
+
+fn l33t_matches(chars: &[char]) -> Vec<PatternMatch> {
+    // Every l33t character in the password, with the letters it could stand for.
+    let mut choices: Vec<(char, Vec<char>)> = Vec::new();
+    for &c in chars {
+        if choices.iter().any(|(l33t, _)| *l33t == c) {
+            continue;
+        }
+        let letters: Vec<char> = L33T_TABLE
+            .iter()
+            .filter(|(_, subs)| subs.contains(&c))
+            .map(|(letter, _)| *letter)
+            .collect();
+        if !letters.is_empty() {
+            choices.push((c, letters));
+        }
+    }
+    if choices.is_empty() {
+        return Vec::new();
+    }
+
+    // Try each combination of ambiguous substitutions, such as '1' for 'i' or 'l'.
+    let mut substitutions: Vec<Vec<(char, char)>> = vec![Vec::new()];
+    for (l33t, letters) in &choices {
+        substitutions = substitutions
+            .into_iter()
+            .flat_map(|existing| {
+                letters.iter().map(move |letter| {
+                    let mut next = existing.clone();
+                    next.push((*l33t, *letter));
+                    next
+                })
+            })
+            .take(64)
+            .collect();
+    }
+
+    let mut matches = Vec::new();
+    for substitution in substitutions {
+        let translated: Vec<char> = chars
+            .iter()
+            .map(|c| {
+                substitution
+                    .iter()
+                    .find(|(l33t, _)| l33t == c)
+                    .map_or(*c, |(_, letter)| *letter)
+            })
+            .collect();
+
+        for mut found in dictionary_matches(&translated) {
+            let original: String = chars[found.start..=found.end].iter().collect();
+            let used: Vec<(char, char)> = substitution
+                .iter()
+                .filter(|(l33t, _)| original.contains(*l33t))
+                .copied()
+                .collect();
+            if used.is_empty() {
+                continue;
+            }
+
+            found.guesses *= l33t_variations(&original, &used);
+            found.token = original;
+            if let Pattern::Dictionary { l33t, .. } = &mut found.pattern {
+                *l33t = true;
+            }
+            matches.push(found);
+        }
+    }
+
+    matches
+}
+
+fn spatial_matches(chars: &[char]) -> Vec<PatternMatch> {
+    let mut matches = Vec::new();
+
+    for keyboard in KEYBOARDS.iter() {
+        let mut start = 0;
+        while start + 2 < chars.len() {
+            let Some(&(first, first_shifted)) = keyboard.keys.get(&chars[start]) else {
+                start += 1;
+                continue;
+            };
+
+            let mut end = start;
+            let mut previous = first;
+            let mut direction = None;
+            let mut turns = 0;
+            let mut shifted = usize::from(first_shifted);
+
+            while let Some(&(position, is_shifted)) =
+                chars.get(end + 1).and_then(|c| keyboard.keys.get(c))
+            {
+                if !(keyboard.adjacent)(previous, position) {
+                    break;
+                }
+                let step = (
+                    (position.0 - previous.0).signum(),
+                    (position.1 - previous.1).signum(),
+                );
+                if direction != Some(step) {
+                    turns += 1;
+                    direction = Some(step);
+                }
+                shifted += usize::from(is_shifted);
+                previous = position;
+                end += 1;
+            }
+
+            if end - start >= 2 {
+                let token: String = chars[start..=end].iter().collect();
+                matches.push(PatternMatch {
+                    start,
+                    end,
+                    guesses: spatial_guesses(keyboard, end - start + 1, turns, shifted),
+                    token,
+                    pattern: Pattern::Spatial {
+                        keyboard: keyboard.name,
+                        turns,
+                        shifted,
+                    },
+                });
+                start = end;
+            } else {
+                start += 1;
+            }
+        }
+    }
+
+    matches
+}
+
+fn spatial_guesses(keyboard: &KeyboardGraph, length: usize, turns: usize, shifted: usize) -> f64 {
+    let mut guesses = 0.0;
+    for i in 2..=length {
+        for j in 1..=turns.min(i - 1) {
+            guesses += binomial(i - 1, j - 1)
+                * keyboard.starting_positions
+                * keyboard
+                    .average_degree
+                    .powi(i32::try_from(j).unwrap_or(i32::MAX));
+        }
+    }
+
+    let unshifted = length - shifted;
+    if shifted == 0 {
+        guesses
+    } else if unshifted == 0 {
+        guesses * 2.0
+    } else {
+        guesses
+            * (1..=shifted.min(unshifted))
+                .map(|i| binomial(shifted + unshifted, i))
+                .sum::<f64>()
+    }
+}
+
+fn repeat_matches(chars: &[char]) -> Vec<PatternMatch> {
+    let mut matches = Vec::new();
+    let mut start = 0;
+
+    while start + 1 < chars.len() {
+        // The repeat covering the most characters, preferring the shortest base.
+        let mut best: Option<(usize, usize)> = None;
+        for base_length in 1..=(chars.len() - start) / 2 {
+            let base = &chars[start..start + base_length];
+            let mut count = 1;
+            while chars[start + count * base_length..]
+                .get(..base_length)
+                .is_some_and(|next| next == base)
+            {
+                count += 1;
+            }
+            if count >= 2
+                && best.is_none_or(|(length, best_count)| base_length * count > length * best_count)
+            {
+                best = Some((base_length, count));
+            }
+        }
+
+        let Some((base_length, count)) = best else {
+            start += 1;
+            continue;
+        };
+
+        let base: String = chars[start..start + base_length].iter().collect();
+        let base_chars: Vec<char> = base.chars().collect();
+        let (base_guesses, _) = long_guessable_sequence(&base_chars);
+        let end = start + base_length * count - 1;
+
+        matches.push(PatternMatch {
+            start,
+            end,
+            token: chars[start..=end].iter().collect(),
+            guesses: base_guesses * count as f64,
+            pattern: Pattern::Repeat { base, count },
+        });
+        start = end + 1;
+    }
+
+    matches
+}
+
+fn sequence_matches(chars: &[char]) -> Vec<PatternMatch> {
+    let mut matches = Vec::new();
+    if chars.len() < 3 {
+        return matches;
+    }
+
+    let delta = |i: usize| chars[i + 1] as i32 - chars[i] as i32;
+    let mut start = 0;
+    while start + 2 < chars.len() {
+        let step = delta(start);
+        let mut end = start + 1;
+        while end + 1 < chars.len() && delta(end) == step {
+            end += 1;
+        }
+
+        if step != 0 && step.abs() <= MAX_SEQUENCE_DELTA && end - start >= 2 {
+            let token: String = chars[start..=end].iter().collect();
+            let first = chars[start];
+            let mut base = if "aAzZ019".contains(first) {
+                4.0
+            } else if first.is_ascii_digit() {
+                10.0
+            } else {
+                26.0
+            };
+            if step < 0 {
+                base *= 2.0;
+            }
+
+            matches.push(PatternMatch {
+                start,
+                end,
+                guesses: base * (end - start + 1) as f64,
+                token,
+                pattern: Pattern::Sequence {
+                    ascending: step > 0,
+                },
+            });
+            start = end;
+        } else {
+            start += 1;
+        }
+    }
+
+    matches
+}
+
+fn date_matches(chars: &[char]) -> Vec<PatternMatch> {
+    let reference_year = chrono::Utc::now().year();
+    let year_space = |year: i32| f64::from((year - reference_year).abs().max(MIN_YEAR_SPACE));
+    let mut matches = Vec::new();
+
+    for start in 0..chars.len() {
+        for end in (start + 3)..chars.len().min(start + 10) {
+            let token: String = chars[start..=end].iter().collect();
+            let all_digits = token.chars().all(|c| c.is_ascii_digit());
+
+            if all_digits && token.len() == 4 {
+                let year: i32 = token.parse().unwrap_or(0);
+                if (1900..=2099).contains(&year) {
+                    matches.push(PatternMatch {
+                        start,
+                        end,
+                        token: token.clone(),
+                        pattern: Pattern::Year,
+                        guesses: year_space(year),
+                    });
+                }
+            }
+
+            let Some((year, separator)) = parse_date(&token, all_digits, reference_year) else {
+                continue;
+            };
+            let multiplier = if separator { 4.0 } else { 1.0 };
+            matches.push(PatternMatch {
+                start,
+                end,
+                token,
+                pattern: Pattern::Date { year, separator },
+                guesses: 365.0 * year_space(year) * multiplier,
+            });
+        }
+    }
+
+    matches
+}
+
+/// Reads a token as a day, month and year, returning the year closest to `reference_year`.
+fn parse_date(token: &str, all_digits: bool, reference_year: i32) -> Option<(i32, bool)> {
+    let parts: Vec<&str> = if all_digits {
+        if !(4..=8).contains(&token.len()) {
+            return None;
+        }
+        let splits: &[(usize, usize)] = match token.len() {
+            4 => &[(1, 2), (2, 3)],
+            5 => &[(1, 3), (2, 3)],
+            6 => &[(1, 2), (2, 4), (4, 5)],
+            7 => &[(1, 3), (2, 3), (4, 5), (4, 6)],
+            _ => &[(2, 4), (4, 6)],
+        };
+        return splits
+            .iter()
+            .filter_map(|(k, l)| date_from_parts([&token[..*k], &token[*k..*l], &token[*l..]]))
+            .min_by_key(|year| (year - reference_year).abs())
+            .map(|year| (year, false));
+    } else {
+        let separator = token.chars().find(|c| !c.is_ascii_digit())?;
+        if !" /\\_.-".contains(separator) {
+            return None;
+        }
+        token.split(separator).collect()
+    };
+
+    if parts.len() != 3
+        || parts
+            .iter()
+            .any(|part| part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()))
+    {
+        return None;
+    }
+    date_from_parts([parts[0], parts[1], parts[2]]).map(|year| (year, true))
+}
+
+fn date_from_parts(parts: [&str; 3]) -> Option<i32> {
+    let numbers: Vec<i32> = parts
+        .iter()
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    let valid_day_month = |a: i32, b: i32| {
+        ((1..=31).contains(&a) && (1..=12).contains(&b))
+            || ((1..=12).contains(&a) && (1..=31).contains(&b))
+    };
+    let to_year = |part: &str, value: i32| match part.len() {
+        1 | 2 => Some(if value > 50 {
+            1900 + value
+        } else {
+            2000 + value
+        }),
+        4 if (1000..=2099).contains(&value) => Some(value),
+        _ => None,
+    };
+
+    // Year last (day/month/year), then year first (year/month/day).
+    if parts[0].len() <= 2
+        && parts[1].len() <= 2
+        && valid_day_month(numbers[0], numbers[1])
+        && let Some(year) = to_year(parts[2], numbers[2])
+    {
+        return Some(year);
+    }
+    if parts[1].len() <= 2 && parts[2].len() <= 2 && valid_day_month(numbers[1], numbers[2]) {
+        return to_year(parts[0], numbers[0]);
+    }
+    None
+}
+
+fn uppercase_variations(token: &str) -> f64 {
+    let upper = token.chars().filter(char::is_ascii_uppercase).count();
+    let lower = token.chars().filter(char::is_ascii_lowercase).count();
+    if upper == 0 {
+        return 1.0;
+    }
+
+    let first_upper = token.chars().next().is_some_and(|c| c.is_ascii_uppercase());
+    let last_upper = token.chars().last().is_some_and(|c| c.is_ascii_uppercase());
+    if lower == 0 || (upper == 1 && (first_upper || last_upper)) {
+        return 2.0;
+    }
+
+    (1..=upper.min(lower))
+        .map(|i| binomial(upper + lower, i))
+        .sum()
+}
+
+fn l33t_variations(token: &str, substitutions: &[(char, char)]) -> f64 {
+    let lowered = token.to_lowercase();
+    substitutions
+        .iter()
+        .map(|(l33t, letter)| {
+            let subbed = lowered.chars().filter(|c| c == l33t).count();
+            let unsubbed = lowered.chars().filter(|c| c == letter).count();
+            if unsubbed == 0 {
+                2.0
+            } else {
+                (1..=subbed.min(unsubbed))
+                    .map(|i| binomial(subbed + unsubbed, i))
+                    .sum()
+            }
+        })
+        .product()
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    if k > n {
+        return 0.0;
+    }
+    (1..=k).fold(1.0, |result, i| result * (n + 1 - i) as f64 / i as f64)
+}
+
+fn factorial(n: usize) -> f64 {
+    (2..=n).map(|i| i as f64).product()
+}
+
+fn feedback(sequence: &[PatternMatch], score: u8) -> (Option<&'static str>, Vec<&'static str>) {
+    if sequence.is_empty() {
+        return (
+            None,
+            vec![
+                "Use a few words, avoid common phrases",
+                "No need for symbols, digits, or uppercase letters",
+            ],
+        );
+    }
+    if score > 2 {
+        return (None, Vec::new());
+    }
+
+    let mut suggestions = vec!["Add another word or two. Uncommon words are better."];
+    let Some(longest) = sequence
+        .iter()
+        .max_by_key(|found| found.token.chars().count())
+    else {
+        return (None, suggestions);
+    };
+    let sole_match = sequence.len() == 1;
+
+    let warning = match &longest.pattern {
+        Pattern::Dictionary {
+            dictionary,
+            rank,
+            reversed,
+            l33t,
+            ..
+        } => {
+            if longest.token.chars().next().is_some_and(char::is_uppercase) {
+                suggestions.push("Capitalization doesn't help very much");
+            } else if longest.token.chars().any(char::is_alphabetic)
+                && longest.token.chars().all(|c| !c.is_lowercase())
+            {
+                suggestions.push("All-uppercase is almost as easy to guess as all-lowercase");
+            }
+            if *reversed {
+                suggestions.push("Reversed words aren't much harder to guess");
+            }
+            if *l33t {
+                suggestions
+                    .push("Predictable substitutions like '@' instead of 'a' don't help very much");
+            }
+
+            match dictionary {
+                Dictionary::CommonPasswords if sole_match && !*l33t && !*reversed => {
+                    Some(match rank {
+                        ..=10 => "This is a top-10 common password",
+                        ..=100 => "This is a top-100 common password",
+                        _ => "This is a very common password",
+                    })
+                }
+                Dictionary::CommonPasswords => Some("This is similar to a commonly used password"),
+                Dictionary::EnglishWords | Dictionary::Passphrase if sole_match => {
+                    Some("A word by itself is easy to guess")
+                }
+                Dictionary::Names if sole_match => {
+                    Some("Names and surnames by themselves are easy to guess")
+                }
+                Dictionary::Names => Some("Common names and surnames are easy to guess"),
+                _ => None,
+            }
+        }
+        Pattern::Spatial { turns, .. } => {
+            suggestions.push("Use a longer keyboard pattern with more turns");
+            Some(if *turns == 1 {
+                "Straight rows of keys are easy to guess"
+            } else {
+                "Short keyboard patterns are easy to guess"
+            })
+        }
+        Pattern::Repeat { base, .. } => {
+            suggestions.push("Avoid repeated words and characters");
+            Some(if base.chars().count() == 1 {
+                "Repeats like \"aaa\" are easy to guess"
+            } else {
+                "Repeats like \"abcabcabc\" are only slightly harder to guess than \"abc\""
+            })
+        }
+        Pattern::Sequence { .. } => {
+            suggestions.push("Avoid sequences");
+            Some("Sequences like abc or 6543 are easy to guess")
+        }
+        Pattern::Year => {
+            suggestions.push("Avoid recent years");
+            suggestions.push("Avoid years that are associated with you");
+            Some("Recent years are easy to guess")
+        }
+        Pattern::Date { .. } => {
+            suggestions.push("Avoid dates and years that are associated with you");
+            Some("Dates are often easy to guess")
+        }
+        Pattern::Bruteforce => None,
+    };
+
+    (warning, suggestions)
+}
@@ -0,0 +1,58 @@
+use crate::storage::account_names::*;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_ids_are_stable_per_key_and_opaque() {
+    let mut index = NameIndex::generate();
+    let id = index.id_for("bank-of-x");
+
+    assert_eq!(id, index.id_for("bank-of-x"));
+    assert_ne!(id, index.id_for("work-vpn"));
+    assert_eq!(id.len(), 32);
+    assert!(id.chars().all(|c| c.is_ascii_hexdigit()));
+    assert!(!id.contains("bank"));
+
+    // Another vault's key gives the same name another ID
+    assert_ne!(id, NameIndex::generate().id_for("bank-of-x"));
+
+    assert_eq!(index.name_for(&id), None);
+    assert_eq!(index.insert("bank-of-x"), id);
+    assert_eq!(index.name_for(&id), Some("bank-of-x"));
+
+    let parsed: NameIndex = serde_json::from_str(&serde_json::to_string(&index).unwrap()).unwrap();
+    assert_eq!(parsed, index);
+}
+
+#[test]
+fn test_vaults_without_an_index_use_account_names() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path();
+
+    assert!(!hides_names(vault));
+    assert_eq!(account_dir(vault, "bank").unwrap(), vault.join("bank"));
+    assert_eq!(
+        account_names_for_dirs(vault, vec!["bank".to_string(), "mail".to_string()]),
+        vec!["bank".to_string(), "mail".to_string()]
+    );
+    // Nothing to record or forget
+    record_account_name(vault, "bank").unwrap();
+    forget_removed_accounts(vault).unwrap();
+    assert!(!vault.join(NAMES_FILE).exists());
+}
+
+#[test]
+fn test_unreadable_index_never_points_at_a_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path();
+    fs::write(vault.join(NAMES_FILE), b"not encrypted").unwrap();
+    fs::create_dir(vault.join("0123456789abcdef0123456789abcdef")).unwrap();
+
+    assert!(hides_names(vault));
+    assert!(account_dir(vault, "bank").is_err());
+    // Directories are still listed, under the only names known for them
+    assert_eq!(
+        account_name_for_dir(vault, "0123456789abcdef0123456789abcdef"),
+        "0123456789abcdef0123456789abcdef"
+    );
+}
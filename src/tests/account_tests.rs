@@ -1,4 +1,4 @@
-use crate::models::account::Account;
+use crate::models::account::{Account, MAX_PASSWORD_HISTORY};
 use crate::security::secure_password::SecurePassword;
 
 #[test]
@@ -221,3 +221,147 @@ fn test_account_unicode_fields() {
     assert_eq!(account.notes, deserialized.notes);
     assert_eq!(account.additional_fields, deserialized.additional_fields);
 }
+
+#[test]
+fn test_account_without_totp_field_deserializes() {
+    let json = r#"{"name":"Old","account_type":"Password Account","website":"","username":"u","password":"p","notes":"","additional_fields":{},"created_at":"2025-01-01 00:00:00","modified_at":"2025-01-01 00:00:00"}"#;
+
+    let account: Account = serde_json::from_str(json).unwrap();
+
+    assert_eq!(account.name, "Old");
+    assert!(account.totp.is_none());
+    assert!(account.password_history.is_empty());
+}
+
+#[test]
+fn test_record_password_change() {
+    let previous = Account {
+        password: SecurePassword::new("old-password".to_string()),
+        ..Account::default()
+    };
+    let mut updated = previous.clone();
+
+    // Saving without changing the password adds nothing
+    assert!(!updated.record_password_change(&previous));
+    assert!(updated.password_history.is_empty());
+
+    updated.password.update("new-password");
+    assert!(updated.record_password_change(&previous));
+    assert_eq!(updated.password_history.len(), 1);
+    updated.password_history[0]
+        .password
+        .with_exposed(|password| assert_eq!(password, "old-password"));
+    assert!(!updated.password_history[0].replaced_at.is_empty());
+}
+
+#[test]
+fn test_record_password_change_skips_empty_and_caps_history() {
+    let mut account = Account::default();
+    account.password.update("first");
+    assert!(!account.record_password_change(&Account::default()));
+
+    for i in 0..(MAX_PASSWORD_HISTORY + 5) {
+        let previous = account.clone();
+        account.password.update(&format!("password-{i}"));
+        assert!(account.record_password_change(&previous));
+    }
+
+    assert_eq!(account.password_history.len(), MAX_PASSWORD_HISTORY);
+    // Newest first
+    let last = MAX_PASSWORD_HISTORY + 3;
+    account.password_history[0]
+        .password
+        .with_exposed(|password| assert_eq!(password, format!("password-{last}")));
+}
+
+#[test]
+fn test_restore_password_from_history() {
+    let previous = Account {
+        password: SecurePassword::new("working-password".to_string()),
+        ..Account::default()
+    };
+    let mut account = previous.clone();
+    account.password.update("botched-rotation");
+    account.record_password_change(&previous);
+
+    let saved = account.clone();
+    assert!(account.restore_password_from_history(0));
+    account
+        .password
+        .with_exposed(|password| assert_eq!(password, "working-password"));
+    assert!(account.password_history.is_empty());
+    assert!(!account.restore_password_from_history(5));
+
+    // Saving the restored version keeps the botched password in the history instead
+    assert!(account.record_password_change(&saved));
+    assert_eq!(account.password_history.len(), 1);
+    account.password_history[0]
+        .password
+        .with_exposed(|password| assert_eq!(password, "botched-rotation"));
+}
+
+#[test]
+fn test_repeated_restores_do_not_duplicate_history() {
+    let mut account = Account::default();
+    account.password.update("first");
+    let previous = account.clone();
+    account.password.update("second");
+    account.record_password_change(&previous);
+
+    // Switching back and forth keeps one entry per password
+    for _ in 0..(MAX_PASSWORD_HISTORY + 5) {
+        let saved = account.clone();
+        assert!(account.restore_password_from_history(0));
+        assert!(account.record_password_change(&saved));
+        assert_eq!(account.password_history.len(), 1);
+    }
+
+    // Replacing a password already in the history moves its entry to the front
+    let mut account = Account::default();
+    for password in ["a", "b", "a", "b"] {
+        let previous = account.clone();
+        account.password.update(password);
+        account.record_password_change(&previous);
+    }
+    let history: Vec<String> = account
+        .password_history
+        .iter()
+        .map(|entry| entry.password.with_exposed(str::to_string))
+        .collect();
+    assert_eq!(history, vec!["a", "b"]);
+}
+
+#[test]
+fn test_password_history_round_trips_through_json() {
+    let previous = Account {
+        password: SecurePassword::new("old".to_string()),
+        ..Account::default()
+    };
+    let mut account = previous.clone();
+    account.password.update("new");
+    account.record_password_change(&previous);
+
+    let json = serde_json::to_string(&account).unwrap();
+    let restored: Account = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored.password_history.len(), 1);
+    restored.password_history[0]
+        .password
+        .with_exposed(|password| assert_eq!(password, "old"));
+}
+
+#[test]
+fn test_parse_tags() {
+    assert_eq!(
+        Account::parse_tags(" work, finance,,work ,"),
+        vec!["work".to_string(), "finance".to_string()]
+    );
+    assert!(Account::parse_tags(" , ").is_empty());
+
+    // Accounts saved before tags existed read back without any
+    let mut json = serde_json::to_value(Account::default()).unwrap();
+    json.as_object_mut().unwrap().remove("tags");
+    let account: Account = serde_json::from_value(json).unwrap();
+    assert!(account.tags.is_empty());
+    assert_eq!(account.tags_display(), "");
+}
@@ -0,0 +1,224 @@
+use crate::storage::archive::*;
+use chrono::NaiveDateTime;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn time(value: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap()
+}
+
+/// Creates a vault directory with a recipient, gate, settings and a few accounts.
+fn create_vault(dir: &Path) -> PathBuf {
+    let vault_dir = dir.join("vault");
+    fs::create_dir_all(vault_dir.join("bank/revisions")).unwrap();
+    fs::create_dir_all(vault_dir.join("mail")).unwrap();
+    fs::create_dir_all(vault_dir.join(".trash/20250101T000000000-forum")).unwrap();
+    fs::write(vault_dir.join("recipient"), "user@example.com\n").unwrap();
+    fs::write(vault_dir.join("gate.gpg"), [0u8, 1, 2, 255]).unwrap();
+    fs::write(vault_dir.join("settings.json"), "{}").unwrap();
+    fs::write(vault_dir.join("bank/data.gpg"), "bank data").unwrap();
+    fs::write(
+        vault_dir.join("bank/revisions/000001-20250101T000000.gpg"),
+        "old bank data",
+    )
+    .unwrap();
+    fs::write(vault_dir.join("mail/data.gpg"), "mail data").unwrap();
+    fs::write(
+        vault_dir.join(".trash/20250101T000000000-forum/data.gpg"),
+        "forum data",
+    )
+    .unwrap();
+    vault_dir
+}
+
+/// Writes an archive with the given manifest and file data, bypassing `write_archive`.
+fn write_raw_archive(path: &Path, manifest: &ArchiveManifest, data: &[u8]) {
+    let manifest_json = serde_json::to_vec(manifest).unwrap();
+    let mut contents = ARCHIVE_MAGIC.to_vec();
+    contents.extend((manifest_json.len() as u64).to_le_bytes());
+    contents.extend(manifest_json);
+    contents.extend(data);
+    fs::write(path, contents).unwrap();
+}
+
+#[test]
+fn test_archive_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault_dir = create_vault(temp_dir.path());
+    let archive = temp_dir.path().join("vault.fmparchive");
+
+    let manifest = write_archive(
+        &vault_dir,
+        "personal",
+        &archive,
+        time("2025-03-01 12:00:00"),
+    )
+    .unwrap();
+    assert_eq!(manifest.vault_name, "personal");
+    assert_eq!(manifest.created_at, "2025-03-01 12:00:00");
+    assert_eq!(manifest.files.len(), 7);
+    assert_eq!(manifest.account_names(), vec!["bank", "mail"]);
+    assert!(!temp_dir.path().join("vault.fmparchive.partial").exists());
+
+    assert_eq!(verify_archive(&archive).unwrap(), manifest);
+
+    let destination = temp_dir.path().join("restored");
+    extract_archive(&archive, &destination).unwrap();
+    for entry in &manifest.files {
+        assert_eq!(
+            fs::read(destination.join(&entry.path)).unwrap(),
+            fs::read(vault_dir.join(&entry.path)).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_archive_skips_rekey_journal() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault_dir = create_vault(temp_dir.path());
+    fs::write(vault_dir.join("rekey.journal"), "committed\n").unwrap();
+    let archive = temp_dir.path().join("vault.fmparchive");
+
+    let manifest = write_archive(
+        &vault_dir,
+        "personal",
+        &archive,
+        time("2025-03-01 12:00:00"),
+    )
+    .unwrap();
+    assert!(
+        manifest
+            .files
+            .iter()
+            .all(|entry| entry.path != "rekey.journal")
+    );
+}
+
+#[test]
+fn test_verify_detects_damage() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault_dir = create_vault(temp_dir.path());
+    let archive = temp_dir.path().join("vault.fmparchive");
+    write_archive(
+        &vault_dir,
+        "personal",
+        &archive,
+        time("2025-03-01 12:00:00"),
+    )
+    .unwrap();
+    let original = fs::read(&archive).unwrap();
+
+    let mut altered = original.clone();
+    let last = altered.len() - 1;
+    altered[last] ^= 0xff;
+    fs::write(&archive, &altered).unwrap();
+    assert!(verify_archive(&archive).is_err());
+
+    fs::write(&archive, &original[..original.len() - 3]).unwrap();
+    assert!(verify_archive(&archive).is_err());
+
+    let mut extended = original.clone();
+    extended.push(0);
+    fs::write(&archive, &extended).unwrap();
+    assert!(verify_archive(&archive).is_err());
+
+    let destination = temp_dir.path().join("restored");
+    assert!(extract_archive(&archive, &destination).is_err());
+    assert!(!destination.exists());
+}
+
+#[test]
+fn test_rejects_other_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("notes.txt");
+    fs::write(&archive, "not an archive").unwrap();
+    assert!(verify_archive(&archive).is_err());
+
+    fs::write(&archive, "").unwrap();
+    assert!(verify_archive(&archive).is_err());
+
+    assert!(verify_archive(&temp_dir.path().join("missing.fmparchive")).is_err());
+}
+
+#[test]
+fn test_rejects_unsafe_and_incomplete_manifests() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("vault.fmparchive");
+    let entry = |path: &str| ArchiveEntry {
+        path: path.to_string(),
+        size: 0,
+        sha256: "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string(),
+    };
+    let manifest = |paths: &[&str]| ArchiveManifest {
+        format_version: ARCHIVE_FORMAT_VERSION,
+        vault_name: "personal".to_string(),
+        created_at: "2025-03-01 12:00:00".to_string(),
+        files: paths.iter().map(|path| entry(path)).collect(),
+    };
+
+    write_raw_archive(&archive, &manifest(&["recipient", "gate.gpg"]), &[]);
+    assert!(verify_archive(&archive).is_ok());
+
+    for paths in [
+        &["recipient", "gate.gpg", "../escape"][..],
+        &["recipient", "gate.gpg", "/etc/passwd"],
+        &["recipient", "gate.gpg", "bank//data.gpg"],
+        &["recipient", "gate.gpg", "recipient"],
+        &["recipient"],
+    ] {
+        write_raw_archive(&archive, &manifest(paths), &[]);
+        assert!(verify_archive(&archive).is_err(), "{paths:?}");
+    }
+
+    let newer = ArchiveManifest {
+        format_version: ARCHIVE_FORMAT_VERSION + 1,
+        ..manifest(&["recipient", "gate.gpg"])
+    };
+    write_raw_archive(&archive, &newer, &[]);
+    assert!(verify_archive(&archive).is_err());
+}
+
+#[test]
+fn test_write_requires_vault_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let not_a_vault = temp_dir.path().join("empty");
+    fs::create_dir_all(&not_a_vault).unwrap();
+    let archive = temp_dir.path().join("vault.fmparchive");
+
+    assert!(write_archive(&not_a_vault, "empty", &archive, time("2025-03-01 12:00:00")).is_err());
+    assert!(!archive.exists());
+}
+
+#[test]
+fn test_extract_refuses_existing_destination() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault_dir = create_vault(temp_dir.path());
+    let archive = temp_dir.path().join("vault.fmparchive");
+    write_archive(
+        &vault_dir,
+        "personal",
+        &archive,
+        time("2025-03-01 12:00:00"),
+    )
+    .unwrap();
+
+    assert!(extract_archive(&archive, &vault_dir).is_err());
+}
+
+#[test]
+fn test_import_rejects_invalid_names() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("vault.fmparchive");
+
+    assert!(import_vault(&archive, "", ImportMode::New).is_err());
+    assert!(import_vault(&archive, "../outside", ImportMode::New).is_err());
+    assert!(
+        import_vault(
+            &archive,
+            "fmp-archive-tests-missing-vault",
+            ImportMode::Replace
+        )
+        .is_err()
+    );
+}
@@ -0,0 +1,178 @@
+use crate::breach::PwnedPasswords;
+use crate::models::account::Account;
+use crate::security::secure_password::SecurePassword;
+use crate::vault::audit::*;
+use chrono::NaiveDateTime;
+use sha1::{Digest, Sha1};
+use std::fs;
+use tempfile::TempDir;
+
+const STRONG_PASSWORD: &str = "k7#Vq9!xLm2$Rw8zT4";
+
+fn now() -> NaiveDateTime {
+    NaiveDateTime::parse_from_str("2025-06-01 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap()
+}
+
+fn account(name: &str, password: &str, modified_at: &str) -> Account {
+    Account {
+        name: name.to_string(),
+        password: SecurePassword::new(password.to_string()),
+        modified_at: modified_at.to_string(),
+        ..Account::default()
+    }
+}
+
+fn issues_for<'a>(audit: &'a VaultAudit, name: &str) -> &'a [AuditIssue] {
+    audit
+        .accounts
+        .iter()
+        .find(|account| account.account_name == name)
+        .map_or(&[], |account| account.issues.as_slice())
+}
+
+#[test]
+fn test_audit_healthy_accounts() {
+    let accounts = vec![
+        account("Bank", STRONG_PASSWORD, "2025-05-01 09:00:00"),
+        account("Mail", "u3&Pz8!rQw@5Lk#2", "2025-05-20 09:00:00"),
+    ];
+
+    let audit = audit_accounts(&accounts, &AuditOptions::default(), now(), None);
+
+    assert_eq!(audit.accounts_checked, 2);
+    assert!(audit.accounts.is_empty());
+    assert_eq!(audit.issue_count(), 0);
+}
+
+#[test]
+fn test_audit_empty_password() {
+    let accounts = vec![account("Blank", "", "2025-05-01 09:00:00")];
+
+    let audit = audit_accounts(&accounts, &AuditOptions::default(), now(), None);
+
+    assert_eq!(issues_for(&audit, "Blank"), &[AuditIssue::Empty]);
+}
+
+#[test]
+fn test_audit_weak_password() {
+    let accounts = vec![account("Forum", "password", "2025-05-01 09:00:00")];
+
+    let audit = audit_accounts(&accounts, &AuditOptions::default(), now(), None);
+
+    assert!(matches!(
+        issues_for(&audit, "Forum"),
+        [AuditIssue::Weak { score: 0, .. }]
+    ));
+
+    let lenient = AuditOptions {
+        min_score: 0,
+        ..AuditOptions::default()
+    };
+    assert!(
+        audit_accounts(&accounts, &lenient, now(), None)
+            .accounts
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_audit_reused_passwords() {
+    let accounts = vec![
+        account("Alpha", STRONG_PASSWORD, "2025-05-01 09:00:00"),
+        account("Beta", STRONG_PASSWORD, "2025-05-01 09:00:00"),
+        account("Gamma", STRONG_PASSWORD, "2025-05-01 09:00:00"),
+        account("Delta", "u3&Pz8!rQw@5Lk#2", "2025-05-01 09:00:00"),
+        // Empty passwords are reported as empty, not as reused.
+        account("Empty1", "", "2025-05-01 09:00:00"),
+        account("Empty2", "", "2025-05-01 09:00:00"),
+    ];
+
+    let audit = audit_accounts(&accounts, &AuditOptions::default(), now(), None);
+
+    assert_eq!(
+        issues_for(&audit, "Alpha"),
+        &[AuditIssue::Reused {
+            shared_with: vec!["Beta".to_string(), "Gamma".to_string()],
+        }]
+    );
+    assert!(issues_for(&audit, "Delta").is_empty());
+    assert_eq!(issues_for(&audit, "Empty1"), &[AuditIssue::Empty]);
+}
+
+#[test]
+fn test_audit_stale_password() {
+    let accounts = vec![
+        account("Old", STRONG_PASSWORD, "2024-01-01 00:00:00"),
+        account("Unparsable", "u3&Pz8!rQw@5Lk#2", "sometime"),
+    ];
+
+    let audit = audit_accounts(&accounts, &AuditOptions::default(), now(), None);
+
+    assert!(matches!(
+        issues_for(&audit, "Old"),
+        [AuditIssue::Stale { age_days }] if *age_days > 365
+    ));
+    assert!(issues_for(&audit, "Unparsable").is_empty());
+    assert_eq!(audit.accounts.len(), 1);
+}
+
+#[test]
+fn test_audit_results_sorted_and_counted() {
+    let accounts = vec![
+        account("Zulu", "", "2020-01-01 00:00:00"),
+        account("Alpha", "password", "2025-05-01 09:00:00"),
+    ];
+
+    let audit = audit_accounts(&accounts, &AuditOptions::default(), now(), None);
+
+    let names: Vec<&str> = audit
+        .accounts
+        .iter()
+        .map(|account| account.account_name.as_str())
+        .collect();
+    assert_eq!(names, ["Alpha", "Zulu"]);
+    assert_eq!(audit.issue_count(), 3);
+}
+
+#[test]
+fn test_audit_issue_descriptions() {
+    assert_eq!(AuditIssue::Empty.description(), "Password is empty");
+    assert_eq!(
+        AuditIssue::Reused {
+            shared_with: vec!["A".to_string(), "B".to_string()],
+        }
+        .description(),
+        "Password also used by A, B"
+    );
+    assert_eq!(
+        AuditIssue::Stale { age_days: 400 }.description(),
+        "Not changed in 400 days"
+    );
+}
+
+#[test]
+fn test_audit_breached_password() {
+    let breached = "u3&Pz8!rQw@5Lk#2";
+    let hash: String = Sha1::digest(breached.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect();
+
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("pwned.txt");
+    fs::write(&path, format!("{hash}:42\r\n")).unwrap();
+    let mut pwned = PwnedPasswords::open(&path).unwrap();
+
+    let accounts = vec![
+        account("Leaked", breached, "2025-05-01 09:00:00"),
+        account("Safe", STRONG_PASSWORD, "2025-05-01 09:00:00"),
+    ];
+
+    let audit = audit_accounts(&accounts, &AuditOptions::default(), now(), Some(&mut pwned));
+
+    assert_eq!(
+        issues_for(&audit, "Leaked"),
+        &[AuditIssue::Breached { count: 42 }]
+    );
+    assert!(issues_for(&audit, "Safe").is_empty());
+}
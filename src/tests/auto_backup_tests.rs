@@ -0,0 +1,142 @@
+use crate::storage::auto_backup::*;
+use chrono::NaiveDateTime;
+use std::fs;
+use tempfile::TempDir;
+
+fn time(value: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap()
+}
+
+fn policy(every_changes: u32, on_exit: bool, daily: bool) -> AutoBackupPolicy {
+    AutoBackupPolicy {
+        every_changes,
+        on_exit,
+        daily,
+    }
+}
+
+#[test]
+fn test_backup_due_after_changes() {
+    let now = time("2025-06-10 12:00:00");
+    let latest = Some(time("2025-06-10 11:00:00"));
+    let every_three = policy(3, false, false);
+
+    assert!(!backup_due(
+        &every_three,
+        2,
+        latest,
+        now,
+        BackupTrigger::Activity
+    ));
+    assert!(backup_due(
+        &every_three,
+        3,
+        latest,
+        now,
+        BackupTrigger::Activity
+    ));
+    assert!(backup_due(
+        &every_three,
+        7,
+        latest,
+        now,
+        BackupTrigger::Exit
+    ));
+    assert!(!backup_due(
+        &policy(0, false, false),
+        100,
+        latest,
+        now,
+        BackupTrigger::Activity
+    ));
+}
+
+#[test]
+fn test_backup_due_daily_only_with_changes() {
+    let now = time("2025-06-10 12:00:00");
+    let daily = policy(0, false, true);
+
+    assert!(!backup_due(
+        &daily,
+        5,
+        Some(time("2025-06-10 08:00:00")),
+        now,
+        BackupTrigger::Activity
+    ));
+    assert!(backup_due(
+        &daily,
+        1,
+        Some(time("2025-06-09 12:00:00")),
+        now,
+        BackupTrigger::Activity
+    ));
+    // Nothing changed, so the last backup is still current
+    assert!(!backup_due(
+        &daily,
+        0,
+        Some(time("2025-06-01 12:00:00")),
+        now,
+        BackupTrigger::Activity
+    ));
+    // A vault that was never backed up is backed up straight away
+    assert!(backup_due(&daily, 0, None, now, BackupTrigger::Activity));
+}
+
+#[test]
+fn test_backup_due_on_exit() {
+    let now = time("2025-06-10 12:00:00");
+    let latest = Some(time("2025-06-10 11:59:00"));
+    let on_exit = policy(0, true, false);
+
+    assert!(backup_due(&on_exit, 1, latest, now, BackupTrigger::Exit));
+    assert!(!backup_due(
+        &on_exit,
+        1,
+        latest,
+        now,
+        BackupTrigger::Activity
+    ));
+    assert!(!backup_due(&on_exit, 0, latest, now, BackupTrigger::Exit));
+    assert!(!backup_due(
+        &AutoBackupPolicy::default(),
+        1,
+        latest,
+        now,
+        BackupTrigger::Exit
+    ));
+}
+
+#[test]
+fn test_count_and_clear_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("backups").join("auto-backup.json");
+
+    assert_eq!(AutoBackupState::load(&path).unwrap().changes, 0);
+    assert_eq!(count_change(&path).unwrap(), 1);
+    assert_eq!(count_change(&path).unwrap(), 2);
+    let covered = AutoBackupState::load(&path).unwrap().changes;
+
+    // A change made while the backup ran still counts towards the next one
+    count_change(&path).unwrap();
+    clear_changes(&path, covered).unwrap();
+    assert_eq!(AutoBackupState::load(&path).unwrap().changes, 1);
+
+    fs::write(&path, "not json").unwrap();
+    assert!(AutoBackupState::load(&path).is_err());
+    assert!(count_change(&path).is_err());
+}
+
+#[test]
+fn test_describe_policy() {
+    assert_eq!(AutoBackupPolicy::default().describe(), "off");
+    assert!(!AutoBackupPolicy::default().is_enabled());
+    assert_eq!(policy(1, false, false).describe(), "after every change");
+    assert_eq!(
+        policy(10, false, true).describe(),
+        "after every 10 changes and daily"
+    );
+    assert_eq!(
+        policy(5, true, true).describe(),
+        "after every 5 changes, daily and when closing"
+    );
+}
@@ -0,0 +1,330 @@
+use crate::crypto::LockedBuffer;
+use crate::models::Account;
+use crate::storage::backup::*;
+use chrono::NaiveDateTime;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn time(value: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap()
+}
+
+/// Creates a vault directory with a recipient file and one account.
+fn create_vault(temp_dir: &TempDir, password: &str) -> PathBuf {
+    let vault_dir = temp_dir.path().join("vaults").join("Personal");
+    create_dir_all(vault_dir.join("Mail")).unwrap();
+    write(vault_dir.join("recipient"), "test@example.com").unwrap();
+    write(vault_dir.join("Mail").join("data.gpg"), password).unwrap();
+    vault_dir
+}
+
+fn snapshot(created_at: &str) -> BackupSnapshot {
+    BackupSnapshot {
+        id: created_at.to_string(),
+        created_at: time(created_at),
+        path: PathBuf::from(created_at),
+    }
+}
+
+fn pruned_times(snapshots: &[BackupSnapshot], policy: &RetentionPolicy, now: &str) -> Vec<String> {
+    snapshots_to_prune(snapshots, policy, time(now))
+        .iter()
+        .map(BackupSnapshot::created_at_display)
+        .collect()
+}
+
+fn account_data(vault_dir: &Path) -> String {
+    read_to_string(vault_dir.join("Mail").join("data.gpg")).unwrap()
+}
+
+#[test]
+fn test_create_backup_successfully() {
+    // Since create_backup uses Locations which uses system paths,
+    // test with a non-existent vault to verify error handling
+    let result = create_backup("definitely_non_existent_test_vault");
+
+    // This should fail since the vault doesn't exist
+    assert!(result.is_err());
+
+    // The error could be various types depending on the system state
+    let error_msg = result.unwrap_err().to_string();
+
+    // Be more lenient with error message checking
+    assert!(!error_msg.is_empty(), "Error message should not be empty");
+}
+
+#[test]
+fn test_install_backup_successfully() {
+    // This test would require a more complex setup with actual backup directories
+    // For now, test the error case when backup doesn't exist
+    let result = install_backup("non_existent_vault", "20250101T000000000");
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_backup_exists_false() {
+    let result = backup_exists("definitely_non_existent_vault");
+    assert!(!result);
+}
+
+#[test]
+fn test_delete_non_existent_backup() {
+    let result = delete_backup("non_existent_vault", "20250101T000000000");
+
+    assert!(result.is_err());
+    let error_msg = result.unwrap_err().to_string();
+    assert!(error_msg.contains("does not exist"));
+}
+
+#[test]
+fn test_create_and_install_snapshots() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault_dir = create_vault(&temp_dir, "first");
+    let backup_dir = temp_dir.path().join("backups").join("Personal");
+
+    let first = create_snapshot(&vault_dir, &backup_dir, time("2025-05-01 09:00:00")).unwrap();
+    write(vault_dir.join("Mail").join("data.gpg"), "second").unwrap();
+    let second = create_snapshot(&vault_dir, &backup_dir, time("2025-05-02 09:00:00")).unwrap();
+    assert_eq!(account_data(&first.path), "first");
+    assert_eq!(account_data(&second.path), "second");
+
+    // Stray entries are not snapshots.
+    write(backup_dir.join("notes.txt"), "").unwrap();
+    assert_eq!(
+        read_snapshots(&backup_dir).unwrap(),
+        vec![second.clone(), first.clone()]
+    );
+
+    write(vault_dir.join("Mail").join("data.gpg"), "third").unwrap();
+    install_snapshot(&first, &vault_dir).unwrap();
+    assert_eq!(account_data(&vault_dir), "first");
+    assert_eq!(
+        read_to_string(vault_dir.join("recipient")).unwrap(),
+        "test@example.com"
+    );
+
+    // Restoring leaves the snapshot in place and nothing else behind.
+    assert_eq!(read_snapshots(&backup_dir).unwrap().len(), 2);
+    assert!(first.path.exists());
+    assert_eq!(
+        std::fs::read_dir(vault_dir.parent().unwrap())
+            .unwrap()
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn test_create_snapshot_does_not_overwrite() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault_dir = create_vault(&temp_dir, "first");
+    let backup_dir = temp_dir.path().join("backups").join("Personal");
+
+    create_snapshot(&vault_dir, &backup_dir, time("2025-05-01 09:00:00")).unwrap();
+    assert!(create_snapshot(&vault_dir, &backup_dir, time("2025-05-01 09:00:00")).is_err());
+}
+
+#[test]
+fn test_migrate_legacy_backup() {
+    let temp_dir = TempDir::new().unwrap();
+    let backup_dir = temp_dir.path().join("Personal");
+    create_dir_all(backup_dir.join("Mail")).unwrap();
+    write(backup_dir.join("recipient"), "test@example.com").unwrap();
+    write(backup_dir.join("Mail").join("data.gpg"), "legacy").unwrap();
+
+    let migrated = migrate_legacy_backup(&backup_dir).unwrap().unwrap();
+    assert_eq!(read_snapshots(&backup_dir).unwrap(), vec![migrated.clone()]);
+    assert_eq!(account_data(&migrated.path), "legacy");
+    assert!(!backup_dir.join("recipient").exists());
+
+    assert!(migrate_legacy_backup(&backup_dir).unwrap().is_none());
+}
+
+#[test]
+fn test_prune_keeps_latest() {
+    let snapshots: Vec<BackupSnapshot> = (1..=5)
+        .rev()
+        .map(|day| snapshot(&format!("2024-01-0{day} 09:00:00")))
+        .collect();
+    let policy = RetentionPolicy {
+        keep_last: 2,
+        keep_daily_days: 0,
+        keep_weekly_weeks: 0,
+    };
+
+    assert_eq!(
+        pruned_times(&snapshots, &policy, "2025-06-01 09:00:00"),
+        [
+            "2024-01-03 09:00:00",
+            "2024-01-02 09:00:00",
+            "2024-01-01 09:00:00"
+        ]
+    );
+
+    // The newest snapshot is kept even if the policy keeps nothing.
+    let keep_nothing = RetentionPolicy {
+        keep_last: 0,
+        ..policy
+    };
+    assert_eq!(
+        pruned_times(&snapshots, &keep_nothing, "2025-06-01 09:00:00").len(),
+        4
+    );
+}
+
+#[test]
+fn test_prune_keeps_newest_per_day_and_week() {
+    let snapshots = vec![
+        snapshot("2025-06-01 18:00:00"),
+        snapshot("2025-06-01 09:00:00"),
+        snapshot("2025-05-31 09:00:00"),
+        snapshot("2025-05-20 18:00:00"),
+        snapshot("2025-05-19 09:00:00"),
+        snapshot("2025-05-01 09:00:00"),
+        snapshot("2025-03-01 09:00:00"),
+    ];
+    let policy = RetentionPolicy {
+        keep_last: 1,
+        keep_daily_days: 7,
+        keep_weekly_weeks: 4,
+    };
+
+    // Daily: June 1st 18:00 and May 31st. Weekly: the newest of the weeks of May 26th and
+    // May 19th. May 1st and March 1st are older than four weeks.
+    assert_eq!(
+        pruned_times(&snapshots, &policy, "2025-06-01 20:00:00"),
+        [
+            "2025-06-01 09:00:00",
+            "2025-05-19 09:00:00",
+            "2025-05-01 09:00:00",
+            "2025-03-01 09:00:00"
+        ]
+    );
+}
+
+#[test]
+fn test_retention_policy_defaults() {
+    let policy = RetentionPolicy::default();
+    assert_eq!(policy.keep_last, 5);
+    assert_eq!(policy.keep_daily_days, 7);
+    assert_eq!(policy.keep_weekly_weeks, 4);
+}
+
+/// Decrypts by reading the file as-is, failing for files that start with "corrupt".
+fn fake_decrypt(path: &Path) -> Result<LockedBuffer, anyhow::Error> {
+    let data = std::fs::read(path)?;
+    if data.starts_with(b"corrupt") {
+        return Err(anyhow::anyhow!("Decryption failed"));
+    }
+    Ok(LockedBuffer::new(data))
+}
+
+fn account_json(name: &str) -> String {
+    serde_json::to_string(&Account {
+        name: name.to_string(),
+        ..Account::default()
+    })
+    .unwrap()
+}
+
+#[test]
+fn test_verify_snapshot_reports_each_account() {
+    let temp_dir = TempDir::new().unwrap();
+    let snapshot_dir = temp_dir.path().join("20250301T120000000");
+    for (account, data) in [
+        ("Bank", account_json("Bank")),
+        ("Forum", "corrupt".to_string()),
+        ("Mail", account_json("Mail")),
+        ("Legacy", "alice:hunter2".to_string()),
+        ("Broken", "not an account".to_string()),
+    ] {
+        create_dir_all(snapshot_dir.join(account)).unwrap();
+        write(snapshot_dir.join(account).join("data.gpg"), data).unwrap();
+    }
+    write(snapshot_dir.join("gate.gpg"), "gate").unwrap();
+
+    let verification =
+        verify_snapshot(&snapshot_dir, time("2025-03-01 12:00:01"), fake_decrypt).unwrap();
+
+    assert_eq!(verification.verified_at, "2025-03-01 12:00:01");
+    assert!(verification.gate.is_ok());
+    assert_eq!(verification.totp, None);
+    let results: Vec<(&str, bool)> = verification
+        .accounts
+        .iter()
+        .map(|check| (check.name.as_str(), check.is_ok()))
+        .collect();
+    assert_eq!(
+        results,
+        vec![
+            ("Bank", true),
+            ("Broken", false),
+            ("Forum", false),
+            ("Legacy", true),
+            ("Mail", true),
+        ]
+    );
+    assert!(!verification.is_ok());
+    assert_eq!(verification.failures().len(), 2);
+    assert_eq!(
+        verification.summary(),
+        "2 of 5 account(s) failed to decrypt"
+    );
+}
+
+#[test]
+fn test_verify_snapshot_checks_gate_and_totp() {
+    let temp_dir = TempDir::new().unwrap();
+    let snapshot_dir = temp_dir.path().join("20250301T120000000");
+    create_dir_all(snapshot_dir.join("Mail")).unwrap();
+    write(
+        snapshot_dir.join("Mail").join("data.gpg"),
+        account_json("Mail"),
+    )
+    .unwrap();
+    write(snapshot_dir.join("totp.gpg"), "corrupt").unwrap();
+
+    let verification =
+        verify_snapshot(&snapshot_dir, time("2025-03-01 12:00:01"), fake_decrypt).unwrap();
+
+    assert!(!verification.gate.is_ok());
+    assert!(!verification.totp.as_ref().unwrap().is_ok());
+    assert!(verification.accounts[0].is_ok());
+    let failed: Vec<&str> = verification
+        .failures()
+        .iter()
+        .map(|check| check.name.as_str())
+        .collect();
+    assert_eq!(failed, vec!["gate.gpg", "totp.gpg"]);
+
+    write(snapshot_dir.join("gate.gpg"), "gate").unwrap();
+    write(snapshot_dir.join("totp.gpg"), "JBSWY3DPEHPK3PXP").unwrap();
+    let verification =
+        verify_snapshot(&snapshot_dir, time("2025-03-01 12:00:02"), fake_decrypt).unwrap();
+    assert!(verification.is_ok());
+    assert_eq!(verification.summary(), "All 1 account(s) verified");
+}
+
+#[test]
+fn test_migrate_legacy_backup_leaves_verifications() {
+    let temp_dir = TempDir::new().unwrap();
+    let backup_dir = temp_dir.path().join("Personal");
+    create_dir_all(&backup_dir).unwrap();
+    write(backup_dir.join("recipient"), "test@example.com").unwrap();
+    write(
+        backup_dir.join("20250301T120000000.verification.json"),
+        "{}",
+    )
+    .unwrap();
+
+    let migrated = migrate_legacy_backup(&backup_dir).unwrap().unwrap();
+
+    assert!(migrated.path.join("recipient").exists());
+    assert!(
+        backup_dir
+            .join("20250301T120000000.verification.json")
+            .exists()
+    );
+}
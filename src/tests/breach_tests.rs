@@ -0,0 +1,117 @@
+use crate::breach::*;
+use sha1::{Digest, Sha1};
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+fn sha1_hex(password: &str) -> String {
+    Sha1::digest(password.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect()
+}
+
+/// Writes a hash-ordered list in the HIBP format, with CRLF line endings like the real download.
+fn write_list(temp_dir: &TempDir, passwords: &[(&str, u64)]) -> PathBuf {
+    let mut lines: Vec<String> = passwords
+        .iter()
+        .map(|(password, count)| format!("{}:{count}", sha1_hex(password)))
+        .collect();
+    lines.sort();
+
+    let path = temp_dir.path().join("pwned.txt");
+    fs::write(&path, lines.join("\r\n") + "\r\n").unwrap();
+    path
+}
+
+#[test]
+fn test_check_password_found_and_not_found() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = write_list(
+        &temp_dir,
+        &[
+            ("password", 9_545_824),
+            ("123456", 37_359_195),
+            ("letmein", 10_000),
+        ],
+    );
+
+    let mut list = PwnedPasswords::open(&path).unwrap();
+    assert_eq!(list.check_password("password").unwrap(), Some(9_545_824));
+    assert_eq!(list.check_password("123456").unwrap(), Some(37_359_195));
+    assert_eq!(list.check_password("letmein").unwrap(), Some(10_000));
+    assert_eq!(list.check_password("k7#Vq9!xLm2$Rw8zT4").unwrap(), None);
+}
+
+#[test]
+fn test_every_entry_found_in_large_list() {
+    let temp_dir = TempDir::new().unwrap();
+    let passwords: Vec<String> = (0..2000).map(|i| format!("password{i}")).collect();
+    let entries: Vec<(&str, u64)> = passwords
+        .iter()
+        .enumerate()
+        .map(|(i, password)| (password.as_str(), i as u64 + 1))
+        .collect();
+    let path = write_list(&temp_dir, &entries);
+
+    let mut list = PwnedPasswords::open(&path).unwrap();
+    for (password, count) in &entries {
+        assert_eq!(list.check_password(password).unwrap(), Some(*count));
+    }
+    for i in 2000..2100 {
+        assert_eq!(list.check_password(&format!("password{i}")).unwrap(), None);
+    }
+}
+
+#[test]
+fn test_lookup_hash_is_case_insensitive() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = write_list(&temp_dir, &[("password", 3)]);
+
+    let mut list = PwnedPasswords::open(&path).unwrap();
+    let hash = sha1_hex("password").to_lowercase();
+    assert_eq!(list.lookup_hash(&hash).unwrap(), Some(3));
+    assert!(list.lookup_hash("not-a-hash").is_err());
+}
+
+#[test]
+fn test_first_and_last_entries() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("edges.txt");
+    fs::write(
+        &path,
+        format!(
+            "{}:1\n{}:2\n{}:3",
+            "0".repeat(40),
+            "8".repeat(40),
+            "F".repeat(40)
+        ),
+    )
+    .unwrap();
+
+    let mut list = PwnedPasswords::open(&path).unwrap();
+    assert_eq!(list.lookup_hash(&"0".repeat(40)).unwrap(), Some(1));
+    assert_eq!(list.lookup_hash(&"8".repeat(40)).unwrap(), Some(2));
+    assert_eq!(list.lookup_hash(&"F".repeat(40)).unwrap(), Some(3));
+    assert_eq!(list.lookup_hash(&"7".repeat(40)).unwrap(), None);
+}
+
+#[test]
+fn test_empty_list() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("empty.txt");
+    fs::write(&path, "").unwrap();
+
+    let mut list = PwnedPasswords::open(&path).unwrap();
+    assert_eq!(list.check_password("password").unwrap(), None);
+}
+
+#[test]
+fn test_open_rejects_other_formats() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("ntlm.txt");
+    fs::write(&path, "8846F7EAEE8FB117AD06BDD830B7586C:1\n").unwrap();
+    assert!(PwnedPasswords::open(&path).is_err());
+
+    assert!(PwnedPasswords::open(&temp_dir.path().join("missing.txt")).is_err());
+}
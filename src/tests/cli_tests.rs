@@ -0,0 +1,535 @@
+use crate::cli::*;
+use crate::password::{PassphraseConfig, PasswordConfig, PasswordMode};
+use crate::totp::{TotpAlgorithm, TotpParams};
+
+fn args(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_parse_no_arguments_is_help() {
+    assert_eq!(parse_args(&[]).unwrap(), Command::Help);
+    assert_eq!(parse_args(&args(&["--help"])).unwrap(), Command::Help);
+}
+
+#[test]
+fn test_parse_vaults_and_list() {
+    assert_eq!(parse_args(&args(&["vaults"])).unwrap(), Command::Vaults);
+    assert_eq!(
+        parse_args(&args(&["list", "work"])).unwrap(),
+        Command::List {
+            vault: "work".to_string(),
+            totp: None,
+        }
+    );
+}
+
+#[test]
+fn test_parse_show_with_flags() {
+    let command = parse_args(&args(&[
+        "show",
+        "work",
+        "email",
+        "--reveal",
+        "--totp=123456",
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        command,
+        Command::Show {
+            vault: "work".to_string(),
+            account: "email".to_string(),
+            reveal: true,
+            password_only: false,
+            totp: Some("123456".to_string()),
+        }
+    );
+}
+
+#[test]
+fn test_parse_add_with_fields_and_generated_password() {
+    let command = parse_args(&args(&[
+        "add",
+        "work",
+        "vpn",
+        "--username",
+        "alice",
+        "--tags",
+        "work, vpn",
+        "--field",
+        "pin=1234",
+        "--generate",
+        "--length",
+        "24",
+        "--symbols",
+    ]))
+    .unwrap();
+
+    let Command::Add {
+        vault,
+        account,
+        changes,
+        ..
+    } = command
+    else {
+        panic!("Expected add command");
+    };
+
+    assert_eq!(vault, "work");
+    assert_eq!(account, "vpn");
+    assert_eq!(changes.username.as_deref(), Some("alice"));
+    assert_eq!(
+        changes.tags,
+        Some(vec!["work".to_string(), "vpn".to_string()])
+    );
+    assert_eq!(
+        changes.fields,
+        vec![("pin".to_string(), "1234".to_string())]
+    );
+    assert_eq!(
+        changes.password,
+        Some(PasswordSource::Generate(PasswordConfig {
+            length: 24,
+            include_symbols: true,
+            ..PasswordConfig::default()
+        }))
+    );
+}
+
+#[test]
+fn test_parse_generate_passphrase_options() {
+    let command = parse_args(&args(&[
+        "generate",
+        "--words",
+        "5",
+        "--separator",
+        " ",
+        "--capitalize",
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        command,
+        Command::Generate {
+            config: PasswordConfig {
+                mode: PasswordMode::Passphrase,
+                passphrase: PassphraseConfig {
+                    word_count: 5,
+                    separator: " ".to_string(),
+                    capitalize: true,
+                    ..PassphraseConfig::default()
+                },
+                ..PasswordConfig::default()
+            },
+            count: 1,
+        }
+    );
+    assert!(parse_args(&args(&["generate", "--words", "5", "--length", "9"])).is_err());
+    assert!(parse_args(&args(&["generate", "--capitalize"])).is_err());
+}
+
+#[test]
+fn test_parse_positional_after_double_dash() {
+    let command = parse_args(&args(&["rename", "work", "--", "--odd-name", "new"])).unwrap();
+
+    assert_eq!(
+        command,
+        Command::Rename {
+            vault: "work".to_string(),
+            account: "--odd-name".to_string(),
+            new_name: "new".to_string(),
+            totp: None,
+        }
+    );
+}
+
+#[test]
+fn test_parse_generate_options() {
+    let command = parse_args(&args(&["generate", "--no-numbers", "--count", "3"])).unwrap();
+
+    assert_eq!(
+        command,
+        Command::Generate {
+            config: PasswordConfig {
+                include_numbers: false,
+                ..PasswordConfig::default()
+            },
+            count: 3,
+        }
+    );
+}
+
+#[test]
+fn test_parse_rejects_invalid_usage() {
+    assert!(parse_args(&args(&["frobnicate"])).is_err());
+    assert!(parse_args(&args(&["show", "work"])).is_err());
+    assert!(parse_args(&args(&["list", "work", "--bogus"])).is_err());
+    assert!(parse_args(&args(&["generate", "--length"])).is_err());
+    assert!(parse_args(&args(&["generate", "--length", "many"])).is_err());
+    assert!(parse_args(&args(&["add", "work", "a", "--field", "novalue"])).is_err());
+    assert!(parse_args(&args(&["add", "work", "a", "--length", "8"])).is_err());
+    assert!(
+        parse_args(&args(&[
+            "edit",
+            "work",
+            "a",
+            "--password-stdin",
+            "--generate"
+        ]))
+        .is_err()
+    );
+}
+
+#[test]
+fn test_run_reports_usage_errors() {
+    assert_eq!(run(&args(&["frobnicate"])), 2);
+    assert_eq!(run(&args(&["help"])), 0);
+}
+
+#[test]
+fn test_parse_member_commands() {
+    assert_eq!(
+        parse_args(&args(&["add-member", "team", "bob@example.com"])).unwrap(),
+        Command::AddMember {
+            vault: "team".to_string(),
+            recipient: "bob@example.com".to_string(),
+            totp: None,
+        }
+    );
+    assert_eq!(
+        parse_args(&args(&["remove-member", "team", "ABCD1234"])).unwrap(),
+        Command::RemoveMember {
+            vault: "team".to_string(),
+            recipient: "ABCD1234".to_string(),
+            totp: None,
+        }
+    );
+    assert!(parse_args(&args(&["add-member", "team"])).is_err());
+}
+
+#[test]
+fn test_parse_rekey_takes_several_keys() {
+    assert_eq!(
+        parse_args(&args(&["rekey", "team", "NEWKEY1", "NEWKEY2"])).unwrap(),
+        Command::Rekey {
+            vault: "team".to_string(),
+            recipients: vec!["NEWKEY1".to_string(), "NEWKEY2".to_string()],
+            totp: None,
+        }
+    );
+    assert!(parse_args(&args(&["rekey", "team"])).is_err());
+}
+
+#[test]
+fn test_parse_convert() {
+    assert_eq!(
+        parse_args(&args(&["convert", "work", "single-file"])).unwrap(),
+        Command::Convert {
+            vault: "work".to_string(),
+            single_file: true,
+            totp: None,
+        }
+    );
+    assert_eq!(
+        parse_args(&args(&[
+            "convert",
+            "work",
+            "directories",
+            "--totp",
+            "123456"
+        ]))
+        .unwrap(),
+        Command::Convert {
+            vault: "work".to_string(),
+            single_file: false,
+            totp: Some("123456".to_string()),
+        }
+    );
+    assert!(parse_args(&args(&["convert", "work"])).is_err());
+    assert!(parse_args(&args(&["convert", "work", "zip"])).is_err());
+}
+
+#[test]
+fn test_parse_verify() {
+    assert_eq!(
+        parse_args(&args(&["verify", "work"])).unwrap(),
+        Command::Verify {
+            vault: "work".to_string(),
+            trust: false,
+            totp: None,
+        }
+    );
+    assert_eq!(
+        parse_args(&args(&["verify", "work", "--trust", "--totp", "123456"])).unwrap(),
+        Command::Verify {
+            vault: "work".to_string(),
+            trust: true,
+            totp: Some("123456".to_string()),
+        }
+    );
+    assert!(parse_args(&args(&["verify"])).is_err());
+}
+
+#[test]
+fn test_parse_account_names() {
+    assert_eq!(
+        parse_args(&args(&["account-names", "work", "hide"])).unwrap(),
+        Command::AccountNames {
+            vault: "work".to_string(),
+            hide: true,
+            totp: None,
+        }
+    );
+    assert_eq!(
+        parse_args(&args(&["account-names", "work", "show"])).unwrap(),
+        Command::AccountNames {
+            vault: "work".to_string(),
+            hide: false,
+            totp: None,
+        }
+    );
+    assert!(parse_args(&args(&["account-names", "work"])).is_err());
+    assert!(parse_args(&args(&["account-names", "work", "encrypt"])).is_err());
+}
+
+#[test]
+fn test_parse_export_and_import() {
+    assert_eq!(
+        parse_args(&args(&["export", "work", "/media/usb/work.fmparchive"])).unwrap(),
+        Command::Export {
+            vault: "work".to_string(),
+            file: "/media/usb/work.fmparchive".into(),
+            totp: None,
+        }
+    );
+    assert_eq!(
+        parse_args(&args(&["import", "work.fmparchive"])).unwrap(),
+        Command::Import {
+            file: "work.fmparchive".into(),
+            vault: None,
+            replace: false,
+            verify_only: false,
+            assume_yes: false,
+            totp: None,
+        }
+    );
+    assert_eq!(
+        parse_args(&args(&[
+            "import",
+            "work.fmparchive",
+            "work",
+            "--replace",
+            "--yes"
+        ]))
+        .unwrap(),
+        Command::Import {
+            file: "work.fmparchive".into(),
+            vault: Some("work".to_string()),
+            replace: true,
+            verify_only: false,
+            assume_yes: true,
+            totp: None,
+        }
+    );
+    assert!(parse_args(&args(&["export", "work"])).is_err());
+    assert!(parse_args(&args(&["import"])).is_err());
+    assert!(parse_args(&args(&["import", "a", "b", "c"])).is_err());
+    assert!(parse_args(&args(&["import", "a", "--replace", "--verify-only"])).is_err());
+}
+
+#[test]
+fn test_parse_one_time_code_options() {
+    let Command::Edit { changes, .. } = parse_args(&args(&[
+        "edit",
+        "work",
+        "email",
+        "--otp-stdin",
+        "--otp-digits",
+        "8",
+    ]))
+    .unwrap() else {
+        panic!("Expected edit command");
+    };
+    assert_eq!(
+        changes.otp,
+        Some(OtpChange::Stdin(TotpParams {
+            digits: 8,
+            ..TotpParams::default()
+        }))
+    );
+
+    assert_eq!(
+        parse_args(&args(&["code", "work", "email"])).unwrap(),
+        Command::Code {
+            vault: "work".to_string(),
+            account: "email".to_string(),
+            totp: None,
+        }
+    );
+    assert!(parse_args(&args(&["edit", "w", "a", "--otp-digits", "8"])).is_err());
+    assert!(
+        parse_args(&args(&[
+            "edit",
+            "w",
+            "a",
+            "--otp-stdin",
+            "--otp-digits",
+            "9"
+        ]))
+        .is_err()
+    );
+    assert!(parse_args(&args(&["add", "w", "a", "--remove-otp"])).is_err());
+}
+
+#[test]
+fn test_parse_one_time_code_algorithm_and_period() {
+    let Command::Add { changes, .. } = parse_args(&args(&[
+        "add",
+        "work",
+        "bank",
+        "--otp-stdin",
+        "--otp-algorithm",
+        "sha-256",
+        "--otp-period=60",
+    ]))
+    .unwrap() else {
+        panic!("Expected add command");
+    };
+
+    assert_eq!(
+        changes.otp,
+        Some(OtpChange::Stdin(TotpParams {
+            algorithm: TotpAlgorithm::Sha256,
+            period: 60,
+            ..TotpParams::default()
+        }))
+    );
+    assert!(
+        parse_args(&args(&[
+            "add",
+            "w",
+            "a",
+            "--otp-stdin",
+            "--otp-algorithm",
+            "md5"
+        ]))
+        .is_err()
+    );
+    assert!(
+        parse_args(&args(&[
+            "add",
+            "w",
+            "a",
+            "--otp-stdin",
+            "--otp-period",
+            "0"
+        ]))
+        .is_err()
+    );
+}
+
+#[test]
+fn test_parse_one_time_code_skew() {
+    let Command::Add { changes, .. } = parse_args(&args(&[
+        "add",
+        "work",
+        "bank",
+        "--otp-stdin",
+        "--otp-digits=7",
+        "--otp-period",
+        "45",
+        "--otp-skew",
+        "3",
+    ]))
+    .unwrap() else {
+        panic!("Expected add command");
+    };
+
+    assert_eq!(
+        changes.otp,
+        Some(OtpChange::Stdin(TotpParams {
+            digits: 7,
+            period: 45,
+            skew: 3,
+            ..TotpParams::default()
+        }))
+    );
+    assert!(parse_args(&args(&["add", "w", "a", "--otp-skew", "2"])).is_err());
+    assert!(
+        parse_args(&args(&[
+            "add",
+            "w",
+            "a",
+            "--otp-stdin",
+            "--otp-skew",
+            "11"
+        ]))
+        .is_err()
+    );
+}
+
+#[test]
+fn test_parse_one_time_code_import_sources() {
+    let Command::Edit { changes, .. } =
+        parse_args(&args(&["edit", "work", "email", "--otp-qr", "shot.png"])).unwrap()
+    else {
+        panic!("Expected edit command");
+    };
+    assert_eq!(
+        changes.otp,
+        Some(OtpChange::QrImage(std::path::PathBuf::from("shot.png")))
+    );
+
+    let Command::Add { changes, .. } =
+        parse_args(&args(&["add", "work", "email", "--otp-uri-stdin"])).unwrap()
+    else {
+        panic!("Expected add command");
+    };
+    assert_eq!(changes.otp, Some(OtpChange::UriStdin));
+
+    assert!(
+        parse_args(&args(&[
+            "edit",
+            "w",
+            "a",
+            "--otp-uri-stdin",
+            "--otp-qr",
+            "shot.png"
+        ]))
+        .is_err()
+    );
+}
+
+#[test]
+fn test_parse_global_options() {
+    use crate::storage::profile::HomeSelection;
+    use std::path::PathBuf;
+
+    let (selection, rest) = parse_global_options(&args(&["list", "work"])).unwrap();
+    assert_eq!(selection, None);
+    assert_eq!(rest, args(&["list", "work"]));
+
+    let (selection, rest) =
+        parse_global_options(&args(&["--profile", "work", "list", "mail"])).unwrap();
+    assert_eq!(selection, Some(HomeSelection::Profile("work".to_string())));
+    assert_eq!(rest, args(&["list", "mail"]));
+
+    // With no command left the graphical interface starts
+    let (selection, rest) = parse_global_options(&args(&["--home=/tmp/fmp-test"])).unwrap();
+    assert_eq!(
+        selection,
+        Some(HomeSelection::Home(PathBuf::from("/tmp/fmp-test")))
+    );
+    assert!(rest.is_empty());
+
+    // Options after the command belong to it
+    let (selection, rest) = parse_global_options(&args(&["list", "--profile", "work"])).unwrap();
+    assert_eq!(selection, None);
+    assert_eq!(rest.len(), 3);
+
+    assert!(parse_global_options(&args(&["--profile"])).is_err());
+    assert!(parse_global_options(&args(&["--home="])).is_err());
+    assert!(parse_global_options(&args(&["--home", "/a", "--profile", "b"])).is_err());
+    assert_eq!(parse_args(&args(&["profiles"])).unwrap(), Command::Profiles);
+}
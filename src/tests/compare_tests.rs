@@ -0,0 +1,131 @@
+use crate::models::Account;
+use crate::security::SecurePassword;
+use crate::vault::compare::*;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Writes an account as plain JSON, which `fake_decrypt` reads back.
+fn write_account(dir: &Path, account: &Account) {
+    let account_dir = dir.join(&account.name);
+    fs::create_dir_all(&account_dir).unwrap();
+    fs::write(
+        account_dir.join("data.gpg"),
+        serde_json::to_string(account).unwrap(),
+    )
+    .unwrap();
+}
+
+fn fake_decrypt(path: &Path) -> Result<Account, anyhow::Error> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+fn account(name: &str, username: &str, password: &str) -> Account {
+    Account {
+        name: name.to_string(),
+        username: username.to_string(),
+        password: SecurePassword::new(password.to_string()),
+        ..Account::default()
+    }
+}
+
+#[test]
+fn test_diff_lists_added_removed_and_modified() {
+    let temp_dir = TempDir::new().unwrap();
+    let backup_dir = temp_dir.path().join("backup");
+    let vault_dir = temp_dir.path().join("vault");
+
+    write_account(&backup_dir, &account("bank", "alice", "hunter2"));
+    write_account(&backup_dir, &account("forum", "alice", "secret"));
+    write_account(&backup_dir, &account("mail", "alice", "secret"));
+    write_account(&vault_dir, &account("bank", "alice", "correct horse"));
+    write_account(&vault_dir, &account("mail", "alice", "secret"));
+    write_account(&vault_dir, &account("shop", "bob", "secret"));
+
+    let differences = diff_account_dirs(&backup_dir, &vault_dir, fake_decrypt).unwrap();
+    let summary: Vec<(&str, AccountStatus)> = differences
+        .iter()
+        .map(|difference| (difference.account_name.as_str(), difference.status))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("bank", AccountStatus::Modified),
+            ("forum", AccountStatus::Removed),
+            ("shop", AccountStatus::Added),
+        ]
+    );
+
+    let bank = &differences[0];
+    assert_eq!(bank.changes.len(), 1);
+    assert_eq!(bank.changes[0].field, "Password");
+    assert!(bank.changes[0].sensitive);
+    assert_eq!(bank.changes[0].old, None);
+
+    // Both versions are kept so the passwords can be revealed on request
+    let (backup, current) = bank.versions.as_ref().unwrap();
+    assert!(
+        backup
+            .password
+            .matches(&SecurePassword::new("hunter2".to_string()))
+    );
+    assert!(
+        current
+            .password
+            .matches(&SecurePassword::new("correct horse".to_string()))
+    );
+
+    assert!(differences[1].versions.is_none());
+}
+
+#[test]
+fn test_diff_ignores_resaved_accounts_and_skips_identical_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let backup_dir = temp_dir.path().join("backup");
+    let vault_dir = temp_dir.path().join("vault");
+
+    let mut resaved = account("bank", "alice", "hunter2");
+    write_account(&backup_dir, &resaved);
+    resaved.modified_at = "2030-01-01 00:00:00".to_string();
+    write_account(&vault_dir, &resaved);
+    write_account(&backup_dir, &account("mail", "alice", "secret"));
+    write_account(&vault_dir, &account("mail", "alice", "secret"));
+
+    let mut decrypted = Vec::new();
+    let differences = diff_account_dirs(&backup_dir, &vault_dir, |path| {
+        decrypted.push(path.to_path_buf());
+        fake_decrypt(path)
+    })
+    .unwrap();
+
+    assert!(differences.is_empty());
+    // Only the re-saved account was decrypted, once on each side
+    assert_eq!(decrypted.len(), 2);
+    assert!(
+        decrypted
+            .iter()
+            .all(|path| path.starts_with(&backup_dir) || path.starts_with(&vault_dir))
+    );
+    assert!(
+        decrypted
+            .iter()
+            .all(|path| path.parent().unwrap().ends_with("bank"))
+    );
+}
+
+#[test]
+fn test_diff_reports_decryption_failures() {
+    let temp_dir = TempDir::new().unwrap();
+    let backup_dir = temp_dir.path().join("backup");
+    let vault_dir = temp_dir.path().join("vault");
+
+    write_account(&backup_dir, &account("bank", "alice", "hunter2"));
+    write_account(&vault_dir, &account("bank", "bob", "hunter2"));
+
+    assert!(
+        diff_account_dirs(&backup_dir, &vault_dir, |_| Err(anyhow::anyhow!("locked"))).is_err()
+    );
+    assert!(
+        diff_vault_with_backup("fmp-compare-tests-missing-vault", "20250101T000000000").is_err()
+    );
+}
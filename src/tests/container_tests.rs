@@ -0,0 +1,126 @@
+use crate::crypto::LockedBuffer;
+use crate::storage::container::*;
+use std::collections::BTreeMap;
+
+/// Stands in for GPG: the index is stored reversed, so its plain text is not in the container.
+fn fake_encrypt(index: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+    Ok(index.iter().rev().copied().collect())
+}
+
+fn fake_decrypt(index: &[u8]) -> Result<LockedBuffer, anyhow::Error> {
+    Ok(LockedBuffer::new(index.iter().rev().copied().collect()))
+}
+
+fn container(files: &[(&str, &[u8])]) -> VaultContainer {
+    VaultContainer {
+        files: files
+            .iter()
+            .map(|(path, contents)| (path.to_string(), contents.to_vec()))
+            .collect::<BTreeMap<_, _>>(),
+    }
+}
+
+/// Builds a container by hand, with `index` as the plain-text index JSON.
+fn raw_container(version: u32, index: &str, records: &[u8]) -> Vec<u8> {
+    let encrypted_index = fake_encrypt(index.as_bytes()).unwrap();
+
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend_from_slice(&version.to_le_bytes());
+    bytes.extend_from_slice(&(encrypted_index.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&encrypted_index);
+    bytes.extend_from_slice(records);
+    bytes
+}
+
+#[test]
+fn test_container_round_trip_hides_account_names() {
+    let original = container(&[
+        ("bank/data.gpg", b"BANK RECORD"),
+        ("bank/revisions/000001-20250101T000000.gpg", b"OLD BANK"),
+        (".trash/20250102T030405000-forum/data.gpg", b"FORUM"),
+        ("mail/data.gpg", b""),
+    ]);
+
+    let bytes = original.to_bytes(fake_encrypt).unwrap();
+    assert!(bytes.starts_with(MAGIC));
+    assert_eq!(bytes[8..12], FORMAT_VERSION.to_le_bytes());
+    // Only the encrypted index knows the paths
+    let text = String::from_utf8_lossy(&bytes);
+    assert!(!text.contains("bank/"));
+    assert!(!text.contains("forum"));
+    assert!(text.contains("BANK RECORD"));
+
+    assert_eq!(
+        VaultContainer::from_bytes(&bytes, fake_decrypt).unwrap(),
+        original
+    );
+    assert_eq!(
+        VaultContainer::default()
+            .to_bytes(fake_encrypt)
+            .map(|bytes| { VaultContainer::from_bytes(&bytes, fake_decrypt).unwrap() })
+            .unwrap(),
+        VaultContainer::default()
+    );
+}
+
+#[test]
+fn test_container_rejects_damaged_or_unsupported_data() {
+    let valid = container(&[("bank/data.gpg", b"RECORD")])
+        .to_bytes(fake_encrypt)
+        .unwrap();
+
+    assert!(VaultContainer::from_bytes(b"FMPVAULT", fake_decrypt).is_err());
+    assert!(VaultContainer::from_bytes(&valid[..valid.len() - 1], fake_decrypt).is_err());
+    assert!(VaultContainer::from_bytes(&valid[..30], fake_decrypt).is_err());
+    assert!(VaultContainer::from_bytes(&valid, |_| Err(anyhow::anyhow!("locked"))).is_err());
+
+    let mut wrong_magic = valid.clone();
+    wrong_magic[0] = b'X';
+    assert!(VaultContainer::from_bytes(&wrong_magic, fake_decrypt).is_err());
+
+    let newer = raw_container(FORMAT_VERSION + 1, r#"{"entries":[]}"#, b"");
+    let error = VaultContainer::from_bytes(&newer, fake_decrypt).unwrap_err();
+    assert!(error.to_string().contains("newer version"));
+
+    let out_of_range = raw_container(
+        FORMAT_VERSION,
+        r#"{"entries":[{"path":"bank/data.gpg","offset":2,"length":10}]}"#,
+        b"RECORD",
+    );
+    assert!(VaultContainer::from_bytes(&out_of_range, fake_decrypt).is_err());
+
+    let duplicate = raw_container(
+        FORMAT_VERSION,
+        r#"{"entries":[{"path":"bank/data.gpg","offset":0,"length":3},{"path":"bank/data.gpg","offset":3,"length":3}]}"#,
+        b"RECORD",
+    );
+    assert!(VaultContainer::from_bytes(&duplicate, fake_decrypt).is_err());
+}
+
+#[test]
+fn test_container_rejects_paths_outside_account_directories() {
+    for path in [
+        "../bank/data.gpg",
+        "recipient",
+        "bank//data.gpg",
+        "/bank/data.gpg",
+        "bank\\data.gpg",
+    ] {
+        assert!(
+            container(&[(path, b"RECORD")])
+                .to_bytes(fake_encrypt)
+                .is_err(),
+            "{path} was accepted"
+        );
+
+        let escaping = raw_container(
+            FORMAT_VERSION,
+            &format!(
+                r#"{{"entries":[{{"path":{},"offset":0,"length":6}}]}}"#,
+                serde_json::to_string(path).unwrap()
+            ),
+            b"RECORD",
+        );
+        assert!(VaultContainer::from_bytes(&escaping, fake_decrypt).is_err());
+    }
+}
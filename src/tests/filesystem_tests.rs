@@ -1,5 +1,5 @@
 use crate::storage::filesystem::*;
-use std::fs::{File, create_dir_all, write};
+use std::fs::{File, create_dir_all, read_to_string, write};
 use std::path::PathBuf;
 use tempfile::TempDir;
 
@@ -54,31 +54,6 @@ fn test_read_directory_with_subdirectories() {
     assert_eq!(directories, vec!["account1", "account2", "account3"]);
 }
 
-#[test]
-fn test_create_backup_successfully() {
-    // Since create_backup uses Locations which uses system paths,
-    // test with a non-existent vault to verify error handling
-    let result = create_backup("definitely_non_existent_test_vault");
-
-    // This should fail since the vault doesn't exist
-    assert!(result.is_err());
-
-    // The error could be various types depending on the system state
-    let error_msg = result.unwrap_err().to_string();
-
-    // Be more lenient with error message checking
-    assert!(!error_msg.is_empty(), "Error message should not be empty");
-}
-
-#[test]
-fn test_install_backup_successfully() {
-    // This test would require a more complex setup with actual backup directories
-    // For now, test the error case when backup doesn't exist
-    let result = install_backup("non_existent_vault");
-
-    assert!(result.is_err());
-}
-
 #[test]
 fn test_read_non_existent_directory() {
     let non_existent_path = PathBuf::from("/non/existent/directory");
@@ -110,25 +85,85 @@ fn test_rename_vault_with_existing_target() {
 }
 
 #[test]
-fn test_backup_exists_false() {
-    let result = backup_exists("definitely_non_existent_vault");
-    assert!(!result);
-}
-
-#[test]
-fn test_delete_non_existent_backup() {
-    let result = delete_backup("non_existent_vault");
+fn test_rename_account_non_existent_vault() {
+    let result = rename_account("non_existent_vault", "old_account", "new_account");
 
     assert!(result.is_err());
     let error_msg = result.unwrap_err().to_string();
-    assert!(error_msg.contains("does not exist"));
+    assert!(error_msg.contains("does not exist") || error_msg.contains("not found"));
 }
 
 #[test]
-fn test_rename_account_non_existent_vault() {
-    let result = rename_account("non_existent_vault", "old_account", "new_account");
+fn test_replace_files_transactionally_swaps_every_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let first = temp_dir.path().join("first.gpg");
+    let second = temp_dir.path().join("second.gpg");
+    let journal = temp_dir.path().join("rekey.journal");
+    write(&first, "old first").unwrap();
+    write(&second, "old second").unwrap();
+
+    replace_files_transactionally(
+        &[
+            (first.clone(), b"new first".to_vec()),
+            (second.clone(), b"new second".to_vec()),
+        ],
+        &journal,
+    )
+    .unwrap();
+
+    assert_eq!(read_to_string(&first).unwrap(), "new first");
+    assert_eq!(read_to_string(&second).unwrap(), "new second");
+    assert!(!journal.exists());
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+}
+
+#[test]
+fn test_replace_files_transactionally_rolls_back_on_failure() {
+    let temp_dir = TempDir::new().unwrap();
+    let first = temp_dir.path().join("first.gpg");
+    let journal = temp_dir.path().join("rekey.journal");
+    write(&first, "old first").unwrap();
+
+    // The second target's directory does not exist, so it cannot be staged.
+    let missing = temp_dir.path().join("missing").join("second.gpg");
+
+    let result = replace_files_transactionally(
+        &[
+            (first.clone(), b"new first".to_vec()),
+            (missing, b"new second".to_vec()),
+        ],
+        &journal,
+    );
 
     assert!(result.is_err());
-    let error_msg = result.unwrap_err().to_string();
-    assert!(error_msg.contains("does not exist") || error_msg.contains("not found"));
+    assert_eq!(read_to_string(&first).unwrap(), "old first");
+    assert!(!journal.exists());
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn test_recover_file_transaction_rolls_back_interrupted_swap() {
+    let temp_dir = TempDir::new().unwrap();
+    let first = temp_dir.path().join("first.gpg");
+    let second = temp_dir.path().join("second.gpg");
+    let journal = temp_dir.path().join("rekey.journal");
+
+    // State left by a crash after `first` was swapped but before `second` was.
+    write(&first, "new first").unwrap();
+    write(temp_dir.path().join("first.gpg.txn-old"), "old first").unwrap();
+    write(&second, "old second").unwrap();
+    write(temp_dir.path().join("second.gpg.txn-new"), "new second").unwrap();
+    write(
+        &journal,
+        format!("swapping\n{}\n{}\n", first.display(), second.display()),
+    )
+    .unwrap();
+
+    assert!(recover_file_transaction(&journal).unwrap());
+
+    assert_eq!(read_to_string(&first).unwrap(), "old first");
+    assert_eq!(read_to_string(&second).unwrap(), "old second");
+    assert!(!journal.exists());
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+    assert!(!recover_file_transaction(&journal).unwrap());
 }
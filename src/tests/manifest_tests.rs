@@ -0,0 +1,264 @@
+use crate::storage::backup::{create_snapshot, install_snapshot};
+use crate::storage::manifest::*;
+use crate::vault::{metadata::METADATA_FILE, settings::SETTINGS_FILE};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const SIGNATURE: &[u8] = b"signed:";
+
+/// Stands in for a GPG signature, which `fake_verify` checks for.
+fn fake_sign(manifest: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+    Ok([SIGNATURE, manifest].concat())
+}
+
+fn fake_verify(signed: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+    signed
+        .strip_prefix(SIGNATURE)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| anyhow::anyhow!("bad signature"))
+}
+
+fn create_vault(vault: &Path) {
+    fs::write(vault.join("recipient"), "alice@example.com\n").unwrap();
+    fs::write(vault.join("gate.gpg"), "gate").unwrap();
+    fs::write(vault.join(SETTINGS_FILE), "{}").unwrap();
+    fs::write(vault.join(METADATA_FILE), "index").unwrap();
+    fs::create_dir_all(vault.join("bank/revisions")).unwrap();
+    fs::write(vault.join("bank/data.gpg"), "bank").unwrap();
+    fs::write(vault.join("bank/revisions/1.gpg"), "old bank").unwrap();
+    fs::create_dir_all(vault.join("mail")).unwrap();
+    fs::write(vault.join("mail/data.gpg"), "mail").unwrap();
+}
+
+#[test]
+fn test_manifest_covers_account_files_vault_keys_index_and_settings() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path();
+    create_vault(vault);
+    fs::write(vault.join("mail/data.gpg.1-0.fmp-tmp"), "partial").unwrap();
+
+    let manifest = VaultManifest::for_vault(vault, 3).unwrap();
+    let paths: Vec<&str> = manifest.files.keys().map(String::as_str).collect();
+
+    assert_eq!(manifest.version, 3);
+    assert_eq!(
+        paths,
+        vec![
+            "bank/data.gpg",
+            "bank/revisions/1.gpg",
+            "gate.gpg",
+            "mail/data.gpg",
+            METADATA_FILE,
+            "recipient",
+            SETTINGS_FILE,
+        ]
+    );
+    assert_eq!(
+        manifest.files,
+        VaultManifest::for_vault(vault, 4).unwrap().files
+    );
+}
+
+#[test]
+fn test_check_reports_changed_removed_and_added_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path();
+    create_vault(vault);
+    write_manifest(vault, 1, fake_sign).unwrap();
+
+    let check = check_manifest(vault, Some(1), fake_verify).unwrap();
+    assert!(check.is_ok());
+    assert_eq!(check.version, Some(1));
+
+    fs::write(vault.join("bank/data.gpg"), "older bank").unwrap();
+    fs::write(vault.join(METADATA_FILE), "forged index").unwrap();
+    fs::write(vault.join("recipient"), "mallory@example.com\n").unwrap();
+    fs::remove_dir_all(vault.join("mail")).unwrap();
+    fs::create_dir_all(vault.join("shop")).unwrap();
+    fs::write(vault.join("shop/data.gpg"), "shop").unwrap();
+
+    assert_eq!(
+        check_manifest(vault, Some(1), fake_verify)
+            .unwrap()
+            .problems,
+        vec![
+            ManifestProblem::Changed("bank/data.gpg".to_string()),
+            ManifestProblem::Removed("mail/data.gpg".to_string()),
+            ManifestProblem::Changed(METADATA_FILE.to_string()),
+            ManifestProblem::Changed("recipient".to_string()),
+            ManifestProblem::Added("shop/data.gpg".to_string()),
+        ]
+    );
+
+    // Signing again accepts the vault as it is
+    write_manifest(vault, 2, fake_sign).unwrap();
+    assert!(check_manifest(vault, Some(1), fake_verify).unwrap().is_ok());
+}
+
+#[test]
+fn test_check_reports_rollback_and_missing_or_forged_manifests() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path();
+    create_vault(vault);
+
+    // A vault that never had a manifest is fine, one that lost it is not
+    assert_eq!(
+        check_manifest(vault, None, fake_verify).unwrap(),
+        ManifestCheck::default()
+    );
+    assert_eq!(
+        check_manifest(vault, Some(2), fake_verify)
+            .unwrap()
+            .problems,
+        vec![ManifestProblem::Missing]
+    );
+
+    // The whole vault put back as it was at version 1
+    write_manifest(vault, 1, fake_sign).unwrap();
+    assert_eq!(
+        check_manifest(vault, Some(2), fake_verify)
+            .unwrap()
+            .problems,
+        vec![ManifestProblem::RolledBack {
+            version: 1,
+            seen: 2
+        }]
+    );
+
+    write_manifest(vault, 3, |manifest| Ok(manifest.to_vec())).unwrap();
+    let check = check_manifest(vault, Some(2), fake_verify).unwrap();
+    assert_eq!(check.version, None);
+    assert_eq!(
+        check.problems,
+        vec![ManifestProblem::Unverified("bad signature".to_string())]
+    );
+}
+
+#[test]
+fn test_seen_versions_only_go_up() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("config").join(SEEN_VERSIONS_FILE);
+
+    let mut versions = SeenVersions::load(&path).unwrap();
+    assert!(versions.record("work", 2));
+    assert!(!versions.record("work", 1));
+    assert!(!versions.record("work", 2));
+    assert!(versions.record("work", 5));
+    versions.save(&path).unwrap();
+
+    let loaded = SeenVersions::load(&path).unwrap();
+    assert_eq!(loaded.vaults.get("work"), Some(&5));
+    assert_eq!(loaded, versions);
+}
+
+#[test]
+fn test_changes_are_not_signed_while_problems_are_pending() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path();
+    create_vault(vault);
+    let mut versions = SeenVersions::default();
+    sign_manifest(vault, "work", &mut versions, fake_sign, fake_verify).unwrap();
+
+    // Tampered with outside fmp, then noticed when the vault is opened
+    fs::write(vault.join("bank/data.gpg"), "older bank").unwrap();
+    let check = check_and_record_manifest(vault, "work", &mut versions, fake_verify).unwrap();
+    assert!(!check.is_ok());
+    assert!(versions.is_pending("work"));
+
+    // A change fmp makes afterwards must not sign the tampered file
+    fs::write(vault.join("mail/data.gpg"), "new mail").unwrap();
+    let signed = fs::read(vault.join(MANIFEST_FILE)).unwrap();
+    assert_eq!(
+        resign_manifest(vault, "work", &mut versions, fake_sign, fake_verify).unwrap(),
+        None
+    );
+    assert_eq!(fs::read(vault.join(MANIFEST_FILE)).unwrap(), signed);
+
+    let check = check_and_record_manifest(vault, "work", &mut versions, fake_verify).unwrap();
+    assert_eq!(
+        check.problems,
+        vec![
+            ManifestProblem::Changed("bank/data.gpg".to_string()),
+            ManifestProblem::Changed("mail/data.gpg".to_string()),
+        ]
+    );
+
+    // Only trusting the vault as it is clears the problems
+    assert_eq!(
+        sign_manifest(vault, "work", &mut versions, fake_sign, fake_verify).unwrap(),
+        2
+    );
+    assert!(!versions.is_pending("work"));
+    assert!(
+        check_and_record_manifest(vault, "work", &mut versions, fake_verify)
+            .unwrap()
+            .is_ok()
+    );
+    assert_eq!(
+        resign_manifest(vault, "work", &mut versions, fake_sign, fake_verify).unwrap(),
+        Some(3)
+    );
+}
+
+#[test]
+fn test_forged_manifest_is_not_signed_over() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path();
+    create_vault(vault);
+    let mut versions = SeenVersions::default();
+    sign_manifest(vault, "work", &mut versions, fake_sign, fake_verify).unwrap();
+
+    write_manifest(vault, 9, |manifest| Ok(manifest.to_vec())).unwrap();
+    assert_eq!(
+        resign_manifest(vault, "work", &mut versions, fake_sign, fake_verify).unwrap(),
+        None
+    );
+    assert!(versions.is_pending("work"));
+}
+
+#[test]
+fn test_restored_backup_is_accepted_once_signed() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path().join("vaults").join("work");
+    fs::create_dir_all(&vault).unwrap();
+    create_vault(&vault);
+    let mut versions = SeenVersions::default();
+    sign_manifest(&vault, "work", &mut versions, fake_sign, fake_verify).unwrap();
+
+    let snapshot = create_snapshot(
+        &vault,
+        &temp_dir.path().join("backups").join("work"),
+        chrono::Utc::now().naive_utc(),
+    )
+    .unwrap();
+    fs::write(vault.join("bank/data.gpg"), "newer bank").unwrap();
+    assert_eq!(
+        resign_manifest(&vault, "work", &mut versions, fake_sign, fake_verify).unwrap(),
+        Some(2)
+    );
+
+    // The snapshot brings back its version 1 manifest, which looks like a rollback
+    install_snapshot(&snapshot, &vault).unwrap();
+    assert_eq!(
+        resign_manifest(
+            &vault,
+            "work",
+            &mut versions.clone(),
+            fake_sign,
+            fake_verify
+        )
+        .unwrap(),
+        None
+    );
+
+    // So a restore signs the vault as restored, as `install_backup` does
+    assert_eq!(
+        sign_manifest(&vault, "work", &mut versions, fake_sign, fake_verify).unwrap(),
+        3
+    );
+    let check = check_and_record_manifest(&vault, "work", &mut versions, fake_verify).unwrap();
+    assert!(check.is_ok());
+    assert_eq!(check.version, Some(3));
+    assert!(!versions.is_pending("work"));
+}
@@ -0,0 +1,135 @@
+use crate::models::Account;
+use crate::vault::metadata::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Writes an account as plain JSON, which `fake_decrypt` reads back.
+fn write_account(dir: &Path, account: &Account) -> (String, PathBuf) {
+    let account_dir = dir.join(&account.name);
+    fs::create_dir_all(&account_dir).unwrap();
+    let data = account_dir.join("data.gpg");
+    fs::write(&data, serde_json::to_string(account).unwrap()).unwrap();
+    (account.name.clone(), data)
+}
+
+fn fake_decrypt(path: &Path) -> Result<Account, anyhow::Error> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+fn metadata(name: &str, account_type: &str, modified_at: &str) -> AccountMetadata {
+    AccountMetadata {
+        name: name.to_string(),
+        account_type: account_type.to_string(),
+        modified_at: modified_at.to_string(),
+        ..AccountMetadata::default()
+    }
+}
+
+#[test]
+fn test_refresh_only_decrypts_changed_accounts() {
+    let temp_dir = TempDir::new().unwrap();
+    let bank = Account {
+        name: "bank".to_string(),
+        username: "alice".to_string(),
+        tags: vec!["finance".to_string()],
+        ..Account::default()
+    };
+    let mail = Account {
+        name: "mail".to_string(),
+        website: "mail.example.com".to_string(),
+        ..Account::default()
+    };
+    let accounts = vec![
+        write_account(temp_dir.path(), &bank),
+        write_account(temp_dir.path(), &mail),
+    ];
+
+    let mut index = MetadataIndex::default();
+    let mut decrypted = 0;
+    assert!(index.refresh(&accounts, |path| {
+        decrypted += 1;
+        fake_decrypt(path)
+    }));
+    assert_eq!(decrypted, 2);
+    assert_eq!(index.accounts["bank"].username, "alice");
+    assert_eq!(index.accounts["bank"].tags, vec!["finance".to_string()]);
+    assert_eq!(index.accounts["mail"].website, "mail.example.com");
+
+    // Nothing changed, so nothing is decrypted
+    assert!(!index.refresh(&accounts, |_| panic!("decrypted an unchanged account")));
+
+    // A changed file is read again, and a removed account is dropped
+    let changed = Account {
+        username: "bob".to_string(),
+        ..bank
+    };
+    let accounts = vec![write_account(temp_dir.path(), &changed)];
+    let mut decrypted = Vec::new();
+    assert!(index.refresh(&accounts, |path| {
+        decrypted.push(path.to_path_buf());
+        fake_decrypt(path)
+    }));
+    assert_eq!(decrypted, vec![accounts[0].1.clone()]);
+    assert_eq!(index.accounts["bank"].username, "bob");
+    assert!(!index.accounts.contains_key("mail"));
+}
+
+#[test]
+fn test_refresh_leaves_out_accounts_that_cannot_be_decrypted() {
+    let temp_dir = TempDir::new().unwrap();
+    let accounts = vec![write_account(
+        temp_dir.path(),
+        &Account {
+            name: "bank".to_string(),
+            ..Account::default()
+        },
+    )];
+
+    let mut index = MetadataIndex::default();
+    assert!(!index.refresh(&accounts, |_| Err(anyhow::anyhow!("locked"))));
+    assert!(index.accounts.is_empty());
+    assert!(load_account_metadata("fmp-metadata-tests-missing-vault").is_err());
+}
+
+#[test]
+fn test_search_matches_every_word_in_any_field() {
+    let account = AccountMetadata {
+        name: "Bank of X".to_string(),
+        account_type: "Finance".to_string(),
+        website: "bank.example.com".to_string(),
+        username: "alice".to_string(),
+        tags: vec!["personal".to_string()],
+        ..AccountMetadata::default()
+    };
+
+    assert!(account.matches(""));
+    assert!(account.matches("bank"));
+    assert!(account.matches("ALICE example"));
+    assert!(account.matches("person"));
+    assert!(!account.matches("alice work"));
+}
+
+#[test]
+fn test_sort_account_metadata() {
+    let mut accounts = vec![
+        metadata("mail", "Email", "2025-01-02 00:00:00"),
+        metadata("Bank", "Finance", "2025-01-01 00:00:00"),
+        metadata("shop", "email", "2025-03-01 00:00:00"),
+    ];
+    let names = |accounts: &[AccountMetadata]| -> Vec<String> {
+        accounts
+            .iter()
+            .map(|account| account.name.clone())
+            .collect()
+    };
+
+    sort_account_metadata(&mut accounts, AccountSort::Name);
+    assert_eq!(names(&accounts), vec!["Bank", "mail", "shop"]);
+
+    sort_account_metadata(&mut accounts, AccountSort::RecentlyModified);
+    assert_eq!(names(&accounts), vec!["shop", "mail", "Bank"]);
+
+    sort_account_metadata(&mut accounts, AccountSort::Type);
+    assert_eq!(names(&accounts), vec!["mail", "shop", "Bank"]);
+}
@@ -1,10 +1,28 @@
+pub mod account_names_tests;
 pub mod account_tests;
+pub mod archive_tests;
+pub mod audit_tests;
+pub mod auto_backup_tests;
+pub mod backup_tests;
+pub mod breach_tests;
+pub mod cli_tests;
+pub mod compare_tests;
+pub mod container_tests;
 pub mod crypto_tests;
 pub mod filesystem_tests;
 pub mod locations_tests;
+pub mod manifest_tests;
+pub mod metadata_tests;
 pub mod password_tests;
+pub mod profile_tests;
+pub mod restore_tests;
+pub mod revisions_tests;
 pub mod secure_clipboard_tests;
 pub mod secure_password_tests;
+pub mod settings_tests;
 pub mod store_tests;
+pub mod strength_tests;
 pub mod totp_tests;
+pub mod trash_tests;
+pub mod vault_files_tests;
 pub mod vault_operations_tests;
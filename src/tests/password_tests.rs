@@ -98,6 +98,7 @@ fn test_generate_password_mixed() {
         include_extended: false,
         additional_characters: String::new(),
         excluded_characters: String::new(),
+        ..PasswordConfig::default()
     };
 
     let password = generate_password(&config).unwrap();
@@ -128,6 +129,7 @@ fn test_generate_password_exclude_ambiguous() {
         include_extended: false,
         additional_characters: String::new(),
         excluded_characters: "0OIl1".to_string(), // Exclude ambiguous characters
+        ..PasswordConfig::default()
     };
 
     let password = generate_password(&config).unwrap();
@@ -152,6 +154,7 @@ fn test_generate_password_with_lowercase_and_symbols() {
         include_extended: false,
         additional_characters: String::new(),
         excluded_characters: String::new(),
+        ..PasswordConfig::default()
     };
 
     let password = generate_password(&config).unwrap();
@@ -181,8 +184,12 @@ fn test_calculate_strength_various_passwords() {
     let strong_strength = calculate_password_strength("Abc123!@#XyzLongPassword");
     assert!(strong_strength >= 76);
 
-    // Test medium password
-    let medium_strength = calculate_password_strength("Password123");
+    // A common password with a predictable suffix is weak despite mixing character classes
+    let common_strength = calculate_password_strength("Password123");
+    assert!(common_strength <= 25);
+
+    // Test medium password (an uncommon word and digits)
+    let medium_strength = calculate_password_strength("dragonfly42");
     assert!(medium_strength > 25 && medium_strength <= 75);
 }
 
@@ -209,6 +216,7 @@ fn test_handle_no_character_types() {
         include_extended: false,
         additional_characters: String::new(),
         excluded_characters: String::new(),
+        ..PasswordConfig::default()
     };
 
     let result = generate_password(&config);
@@ -246,3 +254,111 @@ fn test_calculate_password_strength_edge_cases() {
     let special_strength = calculate_password_strength("@#$%^&*()");
     assert!(special_strength > 0 && special_strength <= 100);
 }
+
+#[test]
+fn test_eff_wordlist_is_complete() {
+    let words = eff_wordlist();
+
+    assert_eq!(words.len(), 7776);
+    assert!(words.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(
+        words
+            .iter()
+            .all(|word| word.chars().all(|c| c.is_ascii_lowercase() || c == '-'))
+    );
+}
+
+#[test]
+fn test_eff_wordlist_follows_dice_rolls() {
+    let lines: Vec<&str> = include_str!("../wordlists/eff_large_wordlist.txt")
+        .lines()
+        .collect();
+
+    // Every roll of five dice appears once, in order, as in the published list
+    let mut rolls = Vec::new();
+    for roll in 0..7776u32 {
+        let digits: String = (0..5)
+            .rev()
+            .map(|place| char::from(b'1' + (roll / 6u32.pow(place) % 6) as u8))
+            .collect();
+        rolls.push(digits);
+    }
+    let indices: Vec<&str> = lines
+        .iter()
+        .map(|line| line.split('\t').next().unwrap())
+        .collect();
+    assert_eq!(indices, rolls);
+
+    assert_eq!(lines.first(), Some(&"11111\tabacus"));
+    assert_eq!(lines.last(), Some(&"66666\tzoom"));
+}
+
+#[test]
+fn test_generate_passphrase_words_and_separator() {
+    let config = PasswordConfig {
+        mode: PasswordMode::Passphrase,
+        passphrase: PassphraseConfig {
+            word_count: 5,
+            separator: ".".to_string(),
+            ..PassphraseConfig::default()
+        },
+        ..PasswordConfig::default()
+    };
+
+    let passphrase = generate_password(&config).unwrap();
+    let words: Vec<&str> = passphrase.split('.').collect();
+
+    assert_eq!(words.len(), 5);
+    assert!(words.iter().all(|word| eff_wordlist().contains(word)));
+}
+
+#[test]
+fn test_generate_passphrase_capitalize_and_suffix() {
+    let config = PassphraseConfig {
+        word_count: 4,
+        separator: " ".to_string(),
+        capitalize: true,
+        append_digit: true,
+        append_symbol: true,
+    };
+
+    let passphrase = generate_passphrase(&config).unwrap();
+    let mut chars: Vec<char> = passphrase.chars().collect();
+    let symbol = chars.pop().unwrap();
+    let digit = chars.pop().unwrap();
+
+    assert!(SYMBOLS.contains(symbol));
+    assert!(digit.is_ascii_digit());
+    for word in chars.iter().collect::<String>().split(' ') {
+        assert!(word.starts_with(|c: char| c.is_ascii_uppercase()));
+        assert!(eff_wordlist().contains(&word.to_lowercase().as_str()));
+    }
+}
+
+#[test]
+fn test_passphrase_word_count_limits() {
+    for word_count in [0, MAX_PASSPHRASE_WORDS + 1] {
+        let config = PassphraseConfig {
+            word_count,
+            ..PassphraseConfig::default()
+        };
+        assert!(generate_passphrase(&config).is_err());
+    }
+}
+
+#[test]
+fn test_passphrase_entropy_bits() {
+    let six_words = PassphraseConfig::default();
+    let with_suffix = PassphraseConfig {
+        append_digit: true,
+        append_symbol: true,
+        capitalize: true,
+        ..PassphraseConfig::default()
+    };
+
+    assert!((passphrase_entropy_bits(&six_words) - 6.0 * 7776f64.log2()).abs() < 1e-9);
+    assert!(
+        (passphrase_entropy_bits(&with_suffix) - (6.0 * 7776f64.log2() + 10f64.log2() + 5.0)).abs()
+            < 1e-9
+    );
+}
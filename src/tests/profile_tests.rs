@@ -0,0 +1,114 @@
+use crate::storage::profile::*;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+fn default() -> DataHome {
+    DataHome {
+        profile: None,
+        data: PathBuf::from("/data/fmp"),
+        config: PathBuf::from("/config/fmp"),
+        gnupg_home: None,
+    }
+}
+
+#[test]
+fn test_resolve_default_and_home() {
+    let profiles = BTreeMap::new();
+    assert_eq!(
+        resolve_home(&HomeSelection::Default, &profiles, &default()).unwrap(),
+        default()
+    );
+
+    // A chosen home keeps the config files next to the data, but not among it
+    let home = resolve_home(
+        &HomeSelection::Home(PathBuf::from("/tmp/fmp-test")),
+        &profiles,
+        &default(),
+    )
+    .unwrap();
+    assert_eq!(home.data, PathBuf::from("/tmp/fmp-test/data"));
+    assert_eq!(home.config, PathBuf::from("/tmp/fmp-test/config"));
+    assert_eq!(home.profile, None);
+    assert_eq!(home.describe(), "/tmp/fmp-test/data");
+}
+
+#[test]
+fn test_resolve_profiles() {
+    let mut profiles = BTreeMap::new();
+    profiles.insert(
+        "work".to_string(),
+        ProfileConfig {
+            home: Some(PathBuf::from("/srv/work")),
+            gnupg_home: Some(PathBuf::from("/srv/work/gnupg")),
+        },
+    );
+
+    let work = resolve_home(
+        &HomeSelection::Profile("work".to_string()),
+        &profiles,
+        &default(),
+    )
+    .unwrap();
+    assert_eq!(work.data, PathBuf::from("/srv/work/data"));
+    assert_eq!(work.config, PathBuf::from("/srv/work/config"));
+    assert_eq!(work.gnupg_home, Some(PathBuf::from("/srv/work/gnupg")));
+    assert_eq!(work.describe(), "profile work");
+
+    // Profiles missing from the file are kept under the default data directory
+    let personal = resolve_home(
+        &HomeSelection::Profile("personal".to_string()),
+        &profiles,
+        &default(),
+    )
+    .unwrap();
+    assert_eq!(
+        personal.data,
+        PathBuf::from("/data/fmp/profiles/personal/data")
+    );
+    assert_eq!(
+        personal.config,
+        PathBuf::from("/data/fmp/profiles/personal/config")
+    );
+    assert_eq!(personal.gnupg_home, None);
+
+    for name in ["", ".", "..", "../escape", "a\\b"] {
+        assert!(
+            resolve_home(
+                &HomeSelection::Profile(name.to_string()),
+                &profiles,
+                &default()
+            )
+            .is_err(),
+            "{name:?} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn test_load_profiles() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join(PROFILES_FILE);
+
+    assert!(load_profiles(&path).unwrap().is_empty());
+
+    fs::write(
+        &path,
+        r#"{"work": {"home": "/srv/work", "gnupg_home": "/srv/gnupg"}, "personal": {}}"#,
+    )
+    .unwrap();
+    let profiles = load_profiles(&path).unwrap();
+    assert_eq!(
+        profiles.keys().collect::<Vec<_>>(),
+        vec!["personal", "work"]
+    );
+    assert_eq!(profiles["personal"], ProfileConfig::default());
+    assert_eq!(profiles["work"].home, Some(PathBuf::from("/srv/work")));
+
+    fs::write(&path, r#"{"../work": {}}"#).unwrap();
+    assert!(load_profiles(&path).is_err());
+
+    fs::write(&path, "not json").unwrap();
+    assert!(load_profiles(&path).is_err());
+}
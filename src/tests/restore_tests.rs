@@ -0,0 +1,60 @@
+use crate::vault::restore::*;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Creates an account directory holding a `data.gpg` in `snapshot_dir`.
+fn create_account(snapshot_dir: &Path, name: &str) {
+    let account_dir = snapshot_dir.join(name);
+    fs::create_dir_all(&account_dir).unwrap();
+    fs::write(account_dir.join("data.gpg"), name).unwrap();
+}
+
+#[test]
+fn test_read_snapshot_accounts_sorted() {
+    let temp_dir = TempDir::new().unwrap();
+    create_account(temp_dir.path(), "mail");
+    create_account(temp_dir.path(), "bank");
+    create_account(temp_dir.path(), "forum");
+
+    assert_eq!(
+        read_snapshot_accounts(temp_dir.path()).unwrap(),
+        vec!["bank", "forum", "mail"]
+    );
+}
+
+#[test]
+fn test_read_snapshot_accounts_skips_non_accounts() {
+    let temp_dir = TempDir::new().unwrap();
+    create_account(temp_dir.path(), "bank");
+    create_account(&temp_dir.path().join(".trash"), "20250101T000000000-old");
+    fs::create_dir_all(temp_dir.path().join("empty")).unwrap();
+    fs::write(temp_dir.path().join("recipient"), "user@example.com").unwrap();
+    fs::write(temp_dir.path().join("settings.json"), "{}").unwrap();
+
+    assert_eq!(
+        read_snapshot_accounts(temp_dir.path()).unwrap(),
+        vec!["bank"]
+    );
+}
+
+#[test]
+fn test_read_snapshot_accounts_missing_snapshot() {
+    let temp_dir = TempDir::new().unwrap();
+
+    assert!(read_snapshot_accounts(&temp_dir.path().join("missing")).is_err());
+}
+
+#[test]
+fn test_restore_from_missing_vault_fails() {
+    assert!(
+        restore_account_from_backup(
+            "fmp-restore-tests-missing-vault",
+            "20250101T000000000",
+            "bank",
+            &RestoreTarget::Overwrite,
+        )
+        .is_err()
+    );
+    assert!(list_backup_accounts("fmp-restore-tests-missing-vault", "20250101T000000000").is_err());
+}
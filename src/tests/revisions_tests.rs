@@ -0,0 +1,152 @@
+use crate::models::account::{Account, AccountTotp};
+use crate::security::secure_password::SecurePassword;
+use crate::storage::Locations;
+use crate::vault::revisions::*;
+use std::fs;
+use tempfile::TempDir;
+
+fn account() -> Account {
+    Account {
+        name: "Mail".to_string(),
+        account_type: "Email".to_string(),
+        website: "https://mail.example.com".to_string(),
+        username: "alice".to_string(),
+        password: SecurePassword::new("k7#Vq9!xLm2$Rw8zT4".to_string()),
+        notes: "Personal inbox".to_string(),
+        modified_at: "2025-05-01 09:00:00".to_string(),
+        ..Account::default()
+    }
+}
+
+/// Points an account's `Locations` at a temporary directory holding a `data.gpg`.
+fn temp_locations(temp_dir: &TempDir) -> Locations {
+    let mut locations = Locations::new("revisions_test_vault", "revisions_test_account");
+    locations.account = temp_dir.path().to_path_buf();
+    locations.data = temp_dir.path().join("data.gpg");
+    locations.revisions = temp_dir.path().join("revisions");
+    fs::write(&locations.data, b"encrypted").unwrap();
+    locations
+}
+
+#[test]
+fn test_diff_identical_accounts() {
+    assert!(diff_accounts(&account(), &account()).is_empty());
+
+    // Only the timestamps differ.
+    let mut later = account();
+    later.modified_at = "2025-06-01 09:00:00".to_string();
+    assert!(diff_accounts(&account(), &later).is_empty());
+}
+
+#[test]
+fn test_diff_plain_fields() {
+    let mut new = account();
+    new.username = "alice@example.com".to_string();
+    new.tags = vec!["mail".to_string(), "personal".to_string()];
+    new.notes = String::new();
+
+    assert_eq!(
+        diff_accounts(&account(), &new),
+        vec![
+            FieldChange {
+                field: "Username".to_string(),
+                old: Some("alice".to_string()),
+                new: Some("alice@example.com".to_string()),
+                sensitive: false,
+            },
+            FieldChange {
+                field: "Tags".to_string(),
+                old: Some(String::new()),
+                new: Some("mail, personal".to_string()),
+                sensitive: false,
+            },
+            FieldChange {
+                field: "Notes".to_string(),
+                old: Some("Personal inbox".to_string()),
+                new: Some(String::new()),
+                sensitive: false,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_diff_hides_secrets() {
+    let mut new = account();
+    new.password = SecurePassword::new("u3&Pz8!rQw@5Lk#2".to_string());
+    new.totp = Some(AccountTotp::default());
+
+    let changes = diff_accounts(&account(), &new);
+    let fields: Vec<&str> = changes.iter().map(|change| change.field.as_str()).collect();
+    assert_eq!(fields, ["Password", "Two-factor secret"]);
+    assert!(
+        changes
+            .iter()
+            .all(|change| change.sensitive && change.old.is_none() && change.new.is_none())
+    );
+}
+
+#[test]
+fn test_diff_additional_fields() {
+    let mut old = account();
+    old.additional_fields
+        .insert("PIN".to_string(), "1234".to_string());
+    old.additional_fields
+        .insert("Recovery email".to_string(), "old@example.com".to_string());
+
+    let mut new = account();
+    new.additional_fields
+        .insert("PIN".to_string(), "5678".to_string());
+    new.additional_fields
+        .insert("Security question".to_string(), "First pet".to_string());
+
+    let changes = diff_accounts(&old, &new);
+    assert_eq!(changes.len(), 3);
+    assert_eq!(changes[0].field, "PIN");
+    assert_eq!(changes[0].new.as_deref(), Some("5678"));
+    assert_eq!(changes[1].field, "Recovery email");
+    assert_eq!(changes[1].new, None);
+    assert_eq!(changes[2].field, "Security question");
+    assert_eq!(changes[2].old, None);
+}
+
+#[test]
+fn test_record_and_read_revisions() {
+    let temp_dir = TempDir::new().unwrap();
+    let locations = temp_locations(&temp_dir);
+
+    assert!(read_revisions(&locations.revisions).unwrap().is_empty());
+
+    let first = record_revision(&locations, &account()).unwrap();
+    assert_eq!(first.id, 1);
+    assert_eq!(first.saved_at, "2025-05-01 09:00:00");
+    assert_eq!(fs::read(&first.path).unwrap(), b"encrypted");
+
+    let mut edited = account();
+    edited.modified_at = "2025-05-02 10:30:00".to_string();
+    let second = record_revision(&locations, &edited).unwrap();
+    assert_eq!(second.id, 2);
+
+    // Stray files are ignored.
+    fs::write(locations.revisions.join("notes.txt"), "").unwrap();
+
+    assert_eq!(
+        read_revisions(&locations.revisions).unwrap(),
+        vec![second, first]
+    );
+}
+
+#[test]
+fn test_record_revision_prunes_oldest() {
+    let temp_dir = TempDir::new().unwrap();
+    let locations = temp_locations(&temp_dir);
+
+    for _ in 0..MAX_REVISIONS + 5 {
+        record_revision(&locations, &account()).unwrap();
+    }
+
+    let revisions = read_revisions(&locations.revisions).unwrap();
+    assert_eq!(revisions.len(), MAX_REVISIONS);
+    assert_eq!(revisions[0].id as usize, MAX_REVISIONS + 5);
+    assert_eq!(revisions.last().unwrap().id, 6);
+}
@@ -0,0 +1,50 @@
+use crate::storage::{auto_backup::AutoBackupPolicy, backup::RetentionPolicy};
+use crate::vault::settings::*;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_missing_settings_use_defaults() {
+    let temp_dir = TempDir::new().unwrap();
+    let settings = VaultSettings::load(&temp_dir.path().join("settings.json")).unwrap();
+
+    assert_eq!(settings, VaultSettings::default());
+    assert_eq!(settings.trash_retention_days, DEFAULT_TRASH_RETENTION_DAYS);
+    assert!(!settings.auto_backup.is_enabled());
+}
+
+#[test]
+fn test_settings_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("settings.json");
+    let settings = VaultSettings {
+        trash_retention_days: 7,
+        backup_retention: RetentionPolicy {
+            keep_last: 3,
+            ..RetentionPolicy::default()
+        },
+        auto_backup: AutoBackupPolicy {
+            every_changes: 10,
+            on_exit: true,
+            daily: false,
+        },
+    };
+
+    settings.save(&path).unwrap();
+    assert_eq!(VaultSettings::load(&path).unwrap(), settings);
+}
+
+#[test]
+fn test_settings_missing_keys_and_invalid_json() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("settings.json");
+
+    fs::write(&path, "{}").unwrap();
+    assert_eq!(
+        VaultSettings::load(&path).unwrap(),
+        VaultSettings::default()
+    );
+
+    fs::write(&path, "not json").unwrap();
+    assert!(VaultSettings::load(&path).is_err());
+}
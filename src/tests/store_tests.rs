@@ -1,6 +1,11 @@
 use crate::models::Account;
 use crate::security::SecurePassword;
-use crate::storage::Store;
+use crate::storage::store::{
+    TEMP_SUFFIX, parse_recipients, read_recipients, recover_unfinished_writes, write_private_file,
+    write_recipients,
+};
+use crate::storage::{Locations, Store};
+use tempfile::TempDir;
 
 #[test]
 fn test_create_store_instance_successfully() {
@@ -181,3 +186,122 @@ fn test_store_with_empty_names() {
         }
     }
 }
+
+#[test]
+fn test_parse_recipients_one_per_line() {
+    let recipients = parse_recipients(
+        "alice@example.com\n\n# old key\n  bob@example.com  \nalice@example.com\n",
+    );
+
+    assert_eq!(recipients, vec!["alice@example.com", "bob@example.com"]);
+}
+
+#[test]
+fn test_parse_recipients_legacy_single_recipient() {
+    // Vaults created before multiple recipients hold one ID with no trailing newline
+    assert_eq!(
+        parse_recipients("ABCDEF0123456789"),
+        vec!["ABCDEF0123456789"]
+    );
+}
+
+#[test]
+fn test_write_and_read_recipients_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut locations = Locations::new("test_vault", "");
+    locations.recipient = temp_dir.path().join("recipient");
+
+    let recipients = vec![
+        "alice@example.com".to_string(),
+        "bob@example.com".to_string(),
+    ];
+    write_recipients(&locations, &recipients).unwrap();
+
+    assert_eq!(read_recipients(&locations).unwrap(), recipients);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&locations.recipient)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
+
+#[test]
+fn test_recipients_cannot_be_empty() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut locations = Locations::new("test_vault", "");
+    locations.recipient = temp_dir.path().join("recipient");
+
+    assert!(write_recipients(&locations, &[]).is_err());
+
+    std::fs::write(&locations.recipient, "\n# nobody\n").unwrap();
+    assert!(read_recipients(&locations).is_err());
+}
+
+#[test]
+fn test_write_private_file_replaces_atomically() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("data.gpg");
+
+    write_private_file(&path, b"first").unwrap();
+    write_private_file(&path, b"second").unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), b"second");
+
+    // Nothing is left beside the file once the write is done
+    let entries: Vec<_> = std::fs::read_dir(temp_dir.path()).unwrap().collect();
+    assert_eq!(entries.len(), 1);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // A failed write leaves the original alone
+    let missing_dir = temp_dir.path().join("missing").join("data.gpg");
+    assert!(write_private_file(&missing_dir, b"lost").is_err());
+    assert_eq!(std::fs::read(&path).unwrap(), b"second");
+}
+
+#[test]
+fn test_recover_unfinished_writes() {
+    let temp_dir = TempDir::new().unwrap();
+    let account_dir = temp_dir.path().join("vaults").join("work").join("email");
+    std::fs::create_dir_all(&account_dir).unwrap();
+
+    std::fs::write(account_dir.join("data.gpg"), "intact").unwrap();
+    // Left by a process that no longer exists
+    let stale = account_dir.join(format!("data.gpg.4294967295-0.{TEMP_SUFFIX}"));
+    std::fs::write(&stale, "").unwrap();
+    // Left by this process before it started recovering
+    let own = account_dir.join(format!("data.gpg.{}-7.{TEMP_SUFFIX}", std::process::id()));
+    std::fs::write(&own, "half").unwrap();
+    // Not a temporary file, despite the name
+    let unrelated = account_dir.join("notes.fmp-tmp");
+    std::fs::write(&unrelated, "keep").unwrap();
+
+    let mut removed = recover_unfinished_writes(temp_dir.path()).unwrap();
+    removed.sort();
+    let mut expected = vec![stale.clone(), own.clone()];
+    expected.sort();
+    assert_eq!(removed, expected);
+
+    assert!(!stale.exists());
+    assert!(!own.exists());
+    assert!(unrelated.exists());
+    assert_eq!(
+        std::fs::read_to_string(account_dir.join("data.gpg")).unwrap(),
+        "intact"
+    );
+
+    assert!(
+        recover_unfinished_writes(&temp_dir.path().join("missing"))
+            .unwrap()
+            .is_empty()
+    );
+}
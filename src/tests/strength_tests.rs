@@ -0,0 +1,196 @@
+use crate::strength::*;
+
+fn has_pattern(estimate: &StrengthEstimate, matches: impl Fn(&Pattern) -> bool) -> bool {
+    estimate
+        .sequence
+        .iter()
+        .any(|found| matches(&found.pattern))
+}
+
+#[test]
+fn test_empty_password() {
+    let estimate = estimate_password_strength("");
+    assert_eq!(estimate.score, 0);
+    assert_eq!(estimate.percent, 0);
+    assert!(estimate.sequence.is_empty());
+}
+
+#[test]
+fn test_repeated_common_password_is_weak() {
+    let estimate = estimate_password_strength("Password1!Password1!");
+    assert!(estimate.score <= 1, "score was {}", estimate.score);
+    assert!(estimate.percent <= 25);
+    assert!(has_pattern(&estimate, |p| matches!(
+        p,
+        Pattern::Repeat { count: 2, .. }
+    )));
+}
+
+#[test]
+fn test_long_repeated_password_is_weak() {
+    for password in [
+        "a".repeat(112),
+        "password".repeat(10),
+        "Password1!".repeat(10),
+        format!("monkey{}", "!".repeat(80)),
+    ] {
+        let estimate = estimate_password_strength(&password);
+        assert!(estimate.score <= 2, "{password} scored {}", estimate.score);
+        assert!(has_pattern(&estimate, |p| matches!(
+            p,
+            Pattern::Repeat { .. }
+        )));
+    }
+
+    // Characters past the repeat still count
+    let padded = format!("{}k7#Vq9!xLm2$Rw8zT4", "a".repeat(100));
+    assert_eq!(estimate_password_strength(&padded).score, 4);
+}
+
+#[test]
+fn test_top_common_password() {
+    let estimate = estimate_password_strength("password");
+    assert_eq!(estimate.score, 0);
+    assert_eq!(estimate.warning, Some("This is a top-10 common password"));
+    assert!(!estimate.suggestions.is_empty());
+}
+
+#[test]
+fn test_dictionary_word_with_capital_and_digits() {
+    let estimate = estimate_password_strength("Sunshine2024");
+    assert!(estimate.score <= 2);
+    assert!(has_pattern(&estimate, |p| matches!(
+        p,
+        Pattern::Dictionary { .. }
+    )));
+    assert!(has_pattern(&estimate, |p| matches!(p, Pattern::Year)));
+}
+
+#[test]
+fn test_reversed_word() {
+    let estimate = estimate_password_strength("drowssap");
+    assert!(estimate.score <= 1);
+    assert!(has_pattern(&estimate, |p| matches!(
+        p,
+        Pattern::Dictionary { reversed: true, .. }
+    )));
+}
+
+#[test]
+fn test_l33t_substitutions() {
+    let estimate = estimate_password_strength("p@55w0rd");
+    assert!(estimate.score <= 1);
+    assert!(has_pattern(&estimate, |p| matches!(
+        p,
+        Pattern::Dictionary { l33t: true, .. }
+    )));
+}
+
+#[test]
+fn test_keyboard_walks() {
+    for walk in ["xcvbnm,./", "3edc4rfv", "!@#$%^&*"] {
+        let estimate = estimate_password_strength(walk);
+        assert!(estimate.score <= 2, "{walk} scored {}", estimate.score);
+        assert!(
+            has_pattern(&estimate, |p| matches!(p, Pattern::Spatial { .. })),
+            "{walk} was not matched as a keyboard walk"
+        );
+    }
+
+    let keypad = estimate_password_strength("7412369");
+    assert!(keypad.score <= 2);
+    assert!(has_pattern(&keypad, |p| matches!(
+        p,
+        Pattern::Spatial {
+            keyboard: "keypad",
+            ..
+        }
+    )));
+}
+
+#[test]
+fn test_sequences_and_repeats() {
+    let sequence = estimate_password_strength("abcdefgh");
+    assert!(sequence.score <= 1);
+    assert!(has_pattern(&sequence, |p| matches!(
+        p,
+        Pattern::Sequence { ascending: true }
+    )));
+
+    let descending = estimate_password_strength("98765432");
+    assert!(has_pattern(&descending, |p| matches!(
+        p,
+        Pattern::Sequence { ascending: false }
+    )));
+
+    let repeat = estimate_password_strength("zzzzzzzzzz");
+    assert_eq!(repeat.score, 0);
+    assert!(has_pattern(&repeat, |p| matches!(
+        p,
+        Pattern::Repeat { .. }
+    )));
+}
+
+#[test]
+fn test_dates() {
+    for date in ["14/07/1989", "1989-07-14", "140789"] {
+        let estimate = estimate_password_strength(date);
+        assert!(estimate.score <= 2, "{date} scored {}", estimate.score);
+        assert!(
+            has_pattern(&estimate, |p| matches!(p, Pattern::Date { year: 1989, .. })),
+            "{date} was not matched as a date"
+        );
+    }
+}
+
+#[test]
+fn test_random_password_is_strong() {
+    let estimate = estimate_password_strength("k7#Vq9!xLm2$Rw8zT4");
+    assert_eq!(estimate.score, 4);
+    assert!(estimate.percent > 75);
+    assert!(estimate.warning.is_none());
+}
+
+#[test]
+fn test_uncommon_words_are_stronger_than_one_word() {
+    let one = estimate_password_strength("correct");
+    let four = estimate_password_strength("correct horse battery staple");
+    assert!(four.guesses > one.guesses * 1e6);
+    assert!(four.score >= 3);
+}
+
+#[test]
+fn test_crack_times_scale_with_guesses() {
+    let estimate = StrengthEstimate::from_guesses(1e10);
+    assert_eq!(estimate.score, 3);
+    assert!(estimate.crack_times.online_throttled > estimate.crack_times.online_unthrottled);
+    assert!(estimate.crack_times.online_unthrottled > estimate.crack_times.offline_slow);
+    assert!(estimate.crack_times.offline_slow > estimate.crack_times.offline_fast);
+    assert!((estimate.crack_times.offline_fast - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_display_crack_time() {
+    assert_eq!(display_crack_time(0.5), "less than a second");
+    assert_eq!(display_crack_time(1.0), "1 second");
+    assert_eq!(display_crack_time(90.0), "2 minutes");
+    assert_eq!(display_crack_time(3.0 * 3600.0), "3 hours");
+    assert_eq!(display_crack_time(1e12), "centuries");
+}
+
+#[test]
+fn test_very_long_password_is_handled() {
+    let long_password = "a".repeat(500);
+    let estimate = estimate_password_strength(&long_password);
+    assert!(estimate.guesses.is_finite());
+    assert!(estimate.percent <= 100);
+}
+
+#[test]
+fn test_very_long_random_password_is_strong() {
+    let long_password: String = (0..2000u32)
+        .map(|i| char::from_u32(0x4e00 + (i * 7919) % 20000).unwrap())
+        .collect();
+    let estimate = estimate_password_strength(&long_password);
+    assert_eq!(estimate.score, 4);
+}
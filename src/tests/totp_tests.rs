@@ -49,14 +49,14 @@ fn test_verify_totp_code_with_secret_valid_format() {
     let secret = b"test_secret_for_totp_validation";
 
     // Test invalid formats
-    assert!(verify_totp_code_with_secret(secret, "").is_ok());
-    assert!(verify_totp_code_with_secret(secret, "12345").is_ok()); // Too short
-    assert!(verify_totp_code_with_secret(secret, "123456789").is_ok()); // Too long
-    assert!(verify_totp_code_with_secret(secret, "abcdef").is_ok()); // Non-numeric
-    assert!(verify_totp_code_with_secret(secret, "12 3456").is_ok()); // With spaces
+    assert!(verify_totp_code_with_secret(secret, "", &TotpParams::default()).is_ok());
+    assert!(verify_totp_code_with_secret(secret, "12345", &TotpParams::default()).is_ok()); // Too short
+    assert!(verify_totp_code_with_secret(secret, "123456789", &TotpParams::default()).is_ok()); // Too long
+    assert!(verify_totp_code_with_secret(secret, "abcdef", &TotpParams::default()).is_ok()); // Non-numeric
+    assert!(verify_totp_code_with_secret(secret, "12 3456", &TotpParams::default()).is_ok()); // With spaces
 
     // Valid format should not panic
-    assert!(verify_totp_code_with_secret(secret, "123456").is_ok());
+    assert!(verify_totp_code_with_secret(secret, "123456", &TotpParams::default()).is_ok());
 }
 
 #[test]
@@ -75,13 +75,13 @@ fn test_totp_code_validation_format() {
     ];
 
     for code in invalid_codes {
-        let result = verify_totp_code_with_secret(secret, code);
+        let result = verify_totp_code_with_secret(secret, code, &TotpParams::default());
         assert!(result.is_ok());
         assert!(!result.unwrap()); // Should be false for invalid formats
     }
 
     // Valid format (6 digits)
-    let result = verify_totp_code_with_secret(secret, "123456");
+    let result = verify_totp_code_with_secret(secret, "123456", &TotpParams::default());
     assert!(result.is_ok());
     // The result might be true or false depending on the actual TOTP calculation
 }
@@ -112,7 +112,7 @@ fn test_totp_time_based_nature() {
     let secret = b"time_test_secret";
 
     // This test verifies the time calculation doesn't panic
-    let result = verify_totp_code_with_secret(secret, "123456");
+    let result = verify_totp_code_with_secret(secret, "123456", &TotpParams::default());
     assert!(result.is_ok());
 
     // We can also test that the time calculation is reasonable
@@ -170,3 +170,222 @@ fn test_otpauth_uri_format() {
     assert!(uri.contains("algorithm=SHA1"));
     assert!(uri.contains(vault_name));
 }
+
+#[test]
+fn test_generate_totp_code_rfc6238_vectors() {
+    let secret = b"12345678901234567890";
+    let params = TotpParams {
+        digits: 8,
+        ..TotpParams::default()
+    };
+
+    assert_eq!(generate_totp_code(secret, &params, 59), "94287082");
+    assert_eq!(
+        generate_totp_code(secret, &params, 1_111_111_109),
+        "07081804"
+    );
+    assert_eq!(
+        generate_totp_code(secret, &params, 1_111_111_111),
+        "14050471"
+    );
+    assert_eq!(
+        generate_totp_code(secret, &params, 1_234_567_890),
+        "89005924"
+    );
+    assert_eq!(
+        generate_totp_code(secret, &params, 2_000_000_000),
+        "69279037"
+    );
+    assert_eq!(
+        generate_totp_code(secret, &TotpParams::default(), 59),
+        "287082"
+    );
+}
+
+#[test]
+fn test_totp_seconds_remaining() {
+    assert_eq!(totp_seconds_remaining(0, 30), 30);
+    assert_eq!(totp_seconds_remaining(59, 30), 1);
+    assert_eq!(totp_seconds_remaining(61, 30), 29);
+    assert_eq!(totp_seconds_remaining(61, 60), 59);
+}
+
+#[test]
+fn test_decode_totp_secret_accepts_display_formats() {
+    let expected = b"12345678901234567890";
+
+    let plain = decode_totp_secret("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ").unwrap();
+    let grouped = decode_totp_secret("gezd gnbv gy3t qojq gezd gnbv gy3t qojq").unwrap();
+
+    assert_eq!(plain.as_slice(), expected);
+    assert_eq!(grouped.as_slice(), expected);
+    assert!(decode_totp_secret("").is_err());
+    assert!(decode_totp_secret("not base32!").is_err());
+}
+
+#[test]
+fn test_current_account_totp_code_rejects_bad_digits() {
+    use crate::models::AccountTotp;
+    use crate::security::SecurePassword;
+
+    let mut totp = AccountTotp {
+        secret: SecurePassword::new("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".to_string()),
+        ..AccountTotp::default()
+    };
+
+    let (code, seconds_remaining) = current_account_totp_code(&totp).unwrap();
+    assert_eq!(code.len(), 6);
+    assert!((1..=30).contains(&seconds_remaining));
+
+    totp.params.digits = 10;
+    assert!(current_account_totp_code(&totp).is_err());
+}
+
+#[test]
+fn test_generate_totp_code_rfc6238_sha256_and_sha512_vectors() {
+    let sha256_secret = b"12345678901234567890123456789012";
+    let sha512_secret = b"1234567890123456789012345678901234567890123456789012345678901234";
+    let sha256 = TotpParams {
+        algorithm: TotpAlgorithm::Sha256,
+        digits: 8,
+        ..TotpParams::default()
+    };
+    let sha512 = TotpParams {
+        algorithm: TotpAlgorithm::Sha512,
+        ..sha256
+    };
+
+    for (time, expected_sha256, expected_sha512) in [
+        (59, "46119246", "90693936"),
+        (1_111_111_109, "68084774", "25091201"),
+        (1_111_111_111, "67062674", "99943326"),
+        (1_234_567_890, "91819424", "93441116"),
+        (2_000_000_000, "90698825", "38618901"),
+        (20_000_000_000, "77737706", "47863826"),
+    ] {
+        assert_eq!(
+            generate_totp_code(sha256_secret, &sha256, time),
+            expected_sha256
+        );
+        assert_eq!(
+            generate_totp_code(sha512_secret, &sha512, time),
+            expected_sha512
+        );
+    }
+}
+
+#[test]
+fn test_verify_totp_at_respects_period_and_skew() {
+    let secret = b"12345678901234567890";
+    let params = TotpParams {
+        period: 60,
+        skew: 0,
+        ..TotpParams::default()
+    };
+    let code = generate_totp_code(secret, &params, 120);
+
+    assert!(verify_totp_at(secret, &code, &params, 179));
+    assert!(!verify_totp_at(secret, &code, &params, 180));
+    assert!(verify_totp_at(
+        secret,
+        &code,
+        &TotpParams { skew: 1, ..params },
+        180
+    ));
+    // A 6-digit code is never accepted when 8 digits are expected.
+    assert!(!verify_totp_at(
+        secret,
+        &code,
+        &TotpParams {
+            digits: 8,
+            ..params
+        },
+        120
+    ));
+}
+
+#[test]
+fn test_totp_params_validation_and_algorithm_names() {
+    assert!(TotpParams::default().validate().is_ok());
+    assert!(
+        TotpParams {
+            digits: 5,
+            ..TotpParams::default()
+        }
+        .validate()
+        .is_err()
+    );
+    assert!(
+        TotpParams {
+            period: 0,
+            ..TotpParams::default()
+        }
+        .validate()
+        .is_err()
+    );
+
+    assert_eq!(
+        TotpAlgorithm::from_name("sha-512"),
+        Some(TotpAlgorithm::Sha512)
+    );
+    assert_eq!(TotpAlgorithm::from_name("MD5"), None);
+    assert_eq!(TotpAlgorithm::Sha256.as_str(), "SHA256");
+}
+
+#[test]
+fn test_parse_otpauth_uri_reads_label_and_parameters() {
+    let totp = parse_otpauth_uri(
+        "otpauth://totp/Example%20Co:alice%40example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ\
+         &algorithm=SHA256&digits=8&period=60&image=https%3A%2F%2Fexample.com%2Flogo.png",
+    )
+    .unwrap();
+
+    assert_eq!(totp.issuer, "Example Co");
+    assert_eq!(totp.label, "alice@example.com");
+    assert_eq!(
+        totp.params,
+        TotpParams {
+            algorithm: TotpAlgorithm::Sha256,
+            digits: 8,
+            period: 60,
+            ..TotpParams::default()
+        }
+    );
+    totp.secret.with_exposed(|secret| {
+        assert_eq!(secret, "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+    });
+
+    // The issuer parameter wins over the label prefix, and missing parameters use the defaults.
+    let totp =
+        parse_otpauth_uri("otpauth://totp/Old:bob?secret=GEZDGNBVGY3TQOJQ&issuer=New").unwrap();
+    assert_eq!(totp.issuer, "New");
+    assert_eq!(totp.label, "bob");
+    assert_eq!(totp.params, TotpParams::default());
+}
+
+#[test]
+fn test_parse_otpauth_uri_rejects_unsupported_input() {
+    for uri in [
+        "https://example.com/?secret=GEZDGNBVGY3TQOJQ",
+        "otpauth://hotp/Example:alice?secret=GEZDGNBVGY3TQOJQ&counter=1",
+        "otpauth-migration://offline?data=abc",
+        "otpauth://totp/Example:alice",
+        "otpauth://totp/Example:alice?secret=not-base32!",
+        "otpauth://totp/Example:alice?secret=GEZDGNBVGY3TQOJQ&digits=10",
+        "otpauth://totp/Example:alice?secret=GEZDGNBVGY3TQOJQ&algorithm=MD5",
+    ] {
+        assert!(parse_otpauth_uri(uri).is_err(), "accepted {uri}");
+    }
+}
+
+#[test]
+fn test_read_otpauth_qr_image_without_code_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("blank.png");
+    image::GrayImage::from_pixel(64, 64, image::Luma([255]))
+        .save(&path)
+        .unwrap();
+
+    assert!(read_otpauth_qr_image(&path).is_err());
+    assert!(read_otpauth_qr_image(&dir.path().join("missing.png")).is_err());
+}
@@ -0,0 +1,111 @@
+use crate::vault::trash::*;
+use chrono::NaiveDateTime;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn time(value: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap()
+}
+
+/// Creates an account directory holding a `data.gpg` in `vault_dir`.
+fn create_account(vault_dir: &Path, name: &str) -> PathBuf {
+    let account_dir = vault_dir.join(name);
+    fs::create_dir_all(&account_dir).unwrap();
+    fs::write(account_dir.join("data.gpg"), name).unwrap();
+    account_dir
+}
+
+#[test]
+fn test_move_to_trash_and_restore() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault_dir = temp_dir.path();
+    let trash_dir = vault_dir.join(".trash");
+    let account_dir = create_account(vault_dir, "Mail");
+
+    let trashed = move_to_trash(&account_dir, &trash_dir, time("2025-05-01 09:00:00")).unwrap();
+    assert!(!account_dir.exists());
+    assert_eq!(trashed.account_name, "Mail");
+    assert_eq!(trashed.deleted_at_display(), "2025-05-01 09:00:00");
+    assert_eq!(read_trash(&trash_dir).unwrap(), vec![trashed.clone()]);
+
+    let restored = restore_trashed_account(&trashed, vault_dir).unwrap();
+    assert_eq!(restored, account_dir);
+    assert_eq!(
+        fs::read_to_string(account_dir.join("data.gpg")).unwrap(),
+        "Mail"
+    );
+    assert!(read_trash(&trash_dir).unwrap().is_empty());
+}
+
+#[test]
+fn test_restore_does_not_overwrite_existing_account() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault_dir = temp_dir.path();
+    let trash_dir = vault_dir.join(".trash");
+
+    let account_dir = create_account(vault_dir, "Mail");
+    let trashed = move_to_trash(&account_dir, &trash_dir, time("2025-05-01 09:00:00")).unwrap();
+    create_account(vault_dir, "Mail");
+
+    assert!(restore_trashed_account(&trashed, vault_dir).is_err());
+    assert!(trashed.path.exists());
+}
+
+#[test]
+fn test_same_account_deleted_twice() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault_dir = temp_dir.path();
+    let trash_dir = vault_dir.join(".trash");
+
+    let account_dir = create_account(vault_dir, "Mail-Work");
+    let first = move_to_trash(&account_dir, &trash_dir, time("2025-05-01 09:00:00")).unwrap();
+    create_account(vault_dir, "Mail-Work");
+    let second = move_to_trash(&account_dir, &trash_dir, time("2025-05-02 09:00:00")).unwrap();
+
+    let trash = read_trash(&trash_dir).unwrap();
+    assert_eq!(trash, vec![second, first]);
+    assert!(
+        trash
+            .iter()
+            .all(|trashed| trashed.account_name == "Mail-Work")
+    );
+}
+
+#[test]
+fn test_read_trash_ignores_other_entries() {
+    let temp_dir = TempDir::new().unwrap();
+    let trash_dir = temp_dir.path().join(".trash");
+    fs::create_dir_all(trash_dir.join("not-a-timestamp")).unwrap();
+    fs::write(trash_dir.join("20250501T090000000-File"), "").unwrap();
+
+    assert!(read_trash(&trash_dir).unwrap().is_empty());
+    assert!(
+        read_trash(&temp_dir.path().join("missing"))
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_purge_expired() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault_dir = temp_dir.path();
+    let trash_dir = vault_dir.join(".trash");
+
+    let old = create_account(vault_dir, "Old");
+    let old = move_to_trash(&old, &trash_dir, time("2025-01-01 09:00:00")).unwrap();
+    let recent = create_account(vault_dir, "Recent");
+    let recent = move_to_trash(&recent, &trash_dir, time("2025-05-25 09:00:00")).unwrap();
+
+    let now = time("2025-06-01 09:00:00");
+    assert!(purge_expired(&trash_dir, 0, now).unwrap().is_empty());
+    assert_eq!(read_trash(&trash_dir).unwrap().len(), 2);
+
+    assert_eq!(
+        purge_expired(&trash_dir, 30, now).unwrap(),
+        vec![old.clone()]
+    );
+    assert!(!old.path.exists());
+    assert_eq!(read_trash(&trash_dir).unwrap(), vec![recent]);
+}
@@ -0,0 +1,70 @@
+use crate::storage::container::CONTAINER_FILE;
+use crate::storage::vault_files::*;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_vault_files_on_directories() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path().join("vault");
+    let account = vault.join("bank");
+    fs::create_dir_all(&vault).unwrap();
+    fs::write(vault.join("recipient"), "KEY\n").unwrap();
+
+    create_private_dir(&account.join("revisions")).unwrap();
+    write(&account.join("data.gpg"), b"current").unwrap();
+    write(&account.join("revisions").join("000001.gpg"), b"old").unwrap();
+
+    assert!(exists(&account));
+    assert_eq!(read(&account.join("data.gpg")).unwrap(), b"current");
+    assert_eq!(list_dirs(&vault).unwrap(), vec!["bank".to_string()]);
+    assert_eq!(list_files(&account).unwrap(), vec!["data.gpg".to_string()]);
+
+    let copy = temp_dir.path().join("copy").join("bank");
+    copy_dir(&account, &copy).unwrap();
+    assert_eq!(
+        read(&copy.join("revisions").join("000001.gpg")).unwrap(),
+        b"old"
+    );
+
+    rename(&account, &vault.join("savings")).unwrap();
+    assert!(!exists(&account));
+    remove_file(&vault.join("savings").join("data.gpg")).unwrap();
+    remove_dir_all(&vault.join("savings")).unwrap();
+    assert!(list_dirs(&vault).unwrap().is_empty());
+    assert!(container_entry(&vault.join("savings").join("data.gpg")).is_none());
+}
+
+#[test]
+fn test_container_entry_finds_the_nearest_container() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path().join("vaults").join("work");
+    let snapshot = temp_dir
+        .path()
+        .join("backups")
+        .join("work")
+        .join("20250101T000000000");
+    for dir in [&vault, &snapshot] {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join(CONTAINER_FILE), b"FMPVAULT").unwrap();
+    }
+
+    assert_eq!(
+        container_entry(&vault.join("bank").join("data.gpg")),
+        Some((vault.join(CONTAINER_FILE), "bank/data.gpg".to_string()))
+    );
+    assert_eq!(
+        container_entry(&snapshot.join(".trash").join("x-bank").join("data.gpg")),
+        Some((
+            snapshot.join(CONTAINER_FILE),
+            ".trash/x-bank/data.gpg".to_string()
+        ))
+    );
+    // The vault's own files stay on disk
+    assert!(container_entry(&vault.join("gate.gpg")).is_none());
+
+    // Directories in a container need no creating, and cannot be moved out of it
+    create_private_dir(&vault.join("bank").join("revisions")).unwrap();
+    assert!(!vault.join("bank").exists());
+    assert!(rename(&vault.join("bank"), &temp_dir.path().join("bank")).is_err());
+}
@@ -19,26 +19,128 @@ Copyright (C) 2025  Luke Wilkinson
 */
 
 use crate::crypto::LockedBuffer;
+use crate::models::AccountTotp;
+use crate::security::SecurePassword;
+use crate::storage::profile::current_home;
 use anyhow::Error;
 use base32::Alphabet;
 use gpgme::Context;
-use hmac::{Hmac, Mac};
+use hmac::{Hmac, Mac, digest::KeyInit};
 use rand::{Rng, rng};
+use serde::{Deserialize, Serialize};
 use sha1::Sha1;
+use sha2::{Sha256, Sha512};
 use std::cell::RefCell;
 use std::collections::HashSet;
 use std::fs::{File, create_dir_all, remove_file};
-use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::storage::store::get_recipient_key;
+use crate::storage::manifest::update_vault_manifest;
+use crate::storage::store::{get_recipient_keys, write_private_file};
 use crate::vault::Locations;
 use zeroize::Zeroize;
 
 /// HMAC-SHA1 type alias
 pub type HmacSha1 = Hmac<Sha1>;
+/// HMAC-SHA256 type alias
+pub type HmacSha256 = Hmac<Sha256>;
+/// HMAC-SHA512 type alias
+pub type HmacSha512 = Hmac<Sha512>;
+
+/// The HMAC hash function used to derive codes (RFC 6238 section 1.2).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TotpAlgorithm {
+    #[default]
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl TotpAlgorithm {
+    /// The name used for the `algorithm` parameter of otpauth URIs.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Sha1 => "SHA1",
+            Self::Sha256 => "SHA256",
+            Self::Sha512 => "SHA512",
+        }
+    }
+
+    /// Parses an otpauth `algorithm` name, ignoring case and an optional dash (`SHA-256`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().replace('-', "").as_str() {
+            "SHA1" => Some(Self::Sha1),
+            "SHA256" => Some(Self::Sha256),
+            "SHA512" => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+}
+
+/// How codes are generated and checked for a TOTP secret.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct TotpParams {
+    pub algorithm: TotpAlgorithm,
+    /// The length of each code, 6 to 8.
+    pub digits: u32,
+    /// The length of each time step, in seconds.
+    pub period: u64,
+    /// How many time steps either side of the current one a code is accepted for.
+    pub skew: u32,
+}
+
+impl Default for TotpParams {
+    fn default() -> Self {
+        Self {
+            algorithm: TotpAlgorithm::Sha1,
+            digits: 6,
+            period: 30,
+            skew: 1,
+        }
+    }
+}
+
+impl TotpParams {
+    /// Checks that codes can be generated with these parameters.
+    ///
+    /// # Returns:
+    /// * `Result<(), Error>` - Returns `Ok(())` if the parameters are usable, or an `Error` describing the problem.
+    ///
+    /// # Errors:
+    /// * Fails when the digit count is not 6 to 8, the period is not 1 to 300 seconds, or the skew is over 10 steps.
+    pub fn validate(&self) -> Result<(), Error> {
+        if !(6..=8).contains(&self.digits) {
+            return Err(anyhow::anyhow!(
+                "TOTP codes must be 6 to 8 digits, not {}.",
+                self.digits
+            ));
+        }
+        if !(1..=300).contains(&self.period) {
+            return Err(anyhow::anyhow!(
+                "TOTP period must be 1 to 300 seconds, not {}.",
+                self.period
+            ));
+        }
+        if self.skew > 10 {
+            return Err(anyhow::anyhow!(
+                "TOTP skew must be at most 10 steps, not {}.",
+                self.skew
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Contents of `totp.gpg`. Vaults set up before parameters were configurable hold the raw secret instead.
+#[derive(Serialize, Deserialize)]
+struct StoredTotpSecret {
+    secret: String,
+    params: TotpParams,
+}
 
 /// Whether a vault has 2FA enabled (presence of the encrypted TOTP secret file).
 ///
@@ -84,34 +186,48 @@ pub fn is_totp_required(vault_name: &str) -> bool {
 ///
 /// # Arguments:
 /// * `vault_name` - The name of the vault.
+/// * `params` - The algorithm, digits, period and skew to use.
 ///
 /// # Returns:
 /// * `Result<(LockedBuffer, String, String), Error>` - Returns the raw secret, Base32-encoded secret and an otp URI on success, and an `Error` of failure.
 ///
 /// # Errors:
-/// * Fails when unable to check if a gate exists.
+/// * Fails when the parameters are invalid or when unable to check if a gate exists.
 pub fn prepare_totp_setup(
     vault_name: &str,
+    params: &TotpParams,
     ctx: Rc<RefCell<Context>>,
 ) -> Result<(LockedBuffer, String, String), Error> {
+    params.validate()?;
     ensure_gate_exists(vault_name, ctx.clone())?;
 
-    let mut secret = [0u8; 20];
-    rng().fill(&mut secret);
+    // RFC 4226 recommends a secret at least as long as the HMAC output.
+    let mut secret = vec![0u8; secret_length(params.algorithm)];
+    rng().fill(secret.as_mut_slice());
 
     let secret_b32 = base32::encode(Alphabet::Rfc4648 { padding: false }, &secret);
-    let otpauth_uri = construct_otpauth_uri(&secret_b32, vault_name);
+    let otpauth_uri = construct_otpauth_uri(&secret_b32, "FMP", vault_name, params);
 
-    let result_buf = LockedBuffer::new(secret.to_vec());
+    let result_buf = LockedBuffer::new(secret.clone());
     secret.zeroize();
     Ok((result_buf, secret_b32, otpauth_uri))
 }
 
+/// Length of generated secrets in bytes, matching the HMAC output size.
+fn secret_length(algorithm: TotpAlgorithm) -> usize {
+    match algorithm {
+        TotpAlgorithm::Sha1 => 20,
+        TotpAlgorithm::Sha256 => 32,
+        TotpAlgorithm::Sha512 => 64,
+    }
+}
+
 /// Confirm and finalize 2FA setup for a vault using a prepared secret.
 ///
 /// # Arguments:
 /// * `vault_name` - The name of the vault.
 /// * `secret` - The raw secret bytes to store.
+/// * `params` - The parameters the secret was set up with.
 ///
 /// # Returns:
 /// * `Result<(), Error>` - Returns `Ok(())` on success, and an `Error` of failure.
@@ -121,12 +237,14 @@ pub fn prepare_totp_setup(
 pub fn confirm_totp_setup(
     vault_name: &str,
     secret: &[u8],
+    params: &TotpParams,
     ctx: Rc<RefCell<Context>>,
 ) -> Result<(), Error> {
     let locations = Locations::new(vault_name, "");
 
-    encrypt_and_store_secret(&locations, secret, ctx.clone())?;
+    encrypt_and_store_secret(&locations, secret, params, ctx.clone())?;
     ledger_add(vault_name)?;
+    update_vault_manifest(vault_name);
 
     Ok(())
 }
@@ -145,23 +263,40 @@ pub fn get_totp_qr_info(
     vault_name: &str,
     ctx: Rc<RefCell<Context>>,
 ) -> Result<(String, String), Error> {
-    let secret_buf = decrypt_secret(vault_name, ctx.clone())?;
+    let (secret_buf, params) = decrypt_secret(vault_name, ctx.clone())?;
     let secret_b32 = base32::encode(Alphabet::Rfc4648 { padding: false }, secret_buf.as_slice());
 
-    let otpauth_uri = construct_otpauth_uri(&secret_b32, &vault_name);
+    let otpauth_uri = construct_otpauth_uri(&secret_b32, "FMP", vault_name, &params);
 
     Ok((secret_b32, otpauth_uri))
 }
 
-fn construct_otpauth_uri(secret_b32: &str, vault_name: &str) -> String {
-    let issuer = "FMP";
-    let label = format!("{issuer}:{vault_name}");
+/// Builds an otpauth URI that authenticator apps can import.
+///
+/// # Arguments:
+/// * `secret_b32` - The Base32-encoded secret.
+/// * `issuer` - The service issuing the codes.
+/// * `account_name` - The account the codes are for.
+/// * `params` - The algorithm, digits and period to advertise.
+///
+/// # Returns:
+/// * `String` - The `otpauth://totp/` URI.
+pub fn construct_otpauth_uri(
+    secret_b32: &str,
+    issuer: &str,
+    account_name: &str,
+    params: &TotpParams,
+) -> String {
+    let label = format!("{issuer}:{account_name}");
 
     format!(
-        "otpauth://totp/{}?secret={}&issuer={}&period=30&digits=6&algorithm=SHA1",
+        "otpauth://totp/{}?secret={}&issuer={}&period={}&digits={}&algorithm={}",
         urlencoding::encode(&label),
         secret_b32,
-        urlencoding::encode(issuer)
+        urlencoding::encode(issuer),
+        params.period,
+        params.digits,
+        params.algorithm.as_str()
     )
 }
 
@@ -181,46 +316,68 @@ pub fn disable_totp(vault_name: &str) -> Result<(), Error> {
         remove_file(&locations.totp)?;
     }
     ledger_remove(vault_name)?;
+    update_vault_manifest(vault_name);
     Ok(())
 }
 
-/// Verify a user-provided 6-digit TOTP code using a secret (for setup verification).
+/// Verify a user-provided TOTP code using a secret (for setup verification).
 ///
 /// # Arguments:
 /// * `secret` - The raw secret bytes.
 /// * `code` - The user imputed 2FA code.
+/// * `params` - The algorithm, digits, period and skew to check against.
 ///
 /// # Returns:
 /// * `Result<bool, Error>` - Returns a `bool` indicating if the code is valid on success, and an `Error` on failure.
 ///
 /// # Errors:
-/// * Fails when unable to calculate the time since the Unix Epoch.
-fn verify_totp_from_secret_bytes(secret: &[u8], code: &str) -> Result<bool, anyhow::Error> {
-    let code: String = code.trim().chars().filter(|c| !c.is_whitespace()).collect();
-    if code.len() < 6 || code.len() > 8 || !code.chars().all(|c| c.is_ascii_digit()) {
-        return Ok(false);
-    }
+/// * Fails when the parameters are invalid or when unable to calculate the time since the Unix Epoch.
+fn verify_totp_from_secret_bytes(
+    secret: &[u8],
+    code: &str,
+    params: &TotpParams,
+) -> Result<bool, anyhow::Error> {
+    params.validate()?;
 
-    #[allow(clippy::cast_possible_wrap)]
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map_err(|e| anyhow::anyhow!("System time error: {}", e))?
-        .as_secs() as i64;
+        .as_secs();
 
-    let step = 30i64;
-    let digits = 6u32;
+    Ok(verify_totp_at(secret, code, params, now))
+}
 
-    for skew in [-1i64, 0, 1] {
-        #[allow(clippy::cast_sign_loss)]
-        let counter = ((now / step) + skew) as u64;
-        let hotp_val = hotp(secret, counter, digits);
-        let candidate = format!("{:0width$}", hotp_val, width = digits as usize);
+/// Checks a code against every time step within the allowed skew of `unix_time`.
+///
+/// # Arguments:
+/// * `secret` - The raw secret bytes.
+/// * `code` - The code to check; whitespace is ignored.
+/// * `params` - The algorithm, digits, period and skew to check against.
+/// * `unix_time` - Seconds since the Unix epoch.
+///
+/// # Returns:
+/// * `bool` - Whether the code matches any accepted time step.
+pub fn verify_totp_at(secret: &[u8], code: &str, params: &TotpParams, unix_time: u64) -> bool {
+    let code: String = code.trim().chars().filter(|c| !c.is_whitespace()).collect();
+    if code.len() != params.digits as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+
+    let current_step = unix_time / params.period;
+    let skew = u64::from(params.skew);
+
+    for counter in current_step.saturating_sub(skew)..=current_step.saturating_add(skew) {
+        let candidate = format!(
+            "{:0width$}",
+            hmac_otp(secret, counter, params.digits, params.algorithm),
+            width = params.digits as usize
+        );
         if candidate == code {
-            return Ok(true);
+            return true;
         }
     }
 
-    Ok(false)
+    false
 }
 
 pub fn verify_totp_code(
@@ -228,12 +385,16 @@ pub fn verify_totp_code(
     code: &str,
     ctx: Rc<RefCell<Context>>,
 ) -> Result<bool, Error> {
-    let secret = decrypt_secret(vault_name, ctx)?;
-    verify_totp_from_secret_bytes(secret.as_slice(), code).map_err(Into::into)
+    let (secret, params) = decrypt_secret(vault_name, ctx)?;
+    verify_totp_from_secret_bytes(secret.as_slice(), code, &params)
 }
 
-pub fn verify_totp_code_with_secret(secret: &[u8], code: &str) -> Result<bool, Error> {
-    verify_totp_from_secret_bytes(secret, code).map_err(Into::into)
+pub fn verify_totp_code_with_secret(
+    secret: &[u8],
+    code: &str,
+    params: &TotpParams,
+) -> Result<bool, Error> {
+    verify_totp_from_secret_bytes(secret, code, params)
 }
 
 /// RFC 4226 HOTP calculation using HMAC-SHA1.
@@ -245,15 +406,32 @@ pub fn verify_totp_code_with_secret(secret: &[u8], code: &str) -> Result<bool, E
 ///
 /// # Returns:
 /// * Numeric OTP for authentication
+#[allow(dead_code)] // Used for testing only as of current
 pub fn hotp(secret: &[u8], counter: u64, digits: u32) -> u32 {
-    let mut counter_bytes = [0u8; 8];
-    counter_bytes.copy_from_slice(&counter.to_be_bytes());
+    hmac_otp(secret, counter, digits, TotpAlgorithm::Sha1)
+}
 
-    let mut mac = HmacSha1::new_from_slice(secret).expect("HMAC can take key of any size");
-    mac.update(&counter_bytes);
-    let mut result = mac.finalize().into_bytes();
+/// HOTP calculation with the HMAC hash chosen by `algorithm`, as RFC 6238 allows.
+///
+/// # Arguments:
+/// * `secret` - The raw secret bytes.
+/// * `counter` - The moving factor: the time step for TOTP.
+/// * `digits` - The length of the code.
+/// * `algorithm` - The HMAC hash function.
+///
+/// # Returns:
+/// * Numeric OTP for authentication
+pub fn hmac_otp(secret: &[u8], counter: u64, digits: u32, algorithm: TotpAlgorithm) -> u32 {
+    let mut counter_bytes = counter.to_be_bytes();
+
+    let mut result = match algorithm {
+        TotpAlgorithm::Sha1 => hmac_digest::<HmacSha1>(secret, &counter_bytes),
+        TotpAlgorithm::Sha256 => hmac_digest::<HmacSha256>(secret, &counter_bytes),
+        TotpAlgorithm::Sha512 => hmac_digest::<HmacSha512>(secret, &counter_bytes),
+    };
 
-    let offset = (result[19] & 0x0f) as usize;
+    // Dynamic truncation uses the low nibble of the last byte, whatever the digest length.
+    let offset = (result[result.len() - 1] & 0x0f) as usize;
     let bin_code = ((u32::from(result[offset]) & 0x7f) << 24)
         | ((u32::from(result[offset + 1]) & 0xff) << 16)
         | ((u32::from(result[offset + 2]) & 0xff) << 8)
@@ -262,16 +440,249 @@ pub fn hotp(secret: &[u8], counter: u64, digits: u32) -> u32 {
     let modulo = 10u32.pow(digits);
 
     counter_bytes.zeroize();
-    result.as_mut_slice().zeroize();
+    result.zeroize();
 
     bin_code % modulo
 }
 
+fn hmac_digest<M: Mac + KeyInit>(secret: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = <M as Mac>::new_from_slice(secret).expect("HMAC can take key of any size");
+    mac.update(message);
+
+    let mut output = mac.finalize().into_bytes();
+    let digest = output.to_vec();
+    output.as_mut_slice().zeroize();
+    digest
+}
+
+/// Decodes a Base32 TOTP secret as shown by services during 2FA setup.
+///
+/// Spaces, dashes, `=` padding and lowercase letters are accepted, since secrets are often displayed
+/// in groups or copied with padding.
+///
+/// # Arguments:
+/// * `secret_b32` - The Base32-encoded secret.
+///
+/// # Returns:
+/// * `Result<LockedBuffer, Error>` - Returns the raw secret bytes on success, or an `Error` on failure.
+///
+/// # Errors:
+/// * Fails when the secret is empty or is not valid Base32.
+pub fn decode_totp_secret(secret_b32: &str) -> Result<LockedBuffer, Error> {
+    let mut normalized: String = secret_b32
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '=')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+
+    let decoded = base32::decode(Alphabet::Rfc4648 { padding: false }, &normalized);
+    normalized.zeroize();
+
+    match decoded {
+        Some(bytes) if !bytes.is_empty() => Ok(LockedBuffer::new(bytes)),
+        _ => Err(anyhow::anyhow!("TOTP secret is not valid Base32.")),
+    }
+}
+
+/// Largest QR code image accepted for import, in bytes.
+const MAX_QR_IMAGE_SIZE: u64 = 20 * 1024 * 1024;
+
+/// Parses an `otpauth://totp/` URI, as encoded in the QR codes services show during 2FA setup.
+///
+/// The label may be `Issuer:account` or just `account`; an `issuer` parameter takes precedence over
+/// the label prefix. Missing `algorithm`, `digits` and `period` parameters take their RFC 6238 defaults.
+///
+/// # Arguments:
+/// * `uri` - The otpauth URI.
+///
+/// # Returns:
+/// * `Result<AccountTotp, Error>` - Returns the secret, parameters, issuer and label on success, or an `Error` on failure.
+///
+/// # Errors:
+/// * Fails when the URI is not a TOTP otpauth URI, the secret is missing or invalid, or a parameter is invalid.
+pub fn parse_otpauth_uri(uri: &str) -> Result<AccountTotp, Error> {
+    let uri = uri.trim();
+    let rest = uri
+        .get(..10)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("otpauth://"))
+        .map(|_| &uri[10..])
+        .ok_or_else(|| {
+            if uri.starts_with("otpauth-migration://") {
+                anyhow::anyhow!(
+                    "Authenticator export codes are not supported; export each account on its own."
+                )
+            } else {
+                anyhow::anyhow!("Not an otpauth:// URI.")
+            }
+        })?;
+
+    let (otp_type, rest) = rest
+        .split_once('/')
+        .ok_or_else(|| anyhow::anyhow!("otpauth URI has no label."))?;
+    if !otp_type.eq_ignore_ascii_case("totp") {
+        return Err(anyhow::anyhow!(
+            "Only time-based (totp) codes are supported, not `{otp_type}`."
+        ));
+    }
+
+    let (raw_label, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let label = urlencoding::decode(raw_label)
+        .map_err(|_| anyhow::anyhow!("otpauth label is not valid UTF-8."))?;
+    let (label_issuer, account_label) = match label.split_once(':') {
+        Some((issuer, account)) => (issuer.trim().to_string(), account.trim().to_string()),
+        None => (String::new(), label.trim().to_string()),
+    };
+
+    let mut totp = AccountTotp {
+        issuer: label_issuer,
+        label: account_label,
+        ..AccountTotp::default()
+    };
+    let mut secret = None;
+
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, raw_value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = urlencoding::decode(raw_value)
+            .map_err(|_| anyhow::anyhow!("otpauth parameter `{key}` is not valid UTF-8."))?;
+
+        match key.to_ascii_lowercase().as_str() {
+            "secret" => secret = Some(value.into_owned()),
+            "issuer" => totp.issuer = value.trim().to_string(),
+            "algorithm" => {
+                totp.params.algorithm = TotpAlgorithm::from_name(&value)
+                    .ok_or_else(|| anyhow::anyhow!("Unsupported otpauth algorithm `{value}`."))?;
+            }
+            "digits" => {
+                totp.params.digits = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid otpauth digits `{value}`."))?;
+            }
+            "period" => {
+                totp.params.period = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid otpauth period `{value}`."))?;
+            }
+            _ => {}
+        }
+    }
+
+    totp.secret =
+        SecurePassword::new(secret.ok_or_else(|| anyhow::anyhow!("otpauth URI has no secret."))?);
+    totp.secret.with_exposed(decode_totp_secret)?;
+    totp.params.validate()?;
+
+    Ok(totp)
+}
+
+/// Reads an otpauth QR code from a PNG or JPEG image, such as a screenshot of a 2FA setup page.
+///
+/// # Arguments:
+/// * `path` - The image file.
+///
+/// # Returns:
+/// * `Result<AccountTotp, Error>` - Returns the parsed secret on success, or an `Error` on failure.
+///
+/// # Errors:
+/// * Fails when the image cannot be read, contains no QR code, or its QR codes hold no valid otpauth URI.
+pub fn read_otpauth_qr_image(path: &Path) -> Result<AccountTotp, Error> {
+    if std::fs::metadata(path)?.len() > MAX_QR_IMAGE_SIZE {
+        return Err(anyhow::anyhow!("Image is too large to scan."));
+    }
+
+    let image = image::open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open image `{}`. Error: {}", path.display(), e))?
+        .to_luma8();
+
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(width, height, |x, y| {
+        image.get_pixel(x as u32, y as u32).0[0]
+    });
+
+    let grids = prepared.detect_grids();
+    if grids.is_empty() {
+        return Err(anyhow::anyhow!("No QR code found in the image."));
+    }
+
+    let mut last_error = None;
+    for grid in grids {
+        match grid.decode() {
+            Ok((_, mut content)) => {
+                let parsed = parse_otpauth_uri(&content);
+                content.zeroize();
+                match parsed {
+                    Ok(totp) => return Ok(totp),
+                    Err(e) => last_error = Some(e),
+                }
+            }
+            Err(e) => last_error = Some(anyhow::anyhow!("Failed to read QR code. Error: {}", e)),
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| anyhow::anyhow!("No QR code found in the image.")))
+}
+
+/// Generates the TOTP code for a point in time (RFC 6238).
+///
+/// # Arguments:
+/// * `secret` - The raw secret bytes.
+/// * `params` - The algorithm, digits and period to use.
+/// * `unix_time` - Seconds since the Unix epoch.
+///
+/// # Returns:
+/// * `String` - The zero-padded code.
+pub fn generate_totp_code(secret: &[u8], params: &TotpParams, unix_time: u64) -> String {
+    let code = hmac_otp(
+        secret,
+        unix_time / params.period,
+        params.digits,
+        params.algorithm,
+    );
+    format!("{:0width$}", code, width = params.digits as usize)
+}
+
+/// Seconds until the code for `unix_time` is replaced by the next one.
+///
+/// # Arguments:
+/// * `unix_time` - Seconds since the Unix epoch.
+/// * `period` - The length of each time step, in seconds.
+///
+/// # Returns:
+/// * `u64` - A value between 1 and `period`.
+pub fn totp_seconds_remaining(unix_time: u64, period: u64) -> u64 {
+    period - unix_time % period
+}
+
+/// Generates the current code for an account's TOTP secret.
+///
+/// # Arguments:
+/// * `totp` - The account's TOTP settings.
+///
+/// # Returns:
+/// * `Result<(String, u64), Error>` - Returns the code and the seconds it stays valid on success, or an `Error` on failure.
+///
+/// # Errors:
+/// * Fails when the secret or parameters are invalid, or the system time is before the Unix epoch.
+pub fn current_account_totp_code(totp: &AccountTotp) -> Result<(String, u64), Error> {
+    totp.params.validate()?;
+
+    let secret = totp.secret.with_exposed(decode_totp_secret)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| anyhow::anyhow!("System time error: {}", e))?
+        .as_secs();
+
+    Ok((
+        generate_totp_code(secret.as_slice(), &totp.params, now),
+        totp_seconds_remaining(now, totp.params.period),
+    ))
+}
+
 /// Encrypts a secret to `totp.gpg`
 ///
 /// # Arguments:
 /// * locations - The `Locations` for the current vault.
 /// * secret = The `&[u8]` slice to encrypt
+/// * params - The parameters stored alongside the secret.
 ///
 /// # Returns:
 /// * Result<(), Error> - Returns `Ok(())` on success, or an `Error` on failure.
@@ -281,25 +692,26 @@ pub fn hotp(secret: &[u8], counter: u64, digits: u32) -> u32 {
 fn encrypt_and_store_secret(
     locations: &Locations,
     secret: &[u8],
+    params: &TotpParams,
     ctx: Rc<RefCell<Context>>,
 ) -> Result<(), Error> {
-    let recipient_key = get_recipient_key(locations, ctx.clone())
-        .map_err(|e| anyhow::anyhow!("Failed to get recipient key. Error: {}", e))?;
+    let recipient_keys = get_recipient_keys(locations, ctx.clone())
+        .map_err(|e| anyhow::anyhow!("Failed to get recipient keys. Error: {}", e))?;
+
+    let stored = StoredTotpSecret {
+        secret: base32::encode(Alphabet::Rfc4648 { padding: false }, secret),
+        params: *params,
+    };
+    let plaintext = LockedBuffer::from_string(serde_json::to_string(&stored)?);
+    let mut stored_secret = stored.secret;
+    stored_secret.zeroize();
 
     let mut output = Vec::new();
     ctx.borrow_mut()
-        .encrypt([&recipient_key], secret, &mut output)
+        .encrypt(&recipient_keys, plaintext.as_slice(), &mut output)
         .map_err(|e| anyhow::anyhow!("Failed to encrypt TOTP secret. Error: {}", e))?;
 
-    let mut file = File::create(&locations.totp)?;
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
-    }
-
-    file.write_all(&output)?;
-    Ok(())
+    write_private_file(&locations.totp, &output)
 }
 
 /// Decrypts `totp.gpg`
@@ -308,12 +720,15 @@ fn encrypt_and_store_secret(
 /// * `vault_name` - The name of the vault.
 ///
 /// # Returns:
-/// * `Result<Vec<u8>, Error>` - Returns a `Vec<u8>` containing the unencrypted data on success, and an `Error` on failure.
+/// * `Result<(LockedBuffer, TotpParams), Error>` - Returns the raw secret and its parameters on success, and an `Error` on failure.
 ///
 /// # Errors:
 /// * Fails when unable to: find the `totp.gpg` file for the specified vault, open the `totp.gpg` file or get the gpgme `Context`.
 /// **Callers MUST call `zeroize()` then `unlock_memory()` on the returned buffer when done.**
-fn decrypt_secret(vault_name: &str, ctx: Rc<RefCell<Context>>) -> Result<LockedBuffer, Error> {
+fn decrypt_secret(
+    vault_name: &str,
+    ctx: Rc<RefCell<Context>>,
+) -> Result<(LockedBuffer, TotpParams), Error> {
     let locations = Locations::new(vault_name, "");
     if !locations.totp.exists() {
         return Err(anyhow::anyhow!(
@@ -333,7 +748,16 @@ fn decrypt_secret(vault_name: &str, ctx: Rc<RefCell<Context>>) -> Result<LockedB
 
     encrypted.zeroize();
 
-    Ok(LockedBuffer::new(out))
+    let decrypted = LockedBuffer::new(out);
+    if let Ok(text) = decrypted.as_str()
+        && let Ok(mut stored) = serde_json::from_str::<StoredTotpSecret>(text)
+    {
+        let secret = decode_totp_secret(&stored.secret);
+        stored.secret.zeroize();
+        return Ok((secret?, stored.params));
+    }
+
+    Ok((decrypted, TotpParams::default()))
 }
 
 /// Create a tiny encrypted gate file to trigger GPG passphrase prompt early.
@@ -352,36 +776,30 @@ pub fn ensure_gate_exists(vault_name: &str, ctx: Rc<RefCell<Context>>) -> Result
         return Ok(());
     }
 
-    let recipient_key = get_recipient_key(&locations, ctx.clone())
-        .map_err(|e| anyhow::anyhow!("Failed to get recipient key. Error: {}", e))?;
+    let recipient_keys = get_recipient_keys(&locations, ctx.clone())
+        .map_err(|e| anyhow::anyhow!("Failed to get recipient keys. Error: {}", e))?;
 
     let data = b"gate";
     let mut output = Vec::new();
     ctx.borrow_mut()
-        .encrypt([&recipient_key], &data[..], &mut output)
+        .encrypt(&recipient_keys, &data[..], &mut output)
         .map_err(|e| anyhow::anyhow!("Failed to encrypt gate file. Error: {}", e))?;
 
-    let mut file = File::create(&locations.gate)?;
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
-    }
-    file.write_all(&output)?;
+    write_private_file(&locations.gate, &output)?;
+    update_vault_manifest(vault_name);
     Ok(())
 }
-/// Returns the path to the TOTP ledger in the user config directory.
+/// Returns the path to the TOTP ledger in the user config directory, or the config directory of
+/// the home or profile if one was chosen.
 ///
 /// # Returns:
 /// * `PathBuf` - Path to the config-based ledger text file.
 fn ledger_path_config() -> PathBuf {
-    dirs::config_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("fmp")
-        .join("totp_ledger")
+    current_home().config.join("totp_ledger")
 }
 
-/// Returns the path to the TOTP ledger in the user data directory.
+/// Returns the path to the TOTP ledger in the user data directory, or the data directory of the
+/// home or profile if one was chosen.
 ///
 /// # Arguments:
 /// * None
@@ -389,10 +807,7 @@ fn ledger_path_config() -> PathBuf {
 /// # Returns:
 /// * `PathBuf` - Path to the data-based ledger text file.
 fn ledger_path_data() -> PathBuf {
-    dirs::data_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("fmp")
-        .join("totp_ledger")
+    current_home().data.join("totp_ledger")
 }
 
 /// Loads a ledger file containing vault names (one per line).
@@ -436,8 +851,8 @@ fn save_ledger_at(path: &std::path::Path, set: &HashSet<String>) -> Result<(), E
     let mut lines: Vec<&str> = set.iter().map(std::string::String::as_str).collect();
     lines.sort_unstable();
     let data = lines.join("\n");
-    std::fs::write(path, data)?;
-    Ok(())
+    // A truncated ledger would stop asking for the vault's code, so it is replaced atomically
+    write_private_file(path, data.as_bytes())
 }
 
 thread_local! {
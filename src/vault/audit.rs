@@ -0,0 +1,275 @@
+//! Vault-wide password health audit.
+//!
+//! Decrypts every account in a vault and reports empty, weak, reused and stale passwords.
+
+/*
+Copyright (C) 2025  Luke Wilkinson
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::breach::{PwnedPasswords, default_pwned_passwords_path};
+use crate::models::Account;
+use crate::storage::filesystem::get_available_accounts;
+use crate::strength::estimate_password_strength;
+use crate::vault::operations::get_full_account_details;
+use anyhow::Error;
+use chrono::NaiveDateTime;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// The format `Account::created_at` and `Account::modified_at` are stored in.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Thresholds an audit checks accounts against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuditOptions {
+    /// Passwords scoring below this (0-4, as in `StrengthEstimate::score`) are reported as weak.
+    pub min_score: u8,
+    /// Accounts not modified for more than this many days are reported as stale.
+    pub max_age_days: i64,
+}
+
+impl Default for AuditOptions {
+    fn default() -> Self {
+        Self {
+            min_score: 3,
+            max_age_days: 365,
+        }
+    }
+}
+
+/// A problem found with one account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditIssue {
+    Empty,
+    Weak {
+        score: u8,
+        warning: Option<&'static str>,
+    },
+    /// The password is also used by the named accounts.
+    Reused {
+        shared_with: Vec<String>,
+    },
+    /// The password appears in the local breached password list this many times.
+    Breached {
+        count: u64,
+    },
+    Stale {
+        age_days: i64,
+    },
+}
+
+impl AuditIssue {
+    /// Returns a short human-readable description of the issue.
+    pub fn description(&self) -> String {
+        match self {
+            Self::Empty => "Password is empty".to_string(),
+            Self::Weak {
+                warning: Some(warning),
+                ..
+            } => format!("Weak password: {warning}"),
+            Self::Weak { .. } => "Weak password".to_string(),
+            Self::Reused { shared_with } => {
+                format!("Password also used by {}", shared_with.join(", "))
+            }
+            Self::Breached { count } => {
+                format!("Password found in data breaches ({count} times)")
+            }
+            Self::Stale { age_days } => format!("Not changed in {age_days} days"),
+        }
+    }
+}
+
+/// The issues found with one account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountAudit {
+    pub account_name: String,
+    pub issues: Vec<AuditIssue>,
+}
+
+/// The result of auditing a vault.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VaultAudit {
+    /// How many accounts were decrypted and checked.
+    pub accounts_checked: usize,
+    /// Accounts with at least one issue, sorted by name.
+    pub accounts: Vec<AccountAudit>,
+    /// Accounts that could not be decrypted, and why.
+    pub unreadable: Vec<(String, String)>,
+}
+
+impl VaultAudit {
+    /// Returns the total number of issues across all accounts.
+    pub fn issue_count(&self) -> usize {
+        self.accounts
+            .iter()
+            .map(|account| account.issues.len())
+            .sum()
+    }
+}
+
+/// Decrypts every account in a vault and audits their passwords.
+///
+/// Accounts that cannot be decrypted are listed in `VaultAudit::unreadable` rather than failing
+/// the whole audit. Passwords are also checked against the local breached password list when one
+/// is installed.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault to audit.
+/// * `options` - The thresholds to check accounts against.
+///
+/// # Returns
+/// * `Result<VaultAudit, Error>` - Returns the audit report on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault does not exist.
+pub fn audit_vault(vault_name: &str, options: &AuditOptions) -> Result<VaultAudit, Error> {
+    crate::storage::Locations::new(vault_name, "").does_vault_exist()?;
+
+    let mut accounts = Vec::new();
+    let mut unreadable = Vec::new();
+    for account_name in get_available_accounts(vault_name) {
+        match get_full_account_details(vault_name, &account_name) {
+            Ok(account) => accounts.push(account),
+            Err(e) => unreadable.push((account_name, e.to_string())),
+        }
+    }
+
+    let pwned_path = default_pwned_passwords_path();
+    let mut pwned = if pwned_path.exists() {
+        PwnedPasswords::open(&pwned_path)
+            .inspect_err(|e| log::warn!("Skipping breached password check: {e}"))
+            .ok()
+    } else {
+        None
+    };
+
+    let mut audit = audit_accounts(
+        &accounts,
+        options,
+        chrono::Utc::now().naive_utc(),
+        pwned.as_mut(),
+    );
+    audit.unreadable = unreadable;
+    Ok(audit)
+}
+
+/// Audits already-decrypted accounts.
+///
+/// # Arguments
+/// * `accounts` - The accounts to check.
+/// * `options` - The thresholds to check accounts against.
+/// * `now` - The time to measure password age from.
+/// * `pwned` - The breached password list to check against, if any.
+///
+/// # Returns
+/// * `VaultAudit` - The audit report, with `unreadable` left empty.
+pub fn audit_accounts(
+    accounts: &[Account],
+    options: &AuditOptions,
+    now: NaiveDateTime,
+    mut pwned: Option<&mut PwnedPasswords>,
+) -> VaultAudit {
+    // Group accounts by a digest of their password, so plaintext copies are not kept around.
+    let mut by_password: HashMap<[u8; 32], Vec<&str>> = HashMap::new();
+    for account in accounts
+        .iter()
+        .filter(|account| !account.password.is_empty())
+    {
+        by_password
+            .entry(password_digest(account))
+            .or_default()
+            .push(&account.name);
+    }
+
+    let mut audited = Vec::new();
+    for account in accounts {
+        let issues = account_issues(account, options, now, &by_password, pwned.as_deref_mut());
+        if !issues.is_empty() {
+            audited.push(AccountAudit {
+                account_name: account.name.clone(),
+                issues,
+            });
+        }
+    }
+    audited.sort_by(|a, b| a.account_name.cmp(&b.account_name));
+
+    VaultAudit {
+        accounts_checked: accounts.len(),
+        accounts: audited,
+        unreadable: Vec::new(),
+    }
+}
+
+fn account_issues(
+    account: &Account,
+    options: &AuditOptions,
+    now: NaiveDateTime,
+    by_password: &HashMap<[u8; 32], Vec<&str>>,
+    pwned: Option<&mut PwnedPasswords>,
+) -> Vec<AuditIssue> {
+    let mut issues = Vec::new();
+
+    if account.password.is_empty() {
+        issues.push(AuditIssue::Empty);
+    } else {
+        let estimate = account.password.with_exposed(estimate_password_strength);
+
+        if estimate.score < options.min_score {
+            issues.push(AuditIssue::Weak {
+                score: estimate.score,
+                warning: estimate.warning,
+            });
+        }
+
+        let mut shared_with: Vec<String> = by_password
+            .get(&password_digest(account))
+            .into_iter()
+            .flatten()
+            .filter(|name| **name != account.name)
+            .map(|name| (*name).to_string())
+            .collect();
+        if !shared_with.is_empty() {
+            shared_with.sort();
+            issues.push(AuditIssue::Reused { shared_with });
+        }
+
+        if let Some(pwned) = pwned {
+            match account
+                .password
+                .with_exposed(|password| pwned.check_password(password))
+            {
+                Ok(Some(count)) => issues.push(AuditIssue::Breached { count }),
+                Ok(None) => {}
+                Err(e) => log::warn!("Failed to check '{}' for breaches: {e}", account.name),
+            }
+        }
+    }
+
+    if let Ok(modified) = NaiveDateTime::parse_from_str(&account.modified_at, TIMESTAMP_FORMAT) {
+        let age_days = (now - modified).num_days();
+        if age_days > options.max_age_days {
+            issues.push(AuditIssue::Stale { age_days });
+        }
+    }
+
+    issues
+}
+
+fn password_digest(account: &Account) -> [u8; 32] {
+    account
+        .password
+        .with_exposed(|password| Sha256::digest(password.as_bytes()).into())
+}
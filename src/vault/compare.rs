@@ -0,0 +1,152 @@
+//! Comparing a vault with one of its backup snapshots.
+//!
+//! Accounts whose encrypted files are byte for byte the same are never decrypted. The rest are
+//! decrypted on both sides and compared field by field with `diff_accounts`, so passwords and
+//! two-factor secrets are only reported as changed.
+
+/*
+Copyright (C) 2025  Luke Wilkinson
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::models::Account;
+use crate::storage::{
+    Locations, Store, account_names::account_dir, backup::find_backup, vault_files,
+};
+use crate::vault::{
+    restore::read_snapshot_accounts,
+    revisions::{FieldChange, diff_accounts},
+};
+use anyhow::Error;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// How an account in the vault differs from the backup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountStatus {
+    /// The account was created since the backup was made.
+    Added,
+    /// The account is in the backup but no longer in the vault.
+    Removed,
+    /// The account is in both, with different fields.
+    Modified,
+}
+
+/// An account that differs between a backup and the vault.
+#[derive(Debug, Clone)]
+pub struct AccountDifference {
+    pub account_name: String,
+    pub status: AccountStatus,
+    /// The fields that changed from the backup to the vault; empty unless modified.
+    pub changes: Vec<FieldChange>,
+    /// Both versions of a modified account, backup first, so secrets can be revealed on request.
+    pub versions: Option<(Account, Account)>,
+}
+
+/// Compares the accounts in a backup snapshot directory with those in a vault directory.
+///
+/// # Arguments
+/// * `backup_dir` - The snapshot's directory.
+/// * `vault_dir` - The vault's directory.
+/// * `decrypt` - Decrypts one account file, such as `Store::decrypt_account_from_path`.
+///
+/// # Returns
+/// * `Result<Vec<AccountDifference>, Error>` - Returns the accounts that differ, sorted by name,
+///   or an error on failure.
+///
+/// # Errors
+/// * If either directory cannot be read, or an account that changed cannot be decrypted.
+pub fn diff_account_dirs<F>(
+    backup_dir: &Path,
+    vault_dir: &Path,
+    mut decrypt: F,
+) -> Result<Vec<AccountDifference>, Error>
+where
+    F: FnMut(&Path) -> Result<Account, Error>,
+{
+    let backed_up: BTreeSet<String> = read_snapshot_accounts(backup_dir)?.into_iter().collect();
+    let current: BTreeSet<String> = read_snapshot_accounts(vault_dir)?.into_iter().collect();
+
+    let mut differences = Vec::new();
+    for account_name in backed_up.union(&current) {
+        let status = match (
+            backed_up.contains(account_name),
+            current.contains(account_name),
+        ) {
+            (false, _) => AccountStatus::Added,
+            (_, false) => AccountStatus::Removed,
+            _ => AccountStatus::Modified,
+        };
+
+        if status != AccountStatus::Modified {
+            differences.push(AccountDifference {
+                account_name: account_name.clone(),
+                status,
+                changes: Vec::new(),
+                versions: None,
+            });
+            continue;
+        }
+
+        let backup_data = account_dir(backup_dir, account_name)?.join("data.gpg");
+        let current_data = account_dir(vault_dir, account_name)?.join("data.gpg");
+        if vault_files::read(&backup_data)? == vault_files::read(&current_data)? {
+            continue;
+        }
+
+        let backup = decrypt(&backup_data)?;
+        let current = decrypt(&current_data)?;
+        let changes = diff_accounts(&backup, &current);
+        // Saving an account without changing it only updates its timestamps
+        if changes.is_empty() {
+            continue;
+        }
+
+        differences.push(AccountDifference {
+            account_name: account_name.clone(),
+            status,
+            changes,
+            versions: Some((backup, current)),
+        });
+    }
+
+    Ok(differences)
+}
+
+/// Compares a vault with one of its backup snapshots.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+/// * `snapshot_id` - The `BackupSnapshot::id` of the snapshot.
+///
+/// # Returns
+/// * `Result<Vec<AccountDifference>, Error>` - Returns the accounts that differ, sorted by name,
+///   or an error on failure.
+///
+/// # Errors
+/// * If the vault or snapshot does not exist, or an account that changed cannot be decrypted.
+pub fn diff_vault_with_backup(
+    vault_name: &str,
+    snapshot_id: &str,
+) -> Result<Vec<AccountDifference>, Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+    let snapshot = find_backup(vault_name, snapshot_id)?;
+
+    let mut store = Store::new(vault_name, "")?;
+    diff_account_dirs(&snapshot.path, &locations.vault, |path| {
+        store.decrypt_account_from_path(path)
+    })
+}
@@ -0,0 +1,358 @@
+//! Encrypted index of account metadata.
+//!
+//! Listing a vault with each account's type, website and username would otherwise mean decrypting
+//! every account. `metadata.gpg` keeps those non-secret fields, with the tags and modification
+//! time, for every account in one file encrypted to the vault's recipients. Each entry records a
+//! hash of the `data.gpg` it was read from, so accounts changed outside fmp, or saved by a version
+//! without the index, are decrypted again the next time the vault is listed.
+
+/*
+Copyright (C) 2025  Luke Wilkinson
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::models::Account;
+use crate::storage::{
+    Locations, Store, archive::sha256_hex, filesystem::get_available_accounts,
+    manifest::update_vault_manifest, store::write_private_file, vault_files,
+};
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the encrypted index of account metadata inside a vault.
+pub const METADATA_FILE: &str = "metadata.gpg";
+
+/// The non-secret fields of one account.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountMetadata {
+    pub name: String,
+    pub account_type: String,
+    pub website: String,
+    pub username: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub modified_at: String,
+    /// SHA-256 of the encrypted `data.gpg` these fields were read from.
+    pub data_hash: String,
+}
+
+impl AccountMetadata {
+    /// Takes the metadata of an account stored under `name`.
+    ///
+    /// # Arguments
+    /// * `name` - The name the account is stored under.
+    /// * `account` - The decrypted account.
+    /// * `data_hash` - SHA-256 of the account's encrypted `data.gpg`.
+    ///
+    /// # Returns
+    /// * `AccountMetadata` - The account's metadata.
+    pub fn from_account(name: &str, account: &Account, data_hash: String) -> Self {
+        Self {
+            name: name.to_string(),
+            account_type: account.account_type.clone(),
+            website: account.website.clone(),
+            username: account.username.clone(),
+            tags: account.tags.clone(),
+            modified_at: account.modified_at.clone(),
+            data_hash,
+        }
+    }
+
+    /// Whether the account matches a search. Every word of the query must appear, ignoring case,
+    /// in the name, type, website, username or one of the tags.
+    ///
+    /// # Arguments
+    /// * `query` - The search text.
+    ///
+    /// # Returns
+    /// * `bool` - `true` if the account matches, or the query is blank.
+    pub fn matches(&self, query: &str) -> bool {
+        let fields: Vec<String> = [
+            &self.name,
+            &self.account_type,
+            &self.website,
+            &self.username,
+        ]
+        .into_iter()
+        .chain(&self.tags)
+        .map(|field| field.to_lowercase())
+        .collect();
+
+        query
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| fields.iter().any(|field| field.contains(word)))
+    }
+}
+
+/// The decrypted contents of `metadata.gpg`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MetadataIndex {
+    /// Each account's metadata, by the name it is stored under.
+    pub accounts: BTreeMap<String, AccountMetadata>,
+}
+
+impl MetadataIndex {
+    /// Brings the index up to date with a vault's accounts. Accounts that are gone are dropped,
+    /// and accounts that are new or whose `data.gpg` changed are decrypted.
+    ///
+    /// # Arguments
+    /// * `accounts` - Each account's name with the path of its `data.gpg`.
+    /// * `decrypt` - Decrypts one account file, such as `Store::decrypt_account_from_path`.
+    ///
+    /// # Returns
+    /// * `bool` - `true` if the index changed. Accounts that cannot be read or decrypted are
+    ///   logged and left out.
+    pub fn refresh<F>(&mut self, accounts: &[(String, PathBuf)], mut decrypt: F) -> bool
+    where
+        F: FnMut(&Path) -> Result<Account, Error>,
+    {
+        let before = self.accounts.len();
+        self.accounts
+            .retain(|name, _| accounts.iter().any(|(account, _)| account == name));
+        let mut changed = self.accounts.len() != before;
+
+        for (name, data) in accounts {
+            let data_hash = match vault_files::read(data) {
+                Ok(bytes) => sha256_hex(&bytes),
+                Err(e) => {
+                    log::warn!("Failed to read `{}`: {e}", data.display());
+                    continue;
+                }
+            };
+            if self
+                .accounts
+                .get(name)
+                .is_some_and(|metadata| metadata.data_hash == data_hash)
+            {
+                continue;
+            }
+
+            match decrypt(data) {
+                Ok(account) => {
+                    self.accounts.insert(
+                        name.clone(),
+                        AccountMetadata::from_account(name, &account, data_hash),
+                    );
+                    changed = true;
+                }
+                Err(e) => log::warn!("Failed to index account `{name}`: {e}"),
+            }
+        }
+
+        changed
+    }
+}
+
+/// How to order a vault's accounts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountSort {
+    /// Alphabetically by name.
+    Name,
+    /// Most recently modified first.
+    RecentlyModified,
+    /// Alphabetically by type, then by name.
+    Type,
+}
+
+impl AccountSort {
+    /// Every order, as offered in the vault view.
+    pub const ALL: [AccountSort; 3] = [
+        AccountSort::Name,
+        AccountSort::RecentlyModified,
+        AccountSort::Type,
+    ];
+
+    /// Returns the order's name for display.
+    pub fn label(self) -> &'static str {
+        match self {
+            AccountSort::Name => "Name",
+            AccountSort::RecentlyModified => "Recently modified",
+            AccountSort::Type => "Type",
+        }
+    }
+}
+
+/// Sorts accounts, ignoring case. Ties are broken by name.
+///
+/// # Arguments
+/// * `accounts` - The accounts to sort.
+/// * `sort` - The order to sort them in.
+pub fn sort_account_metadata(accounts: &mut [AccountMetadata], sort: AccountSort) {
+    let by_name = |a: &AccountMetadata, b: &AccountMetadata| {
+        a.name
+            .to_lowercase()
+            .cmp(&b.name.to_lowercase())
+            .then_with(|| a.name.cmp(&b.name))
+    };
+
+    accounts.sort_by(|a, b| match sort {
+        AccountSort::Name => by_name(a, b),
+        // Timestamps are stored as "%Y-%m-%d %H:%M:%S", which sorts like the times themselves
+        AccountSort::RecentlyModified => b
+            .modified_at
+            .cmp(&a.modified_at)
+            .then_with(|| by_name(a, b)),
+        AccountSort::Type => a
+            .account_type
+            .to_lowercase()
+            .cmp(&b.account_type.to_lowercase())
+            .then_with(|| by_name(a, b)),
+    });
+}
+
+/// Lists a vault's accounts with their metadata, decrypting only the index and any account it is
+/// missing or out of date for. The index is rewritten, and the manifest signed again, if it changed.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+///
+/// # Returns
+/// * `Result<Vec<AccountMetadata>, Error>` - Returns every account, with only its name if it could
+///   not be decrypted, sorted by name, or an error on failure.
+///
+/// # Errors
+/// * If the vault does not exist, or its index exists but cannot be decrypted or parsed.
+pub fn load_account_metadata(vault_name: &str) -> Result<Vec<AccountMetadata>, Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+
+    let mut store = Store::new(vault_name, "")?;
+    let mut index = read_index(&locations, &mut store)?;
+
+    let accounts: Vec<(String, PathBuf)> = get_available_accounts(vault_name)
+        .into_iter()
+        .map(|name| {
+            let data = Locations::new(vault_name, &name).data;
+            (name, data)
+        })
+        .collect();
+
+    if index.refresh(&accounts, |data| store.decrypt_account_from_path(data)) {
+        match write_index(&locations, &mut store, &index) {
+            Ok(()) => update_vault_manifest(vault_name),
+            Err(e) => log::warn!("Failed to save the account index of '{vault_name}': {e}"),
+        }
+    }
+
+    let mut listed: Vec<AccountMetadata> = accounts
+        .into_iter()
+        .map(|(name, _)| {
+            index.accounts.remove(&name).unwrap_or(AccountMetadata {
+                name,
+                ..AccountMetadata::default()
+            })
+        })
+        .collect();
+    sort_account_metadata(&mut listed, AccountSort::Name);
+
+    Ok(listed)
+}
+
+/// Records an account's metadata after it is created or saved. Failures are logged, since the
+/// index is brought up to date the next time the vault is listed.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+/// * `account` - The account as it was saved.
+pub fn record_account_metadata(vault_name: &str, account: &Account) {
+    let result = update_index(vault_name, |index| {
+        let data = vault_files::read(&Locations::new(vault_name, &account.name).data)?;
+        index.accounts.insert(
+            account.name.clone(),
+            AccountMetadata::from_account(&account.name, account, sha256_hex(&data)),
+        );
+        Ok(())
+    });
+
+    if let Err(e) = result {
+        log::warn!(
+            "Failed to index account '{}' of '{vault_name}': {e}",
+            account.name
+        );
+    }
+}
+
+/// Moves an account's metadata to its new name after it is renamed. Failures are logged.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+/// * `old_name` - The account's previous name.
+/// * `new_name` - The account's new name.
+pub fn rename_account_metadata(vault_name: &str, old_name: &str, new_name: &str) {
+    let result = update_index(vault_name, |index| {
+        if let Some(mut metadata) = index.accounts.remove(old_name) {
+            metadata.name = new_name.to_string();
+            index.accounts.insert(new_name.to_string(), metadata);
+        }
+        Ok(())
+    });
+
+    if let Err(e) = result {
+        log::warn!("Failed to rename account '{old_name}' in the index of '{vault_name}': {e}");
+    }
+}
+
+/// Removes an account's metadata after it is deleted. Failures are logged.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+/// * `account_name` - The name of the deleted account.
+pub fn forget_account_metadata(vault_name: &str, account_name: &str) {
+    let result = update_index(vault_name, |index| {
+        index.accounts.remove(account_name);
+        Ok(())
+    });
+
+    if let Err(e) = result {
+        log::warn!(
+            "Failed to remove account '{account_name}' from the index of '{vault_name}': {e}"
+        );
+    }
+}
+
+/// Reads, changes and rewrites a vault's index. A vault without one starts an empty index.
+fn update_index<F>(vault_name: &str, f: F) -> Result<(), Error>
+where
+    F: FnOnce(&mut MetadataIndex) -> Result<(), Error>,
+{
+    let locations = Locations::new(vault_name, "");
+    let mut store = Store::new(vault_name, "")?;
+
+    let mut index = read_index(&locations, &mut store)?;
+    f(&mut index)?;
+    write_index(&locations, &mut store, &index)
+}
+
+fn read_index(locations: &Locations, store: &mut Store) -> Result<MetadataIndex, Error> {
+    if !locations.metadata.exists() {
+        return Ok(MetadataIndex::default());
+    }
+
+    let decrypted = store.decrypt_file(&locations.metadata)?;
+    serde_json::from_slice(decrypted.as_slice())
+        .map_err(|e| anyhow::anyhow!("Failed to parse the account index: {}", e))
+}
+
+fn write_index(
+    locations: &Locations,
+    store: &mut Store,
+    index: &MetadataIndex,
+) -> Result<(), Error> {
+    let encrypted = store.encrypt_for_vault(&serde_json::to_vec(index)?)?;
+    write_private_file(&locations.metadata, &encrypted)
+}
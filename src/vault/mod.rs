@@ -21,7 +21,14 @@ Copyright (C) 2025  Luke Wilkinson
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+pub mod audit;
+pub mod compare;
+pub mod metadata;
 pub mod operations;
+pub mod restore;
+pub mod revisions;
+pub mod settings;
+pub mod trash;
 
 // Re-export all the types and functions that were previously in the vault.rs module
 // to maintain backward compatibility
@@ -18,13 +18,26 @@ Copyright (C) 2025  Luke Wilkinson
 */
 
 use crate::models::Account;
-use crate::storage::{Locations, Store};
+use crate::storage::{
+    Locations, Store,
+    account_names::record_account_name,
+    auto_backup::record_vault_change,
+    container::{container_path, repack_container},
+    filesystem::{get_available_accounts, recover_file_transaction, replace_files_transactionally},
+    manifest::update_vault_manifest,
+    store::{find_recipient_keys, read_recipients, write_private_file, write_recipients},
+    vault_files,
+};
+use crate::vault::metadata::{forget_account_metadata, record_account_metadata};
+use crate::vault::revisions::{diff_accounts, read_revisions, record_revision};
+use crate::vault::trash::{TrashedAccount, move_to_trash, read_trash};
 use anyhow::Error;
 use gpgme::Context;
 use std::{
     cell::RefCell,
-    fs::{File, remove_dir_all},
-    io::{BufReader, Read, Write},
+    fs::File,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
     rc::Rc,
 };
 use zeroize::Zeroize;
@@ -63,13 +76,19 @@ pub fn get_full_account_details(vault_name: &str, account_name: &str) -> Result<
 pub fn create_account(vault_name: &str, account: &Account) -> Result<(), Error> {
     let mut store = Store::new(vault_name, &account.name)?;
     store.storage_locations.does_vault_exist()?;
+    record_account_name(&store.storage_locations.vault, &account.name)?;
     store.storage_locations.create_account_directory()?;
     store.encrypt_account_to_file(account)?;
+    record_account_metadata(vault_name, account);
+    record_vault_change(vault_name);
     Ok(())
 }
 
 /// Updates an existing account in the specified vault.
 ///
+/// If anything changed, the version being replaced is kept as a revision, and if the password
+/// changed, the previously saved password is also added to the account's password history.
+///
 /// # Arguments
 /// * `vault_name` - The name of the vault containing the account.
 /// * `account` - The updated account data to encrypt and store.
@@ -78,12 +97,30 @@ pub fn create_account(vault_name: &str, account: &Account) -> Result<(), Error>
 /// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
 ///
 /// # Errors
-/// * If the vault does not exist, if the account does not exist, or if encryption fails.
+/// * If the vault does not exist, if the account does not exist, if the revision cannot be saved,
+///   or if encryption fails.
 pub fn update_account(vault_name: &str, account: &Account) -> Result<(), Error> {
     let mut store = Store::new(vault_name, &account.name)?;
     store.storage_locations.does_vault_exist()?;
     store.storage_locations.does_account_exist()?;
-    store.encrypt_account_to_file(account)?;
+
+    let mut account = account.clone();
+    match store.decrypt_account_from_file() {
+        Ok(previous) => {
+            if !diff_accounts(&previous, &account).is_empty() {
+                record_revision(&store.storage_locations, &previous)?;
+            }
+            account.record_password_change(&previous);
+        }
+        Err(e) => log::warn!(
+            "Could not read the saved version of '{}', so its password was not added to the history: {e}",
+            account.name
+        ),
+    }
+
+    store.encrypt_account_to_file(&account)?;
+    record_account_metadata(vault_name, &account);
+    record_vault_change(vault_name);
     Ok(())
 }
 
@@ -113,8 +150,7 @@ pub fn create_vault_prepare(vault_name: &str, recipient: &str) -> Result<(), Err
     let locations = Locations::new(vault_name, "");
     locations.initialize_vault()?;
 
-    let mut recipient_file = File::create(&locations.recipient)?;
-    recipient_file.write_all(recipient.as_bytes())?;
+    write_recipients(&locations, &[recipient.trim().to_string()])?;
 
     Ok(())
 }
@@ -126,28 +162,16 @@ pub fn create_vault_finalize(
 ) -> Result<(), Error> {
     let locations = Locations::new(vault_name, "");
 
-    let recipient_key = ctx.borrow_mut().get_key(recipient).map_err(|e| {
-        anyhow::anyhow!(
-            "Failed to find recipient `{}` for encryption. Error: {}",
-            recipient,
-            e
-        )
-    })?;
+    let recipient_keys =
+        find_recipient_keys(&[recipient.trim().to_string()], &mut ctx.borrow_mut())?;
 
     let gate_data = b"gate";
     let mut output = Vec::new();
     ctx.borrow_mut()
-        .encrypt([&recipient_key], &gate_data[..], &mut output)?;
+        .encrypt(&recipient_keys, &gate_data[..], &mut output)?;
 
-    let mut gate_file = File::create(&locations.gate)?;
-
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        gate_file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
-    }
-
-    gate_file.write_all(&output)?;
+    write_private_file(&locations.gate, &output)?;
+    update_vault_manifest(vault_name);
 
     Ok(())
 }
@@ -164,6 +188,7 @@ pub fn create_vault_finalize(
 /// * If the gate file cannot be read or decrypted.
 pub fn warm_up_gpg_blocking(vault_name: &str) -> Result<Vec<u8>, Error> {
     let locations = Locations::new(vault_name, "");
+    recover_interrupted_rekey(vault_name)?;
     let mut encrypted = Vec::new();
 
     let file = File::open(&locations.gate)?;
@@ -184,23 +209,264 @@ pub fn warm_up_gpg_finalize(encrypted: Vec<u8>, ctx: Rc<RefCell<Context>>) -> Re
     Ok(())
 }
 
-/// Deletes an account from the specified vault.
+/// Deletes an account from the specified vault by moving it to the vault's trash.
 ///
 /// # Arguments
 /// * `vault_name` - The name of the vault containing the account.
 /// * `account_name` - The name of the account to delete.
 ///
 /// # Returns
-/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+/// * `Result<TrashedAccount, Error>` - Returns the account's trash entry on success, or an error on failure.
 ///
 /// # Errors
-/// * If the vault does not exist, if the account does not exist, or if the account directory cannot be removed.
-pub fn delete_account(vault_name: &str, account_name: &str) -> Result<(), Error> {
+/// * If the vault does not exist, if the account does not exist, or if the account directory cannot be moved.
+pub fn delete_account(vault_name: &str, account_name: &str) -> Result<TrashedAccount, Error> {
     let store = Store::new(vault_name, account_name)?;
     store.storage_locations.does_vault_exist()?;
     store.storage_locations.does_account_exist()?;
 
-    remove_dir_all(&store.storage_locations.account)?;
+    let trashed = move_to_trash(
+        &store.storage_locations.account,
+        &store.storage_locations.trash,
+        chrono::Utc::now().naive_utc(),
+    )?;
+    forget_account_metadata(vault_name, account_name);
+    record_vault_change(vault_name);
+
+    Ok(trashed)
+}
+
+/// Lists the GPG recipients every file in a vault is encrypted to.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+///
+/// # Returns
+/// * `Result<Vec<String>, Error>` - Returns the recipient key IDs on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault does not exist or its recipient file cannot be read.
+pub fn get_vault_recipients(vault_name: &str) -> Result<Vec<String>, Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+
+    read_recipients(&locations)
+}
+
+/// Adds a member to a vault and re-encrypts every file so the new key can read them.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+/// * `recipient` - The GPG key ID, fingerprint or email of the new member.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault does not exist, the recipient is already a member, the key cannot be found or
+///   cannot encrypt, or re-encryption fails.
+pub fn add_vault_recipient(vault_name: &str, recipient: &str) -> Result<(), Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+
+    let recipient = recipient.trim();
+    if recipient.is_empty() {
+        return Err(anyhow::anyhow!("Recipient cannot be empty."));
+    }
+
+    let mut recipients = read_recipients(&locations)?;
+    if recipients.iter().any(|existing| existing == recipient) {
+        return Err(anyhow::anyhow!(
+            "`{}` is already a member of vault `{}`.",
+            recipient,
+            vault_name
+        ));
+    }
+
+    recipients.push(recipient.to_string());
+
+    rekey_vault(vault_name, &recipients)
+}
+
+/// Removes a member from a vault and re-encrypts every file without their key.
+///
+/// Copies made before removal (such as backups) remain readable by the removed member.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+/// * `recipient` - The recipient to remove, exactly as listed by `get_vault_recipients`.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault does not exist, the recipient is not a member, it is the last member, or re-encryption fails.
+pub fn remove_vault_recipient(vault_name: &str, recipient: &str) -> Result<(), Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+
+    let recipient = recipient.trim();
+    let previous = read_recipients(&locations)?;
+
+    if !previous.iter().any(|existing| existing == recipient) {
+        return Err(anyhow::anyhow!(
+            "`{}` is not a member of vault `{}`.",
+            recipient,
+            vault_name
+        ));
+    }
+
+    let updated: Vec<String> = previous
+        .iter()
+        .filter(|existing| *existing != recipient)
+        .cloned()
+        .collect();
+
+    if updated.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Cannot remove the last member of vault `{}`.",
+            vault_name
+        ));
+    }
+
+    rekey_vault(vault_name, &updated)
+}
+
+/// Re-encrypts every file in a vault to a new set of recipients and replaces the recipient file.
+///
+/// Each account's `data.gpg` and the vault's other encrypted files are all decrypted with the
+/// current keys before anything is written. The re-encrypted files and the new recipient file are
+/// then swapped in as one transaction: if any step fails, the vault is left exactly as it was, and a
+/// re-key interrupted by a crash is rolled back the next time the vault is opened.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+/// * `new_recipients` - The GPG key IDs, fingerprints or emails to encrypt the vault to.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault does not exist, no recipients are given, a key cannot be found or cannot encrypt,
+///   a file cannot be decrypted, or the files cannot be replaced.
+pub fn rekey_vault(vault_name: &str, new_recipients: &[String]) -> Result<(), Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+    recover_interrupted_rekey(vault_name)?;
+
+    let new_recipients: Vec<String> = new_recipients
+        .iter()
+        .map(|recipient| recipient.trim().to_string())
+        .filter(|recipient| !recipient.is_empty())
+        .collect();
+    if new_recipients.is_empty() {
+        return Err(anyhow::anyhow!("A vault needs at least one recipient"));
+    }
+
+    let mut store = Store::new(vault_name, "")?;
+
+    // Make sure every key is usable before decrypting anything.
+    find_recipient_keys(&new_recipients, &mut store.gpg_context)?;
+
+    let mut decrypted = Vec::new();
+    for path in vault_encrypted_files(vault_name)? {
+        let plaintext = store.decrypt_file(&path)?;
+        decrypted.push((path, plaintext));
+    }
+
+    let mut replacements = Vec::new();
+    let mut container_replacements = Vec::new();
+    for (path, plaintext) in decrypted {
+        let ciphertext = store.encrypt_to_recipients(&new_recipients, plaintext.as_slice())?;
+        match vault_files::container_entry(&path) {
+            Some((_, entry)) => container_replacements.push((entry, ciphertext)),
+            None => replacements.push((path, ciphertext)),
+        }
+    }
+
+    // A single-file vault's accounts are swapped in together, with the container's index
+    // encrypted to the new recipients as well.
+    if let Some(container) = container_path(&locations.vault) {
+        let repacked = repack_container(&container, container_replacements, &new_recipients)?;
+        replacements.insert(0, (container, repacked));
+    }
+
+    // The recipient file goes last, so it only changes once every file can be read with the new keys.
+    let mut recipient_file = new_recipients.join("\n");
+    recipient_file.push('\n');
+    replacements.push((locations.recipient.clone(), recipient_file.into_bytes()));
+
+    replace_files_transactionally(&replacements, &locations.rekey_journal)?;
+    update_vault_manifest(vault_name);
+
+    Ok(())
+}
+
+/// Rolls back a re-key that was interrupted before it finished, or tidies up one that completed.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the re-key journal cannot be read or removed.
+pub fn recover_interrupted_rekey(vault_name: &str) -> Result<(), Error> {
+    let locations = Locations::new(vault_name, "");
+
+    if recover_file_transaction(&locations.rekey_journal)? {
+        log::warn!("Recovered an interrupted re-key of vault `{vault_name}`");
+    }
 
     Ok(())
 }
+
+/// Lists every encrypted file in a vault: each account's `data.gpg` and revisions, the same for
+/// accounts in the trash, then `totp.gpg`, `gate.gpg`, `names.gpg` and `metadata.gpg`.
+fn vault_encrypted_files(vault_name: &str) -> Result<Vec<PathBuf>, Error> {
+    let locations = Locations::new(vault_name, "");
+
+    let mut account_dirs: Vec<PathBuf> = get_available_accounts(vault_name)
+        .iter()
+        .map(|account_name| Locations::new(vault_name, account_name).account)
+        .collect();
+    account_dirs.extend(
+        read_trash(&locations.trash)?
+            .into_iter()
+            .map(|trashed| trashed.path),
+    );
+
+    let mut files = Vec::new();
+    for account_dir in account_dirs {
+        files.extend(account_encrypted_files(&account_dir)?);
+    }
+
+    for path in [
+        locations.totp,
+        locations.gate,
+        locations.names,
+        locations.metadata,
+    ] {
+        if path.exists() {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
+
+/// Lists an account directory's `data.gpg` and revisions.
+fn account_encrypted_files(account_dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let data = account_dir.join("data.gpg");
+
+    let mut files: Vec<PathBuf> = read_revisions(&account_dir.join("revisions"))?
+        .into_iter()
+        .map(|revision| revision.path)
+        .collect();
+    if vault_files::exists(&data) {
+        files.insert(0, data);
+    }
+
+    Ok(files)
+}
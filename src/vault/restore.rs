@@ -0,0 +1,188 @@
+//! Restoring single accounts from backup snapshots.
+//!
+//! Restoring a whole snapshot throws away every change made since it was taken, so these
+//! functions browse the accounts inside a snapshot and bring back just one, either over the
+//! current account or as a copy under a new name.
+
+/*
+Copyright (C) 2025  Luke Wilkinson
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::models::Account;
+use crate::storage::{
+    Locations, Store,
+    account_names::{account_dir, account_names_for_dirs, record_account_name},
+    auto_backup::record_vault_change,
+    backup::find_backup,
+    locations::TRASH_DIRECTORY,
+    vault_files,
+};
+use crate::vault::operations::{create_account, update_account};
+use anyhow::Error;
+use std::path::{Path, PathBuf};
+
+/// Where a restored account goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RestoreTarget {
+    /// Replace the current account with the backed up version. If the account still exists, the
+    /// version being replaced is kept as a revision.
+    Overwrite,
+    /// Restore the backed up version as a new account with this name.
+    Copy(String),
+}
+
+/// Lists the accounts in a snapshot directory, sorted by name.
+///
+/// # Arguments
+/// * `snapshot_dir` - The snapshot's directory.
+///
+/// # Returns
+/// * `Result<Vec<String>, Error>` - Returns the account names on success, or an error on failure.
+///
+/// # Errors
+/// * If the snapshot directory cannot be read.
+pub fn read_snapshot_accounts(snapshot_dir: &Path) -> Result<Vec<String>, Error> {
+    let dir_names: Vec<String> = vault_files::list_dirs(snapshot_dir)?
+        .into_iter()
+        .filter(|name| {
+            name != TRASH_DIRECTORY
+                && vault_files::exists(&snapshot_dir.join(name).join("data.gpg"))
+        })
+        .collect();
+    let mut accounts = account_names_for_dirs(snapshot_dir, dir_names);
+    accounts.sort();
+
+    Ok(accounts)
+}
+
+/// Lists the accounts in a backup snapshot of a vault.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+/// * `snapshot_id` - The `BackupSnapshot::id` of the snapshot.
+///
+/// # Returns
+/// * `Result<Vec<String>, Error>` - Returns the account names, sorted, or an error on failure.
+///
+/// # Errors
+/// * If the snapshot does not exist or cannot be read.
+pub fn list_backup_accounts(vault_name: &str, snapshot_id: &str) -> Result<Vec<String>, Error> {
+    let snapshot = find_backup(vault_name, snapshot_id)?;
+
+    read_snapshot_accounts(&snapshot.path)
+}
+
+/// Decrypts an account as it was in a backup snapshot.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+/// * `snapshot_id` - The `BackupSnapshot::id` of the snapshot.
+/// * `account_name` - The name of the account in the snapshot.
+///
+/// # Returns
+/// * `Result<Account, Error>` - Returns the backed up account on success, or an error on failure.
+///
+/// # Errors
+/// * If the snapshot or account does not exist, or decryption fails.
+pub fn get_backup_account(
+    vault_name: &str,
+    snapshot_id: &str,
+    account_name: &str,
+) -> Result<Account, Error> {
+    let data = backup_account_dir(vault_name, snapshot_id, account_name)?.join("data.gpg");
+
+    let mut store = Store::new(vault_name, account_name)?;
+    store.decrypt_account_from_path(&data)
+}
+
+/// Restores one account from a backup snapshot, leaving the rest of the vault as it is.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+/// * `snapshot_id` - The `BackupSnapshot::id` of the snapshot.
+/// * `account_name` - The name of the account in the snapshot.
+/// * `target` - Whether to overwrite the current account or restore a copy.
+///
+/// # Returns
+/// * `Result<String, Error>` - Returns the name of the restored account on success, or an error on failure.
+///
+/// # Errors
+/// * If the snapshot or account does not exist, the copy's name is taken, or the account cannot
+///   be decrypted or saved.
+pub fn restore_account_from_backup(
+    vault_name: &str,
+    snapshot_id: &str,
+    account_name: &str,
+    target: &RestoreTarget,
+) -> Result<String, Error> {
+    let locations = Locations::new(vault_name, account_name);
+    locations.does_vault_exist()?;
+
+    match target {
+        RestoreTarget::Overwrite if vault_files::exists(&locations.account) => {
+            let mut account = get_backup_account(vault_name, snapshot_id, account_name)?;
+            account.update_modified_time();
+            update_account(vault_name, &account)?;
+        }
+        RestoreTarget::Overwrite => {
+            // The account is gone, so bring back its whole directory, revisions included
+            let account_dir = backup_account_dir(vault_name, snapshot_id, account_name)?;
+            record_account_name(&locations.vault, account_name)?;
+            vault_files::copy_dir(&account_dir, &locations.account)?;
+            record_vault_change(vault_name);
+        }
+        RestoreTarget::Copy(new_name) => {
+            if vault_files::exists(&Locations::new(vault_name, new_name).account) {
+                return Err(anyhow::anyhow!(
+                    "Account `{}` already exists in vault `{}`.",
+                    new_name,
+                    vault_name
+                ));
+            }
+
+            let mut account = get_backup_account(vault_name, snapshot_id, account_name)?;
+            account.name = new_name.clone();
+            account.update_modified_time();
+            create_account(vault_name, &account)?;
+        }
+    }
+
+    Ok(match target {
+        RestoreTarget::Overwrite => account_name.to_string(),
+        RestoreTarget::Copy(new_name) => new_name.clone(),
+    })
+}
+
+fn backup_account_dir(
+    vault_name: &str,
+    snapshot_id: &str,
+    account_name: &str,
+) -> Result<PathBuf, Error> {
+    let snapshot = find_backup(vault_name, snapshot_id)?;
+
+    if !read_snapshot_accounts(&snapshot.path)?
+        .iter()
+        .any(|name| name == account_name)
+    {
+        return Err(anyhow::anyhow!(
+            "Account `{}` is not in the backup from {}.",
+            account_name,
+            snapshot.created_at_display()
+        ));
+    }
+
+    account_dir(&snapshot.path, account_name)
+}
@@ -0,0 +1,308 @@
+//! Encrypted revision history for accounts.
+//!
+//! Every time an account is saved with changes, the version it replaces is kept as an encrypted
+//! copy of its `data.gpg` in the account's `revisions` directory. Revisions can be listed,
+//! compared field by field and rolled back to.
+
+/*
+Copyright (C) 2025  Luke Wilkinson
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::models::Account;
+use crate::storage::{Locations, Store, vault_files};
+use crate::vault::operations::update_account;
+use anyhow::Error;
+use chrono::NaiveDateTime;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// How many revisions an account keeps; the oldest are deleted first.
+pub const MAX_REVISIONS: usize = 50;
+
+/// The format of `Account::modified_at`.
+const ACCOUNT_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+/// The timestamp format used in revision file names.
+const FILE_TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+/// A saved earlier version of an account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revision {
+    /// Increases with every revision of the account.
+    pub id: u32,
+    /// When this version was saved, in the same format as `Account::modified_at`.
+    pub saved_at: String,
+    pub path: PathBuf,
+}
+
+/// A field that differs between two versions of an account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: String,
+    /// The old value, or `None` if the field was added or is sensitive.
+    pub old: Option<String>,
+    /// The new value, or `None` if the field was removed or is sensitive.
+    pub new: Option<String>,
+    /// Whether the values are secrets, which are never included in the diff.
+    pub sensitive: bool,
+}
+
+impl FieldChange {
+    fn plain(field: &str, old: &str, new: &str) -> Option<Self> {
+        (old != new).then(|| Self {
+            field: field.to_string(),
+            old: Some(old.to_string()),
+            new: Some(new.to_string()),
+            sensitive: false,
+        })
+    }
+
+    fn sensitive(field: &str, changed: bool) -> Option<Self> {
+        changed.then(|| Self {
+            field: field.to_string(),
+            old: None,
+            new: None,
+            sensitive: true,
+        })
+    }
+}
+
+/// Lists the saved revisions of an account, newest first.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault containing the account.
+/// * `account_name` - The name of the account.
+///
+/// # Returns
+/// * `Result<Vec<Revision>, Error>` - Returns the revisions on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault or account does not exist, or the revisions directory cannot be read.
+pub fn list_revisions(vault_name: &str, account_name: &str) -> Result<Vec<Revision>, Error> {
+    let locations = Locations::new(vault_name, account_name);
+    locations.does_vault_exist()?;
+    locations.does_account_exist()?;
+
+    read_revisions(&locations.revisions)
+}
+
+/// Decrypts a saved revision of an account.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault containing the account.
+/// * `account_name` - The name of the account.
+/// * `id` - The revision to decrypt.
+///
+/// # Returns
+/// * `Result<Account, Error>` - Returns the account as it was at that revision, or an error on failure.
+///
+/// # Errors
+/// * If the revision does not exist or cannot be decrypted.
+pub fn get_account_revision(
+    vault_name: &str,
+    account_name: &str,
+    id: u32,
+) -> Result<Account, Error> {
+    let revision = list_revisions(vault_name, account_name)?
+        .into_iter()
+        .find(|revision| revision.id == id)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Revision {} of account `{}` does not exist.",
+                id,
+                account_name
+            )
+        })?;
+
+    let mut store = Store::new(vault_name, account_name)?;
+    store.decrypt_account_from_path(&revision.path)
+}
+
+/// Makes a saved revision the current version of an account.
+///
+/// The account keeps its current name, creation time and password history, and the version being
+/// replaced is itself kept as a new revision, so a rollback can be undone.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault containing the account.
+/// * `account_name` - The name of the account.
+/// * `id` - The revision to roll back to.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the revision does not exist, cannot be decrypted, or the account cannot be saved.
+pub fn rollback_account(vault_name: &str, account_name: &str, id: u32) -> Result<(), Error> {
+    let revision = get_account_revision(vault_name, account_name, id)?;
+    let mut store = Store::new(vault_name, account_name)?;
+    let current = store.decrypt_account_from_file()?;
+
+    let mut restored = Account {
+        name: current.name.clone(),
+        created_at: current.created_at.clone(),
+        password_history: current.password_history.clone(),
+        ..revision
+    };
+    restored.update_modified_time();
+
+    update_account(vault_name, &restored)
+}
+
+/// Keeps the account's current `data.gpg` as a new revision, before it is overwritten.
+///
+/// The encrypted file is copied as-is, so nothing is decrypted or re-encrypted. The oldest
+/// revisions are deleted once there are more than `MAX_REVISIONS`.
+///
+/// # Arguments
+/// * `locations` - The `Locations` of the account.
+/// * `current` - The decrypted contents of the current `data.gpg`, used for its timestamp.
+///
+/// # Returns
+/// * `Result<Revision, Error>` - Returns the new revision on success, or an error on failure.
+///
+/// # Errors
+/// * If `data.gpg` cannot be read, or the revision cannot be written.
+pub fn record_revision(locations: &Locations, current: &Account) -> Result<Revision, Error> {
+    let existing = read_revisions(&locations.revisions)?;
+    let id = existing.first().map_or(1, |newest| newest.id + 1);
+
+    let saved_at = NaiveDateTime::parse_from_str(&current.modified_at, ACCOUNT_TIMESTAMP_FORMAT)
+        .unwrap_or_else(|_| chrono::Utc::now().naive_utc());
+
+    vault_files::create_private_dir(&locations.revisions)?;
+    let path = locations.revisions.join(format!(
+        "{id:06}-{}.gpg",
+        saved_at.format(FILE_TIMESTAMP_FORMAT)
+    ));
+    vault_files::write(&path, &vault_files::read(&locations.data)?)?;
+
+    for old in existing.iter().skip(MAX_REVISIONS - 1) {
+        if let Err(e) = vault_files::remove_file(&old.path) {
+            log::warn!(
+                "Failed to delete old revision `{}`: {e}",
+                old.path.display()
+            );
+        }
+    }
+
+    Ok(Revision {
+        id,
+        saved_at: saved_at.format(ACCOUNT_TIMESTAMP_FORMAT).to_string(),
+        path,
+    })
+}
+
+/// Compares two versions of an account field by field.
+///
+/// Timestamps and password history are ignored. Passwords and two-factor secrets are reported as
+/// changed without their values.
+///
+/// # Arguments
+/// * `old` - The earlier version.
+/// * `new` - The later version.
+///
+/// # Returns
+/// * `Vec<FieldChange>` - The fields that differ, in display order; empty if the versions match.
+pub fn diff_accounts(old: &Account, new: &Account) -> Vec<FieldChange> {
+    let mut changes: Vec<FieldChange> = [
+        FieldChange::plain("Name", &old.name, &new.name),
+        FieldChange::plain("Type", &old.account_type, &new.account_type),
+        FieldChange::plain("Website", &old.website, &new.website),
+        FieldChange::plain("Username", &old.username, &new.username),
+        FieldChange::plain("Tags", &old.tags_display(), &new.tags_display()),
+        FieldChange::sensitive("Password", !old.password.matches(&new.password)),
+        FieldChange::sensitive("Two-factor secret", totp_changed(old, new)),
+        FieldChange::plain("Notes", &old.notes, &new.notes),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let field_names: BTreeSet<&String> = old
+        .additional_fields
+        .keys()
+        .chain(new.additional_fields.keys())
+        .collect();
+    for name in field_names {
+        let old_value = old.additional_fields.get(name);
+        let new_value = new.additional_fields.get(name);
+        if old_value != new_value {
+            changes.push(FieldChange {
+                field: name.clone(),
+                old: old_value.cloned(),
+                new: new_value.cloned(),
+                sensitive: false,
+            });
+        }
+    }
+
+    changes
+}
+
+fn totp_changed(old: &Account, new: &Account) -> bool {
+    match (&old.totp, &new.totp) {
+        (None, None) => false,
+        (Some(old), Some(new)) => {
+            !old.secret.matches(&new.secret)
+                || old.params != new.params
+                || old.issuer != new.issuer
+                || old.label != new.label
+        }
+        _ => true,
+    }
+}
+
+/// Reads an account's revisions directory, newest first. Files not named like revisions are ignored.
+///
+/// # Arguments
+/// * `revisions_dir` - The account's revisions directory.
+///
+/// # Returns
+/// * `Result<Vec<Revision>, Error>` - Returns the revisions on success, or an error on failure.
+///
+/// # Errors
+/// * If the revisions directory exists but cannot be read.
+pub fn read_revisions(revisions_dir: &Path) -> Result<Vec<Revision>, Error> {
+    if !vault_files::exists(revisions_dir) {
+        return Ok(Vec::new());
+    }
+
+    let mut revisions = Vec::new();
+    for name in vault_files::list_files(revisions_dir)? {
+        let path = revisions_dir.join(&name);
+        let Some(stem) = name.strip_suffix(".gpg") else {
+            continue;
+        };
+        let Some((id, timestamp)) = stem.split_once('-') else {
+            continue;
+        };
+        let (Ok(id), Ok(saved_at)) = (
+            id.parse::<u32>(),
+            NaiveDateTime::parse_from_str(timestamp, FILE_TIMESTAMP_FORMAT),
+        ) else {
+            continue;
+        };
+
+        revisions.push(Revision {
+            id,
+            saved_at: saved_at.format(ACCOUNT_TIMESTAMP_FORMAT).to_string(),
+            path,
+        });
+    }
+
+    revisions.sort_by_key(|revision| std::cmp::Reverse(revision.id));
+    Ok(revisions)
+}
@@ -0,0 +1,130 @@
+//! Per-vault preferences.
+//!
+//! Settings are stored unencrypted as `settings.json` in the vault directory, since they hold no
+//! secrets. Vaults without the file, or with missing keys, use the defaults.
+
+/*
+Copyright (C) 2025  Luke Wilkinson
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::storage::{
+    Locations, auto_backup::AutoBackupPolicy, backup::RetentionPolicy, store::write_private_file,
+};
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+use std::fs::read_to_string;
+use std::path::Path;
+
+/// Name of the settings file inside a vault.
+pub const SETTINGS_FILE: &str = "settings.json";
+
+/// How long deleted accounts stay in the trash by default.
+pub const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+
+/// Preferences stored alongside a vault.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct VaultSettings {
+    /// Days a deleted account stays in the trash before it is purged; 0 keeps it until the trash
+    /// is emptied.
+    pub trash_retention_days: u32,
+    /// Which backup snapshots are kept when a new one is made.
+    pub backup_retention: RetentionPolicy,
+    /// When the vault is backed up automatically.
+    pub auto_backup: AutoBackupPolicy,
+}
+
+impl Default for VaultSettings {
+    fn default() -> Self {
+        Self {
+            trash_retention_days: DEFAULT_TRASH_RETENTION_DAYS,
+            backup_retention: RetentionPolicy::default(),
+            auto_backup: AutoBackupPolicy::default(),
+        }
+    }
+}
+
+impl VaultSettings {
+    /// Reads settings from a file, falling back to the defaults if it does not exist.
+    ///
+    /// # Arguments
+    /// * `path` - The settings file.
+    ///
+    /// # Returns
+    /// * `Result<VaultSettings, Error>` - Returns the settings on success, or an error on failure.
+    ///
+    /// # Errors
+    /// * If the file exists but cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        serde_json::from_str(&read_to_string(path)?).map_err(|e| {
+            anyhow::anyhow!("Failed to parse vault settings `{}`: {}", path.display(), e)
+        })
+    }
+
+    /// Writes settings to a file.
+    ///
+    /// # Arguments
+    /// * `path` - The settings file.
+    ///
+    /// # Returns
+    /// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+    ///
+    /// # Errors
+    /// * If the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        write_private_file(path, serde_json::to_string_pretty(self)?.as_bytes())
+    }
+}
+
+/// Loads the settings of a vault.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+///
+/// # Returns
+/// * `Result<VaultSettings, Error>` - Returns the settings on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault does not exist, or its settings file cannot be read or parsed.
+pub fn load_vault_settings(vault_name: &str) -> Result<VaultSettings, Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+
+    VaultSettings::load(&locations.settings)
+}
+
+/// Saves the settings of a vault. The caller signs the vault's manifest again afterwards with
+/// `update_vault_manifest`, off the GTK main thread in the GUI.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+/// * `settings` - The settings to save.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault does not exist, or the settings file cannot be written.
+pub fn save_vault_settings(vault_name: &str, settings: &VaultSettings) -> Result<(), Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+
+    settings.save(&locations.settings)
+}
@@ -0,0 +1,351 @@
+//! Trash bin for deleted accounts.
+//!
+//! Deleting an account moves its directory, still encrypted, into the vault's `.trash` directory
+//! under a name recording when it was deleted. From there it can be restored, deleted for good,
+//! or purged automatically once it is older than the vault's `trash_retention_days`.
+
+/*
+Copyright (C) 2025  Luke Wilkinson
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::storage::{
+    Locations,
+    account_names::{account_name_for_dir, account_names_for_dirs, forget_removed_accounts},
+    auto_backup::record_vault_change,
+    manifest::update_vault_manifest,
+    vault_files,
+};
+use crate::vault::settings::VaultSettings;
+use anyhow::Error;
+use chrono::{NaiveDateTime, SubsecRound, TimeDelta};
+use std::path::{Path, PathBuf};
+
+/// The timestamp prefix of trashed account directories, down to the millisecond so deleting an
+/// account, recreating it and deleting it again does not collide.
+const TRASH_TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3f";
+/// The format `TrashedAccount::deleted_at` is displayed in, matching `Account::modified_at`.
+const DISPLAY_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// A deleted account waiting in the trash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashedAccount {
+    /// The name of the account's directory in the trash, used to restore or purge it.
+    pub id: String,
+    pub account_name: String,
+    pub deleted_at: NaiveDateTime,
+    pub path: PathBuf,
+}
+
+impl TrashedAccount {
+    /// Returns when the account was deleted, formatted like `Account::modified_at`.
+    pub fn deleted_at_display(&self) -> String {
+        self.deleted_at.format(DISPLAY_TIMESTAMP_FORMAT).to_string()
+    }
+}
+
+/// Moves an account directory into a trash directory.
+///
+/// # Arguments
+/// * `account_dir` - The account's directory.
+/// * `trash_dir` - The vault's trash directory, created if needed.
+/// * `deleted_at` - When the account was deleted.
+///
+/// # Returns
+/// * `Result<TrashedAccount, Error>` - Returns the trashed account on success, or an error on failure.
+///
+/// # Errors
+/// * If the account directory has no name, or cannot be moved.
+pub fn move_to_trash(
+    account_dir: &Path,
+    trash_dir: &Path,
+    deleted_at: NaiveDateTime,
+) -> Result<TrashedAccount, Error> {
+    let dir_name = account_dir
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Invalid account directory `{}`", account_dir.display()))?
+        .to_string();
+    let account_name = account_dir.parent().map_or_else(
+        || dir_name.clone(),
+        |vault_dir| account_name_for_dir(vault_dir, &dir_name),
+    );
+
+    vault_files::create_private_dir(trash_dir)?;
+
+    // Only milliseconds survive in the directory name
+    let deleted_at = deleted_at.trunc_subsecs(3);
+    let id = format!("{}-{}", deleted_at.format(TRASH_TIMESTAMP_FORMAT), dir_name);
+    let path = trash_dir.join(&id);
+    if vault_files::exists(&path) {
+        return Err(anyhow::anyhow!(
+            "`{}` is already in the trash",
+            path.display()
+        ));
+    }
+
+    vault_files::rename(account_dir, &path)?;
+
+    Ok(TrashedAccount {
+        id,
+        account_name,
+        deleted_at,
+        path,
+    })
+}
+
+/// Reads a trash directory, newest deletion first. Entries not named like trashed accounts are
+/// ignored. Accounts stored under opaque IDs are named from the vault's index.
+///
+/// # Arguments
+/// * `trash_dir` - The vault's trash directory.
+///
+/// # Returns
+/// * `Result<Vec<TrashedAccount>, Error>` - Returns the trashed accounts on success, or an error on failure.
+///
+/// # Errors
+/// * If the trash directory exists but cannot be read.
+pub fn read_trash(trash_dir: &Path) -> Result<Vec<TrashedAccount>, Error> {
+    if !vault_files::exists(trash_dir) {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = Vec::new();
+    for id in vault_files::list_dirs(trash_dir)? {
+        let Some((timestamp, dir_name)) = id.split_once('-') else {
+            continue;
+        };
+        let Ok(deleted_at) = NaiveDateTime::parse_from_str(timestamp, TRASH_TIMESTAMP_FORMAT)
+        else {
+            continue;
+        };
+
+        let dir_name = dir_name.to_string();
+        entries.push((id, deleted_at, dir_name));
+    }
+
+    let dir_names = entries
+        .iter()
+        .map(|(_, _, dir_name)| dir_name.clone())
+        .collect();
+    let account_names = match trash_dir.parent() {
+        Some(vault_dir) => account_names_for_dirs(vault_dir, dir_names),
+        None => dir_names,
+    };
+
+    let mut trashed: Vec<TrashedAccount> = entries
+        .into_iter()
+        .zip(account_names)
+        .map(|((id, deleted_at, _), account_name)| TrashedAccount {
+            account_name,
+            path: trash_dir.join(&id),
+            id,
+            deleted_at,
+        })
+        .collect();
+
+    trashed.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at).then(a.id.cmp(&b.id)));
+    Ok(trashed)
+}
+
+/// Moves a trashed account back into its vault under its original name, or its original ID in a
+/// vault that hides names.
+///
+/// # Arguments
+/// * `trashed` - The account to restore.
+/// * `vault_dir` - The directory of the vault it was deleted from.
+///
+/// # Returns
+/// * `Result<PathBuf, Error>` - Returns the restored account directory on success, or an error on failure.
+///
+/// # Errors
+/// * If an account with the same name exists again, or the directory cannot be moved.
+pub fn restore_trashed_account(
+    trashed: &TrashedAccount,
+    vault_dir: &Path,
+) -> Result<PathBuf, Error> {
+    let dir_name = trashed
+        .id
+        .split_once('-')
+        .map_or(trashed.account_name.as_str(), |(_, dir_name)| dir_name);
+    let account_dir = vault_dir.join(dir_name);
+    if vault_files::exists(&account_dir) {
+        return Err(anyhow::anyhow!(
+            "An account named `{}` already exists. Rename it before restoring.",
+            trashed.account_name
+        ));
+    }
+
+    vault_files::rename(&trashed.path, &account_dir)?;
+
+    Ok(account_dir)
+}
+
+/// Deletes the trashed accounts that were deleted more than `retention_days` ago.
+///
+/// # Arguments
+/// * `trash_dir` - The vault's trash directory.
+/// * `retention_days` - How many days to keep deleted accounts; 0 keeps them forever.
+/// * `now` - The current time.
+///
+/// # Returns
+/// * `Result<Vec<TrashedAccount>, Error>` - Returns the purged accounts on success, or an error on failure.
+///
+/// # Errors
+/// * If the trash cannot be read, or an expired account cannot be deleted.
+pub fn purge_expired(
+    trash_dir: &Path,
+    retention_days: u32,
+    now: NaiveDateTime,
+) -> Result<Vec<TrashedAccount>, Error> {
+    if retention_days == 0 {
+        return Ok(Vec::new());
+    }
+
+    let cutoff = now - TimeDelta::days(i64::from(retention_days));
+    let expired: Vec<TrashedAccount> = read_trash(trash_dir)?
+        .into_iter()
+        .filter(|trashed| trashed.deleted_at < cutoff)
+        .collect();
+
+    for trashed in &expired {
+        vault_files::remove_dir_all(&trashed.path)?;
+    }
+    if !expired.is_empty()
+        && let Some(vault_dir) = trash_dir.parent()
+    {
+        forget_removed_accounts(vault_dir)?;
+    }
+
+    Ok(expired)
+}
+
+/// Lists the accounts in a vault's trash, newest deletion first.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+///
+/// # Returns
+/// * `Result<Vec<TrashedAccount>, Error>` - Returns the trashed accounts on success, or an error on failure.
+///
+/// # Errors
+/// * If the vault does not exist, or the trash cannot be read.
+pub fn list_trash(vault_name: &str) -> Result<Vec<TrashedAccount>, Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+
+    read_trash(&locations.trash)
+}
+
+/// Restores an account from a vault's trash.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+/// * `id` - The `TrashedAccount::id` of the account to restore.
+///
+/// # Returns
+/// * `Result<String, Error>` - Returns the name of the restored account on success, or an error on failure.
+///
+/// # Errors
+/// * If the account is not in the trash, an account with its name exists, or it cannot be moved.
+pub fn restore_account_from_trash(vault_name: &str, id: &str) -> Result<String, Error> {
+    let locations = Locations::new(vault_name, "");
+    let trashed = find_trashed_account(&locations, id)?;
+
+    restore_trashed_account(&trashed, &locations.vault)?;
+    record_vault_change(vault_name);
+
+    Ok(trashed.account_name)
+}
+
+/// Permanently deletes one account from a vault's trash.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+/// * `id` - The `TrashedAccount::id` of the account to delete.
+///
+/// # Returns
+/// * `Result<(), Error>` - Returns `Ok(())` on success, or an error on failure.
+///
+/// # Errors
+/// * If the account is not in the trash, or cannot be deleted.
+pub fn delete_from_trash(vault_name: &str, id: &str) -> Result<(), Error> {
+    let locations = Locations::new(vault_name, "");
+    let trashed = find_trashed_account(&locations, id)?;
+
+    vault_files::remove_dir_all(&trashed.path)?;
+    forget_removed_accounts(&locations.vault)?;
+    update_vault_manifest(vault_name);
+
+    Ok(())
+}
+
+/// Permanently deletes every account in a vault's trash.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+///
+/// # Returns
+/// * `Result<usize, Error>` - Returns how many accounts were deleted, or an error on failure.
+///
+/// # Errors
+/// * If the vault does not exist, or the trash cannot be read or deleted.
+pub fn empty_trash(vault_name: &str) -> Result<usize, Error> {
+    let trashed = list_trash(vault_name)?;
+
+    for account in &trashed {
+        vault_files::remove_dir_all(&account.path)?;
+    }
+    forget_removed_accounts(&Locations::new(vault_name, "").vault)?;
+    update_vault_manifest(vault_name);
+
+    Ok(trashed.len())
+}
+
+/// Purges accounts older than the vault's trash retention setting.
+///
+/// # Arguments
+/// * `vault_name` - The name of the vault.
+///
+/// # Returns
+/// * `Result<usize, Error>` - Returns how many accounts were purged, or an error on failure.
+///
+/// # Errors
+/// * If the vault does not exist, its settings cannot be read, or the trash cannot be purged.
+pub fn purge_expired_trash(vault_name: &str) -> Result<usize, Error> {
+    let locations = Locations::new(vault_name, "");
+    locations.does_vault_exist()?;
+    let settings = VaultSettings::load(&locations.settings)?;
+
+    let purged = purge_expired(
+        &locations.trash,
+        settings.trash_retention_days,
+        chrono::Utc::now().naive_utc(),
+    )?;
+    if !purged.is_empty() {
+        update_vault_manifest(vault_name);
+    }
+
+    Ok(purged.len())
+}
+
+fn find_trashed_account(locations: &Locations, id: &str) -> Result<TrashedAccount, Error> {
+    locations.does_vault_exist()?;
+
+    read_trash(&locations.trash)?
+        .into_iter()
+        .find(|trashed| trashed.id == id)
+        .ok_or_else(|| anyhow::anyhow!("`{}` is not in the trash", id))
+}